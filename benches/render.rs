@@ -0,0 +1,110 @@
+//! Renders into an owned frame, so no window is needed. Run with
+//! `cargo bench`, or `cargo bench -- --quick` for a fast pass in CI.
+
+use chord_explorer::explorer::Main;
+use chord_explorer::font::{Font, COZETTE};
+use chord_explorer::session::Session;
+use chord_explorer::widget::{
+    Canvas, CutDir, Events, HoverState, MenuState, PaneState, Rect, RepeatState, StatusState,
+    TooltipState, Visuals, Widget,
+};
+use chord_explorer::PixBuf;
+use criterion::{criterion_group, criterion_main, Criterion};
+
+// the window size of the binary
+const WIDTH: i32 = 320;
+const HEIGHT: i32 = 240;
+
+fn canvas<'a>(frame: &'a mut [u8], font: &'a Font, text_size: i32) -> Canvas<'a> {
+    Canvas {
+        pix: PixBuf::from_pixels_frame(frame, WIDTH, HEIGHT),
+        rect: Rect {
+            x: 0,
+            y: 0,
+            width: WIDTH,
+            height: HEIGHT,
+        },
+        visuals: Visuals {
+            font,
+            text_size,
+            dir: CutDir::Vertical,
+            color: [255, 255, 255, 255],
+            line_spacing: 0,
+        },
+        events: Events::default(),
+        tooltip: TooltipState::default(),
+        hover: HoverState::default(),
+        repeat: RepeatState::default(),
+        menu: MenuState::default(),
+        panes: PaneState::default(),
+        status: StatusState::default(),
+    }
+}
+
+/// The explorer on `tab`, with C major selected and the given zoom.
+fn explorer(tab: &str, zoom: i32) -> Main {
+    let mut main = Main::default();
+    main.restore(&Session {
+        tab: Some(tab.to_string()),
+        selected: Some((0, 0)),
+        zoom: Some(zoom),
+        ..Session::default()
+    });
+    main
+}
+
+fn render(c: &mut Criterion) {
+    let font = Font::from_bdf_bytes(COZETTE, 6, 13).unwrap();
+    let mut frame = vec![0; (WIDTH * HEIGHT * 4) as usize];
+
+    c.bench_function("fill frame", |b| {
+        b.iter(|| canvas(&mut frame, &font, 2).fill([48, 48, 96, 255]))
+    });
+    for zoom in [2, 3] {
+        let mut main = explorer("Explore", zoom);
+        c.bench_function(&format!("explore at scale {zoom}"), |b| {
+            b.iter(|| {
+                let mut canvas = canvas(&mut frame, &font, zoom);
+                canvas.clear_frame();
+                main.draw(&mut canvas);
+            })
+        });
+    }
+    let mut main = explorer("Tuning", 2);
+    c.bench_function("tuning table", |b| {
+        b.iter(|| {
+            let mut canvas = canvas(&mut frame, &font, 2);
+            canvas.clear_frame();
+            main.draw(&mut canvas);
+        })
+    });
+}
+
+fn text(c: &mut Criterion) {
+    let plain = Font::from_bdf_bytes(COZETTE, 6, 13).unwrap();
+    let atlas = Font::from_bdf_bytes(COZETTE, 6, 13).unwrap().with_atlas(2);
+    let mut frame = vec![0; (WIDTH * HEIGHT * 4) as usize];
+
+    // a frame of text at scale 2, 26 characters on each of 9 lines
+    let line = "C E G B♭ D F♯ A C♯ E♭ G♯ B";
+    for (name, font) in [("text", &plain), ("text from atlas", &atlas)] {
+        c.bench_function(name, |b| {
+            b.iter(|| {
+                let mut canvas = canvas(&mut frame, font, 2);
+                canvas.clear_frame();
+                for _ in 0..HEIGHT / canvas.visuals.font_height() {
+                    canvas.text(line);
+                }
+            })
+        });
+    }
+}
+
+fn parse_font(c: &mut Criterion) {
+    c.bench_function("parse cozette", |b| {
+        b.iter(|| Font::from_bdf_bytes(COZETTE, 6, 13).unwrap())
+    });
+}
+
+criterion_group!(benches, render, text, parse_font);
+criterion_main!(benches);
@@ -0,0 +1,110 @@
+//! Opens a window with a custom widget, using only the public API.
+
+use chord_explorer::font::{Font, COZETTE};
+use chord_explorer::widget::{
+    Canvas, CutDir, Events, HoverState, MenuState, PaneState, Rect, RepeatState, StatusState,
+    TooltipState, Visuals, Widget,
+};
+use chord_explorer::PixBuf;
+use pixels::{Pixels, SurfaceTexture};
+use winit::dpi::LogicalSize;
+use winit::event::Event;
+use winit::event_loop::EventLoop;
+use winit::keyboard::KeyCode;
+use winit::window::WindowBuilder;
+use winit_input_helper::WinitInputHelper;
+
+const WIDTH: u32 = 320;
+const HEIGHT: u32 = 240;
+
+/// Counts clicks on its button.
+struct Counter {
+    clicks: u32,
+}
+
+impl Widget for Counter {
+    fn draw(&mut self, canvas: &mut Canvas) {
+        canvas.cut_top(canvas.visuals.font_height(), |canvas| {
+            canvas.visuals.dir = CutDir::Horizontal;
+            if canvas.button("Click me") {
+                self.clicks += 1;
+            }
+        });
+        canvas.text(&format!("Clicked {} times", self.clicks));
+    }
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let font = Font::from_bdf_bytes(COZETTE, 6, 13)?;
+
+    let event_loop = EventLoop::new()?;
+    let window = WindowBuilder::new()
+        .with_title("Minimal")
+        .with_inner_size(LogicalSize::new(WIDTH, HEIGHT))
+        .with_resizable(false)
+        .build(&event_loop)?;
+    let mut pixels = {
+        let size = window.inner_size();
+        Pixels::new(
+            WIDTH,
+            HEIGHT,
+            SurfaceTexture::new(size.width, size.height, &window),
+        )?
+    };
+
+    let mut input = WinitInputHelper::new();
+    let mut events = Events::default();
+    let mut counter = Counter { clicks: 0 };
+
+    event_loop.run(move |event, target| {
+        if input.update(&event) {
+            if input.key_pressed(KeyCode::Escape) || input.close_requested() {
+                target.exit();
+                return;
+            }
+            events.cursor = input
+                .cursor()
+                .and_then(|pos| pixels.window_pos_to_pixel(pos).ok())
+                .map(|(x, y)| (x as i32, y as i32));
+            events.mouse_left = input.mouse_held(0);
+            events.clicked = input.mouse_pressed(0);
+
+            let (width, height) = (WIDTH as i32, HEIGHT as i32);
+            let mut canvas = Canvas {
+                pix: PixBuf::from_pixels_frame(pixels.frame_mut(), width, height),
+                rect: Rect {
+                    x: 0,
+                    y: 0,
+                    width,
+                    height,
+                },
+                visuals: Visuals {
+                    font: &font,
+                    text_size: 2,
+                    dir: CutDir::Vertical,
+                    color: [255, 255, 255, 255],
+                    line_spacing: 0,
+                },
+                events: events.clone(),
+                tooltip: TooltipState::default(),
+                hover: HoverState::default(),
+                repeat: RepeatState::default(),
+                menu: MenuState::default(),
+                panes: PaneState::default(),
+                status: StatusState::default(),
+            };
+            canvas.fill([0, 0, 0, 255]);
+            counter.draw(&mut canvas);
+
+            if pixels.render().is_err() {
+                target.exit();
+            }
+        }
+
+        if let Event::LoopExiting = event {
+            println!("clicked {} times", counter.clicks);
+        }
+    })?;
+
+    Ok(())
+}
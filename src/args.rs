@@ -0,0 +1,419 @@
+use std::{fmt, fs, io, path::PathBuf};
+
+use chord_explorer::{
+    color::{self, Theme},
+    explorer::{Main, MainTabs},
+    shortcuts::Action,
+    theory::{parse_chord_symbol, Chord, NamingScheme},
+    tuning::{ScalaError, Tuning},
+    widget::Tab,
+};
+
+const OPTIONS: &str = "\
+Usage: chord-explorer [OPTIONS]
+
+Options:
+  --tab <NAME>        Tab to open at startup (explore, modes, tuning,
+                      progression)
+  --chord <SYMBOL>    Chord to select in the Explore grid, as in C7 or C#m
+  --tuning <PATH>     Scala .scl file to add to the tunings and switch to
+  --scale <N>edo      Equal tuning of N steps to add and switch to, as in
+                      24edo
+  --theme <COLORS>    Accent and selection colors as hex codes, as in
+                      #ffb000,#404040, or one for both, or default
+  --font <PATH>       BDF font to use instead of the built-in Cozette
+  --font-size <WxH>   Character cell size of --font [default: 6x13]
+  --fresh             Ask before restoring the previous session
+  --letterbox         Keep a fixed aspect ratio, with bars around it
+  --aspect <W:H>      Aspect ratio for --letterbox [default: 4:3]
+  --letterbox-scale <MODE>
+                      Scale for --letterbox: integer (crisp) or fractional
+                      (fills more of the window) [default: integer]
+  --buffer <MODE>     Pixel buffer size: logical (scaled up crisply) or
+                      physical (one pixel per screen pixel) [default: logical]
+  --no-vsync          Present frames immediately instead of on vsync
+  --fps <N>           Most frames drawn per second, 0 for no limit [default: 60]
+  -h, --help          Print this help and exit
+  -V, --version       Print the version and exit";
+
+/// The usage text, with the keys listed from the shortcut registry.
+pub fn help() -> String {
+    let mut help = format!("{OPTIONS}\n\nKeys:");
+    for action in Action::ALL {
+        let label = action.label();
+        if label.len() < 18 {
+            help += &format!("\n  {label:<18}  {}", action.description());
+        } else {
+            help += &format!("\n  {label}\n  {:<18}  {}", "", action.description());
+        }
+    }
+    help
+}
+
+/// How the pixel buffer relates to the size of the window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BufferMode {
+    /// The window's size in logical pixels, scaled up by a whole factor.
+    Logical,
+    /// The window's size in physical pixels.
+    Physical,
+}
+
+/// How far the letterboxed buffer is scaled up to fit the window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LetterboxScale {
+    /// The largest whole factor, so every buffer pixel stays the same size.
+    Integer,
+    /// As far as fits, with buffer pixels a screen pixel apart in size.
+    Fractional,
+}
+
+pub enum Command {
+    Run(Args),
+    Help,
+    Version,
+}
+
+pub struct Args {
+    pub tab: Option<MainTabs>,
+    /// The chord as given and as read.
+    pub chord: Option<(String, Chord)>,
+    pub tuning: Option<PathBuf>,
+    pub scale: Option<u32>,
+    pub theme: Option<Theme>,
+    pub font: Option<PathBuf>,
+    pub font_size: (i32, i32),
+    pub fresh: bool,
+    pub letterbox: bool,
+    pub aspect: (u32, u32),
+    pub letterbox_scale: LetterboxScale,
+    pub buffer: BufferMode,
+    pub vsync: bool,
+    pub fps: u32,
+}
+
+impl Default for Args {
+    fn default() -> Self {
+        Self {
+            tab: None,
+            chord: None,
+            tuning: None,
+            scale: None,
+            theme: None,
+            font: None,
+            font_size: (6, 13),
+            fresh: false,
+            letterbox: false,
+            aspect: (4, 3),
+            letterbox_scale: LetterboxScale::Integer,
+            buffer: BufferMode::Logical,
+            vsync: true,
+            fps: 60,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ArgsError {
+    Unknown(String),
+    MissingValue(String),
+    InvalidValue(String, String),
+}
+
+impl fmt::Display for ArgsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArgsError::Unknown(arg) => write!(f, "unknown argument '{arg}'"),
+            ArgsError::MissingValue(arg) => write!(f, "'{arg}' requires a value"),
+            ArgsError::InvalidValue(arg, value) => write!(f, "invalid value '{value}' for '{arg}'"),
+        }
+    }
+}
+
+impl std::error::Error for ArgsError {}
+
+/// Parses the command line, not including the program name.
+pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Command, ArgsError> {
+    let mut parsed = Args::default();
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| ArgsError::MissingValue(arg.clone()))
+        };
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "-V" | "--version" => return Ok(Command::Version),
+            "--tab" => {
+                let name = value()?;
+                let tab = MainTabs::iter()
+                    .find(|tab| tab.name().eq_ignore_ascii_case(&name))
+                    .ok_or(ArgsError::InvalidValue(arg, name))?;
+                parsed.tab = Some(tab);
+            }
+            "--chord" => {
+                let symbol = value()?;
+                let chord = parse_chord_symbol(&symbol, NamingScheme::English)
+                    .ok_or(ArgsError::InvalidValue(arg, symbol.clone()))?;
+                parsed.chord = Some((symbol, chord));
+            }
+            "--tuning" => parsed.tuning = Some(value()?.into()),
+            "--scale" => {
+                let scale = value()?;
+                parsed.scale = Some(parse_edo(&scale).ok_or(ArgsError::InvalidValue(arg, scale))?);
+            }
+            "--theme" => {
+                let theme = value()?;
+                parsed.theme =
+                    Some(parse_theme(&theme).ok_or(ArgsError::InvalidValue(arg, theme))?);
+            }
+            "--fresh" => parsed.fresh = true,
+            "--font" => parsed.font = Some(value()?.into()),
+            "--font-size" => {
+                let size = value()?;
+                parsed.font_size = size
+                    .split_once('x')
+                    .and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)))
+                    .filter(|&(w, h)| w > 0 && h > 0)
+                    .ok_or(ArgsError::InvalidValue(arg, size))?;
+            }
+            "--letterbox" => parsed.letterbox = true,
+            "--aspect" => {
+                let aspect = value()?;
+                parsed.aspect = aspect
+                    .split_once(':')
+                    .and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)))
+                    .filter(|&(w, h)| w > 0 && h > 0)
+                    .ok_or(ArgsError::InvalidValue(arg, aspect))?;
+            }
+            "--letterbox-scale" => {
+                let scale = value()?;
+                parsed.letterbox_scale = match scale.to_ascii_lowercase().as_str() {
+                    "integer" => LetterboxScale::Integer,
+                    "fractional" => LetterboxScale::Fractional,
+                    _ => return Err(ArgsError::InvalidValue(arg, scale)),
+                };
+            }
+            "--buffer" => {
+                let mode = value()?;
+                parsed.buffer = match mode.to_ascii_lowercase().as_str() {
+                    "logical" => BufferMode::Logical,
+                    "physical" => BufferMode::Physical,
+                    _ => return Err(ArgsError::InvalidValue(arg, mode)),
+                };
+            }
+            "--no-vsync" => parsed.vsync = false,
+            "--fps" => {
+                let fps = value()?;
+                parsed.fps = fps.parse().map_err(|_| ArgsError::InvalidValue(arg, fps))?;
+            }
+            _ => return Err(ArgsError::Unknown(arg)),
+        }
+    }
+
+    Ok(Command::Run(parsed))
+}
+
+/// The number of steps of an equal tuning written as "24edo", "24-EDO" or
+/// "24tet".
+fn parse_edo(s: &str) -> Option<u32> {
+    let s = s.trim().to_ascii_lowercase();
+    let steps = s.strip_suffix("edo").or_else(|| s.strip_suffix("tet"))?;
+    let steps = steps.strip_suffix('-').unwrap_or(steps);
+    steps.parse().ok().filter(|&steps| steps > 0)
+}
+
+/// A theme with the accent and selection colors given as in
+/// "#ffb000,#404040", both the same if only one is given.
+fn parse_theme(s: &str) -> Option<Theme> {
+    if s.trim().eq_ignore_ascii_case("default") {
+        return Some(Theme::default());
+    }
+    let (accent, selection) = s.split_once(',').unwrap_or((s, s));
+    Some(Theme {
+        accent: color::parse_hex(accent)?,
+        selection: color::parse_hex(selection)?,
+    })
+}
+
+/// Why the startup state asked for on the command line could not be set.
+#[derive(Debug)]
+pub enum StartupError {
+    /// A chord that is read fine but is not one of the Explore grid.
+    ChordNotInGrid(String),
+    UnreadableTuning(PathBuf, io::Error),
+    InvalidTuning(PathBuf, ScalaError),
+}
+
+impl fmt::Display for StartupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StartupError::ChordNotInGrid(symbol) => {
+                write!(f, "chord '{symbol}' is not in the Explore grid")
+            }
+            StartupError::UnreadableTuning(path, err) => {
+                write!(f, "could not read tuning '{}': {err}", path.display())
+            }
+            StartupError::InvalidTuning(path, err) => {
+                write!(f, "could not load tuning '{}': {err}", path.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for StartupError {}
+
+impl Args {
+    /// Sets up `main` as asked, after the session and tunings are loaded so
+    /// the arguments win over them.
+    pub fn apply(&self, main: &mut Main) -> Result<(), StartupError> {
+        if let Some(tab) = self.tab {
+            main.set_tab(tab);
+        }
+        if let Some((symbol, chord)) = &self.chord {
+            if !main.select_chord(chord, symbol) {
+                return Err(StartupError::ChordNotInGrid(symbol.clone()));
+            }
+        }
+        if let Some(path) = &self.tuning {
+            let text = fs::read_to_string(path)
+                .map_err(|err| StartupError::UnreadableTuning(path.clone(), err))?;
+            let name = path
+                .file_stem()
+                .map_or("Scala".into(), |stem| stem.to_string_lossy());
+            let tuning = Tuning::from_scala(&name, &text)
+                .map_err(|err| StartupError::InvalidTuning(path.clone(), err))?;
+            main.add_tuning(tuning);
+        }
+        if let Some(steps) = self.scale {
+            main.add_tuning(Tuning::edo(steps));
+        }
+        if let Some(theme) = self.theme {
+            main.set_theme(theme);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(args: &[&str]) -> Result<Args, ArgsError> {
+        match parse(args.iter().map(|arg| arg.to_string()))? {
+            Command::Run(args) => Ok(args),
+            _ => panic!("expected to run"),
+        }
+    }
+
+    fn started(args: &[&str]) -> Result<Main, StartupError> {
+        let mut main = Main::default();
+        run(args).unwrap().apply(&mut main)?;
+        Ok(main)
+    }
+
+    #[test]
+    fn help_and_version_win_over_other_arguments() {
+        assert!(parse(["--tab".into(), "x".into()]).is_err());
+        assert!(matches!(parse(["--help".into()]), Ok(Command::Help)));
+        assert!(matches!(
+            parse(["-V".into(), "--nope".into()]),
+            Ok(Command::Version)
+        ));
+    }
+
+    #[test]
+    fn rejects_unknown_missing_and_invalid_values() {
+        assert!(matches!(run(&["--nope"]), Err(ArgsError::Unknown(_))));
+        assert!(matches!(run(&["--chord"]), Err(ArgsError::MissingValue(_))));
+        for args in [
+            ["--chord", "Cmaj13#11"],
+            ["--tab", "nowhere"],
+            ["--scale", "24"],
+            ["--scale", "0edo"],
+            ["--theme", "light"],
+            ["--fps", "fast"],
+            ["--font-size", "6x0"],
+            ["--letterbox-scale", "half"],
+        ] {
+            assert!(
+                matches!(run(&args), Err(ArgsError::InvalidValue(..))),
+                "{args:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn reads_edo_and_theme_values() {
+        for scale in ["24edo", "24-EDO", "24tet"] {
+            assert_eq!(run(&["--scale", scale]).unwrap().scale, Some(24));
+        }
+        let theme = run(&["--theme", "#ffb000,404040"]).unwrap().theme.unwrap();
+        assert_eq!(theme.accent, [255, 176, 0, 255]);
+        assert_eq!(theme.selection, [64, 64, 64, 255]);
+        let theme = run(&["--theme", "f00"]).unwrap().theme.unwrap();
+        assert_eq!(theme.accent, theme.selection);
+        assert_eq!(
+            run(&["--theme", "Default"]).unwrap().theme,
+            Some(Theme::default())
+        );
+    }
+
+    #[test]
+    fn reads_the_letterbox_scale() {
+        assert_eq!(run(&[]).unwrap().letterbox_scale, LetterboxScale::Integer);
+        let args = run(&["--letterbox", "--letterbox-scale", "Fractional"]).unwrap();
+        assert_eq!(args.letterbox_scale, LetterboxScale::Fractional);
+    }
+
+    #[test]
+    fn selects_the_tab_and_chord() {
+        let session = started(&["--tab", "tuning", "--chord", "C#m"])
+            .unwrap()
+            .session();
+        assert_eq!(session.tab.as_deref(), Some("Tuning"));
+        assert_eq!(session.selected, Some((1, 2)));
+        // C♮ is the same chord, but the plain spelling is picked
+        assert_eq!(
+            started(&["--chord", "C"]).unwrap().session().selected,
+            Some((0, 0))
+        );
+        // and D is C𝄪
+        assert_eq!(
+            started(&["--chord", "D7"]).unwrap().session().selected,
+            Some((4, 1))
+        );
+    }
+
+    #[test]
+    fn rejects_chords_outside_the_grid() {
+        assert!(matches!(
+            started(&["--chord", "Em"]),
+            Err(StartupError::ChordNotInGrid(symbol)) if symbol == "Em"
+        ));
+    }
+
+    #[test]
+    fn adds_and_switches_to_tunings() {
+        let path = std::env::temp_dir().join(format!("args-test-{}.scl", std::process::id()));
+        fs::write(&path, "! test\nquarter tones\n2\n18/17\n2/1\n").unwrap();
+        let main = started(&["--tuning", path.to_str().unwrap(), "--scale", "19edo"]);
+        fs::remove_file(&path).unwrap();
+        // the scale comes after the tuning, so it is the one switched to
+        assert_eq!(main.unwrap().session().tuning.as_deref(), Some("19-EDO"));
+
+        let missing = std::env::temp_dir().join("args-test-missing.scl");
+        assert!(matches!(
+            started(&["--tuning", missing.to_str().unwrap()]),
+            Err(StartupError::UnreadableTuning(..))
+        ));
+    }
+
+    #[test]
+    fn sets_the_theme() {
+        let session = started(&["--theme", "#ffb000"]).unwrap().session();
+        assert_eq!(session.accent.as_deref(), Some("#ffb000"));
+        assert_eq!(session.selection.as_deref(), Some("#ffb000"));
+    }
+}
@@ -0,0 +1,137 @@
+//! Conversions between RGB, HSV and hex colors, and the theme of colors the
+//! explorer can be given in the settings.
+
+use crate::{widget::BACKGROUND, Color};
+
+/// Steps of [`Hsv::h`] around the color wheel: 256 for each of the six
+/// sectors between red, yellow, green, cyan, blue and magenta.
+pub const HUE_STEPS: u16 = 6 * 256;
+
+/// A color as hue, saturation and value in integers. Converting to RGB and
+/// back is off by at most one in any channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hsv {
+    /// From 0 for red up to [`HUE_STEPS`], exclusive.
+    pub h: u16,
+    pub s: u8,
+    pub v: u8,
+}
+
+impl Hsv {
+    /// The opaque RGB color, rounding to the nearest channel value.
+    pub fn to_rgb(self) -> Color {
+        let h = (self.h % HUE_STEPS) as u32;
+        let (s, v) = (self.s as u32, self.v as u32);
+        let (sector, f) = (h / 256, h % 256);
+        // the channels that fall, rise and stay lowest across the sector
+        let falling = div_round(v * (255 * 256 - s * f), 255 * 256);
+        let rising = div_round(v * (255 * 256 - s * (256 - f)), 255 * 256);
+        let lowest = div_round(v * (255 - s), 255);
+        let [r, g, b] = match sector {
+            0 => [v, rising, lowest],
+            1 => [falling, v, lowest],
+            2 => [lowest, v, rising],
+            3 => [lowest, falling, v],
+            4 => [rising, lowest, v],
+            _ => [v, lowest, falling],
+        };
+        [r as u8, g as u8, b as u8, 255]
+    }
+
+    /// The hue, saturation and value of `color`, ignoring its alpha. Greys
+    /// have a hue of 0.
+    pub fn from_rgb(color: Color) -> Self {
+        let [r, g, b] = [color[0], color[1], color[2]].map(i32::from);
+        let (max, min) = (r.max(g).max(b), r.min(g).min(b));
+        let delta = max - min;
+        let s = if max == 0 {
+            0
+        } else {
+            div_round((delta * 255) as u32, max as u32)
+        };
+        let h = if delta == 0 {
+            0
+        } else {
+            let offset = |a: i32, b: i32| (256 * (a - b) * 2 + delta).div_euclid(2 * delta);
+            let h = if max == r {
+                offset(g, b)
+            } else if max == g {
+                512 + offset(b, r)
+            } else {
+                1024 + offset(r, g)
+            };
+            h.rem_euclid(HUE_STEPS as i32)
+        };
+        Self {
+            h: h as u16,
+            s: s as u8,
+            v: max as u8,
+        }
+    }
+}
+
+fn div_round(a: u32, b: u32) -> u32 {
+    (a + b / 2) / b
+}
+
+/// `color` as in "#ff8000", without its alpha.
+pub fn to_hex(color: Color) -> String {
+    format!("#{:02x}{:02x}{:02x}", color[0], color[1], color[2])
+}
+
+/// An opaque color from six hex digits or three, as in "#ff8000" or "f80",
+/// with or without the "#".
+pub fn parse_hex(s: &str) -> Option<Color> {
+    let digits = s.trim().strip_prefix('#').unwrap_or(s.trim());
+    if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |i: usize, len: usize| u8::from_str_radix(&digits[i * len..(i + 1) * len], 16);
+    match digits.len() {
+        6 => Some([
+            channel(0, 2).ok()?,
+            channel(1, 2).ok()?,
+            channel(2, 2).ok()?,
+            255,
+        ]),
+        3 => {
+            let short = [
+                channel(0, 1).ok()?,
+                channel(1, 1).ok()?,
+                channel(2, 1).ok()?,
+            ];
+            let [r, g, b] = short.map(|c| c * 17);
+            Some([r, g, b, 255])
+        }
+        _ => None,
+    }
+}
+
+/// The window background or white, whichever reads better on `fill`, for
+/// text drawn over it.
+pub fn contrasting(fill: Color) -> Color {
+    let [r, g, b] = [fill[0], fill[1], fill[2]].map(u32::from);
+    if 299 * r + 587 * g + 114 * b > 128 * 1000 {
+        BACKGROUND
+    } else {
+        [255, 255, 255, 255]
+    }
+}
+
+/// The colors of the explorer that can be changed in the settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    /// Text, outlines and the selected tab.
+    pub accent: Color,
+    /// Background of the selected chord and mode.
+    pub selection: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            accent: [255, 255, 255, 255],
+            selection: [255, 255, 255, 255],
+        }
+    }
+}
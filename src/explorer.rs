@@ -1,61 +1,3115 @@
-use std::vec;
+use std::{array, fs, ops::RangeInclusive, path::PathBuf};
 
-use crate::widget::{Canvas, CutDir, Tab, Widget};
+use log::Level;
+use winit::keyboard::KeyCode;
+
+use crate::{
+    color::{self, Theme},
+    export,
+    files::FileKind,
+    font::Font,
+    logging,
+    notify::{self, ToastId},
+    ratio::Ratio,
+    session::Session,
+    shortcuts::{Action, Category, Shortcut, ShortcutRegistry},
+    theory::{
+        self, pitch_name, AccidentalPreference, Chord, ChordQuality, NamingScheme, NoteName,
+        OctaveConvention, Partial, Pitch, ProgressionAnnotation, Scale, ScaleMode,
+        TendencyDirection, ToneRow,
+    },
+    tuning::{self, Notation, Tuning, TuningLibrary},
+    widget::{
+        self, Badge, Canvas, ColorPicker, CutDir, DragReorder, Events, GridNav, HoverState,
+        MenuState, Pane, PaneState, Rect, RepeatState, StatusState, Tab, Tabs, TooltipState,
+        Visuals, VuMeter, Widget, WidgetId, DIVIDER, FOCUS, HOVER,
+    },
+    Color, PixBuf,
+};
+
+/// Roots in the Explore grid, with their pitch class if they fall on 12-TET,
+/// and whether they are raised (1), lowered (-1) or neither (0).
+const ROOTS: [(&str, Option<u8>, i8); 10] = [
+    ("C", Some(0), 0),
+    ("C♯", Some(1), 1),
+    ("C♭", Some(11), -1),
+    ("C♮", Some(0), 0),
+    ("C𝄪", Some(2), 1),
+    ("C𝄫", Some(10), -1),
+    ("C𝄲", None, 1),
+    ("C𝄳", None, -1),
+    ("C𝄲♯", None, 1),
+    ("C𝄳♭", None, -1),
+];
+const QUALITIES: [ChordQuality; 3] = ChordQuality::ALL;
+
+const ZOOM: RangeInclusive<i32> = 1..=4;
+const DEFAULT_ZOOM: i32 = 2;
+
+/// The keys of the fields typed into, such as tuning names and ratios.
+pub(crate) const TYPING_SHORTCUTS: &[Shortcut] = &[
+    Shortcut::new(
+        Category::Editing,
+        "Enter",
+        "Finish typing a name, ratio or formula",
+    ),
+    Shortcut::new(
+        Category::Editing,
+        "Backspace",
+        "Delete the last character typed",
+    ),
+];
+
+/// Symbol of the chord in the Explore grid at `(col, row)`. The grid itself
+/// formats these in place, as it draws all of them every frame.
+pub fn chord_symbol(col: usize, row: usize) -> String {
+    format!("{}{}", ROOTS[col].0, QUALITIES[row].symbol())
+}
+
+/// The chord in the Explore grid at `(col, row)`, if its root is in 12-TET.
+pub fn grid_chord(col: usize, row: usize) -> Option<Chord> {
+    ROOTS[col]
+        .1
+        .map(|root| theory::catalog_chord(QUALITIES[row], root).clone())
+}
+
+/// The notes of `chord` with their cents above the root in `tuning`, as in
+/// "C♯ – E – G♯ (0¢, +386¢, +702¢)", for copying.
+pub fn notes_text(chord: &Chord, spelling: AccidentalPreference, tuning: &Tuning) -> String {
+    let notes = chord.note_names(spelling);
+    let notes = notes.iter().map(|note| note.to_string());
+    let off = |pc: u8| tuning.cents_off(pc).unwrap_or(0.0);
+    let cents = chord.intervals.iter().map(|&i| {
+        let cents = (i as f64 * 100.0 + off(chord.root + i) - off(chord.root)).round();
+        match cents as i32 {
+            0 => "0¢".to_string(),
+            cents => format!("{cents:+}¢"),
+        }
+    });
+    format!(
+        "{} ({})",
+        notes.collect::<Vec<_>>().join(" – "),
+        cents.collect::<Vec<_>>().join(", ")
+    )
+}
+
+/// Octave that notes shown without one, such as chord tones and tuning
+/// degrees, are described in.
+pub const DISPLAY_OCTAVE: i32 = 4;
+
+/// A pitch as its tooltip describes it: named by `naming` with the octave
+/// numbered by `octaves`, with its frequency in `tuning` and its interval
+/// above the tuning's 1/1 as by [`describe_interval`], as in
+/// "E4, 329.63 Hz, 386¢ ≈ 5/4 above 1/1".
+pub fn describe_pitch(
+    pitch: Pitch,
+    tuning: &Tuning,
+    naming: NamingScheme,
+    octaves: OctaveConvention,
+) -> String {
+    let cents = 1200.0 * (pitch.frequency(tuning) / tuning.reference_hz).log2();
+    describe(&pitch.name(naming, octaves), cents, tuning)
+}
+
+/// An interval as its tooltip describes it: its size and the nearest just
+/// ratio by [`Ratio::nearest`], with how far it is off when that is a cent
+/// or more, as in "386¢ ≈ 5/4" or "400¢ ≈ 5/4 +14¢".
+pub fn describe_interval(cents: f64) -> String {
+    let ratio = Ratio::nearest(cents);
+    let off = (cents - ratio.cents()).round();
+    if off == 0.0 {
+        format!("{cents:.0}¢ ≈ {ratio}")
+    } else {
+        format!("{cents:.0}¢ ≈ {ratio} {off:+}¢")
+    }
+}
+
+/// [`describe_pitch`] for the pitch `name` at `cents` above the reference.
+fn describe(name: &str, cents: f64, tuning: &Tuning) -> String {
+    let one = tuning.degrees.first().map_or(300.0, |degree| degree.cents);
+    let interval = describe_interval((cents - one).rem_euclid(1200.0));
+    let hz = tuning.frequency(cents);
+    format!("{name}, {hz:.2} Hz, {interval} above 1/1")
+}
+
+/// The interval up to each pitch from the one below it, named by the
+/// letters it is spelled on, with its size in `tuning`, as in
+/// "M3 400¢  m3 300¢" for a major triad.
+fn describe_steps(pitches: &[Pitch], tuning: &Tuning) -> String {
+    let steps = pitches.windows(2).filter_map(|pair| {
+        let interval = theory::Interval::between(pair[0], pair[1])?;
+        let ratio = pair[1].frequency(tuning) / pair[0].frequency(tuning);
+        Some(format!("{interval} {:.0}¢", 1200.0 * ratio.log2()))
+    });
+    steps.collect::<Vec<_>>().join("  ")
+}
+
+/// Forte numbers of the set classes of three to five pitch classes, with
+/// their prime forms. Those of seven to nine are the numbers of their
+/// complements. 5-20 is listed by its prime form as [`prime_form`] finds it,
+/// (01568), where Forte wrote (01378).
+const FORTE: [(&str, &str); 79] = [
+    ("3-1", "012"),
+    ("3-2", "013"),
+    ("3-3", "014"),
+    ("3-4", "015"),
+    ("3-5", "016"),
+    ("3-6", "024"),
+    ("3-7", "025"),
+    ("3-8", "026"),
+    ("3-9", "027"),
+    ("3-10", "036"),
+    ("3-11", "037"),
+    ("3-12", "048"),
+    ("4-1", "0123"),
+    ("4-2", "0124"),
+    ("4-3", "0134"),
+    ("4-4", "0125"),
+    ("4-5", "0126"),
+    ("4-6", "0127"),
+    ("4-7", "0145"),
+    ("4-8", "0156"),
+    ("4-9", "0167"),
+    ("4-10", "0235"),
+    ("4-11", "0135"),
+    ("4-12", "0236"),
+    ("4-13", "0136"),
+    ("4-14", "0237"),
+    ("4-Z15", "0146"),
+    ("4-16", "0157"),
+    ("4-17", "0347"),
+    ("4-18", "0147"),
+    ("4-19", "0148"),
+    ("4-20", "0158"),
+    ("4-21", "0246"),
+    ("4-22", "0247"),
+    ("4-23", "0257"),
+    ("4-24", "0248"),
+    ("4-25", "0268"),
+    ("4-26", "0358"),
+    ("4-27", "0258"),
+    ("4-28", "0369"),
+    ("4-Z29", "0137"),
+    ("5-1", "01234"),
+    ("5-2", "01235"),
+    ("5-3", "01245"),
+    ("5-4", "01236"),
+    ("5-5", "01237"),
+    ("5-6", "01256"),
+    ("5-7", "01267"),
+    ("5-8", "02346"),
+    ("5-9", "01246"),
+    ("5-10", "01346"),
+    ("5-11", "02347"),
+    ("5-Z12", "01356"),
+    ("5-13", "01248"),
+    ("5-14", "01257"),
+    ("5-15", "01268"),
+    ("5-16", "01347"),
+    ("5-Z17", "01348"),
+    ("5-Z18", "01457"),
+    ("5-19", "01367"),
+    ("5-20", "01568"),
+    ("5-21", "01458"),
+    ("5-22", "01478"),
+    ("5-23", "02357"),
+    ("5-24", "01357"),
+    ("5-25", "02358"),
+    ("5-26", "02458"),
+    ("5-27", "01358"),
+    ("5-28", "02368"),
+    ("5-29", "01368"),
+    ("5-30", "01468"),
+    ("5-31", "01369"),
+    ("5-32", "01469"),
+    ("5-33", "02468"),
+    ("5-34", "02469"),
+    ("5-35", "02479"),
+    ("5-Z36", "01247"),
+    ("5-Z37", "03458"),
+    ("5-Z38", "01258"),
+];
+
+/// The pitch classes of `set` modulo `n` in normal order: rotated to start
+/// on the pitch class that spans the fewest steps to the last, with ties
+/// going to the span to the one before it and so on, as Rahn orders them.
+/// So C E G is [0 4 7] and E G C is too.
+pub fn normal_order(set: &[u32], n: u32) -> Vec<u32> {
+    let mut set: Vec<u32> = set.iter().map(|pc| pc % n).collect();
+    set.sort_unstable();
+    set.dedup();
+    let rotations = (0..set.len()).map(|start| {
+        let mut rotation = set.clone();
+        rotation.rotate_left(start);
+        rotation
+    });
+    rotations
+        .min_by_key(|rotation| packing(rotation, n))
+        .unwrap_or_default()
+}
+
+/// The steps from the first pitch class of `set` to each of the others,
+/// from the last back, which [`normal_order`] and [`prime_form`] pick the
+/// smallest of.
+fn packing(set: &[u32], n: u32) -> Vec<u32> {
+    let first = set.first().copied().unwrap_or(0);
+    set.iter().rev().map(|&pc| (pc + n - first) % n).collect()
+}
+
+/// `set` inverted around 0 modulo `n`, so C E G becomes C A♭ F.
+pub fn invert_set(set: &[u32], n: u32) -> Vec<u32> {
+    set.iter().map(|&pc| (n - pc % n) % n).collect()
+}
+
+/// The most packed of the normal orders of `set` and its inversion,
+/// transposed to start on 0, so a major and a minor triad are both (037).
+pub fn prime_form(set: &[u32], n: u32) -> Vec<u32> {
+    let transposed = |set: Vec<u32>| {
+        let first = set.first().copied().unwrap_or(0);
+        set.iter()
+            .map(|&pc| (pc + n - first) % n)
+            .collect::<Vec<_>>()
+    };
+    let forms = [
+        transposed(normal_order(set, n)),
+        transposed(normal_order(&invert_set(set, n), n)),
+    ];
+    forms
+        .into_iter()
+        .min_by_key(|form| packing(form, n))
+        .unwrap()
+}
+
+/// How many pairs of pitch classes of `set` are each interval class apart
+/// modulo `n`, from one step up to half the octave, so C E G is
+/// ⟨0 0 1 1 1 0⟩: a minor third, a major third and a fourth.
+pub fn interval_vector(set: &[u32], n: u32) -> Vec<u32> {
+    let set = normal_order(set, n);
+    let mut vector = vec![0; n as usize / 2];
+    for (i, &a) in set.iter().enumerate() {
+        for &b in &set[i + 1..] {
+            let steps = (b + n - a) % n;
+            vector[steps.min(n - steps) as usize - 1] += 1;
+        }
+    }
+    vector
+}
+
+/// The Forte number of the set class of `set` modulo 12, such as 3-11 for
+/// triads, if it has three to five or seven to nine pitch classes.
+pub fn forte_number(set: &[u32]) -> Option<String> {
+    let set = normal_order(set, 12);
+    let prime = match set.len() {
+        3..=5 => prime_form(&set, 12),
+        7..=9 => {
+            let complement: Vec<u32> = (0..12).filter(|pc| !set.contains(pc)).collect();
+            prime_form(&complement, 12)
+        }
+        _ => return None,
+    };
+    let prime: String = prime.iter().map(|pc| pc.to_string()).collect();
+    let (name, _) = FORTE.iter().find(|(_, form)| *form == prime)?;
+    let (_, ordinal) = name.split_once('-')?;
+    Some(format!("{}-{ordinal}", set.len()))
+}
+
+/// Pitch classes modulo `n` written together as by [`digits`], with t and
+/// e for 10 and 11 modulo 12.
+fn pcset_string(set: &[u32], n: u32) -> String {
+    if n != 12 {
+        return digits(set);
+    }
+    let pcs = set.iter().map(|&pc| match pc {
+        10 => "t".to_string(),
+        11 => "e".to_string(),
+        pc => pc.to_string(),
+    });
+    pcs.collect()
+}
+
+/// Numbers written together, or separated by spaces when any has two
+/// digits.
+fn digits(numbers: &[u32]) -> String {
+    let strings = numbers.iter().map(|n| n.to_string());
+    match numbers.iter().all(|&n| n < 10) {
+        true => strings.collect(),
+        false => strings.collect::<Vec<_>>().join(" "),
+    }
+}
+
+/// Where "Export CSV" writes `tuning`: in the working directory, under a
+/// name made safe like those of exported chord cards, as tuning names can
+/// hold separators.
+fn tuning_csv_path(tuning: &Tuning) -> PathBuf {
+    PathBuf::from(format!("{}.csv", export::file_stem(&tuning.name)))
+}
+
+/// The interval vector of [`interval_vector`] in words, leaving out the
+/// intervals that no pair of notes is apart, as in "1 minor third, 1 major
+/// third, 1 fourth" for a triad.
+fn describe_interval_vector(vector: &[u32]) -> String {
+    const NAMES: [&str; 6] = [
+        "semitone",
+        "whole tone",
+        "minor third",
+        "major third",
+        "fourth",
+        "tritone",
+    ];
+    let counts = vector.iter().enumerate().filter(|(_, &count)| count > 0);
+    let counts = counts.map(|(i, &count)| {
+        let plural = if count == 1 { "" } else { "s" };
+        match vector.len() {
+            6 => format!("{count} {}{plural}", NAMES[i]),
+            _ => format!("{count} of {} step{}", i + 1, if i == 0 { "" } else { "s" }),
+        }
+    });
+    counts.collect::<Vec<_>>().join(", ")
+}
 
 #[derive(Default, Clone, Copy, PartialEq, Eq)]
-enum MainTabs {
+pub enum MainTabs {
     #[default]
     Explore,
+    Modes,
     Tuning,
+    Progression,
 }
 impl Tab for MainTabs {
-    type Iterator = vec::IntoIter<Self>;
+    type Iterator = array::IntoIter<Self, 4>;
     fn iter() -> Self::Iterator {
-        vec![MainTabs::Explore, MainTabs::Tuning].into_iter()
+        [
+            MainTabs::Explore,
+            MainTabs::Modes,
+            MainTabs::Tuning,
+            MainTabs::Progression,
+        ]
+        .into_iter()
     }
     fn name(&self) -> &str {
         match self {
             MainTabs::Explore => "Explore",
+            MainTabs::Modes => "Modes",
             MainTabs::Tuning => "Tuning",
+            MainTabs::Progression => "Progression",
         }
     }
 }
 
-#[derive(Default)]
 pub struct Main {
     tab: MainTabs,
+    /// How notes are named wherever they are shown. Copied and exported
+    /// text always uses English names.
+    naming: NamingScheme,
+    spelling: Spelling,
+    /// How tuning degrees between the semitones of 12-TET are written.
+    notation: Notation,
+    /// Which octave middle C is numbered in.
+    octaves: OctaveConvention,
+    display: DisplayOptions,
+    /// Colors of the UI, edited on the about page.
+    theme: Theme,
+    accent_picker: ColorPicker,
+    selection_picker: ColorPicker,
+    /// Rows of the modes table, named by `naming`.
+    modes: Vec<Vec<String>>,
+    modes_scroll: usize,
+    modes_view: ModesView,
+    /// Mode of the parallel modes view whose notes are highlighted in the
+    /// others.
+    parallel_mode: ScaleMode,
+    /// Degrees typed in the formula view, as in "1 2 b3 4 5 b6 b7".
+    scale_formula: String,
+    /// Whether typing goes into `scale_formula`, until Enter or a click.
+    editing_formula: bool,
+    explore_mode: ExploreMode,
+    selected: Option<(usize, usize)>,
+    /// Cell of the Explore grid focused with the keyboard, as a column of
+    /// the roots shown rather than an index into `ROOTS`.
+    grid_nav: GridNav,
+    /// Lower layer of the polychord, with `selected` as the upper layer.
+    lower: Option<(usize, usize)>,
+    /// Whether the reharmonization panel is shown over the Explore tab.
+    reharmonizing: bool,
+    /// Index of the alternative picked in the reharmonization panel.
+    reharmonized: usize,
+    key: Scale,
+    export_scale: ExportScale,
+    tunings: TuningLibrary,
+    /// New name being typed for the active tuning.
+    renaming: Option<String>,
+    /// Degree of the active tuning and the ratio being typed for it.
+    entering_ratio: Option<(usize, String)>,
+    progression: Vec<Chord>,
+    progression_drag: DragReorder,
+    /// The chord last removed from the progression, where it was, and the
+    /// toast that offers to put it back.
+    removed_chord: Option<(ToastId, usize, Chord)>,
+    restore_prompt: Option<Session>,
+    clipboard: Option<String>,
+    /// Whether the log view is shown over the tabs, toggled with F12.
+    show_log: bool,
+    log_level: LogLevel,
+    /// First visible log line, or `None` to follow new lines.
+    log_scroll: Option<usize>,
+    fullscreen: bool,
+    /// Whether the about page is shown over the tabs, opened from the help
+    /// overlay.
+    show_about: bool,
+    /// Whether the keys are listed over the tabs, toggled with F1 or the "?"
+    /// button.
+    show_help: bool,
+    help_scroll: usize,
+    /// Text size of the whole UI, changed with Ctrl+Plus, Ctrl+Minus, Ctrl+0
+    /// and Ctrl+scroll.
+    zoom: i32,
+    tooltip: TooltipState,
+    hover: HoverState,
+    repeat: RepeatState,
+    menu: MenuState,
+    panes: PaneState,
+    /// Whether the arrow keys go to the main tab bar.
+    tabs_focused: bool,
+}
+
+/// Every scale of the modes table as its name, root and notes.
+fn mode_rows(naming: NamingScheme) -> Vec<Vec<String>> {
+    // (name, root, key signature to spell with, scale)
+    let major = (0..12).map(|root| ("Major pent", root, root, theory::pentatonic_scales(root).0));
+    let minor = (0..12).map(|root| {
+        let relative_major = (root + 3) % 12;
+        (
+            "Minor pent",
+            root,
+            relative_major,
+            theory::pentatonic_scales(root).1,
+        )
+    });
+    let symmetric = theory::symmetric_scales()
+        .into_iter()
+        .map(|(name, root, scale)| (name, root, root, scale));
+    // the hexatonic scales that are not symmetric ones already, sorted by
+    // scale and then by root like them
+    let symmetric_names = theory::symmetric_scales()
+        .into_iter()
+        .map(|(name, ..)| name);
+    let symmetric_names = symmetric_names.collect::<Vec<_>>();
+    let hexatonic = theory::hexatonic_scales(0).into_iter().enumerate();
+    let hexatonic = hexatonic
+        .filter(|(_, (name, _))| !symmetric_names.contains(&name.as_str()))
+        .flat_map(|(i, (name, _))| {
+            (0..12).map(move |root| {
+                let scale = theory::hexatonic_scales(root).swap_remove(i).1;
+                (name.clone(), root, root, scale)
+            })
+        })
+        .collect::<Vec<_>>();
+    // jazz scales come last, spelled like the mode they extend
+    let bebop = theory::bebop_scales()
+        .into_iter()
+        .map(|(kind, root, scale)| (kind.name(), root, kind.mode().parent_major(root), scale));
+
+    theory::all_scale_modes()
+        .into_iter()
+        .map(|(mode, root, scale)| {
+            // spell each mode like the major scale it is a mode of
+            (mode.name(), root, mode.parent_major(root), scale)
+        })
+        .chain(major)
+        .chain(minor)
+        .chain(symmetric)
+        .chain(
+            hexatonic
+                .iter()
+                .map(|(name, root, key, scale)| (name.as_str(), *root, *key, scale.clone())),
+        )
+        .chain(bebop)
+        .map(|(name, root, key, scale)| {
+            let spelling = AccidentalPreference::KeyOf(key);
+            let notes = scale.notes().map(|pc| pitch_name(pc, spelling));
+            let notes = notes.collect::<Vec<_>>().join(" ");
+            vec![
+                name.to_string(),
+                naming.localize(&pitch_name(root, spelling)).to_string(),
+                naming.localize(&notes).to_string(),
+            ]
+        })
+        .collect()
+}
+
+impl Default for Main {
+    fn default() -> Self {
+        Self {
+            tab: MainTabs::default(),
+            naming: NamingScheme::default(),
+            spelling: Spelling::default(),
+            notation: Notation::default(),
+            octaves: OctaveConvention::default(),
+            display: DisplayOptions::default(),
+            theme: Theme::default(),
+            accent_picker: ColorPicker::new(Theme::default().accent),
+            selection_picker: ColorPicker::new(Theme::default().selection),
+            modes: mode_rows(NamingScheme::default()),
+            modes_scroll: 0,
+            modes_view: ModesView::default(),
+            parallel_mode: ScaleMode::Ionian,
+            scale_formula: String::from("1 2 b3 4 5 b6 b7"),
+            editing_formula: false,
+            explore_mode: ExploreMode::default(),
+            selected: None,
+            grid_nav: GridNav::default(),
+            lower: None,
+            reharmonizing: false,
+            reharmonized: 0,
+            key: ScaleMode::Ionian.scale(0),
+            export_scale: ExportScale::default(),
+            tunings: TuningLibrary::default(),
+            renaming: None,
+            entering_ratio: None,
+            progression: Vec::new(),
+            progression_drag: DragReorder::default(),
+            removed_chord: None,
+            restore_prompt: None,
+            clipboard: None,
+            show_log: false,
+            log_level: LogLevel::default(),
+            log_scroll: None,
+            fullscreen: false,
+            show_about: false,
+            show_help: false,
+            help_scroll: 0,
+            zoom: DEFAULT_ZOOM,
+            tooltip: TooltipState::default(),
+            hover: HoverState::default(),
+            repeat: RepeatState::default(),
+            menu: MenuState::default(),
+            panes: PaneState::default(),
+            tabs_focused: false,
+        }
+    }
+}
+
+impl Main {
+    pub fn set_tab(&mut self, tab: MainTabs) {
+        self.tab = tab;
+    }
+
+    pub fn set_tunings(&mut self, tunings: TuningLibrary) {
+        self.tunings = tunings;
+    }
+
+    /// Adds `tuning` to the library and makes it the active one.
+    pub fn add_tuning(&mut self, tuning: Tuning) {
+        self.tunings.add(tuning);
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+        self.accent_picker.set_color(theme.accent);
+        self.selection_picker.set_color(theme.selection);
+    }
+
+    /// Selects `chord` in the Explore grid, preferring the cell spelled
+    /// `symbol`, or returns false if the grid does not have it.
+    pub fn select_chord(&mut self, chord: &Chord, symbol: &str) -> bool {
+        let cells =
+            (0..ROOTS.len()).flat_map(|col| (0..QUALITIES.len()).map(move |row| (col, row)));
+        let cells = cells.filter(|&(col, row)| grid_chord(col, row).as_ref() == Some(chord));
+        let cells = cells.collect::<Vec<_>>();
+        let spelled = cells
+            .iter()
+            .find(|&&(col, row)| chord_symbol(col, row) == symbol.trim());
+        match spelled.or(cells.first()) {
+            Some(&cell) => {
+                self.selected = Some(cell);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn set_naming(&mut self, naming: NamingScheme) {
+        if naming != self.naming {
+            self.naming = naming;
+            self.modes = mode_rows(naming);
+        }
+    }
+
+    pub fn session(&self) -> Session {
+        Session {
+            saved_at: 0,
+            tab: Some(self.tab.name().to_string()),
+            explore_mode: Some(self.explore_mode.name().to_string()),
+            selected: self.selected,
+            lower: self.lower,
+            modes_scroll: self.modes_scroll,
+            help_scroll: self.help_scroll,
+            log_scroll: self.log_scroll,
+            progression: self.progression.clone(),
+            export_scale: Some(self.export_scale.name().to_string()),
+            tuning: Some(self.tunings.active().name.clone()),
+            unsaved_tunings: self.tunings.unsaved(),
+            fullscreen: self.fullscreen,
+            zoom: Some(self.zoom),
+            naming: Some(self.naming.name().to_string()),
+            spelling: Some(self.spelling.name().to_string()),
+            notation: Some(self.notation.name().to_string()),
+            octaves: Some(self.octaves.name().to_string()),
+            cent_offsets: self.display.show_cent_offsets,
+            status_bar_hidden: self.display.hide_status_bar,
+            accent: Some(color::to_hex(self.theme.accent)),
+            selection: Some(color::to_hex(self.theme.selection)),
+        }
+    }
+
+    /// Applies a saved session, skipping anything that no longer fits.
+    pub fn restore(&mut self, session: &Session) {
+        let in_grid = |cell: Option<(usize, usize)>| {
+            cell.filter(|&(col, row)| col < ROOTS.len() && row < QUALITIES.len())
+        };
+
+        if let Some(tab) = session.tab.as_deref().and_then(MainTabs::from_name) {
+            self.tab = tab;
+        }
+        if let Some(mode) = session
+            .explore_mode
+            .as_deref()
+            .and_then(ExploreMode::from_name)
+        {
+            self.explore_mode = mode;
+        }
+        if let Some(scale) = session
+            .export_scale
+            .as_deref()
+            .and_then(ExportScale::from_name)
+        {
+            self.export_scale = scale;
+        }
+        self.selected = in_grid(session.selected);
+        self.lower = in_grid(session.lower);
+        self.modes_scroll = session.modes_scroll.min(self.modes.len());
+        // the help and log views keep their scroll within what they show
+        self.help_scroll = session.help_scroll;
+        self.log_scroll = session.log_scroll;
+        let chords = session.progression.iter();
+        let chords = chords.filter(|chord| chord.root < 12 && !chord.intervals.is_empty());
+        self.progression = chords.cloned().collect();
+        self.fullscreen = session.fullscreen;
+        if let Some(zoom) = session.zoom.filter(|zoom| ZOOM.contains(zoom)) {
+            self.zoom = zoom;
+        }
+        if let Some(naming) = session.naming.as_deref().and_then(NamingScheme::from_name) {
+            self.set_naming(naming);
+        }
+        if let Some(spelling) = session.spelling.as_deref().and_then(Spelling::from_name) {
+            self.spelling = spelling;
+        }
+        if let Some(notation) = session.notation.as_deref().and_then(Notation::from_name) {
+            self.notation = notation;
+        }
+        if let Some(octaves) = session
+            .octaves
+            .as_deref()
+            .and_then(OctaveConvention::from_name)
+        {
+            self.octaves = octaves;
+        }
+        self.display.show_cent_offsets = session.cent_offsets;
+        self.display.hide_status_bar = session.status_bar_hidden;
+        if let Some(accent) = session.accent.as_deref().and_then(color::parse_hex) {
+            self.theme.accent = accent;
+            self.accent_picker.set_color(accent);
+        }
+        if let Some(selection) = session.selection.as_deref().and_then(color::parse_hex) {
+            self.theme.selection = selection;
+            self.selection_picker.set_color(selection);
+        }
+        if let Some(unsaved) = &session.unsaved_tunings {
+            self.tunings.restore_unsaved(unsaved);
+        }
+        if let Some(index) = self
+            .tunings
+            .entries()
+            .iter()
+            .position(|entry| Some(&entry.tuning.name) == session.tuning.as_ref())
+        {
+            self.tunings.select(index);
+        }
+    }
+
+    /// Asks whether to restore `session` instead of restoring it right away.
+    pub fn offer_restore(&mut self, session: Session) {
+        self.restore_prompt = Some(session);
+    }
+
+    /// Whether the window should be fullscreen, toggled with F11 or Alt+Enter.
+    pub fn fullscreen(&self) -> bool {
+        self.fullscreen
+    }
+
+    /// The selected chord and the active tuning, for the window title.
+    pub fn title_fragment(&self) -> String {
+        let chord = self.selected.map(|(col, row)| {
+            let upper = grid_chord(col, row);
+            let lower = self.lower.and_then(|(col, row)| grid_chord(col, row));
+            match (self.explore_mode, upper, lower) {
+                (ExploreMode::Polychord, Some(upper), Some(lower)) => {
+                    Chord::to_polychord_string(&upper, &lower)
+                }
+                _ => chord_symbol(col, row),
+            }
+        });
+        let chord = chord.map(|symbol| self.naming.localize(&symbol).to_string());
+        let tuning = Some(self.tunings.active_label());
+        let parts = chord.into_iter().chain(tuning);
+        parts.collect::<Vec<_>>().join(" — ")
+    }
+
+    /// Whether a tooltip is waiting out its delay, which only counts frames
+    /// that are drawn.
+    pub fn tooltip_pending(&self) -> bool {
+        self.tooltip.pending()
+    }
+
+    /// Whether moving the cursor from `from` to `to` changes what the last
+    /// frame drew, going by what it hovered and put on the status bar and
+    /// the tooltip it showed, so a frame has to be drawn for it.
+    pub fn cursor_moved_redraws(&self, from: Option<(i32, i32)>, to: Option<(i32, i32)>) -> bool {
+        from != to && (self.tooltip.visible || self.hover.cursor_moved(from, to))
+    }
+
+    /// Whether a control is held down to repeat, which is timed rather than
+    /// counted in frames.
+    pub fn holding(&self) -> bool {
+        self.repeat.holding()
+    }
+
+    /// Whether a context menu or the help overlay is open or a chord is
+    /// being dragged, which take Escape to close or put back rather than to
+    /// quit.
+    pub fn takes_escape(&self) -> bool {
+        self.menu.is_open() || self.progression_drag.active() || self.show_help
+    }
+
+    /// Whether text is being typed into a field, which takes pasted text.
+    pub fn typing(&self) -> bool {
+        self.editing_formula
+            || self.renaming.is_some()
+            || self.entering_ratio.is_some()
+            || self.accent_picker.editing()
+            || self.selection_picker.editing()
+    }
+
+    /// Selects the chord `text` names in the Explore grid, read as by
+    /// [`theory::parse_chord_symbol`], or warns that it cannot.
+    pub fn paste(&mut self, text: &str) -> bool {
+        let chord = theory::parse_chord_symbol(text, self.naming);
+        if chord
+            .as_ref()
+            .is_some_and(|chord| self.select_chord(chord, text))
+        {
+            self.tab = MainTabs::Explore;
+            return true;
+        }
+        match chord {
+            Some(_) => notify::warn!("'{}' is not in the Explore grid", text.trim()),
+            None => notify::warn!("could not paste '{}': not a chord", text.trim()),
+        }
+        false
+    }
+
+    /// Text copied during the last frame, to be put on the system clipboard.
+    pub fn take_clipboard(&mut self) -> Option<String> {
+        self.clipboard.take()
+    }
 }
 
 impl Widget for Main {
     fn draw(&mut self, canvas: &mut Canvas) {
+        std::mem::swap(&mut self.tooltip, &mut canvas.tooltip);
+        std::mem::swap(&mut self.hover, &mut canvas.hover);
+        std::mem::swap(&mut self.repeat, &mut canvas.repeat);
+        std::mem::swap(&mut self.menu, &mut canvas.menu);
+        std::mem::swap(&mut self.panes, &mut canvas.panes);
+        canvas.tooltip.begin_frame();
+        canvas.status.begin_frame();
+        canvas.menu.begin_frame(&mut canvas.events);
+        // the status bar is cut off first but drawn last, to show what the
+        // widgets drawn this frame set
+        let rect = canvas.rect;
+        let mut status_bar = None;
+        if !self.display.hide_status_bar {
+            let height = canvas.visuals.font.height * self.zoom;
+            canvas.cut_bottom(height, |canvas| status_bar = Some(canvas.rect));
+        }
+        canvas.profile_scope("main", |canvas| self.draw_contents(canvas));
+        if let Some(status_bar) = status_bar {
+            canvas.absolute(status_bar, |canvas| self.draw_status_bar(canvas));
+        }
+        if self.show_help {
+            self.draw_help(canvas, rect);
+        }
+        canvas.tooltip.end_frame();
+        canvas.repeat.end_frame();
+        canvas.menu.end_frame();
+        canvas.draw_context_menu();
+        canvas.draw_tooltip();
+        // after the menu, which hovers its items too
+        canvas.hover.end_frame();
+        std::mem::swap(&mut self.panes, &mut canvas.panes);
+        std::mem::swap(&mut self.menu, &mut canvas.menu);
+        std::mem::swap(&mut self.repeat, &mut canvas.repeat);
+        std::mem::swap(&mut self.hover, &mut canvas.hover);
+        std::mem::swap(&mut self.tooltip, &mut canvas.tooltip);
+    }
+}
+
+impl Main {
+    fn draw_contents(&mut self, canvas: &mut Canvas) {
         canvas.visuals.dir = CutDir::Vertical;
+        canvas.visuals.color = self.theme.accent;
+
+        for toast in notify::take_activated() {
+            let undone = self
+                .removed_chord
+                .take_if(|(removed, ..)| *removed == toast);
+            if let Some((_, index, chord)) = undone {
+                self.progression
+                    .insert(index.min(self.progression.len()), chord);
+            }
+        }
+
+        if canvas.action(Action::ZoomIn) {
+            self.zoom += 1;
+        }
+        if canvas.action(Action::ZoomOut) {
+            self.zoom -= 1;
+        }
+        if canvas.action(Action::ZoomReset) {
+            self.zoom = DEFAULT_ZOOM;
+        }
+        if canvas.events.control && canvas.events.scroll != 0.0 {
+            self.zoom += canvas.events.scroll.signum() as i32;
+            // zooming takes the scroll away from whatever is under the cursor
+            canvas.consume_scroll();
+        }
+        self.zoom = self.zoom.clamp(*ZOOM.start(), *ZOOM.end());
+        canvas.visuals.text_size = self.zoom;
+
+        if canvas.action(Action::Fullscreen) {
+            self.fullscreen = !self.fullscreen;
+        }
+        if canvas.action(Action::Log) {
+            self.show_log = !self.show_log;
+        }
+        if canvas.action(Action::Help) {
+            self.show_help = !self.show_help;
+        }
+        if self.show_log {
+            self.draw_log(canvas);
+            return;
+        }
+        if self.show_about {
+            self.draw_about(canvas);
+            return;
+        }
+
+        if let Some(session) = self.restore_prompt.take() {
+            let mut answer = None;
+            canvas.cut_top(canvas.visuals.font_height(), |canvas| {
+                canvas.visuals.dir = CutDir::Horizontal;
+                canvas.text("Restore previous session? ");
+                if canvas.button("Yes") {
+                    answer = Some(true);
+                }
+                if canvas.button("No") {
+                    answer = Some(false);
+                }
+            });
+            match answer {
+                Some(true) => self.restore(&session),
+                Some(false) => {}
+                None => self.restore_prompt = Some(session),
+            }
+        }
 
         canvas.cut_top(canvas.visuals.font_height(), |canvas| {
             canvas.visuals.dir = CutDir::Horizontal;
-            canvas.tabs(&mut self.tab);
+            let width = canvas.rect.width - 3 * canvas.visuals.font_width();
+            let height = canvas.rect.height;
+            // pressing the tab bar gives it the arrow keys, until a press
+            // anywhere else
+            canvas.cut(width, height, |canvas| {
+                if canvas.events.clicked {
+                    self.tabs_focused = canvas.hover();
+                }
+                if canvas.hover() {
+                    let switch = &widget::TAB_SHORTCUTS[0];
+                    canvas.status(&format!("{}: {}", switch.keys, switch.description));
+                }
+                // a dot for unsaved tunings and the number of chords in the
+                // progression
+                let unsaved = self.tunings.entries().iter().any(|entry| entry.modified);
+                let chords = self.progression.len() as u32;
+                let mut tabs = Tabs::new(&mut self.tab);
+                tabs.focused = self.tabs_focused;
+                if unsaved {
+                    tabs.badges
+                        .push((MainTabs::Tuning, Badge::Dot([255, 192, 0, 255])));
+                }
+                if chords > 0 {
+                    tabs.badges
+                        .push((MainTabs::Progression, Badge::Count(chords)));
+                }
+                tabs.draw(canvas);
+            });
+            canvas.cut_remaining(|canvas| {
+                if canvas.tooltip_register(WidgetId::new("about")) {
+                    canvas.tooltip_show(&format!("Keys and about, {}", Action::Help.label()));
+                }
+                if canvas.button("?") {
+                    self.show_help = !self.show_help;
+                }
+            });
         });
 
         match self.tab {
-            MainTabs::Explore => {
-                canvas.center(
-                    38 * canvas.visuals.font_width(),
-                    canvas.visuals.font_height() * 3,
-                    |canvas| {
-                        canvas.text("C  C♯  C♭  C♮  C𝄪  C𝄫  C𝄲  C𝄳  C𝄲♯  C𝄳♭ ");
-                        canvas.text("C7 C♯7 C♭7 C♮7 C𝄪7 C𝄫7 C𝄲7 C𝄳7 C𝄲♯7 C𝄳♭7");
-                        canvas.text("Cm C♯m C♭m C♮m C𝄪m C𝄫m C𝄲m C𝄳m C𝄲♯m C𝄳♭m");
-                    },
-                );
-            }
-            MainTabs::Tuning => {
-                canvas.text("C  300");
-                canvas.text("D  500");
-                canvas.text("E  700");
-                canvas.text("F  800");
-                canvas.text("G 1000");
-                canvas.text("A 1200");
-                canvas.text("B 1400");
+            MainTabs::Explore => self.draw_explore(canvas),
+            MainTabs::Modes => {
+                canvas.cut_top(canvas.visuals.font_height(), |canvas| {
+                    canvas.visuals.dir = CutDir::Horizontal;
+                    canvas.tabs(&mut self.modes_view);
+                });
+                match self.modes_view {
+                    ModesView::Table => {
+                        // names up to "2-st tritone", and up to eight notes,
+                        // each with a space after it
+                        let note = self.naming.widest_note() + 1;
+                        let columns = [13, note, 8 * note];
+                        canvas.table(&columns, &self.modes, &mut self.modes_scroll);
+                    }
+                    ModesView::Parallel => self.draw_parallel_modes(canvas),
+                    ModesView::Blues => self.draw_blues(canvas),
+                    ModesView::ToneRow => self.draw_tone_row(canvas),
+                    ModesView::Formula => self.draw_scale_formula(canvas),
+                }
+            }
+            MainTabs::Tuning => self.draw_tuning(canvas),
+            MainTabs::Progression => self.draw_progression(canvas),
+        }
+    }
+
+    /// How many cents the active tuning puts each pitch class off 12-TET,
+    /// where it has a degree near it and cent offsets are shown.
+    fn cent_offsets(&self) -> [Option<f64>; 12] {
+        let tuning = self.tunings.active();
+        let shown = self.display.show_cent_offsets;
+        array::from_fn(|pc| tuning.cents_off(pc as u8).filter(|_| shown))
+    }
+
+    /// How notes without a single obvious spelling are spelled.
+    fn spelling(&self) -> AccidentalPreference {
+        match self.spelling {
+            Spelling::Sharps => AccidentalPreference::Sharps,
+            Spelling::Flats => AccidentalPreference::Flats,
+            Spelling::Both | Spelling::Key => AccidentalPreference::KeyOf(self.key.root),
+        }
+    }
+
+    /// Whether the Explore grid shows the root in `ROOTS` raised or lowered
+    /// by `direction`.
+    fn shows_root(&self, direction: i8) -> bool {
+        match (self.spelling, self.spelling().resolve()) {
+            (Spelling::Both, _) => true,
+            (_, AccidentalPreference::Flats) => direction <= 0,
+            _ => direction >= 0,
+        }
+    }
+
+    fn draw_progression(&mut self, canvas: &mut Canvas) {
+        let selected = self.selected.and_then(|(col, row)| grid_chord(col, row));
+        canvas.cut_top(canvas.visuals.font_height(), |canvas| {
+            canvas.visuals.dir = CutDir::Horizontal;
+            match &selected {
+                Some(chord) => {
+                    let symbol = chord.symbol(self.spelling());
+                    let label = format!("Add {}", self.naming.localize(&symbol));
+                    if canvas.button(&label) {
+                        self.progression.push(chord.clone());
+                    }
+                }
+                None => canvas.text("Pick a chord in Explore to add it "),
+            }
+            if !self.progression.is_empty() && canvas.button("Remove last") {
+                self.progression.pop();
+            }
+            if self.progression.len() > 1 {
+                let smoothness = theory::voice_leading_smoothness(&self.progression);
+                canvas.text(&format!(" {smoothness:.2} per voice"));
+            }
+        });
+        if !self.progression.is_empty() {
+            canvas.cut_top(canvas.visuals.font_height(), |canvas| {
+                canvas.visuals.dir = CutDir::Horizontal;
+                if canvas.button("Copy chart") {
+                    let chart = export::progression_chart(&self.progression, self.spelling());
+                    self.clipboard = Some(chart);
+                }
+                if canvas.button("Copy as Markdown") {
+                    let table = export::progression_markdown(&self.progression, self.spelling());
+                    self.clipboard = Some(table);
+                }
+            });
+        }
+
+        let mut progression = Progression::new(
+            &self.progression,
+            &self.key,
+            self.spelling(),
+            self.naming,
+            self.octaves,
+            &mut self.progression_drag,
+        );
+        progression.draw(canvas);
+        let (removed, moved) = (progression.removed, progression.moved);
+        if let Some(index) = removed {
+            let chord = self.progression.remove(index);
+            let symbol = self
+                .naming
+                .localize(&chord.symbol(self.spelling()))
+                .to_string();
+            let message = format!("removed {symbol}");
+            let toast = notify::push_action(notify::Level::Info, message, "Undo");
+            self.removed_chord = Some((toast, index, chord));
+        }
+        if let Some((from, to)) = moved {
+            let chord = self.progression.remove(from);
+            self.progression.insert(to, chord);
+        }
+    }
+
+    /// What the widget under the cursor set with [`Canvas::status`], or the
+    /// active tuning and its reference pitch when nothing did.
+    fn draw_status_bar(&mut self, canvas: &mut Canvas) {
+        canvas.fill(HOVER);
+        canvas.visuals.color = self.theme.accent;
+        // taken out for the while, so it can be drawn without a copy
+        let status = std::mem::take(&mut canvas.status);
+        match status.text() {
+            Some(text) => canvas.text_truncated(text),
+            None => {
+                let tuning = self.tunings.active();
+                canvas.text_truncated_fmt(format_args!(
+                    "{}, 0¢ = {:.2} Hz",
+                    tuning.name, tuning.reference_hz
+                ));
+            }
+        }
+        canvas.status = status;
+    }
+
+    /// Every shortcut of the [`ShortcutRegistry`] over `area` dimmed, a
+    /// heading for each category and then its keys and what they do,
+    /// scrolling when they do not fit.
+    fn draw_help(&mut self, canvas: &mut Canvas, area: Rect) {
+        let registry = ShortcutRegistry::default();
+        let mut rows = Vec::new();
+        for (category, shortcuts) in registry.by_category() {
+            if !rows.is_empty() {
+                rows.push(vec![String::new(), String::new()]);
+            }
+            rows.push(vec![category.name().to_string(), String::new()]);
+            for shortcut in shortcuts {
+                let keys = format!("  {}", shortcut.keys);
+                rows.push(vec![keys, shortcut.description.to_string()]);
+            }
+        }
+        let width = |column: usize| rows.iter().map(|row| row[column].chars().count()).max();
+        let columns = [
+            width(0).unwrap_or(0) as i32 + 2,
+            width(1).unwrap_or(0) as i32,
+        ];
+
+        canvas.overlay(area, |canvas| {
+            canvas.dim();
+            if canvas.key_pressed(KeyCode::Escape) {
+                canvas.consume_key(KeyCode::Escape);
+                self.show_help = false;
+            }
+            let font_height = canvas.visuals.font_height();
+            canvas.modal(columns[0] + columns[1], rows.len() as i32 + 3, |canvas| {
+                canvas.cut_top(font_height, |canvas| canvas.text("Keys"));
+                let height = canvas.rect.height - 2 * font_height;
+                canvas.cut_top(height, |canvas| {
+                    canvas.table(&columns, &rows, &mut self.help_scroll);
+                });
+                canvas.cut_top(font_height, |_| {});
+                canvas.cut_top(font_height, |canvas| {
+                    canvas.visuals.dir = CutDir::Horizontal;
+                    if canvas.button("Close") {
+                        self.show_help = false;
+                    }
+                    canvas.text(" ");
+                    if canvas.button("About") {
+                        self.show_help = false;
+                        self.show_about = true;
+                    }
+                });
+            });
+        });
+    }
+
+    fn draw_about(&mut self, canvas: &mut Canvas) {
+        let pickers = self.accent_picker.lines() + self.selection_picker.lines();
+        canvas.modal(60, 17 + pickers, |canvas| {
+            canvas.text(&format!("Chord Explorer {}", env!("CARGO_PKG_VERSION")));
+            canvas.cut_top(canvas.visuals.font_height(), |_| {});
+            canvas.wrapped_text(
+                "Built in font: Cozette by slavfox, used under the MIT license. \
+                 See https://github.com/slavfox/Cozette for the full text.",
+            );
+            canvas.cut_top(canvas.visuals.font_height(), |_| {});
+            canvas.cut_top(canvas.visuals.font_height(), |canvas| {
+                canvas.visuals.dir = CutDir::Horizontal;
+                canvas.text("Note names ");
+                let mut naming = self.naming;
+                canvas.tabs(&mut naming);
+                self.set_naming(naming);
+            });
+            canvas.cut_top(canvas.visuals.font_height(), |canvas| {
+                canvas.visuals.dir = CutDir::Horizontal;
+                canvas.text("Accidentals ");
+                canvas.tabs(&mut self.spelling);
+            });
+            canvas.cut_top(canvas.visuals.font_height(), |canvas| {
+                canvas.visuals.dir = CutDir::Horizontal;
+                canvas.text("Microtones ");
+                canvas.tabs(&mut self.notation);
+            });
+            canvas.cut_top(canvas.visuals.font_height(), |canvas| {
+                canvas.visuals.dir = CutDir::Horizontal;
+                canvas.text("Middle C ");
+                canvas.tabs(&mut self.octaves);
+            });
+            canvas.cut_top(canvas.visuals.font_height(), |canvas| {
+                canvas.visuals.dir = CutDir::Horizontal;
+                canvas.text("Cent offsets ");
+                let shown = &mut self.display.show_cent_offsets;
+                if canvas.button(if *shown { "Shown" } else { "Hidden" }) {
+                    *shown = !*shown;
+                }
+            });
+            canvas.cut_top(canvas.visuals.font_height(), |canvas| {
+                canvas.visuals.dir = CutDir::Horizontal;
+                canvas.text("Status bar ");
+                let hidden = &mut self.display.hide_status_bar;
+                if canvas.button(if *hidden { "Hidden" } else { "Shown" }) {
+                    *hidden = !*hidden;
+                }
+            });
+            let width = canvas.visuals.font.len("Selection ") * canvas.visuals.font_width();
+            let pickers = [
+                ("Accent", &mut self.accent_picker, &mut self.theme.accent),
+                (
+                    "Selection",
+                    &mut self.selection_picker,
+                    &mut self.theme.selection,
+                ),
+            ];
+            for (label, picker, color) in pickers {
+                canvas.cut_top(picker.lines() * canvas.visuals.font_height(), |canvas| {
+                    canvas.visuals.dir = CutDir::Horizontal;
+                    canvas.cut(width, canvas.rect.height, |canvas| canvas.text(label));
+                    canvas.cut_remaining(|canvas| picker.draw(canvas));
+                    if picker.changed {
+                        *color = picker.color;
+                    }
+                });
+            }
+            canvas.cut_top(canvas.visuals.font_height(), |_| {});
+            canvas.cut_top(canvas.visuals.font_height(), |canvas| {
+                canvas.visuals.dir = CutDir::Horizontal;
+                if canvas.button("Close") {
+                    self.show_about = false;
+                }
+            });
+        });
+    }
+
+    fn draw_log(&mut self, canvas: &mut Canvas) {
+        let entries = logging::entries(self.log_level.level());
+
+        canvas.cut_top(canvas.visuals.font_height(), |canvas| {
+            canvas.visuals.dir = CutDir::Horizontal;
+            if canvas.button("Copy all") {
+                let lines = entries.iter().map(logging::Entry::line);
+                self.clipboard = Some(lines.collect::<Vec<_>>().join("\n"));
+            }
+            canvas.tabs(&mut self.log_level);
+        });
+
+        let row_height = canvas.visuals.font_height();
+        let visible = (canvas.rect.height / row_height.max(1)) as usize;
+        let max_scroll = entries.len().saturating_sub(visible);
+
+        // scrolling back to the bottom follows new lines again
+        let lines = canvas.scroll().round() as isize;
+        if lines != 0 {
+            let scroll = self.log_scroll.unwrap_or(max_scroll);
+            let scroll = scroll.saturating_add_signed(-lines).min(max_scroll);
+            self.log_scroll = (scroll < max_scroll).then_some(scroll);
+        }
+
+        let first = self.log_scroll.unwrap_or(max_scroll).min(max_scroll);
+        for entry in entries.iter().skip(first).take(visible) {
+            canvas.cut_top(row_height, |canvas| {
+                canvas.visuals.dir = CutDir::Horizontal;
+                canvas.visuals.color = match entry.level {
+                    Level::Error => [255, 96, 96, 255],
+                    Level::Warn => [255, 208, 96, 255],
+                    Level::Info => canvas.visuals.color,
+                    Level::Debug | Level::Trace => [160, 160, 160, 255],
+                };
+                canvas.text(&entry.line());
+            });
+        }
+    }
+
+    /// The root of the selected chord, or of the key when none is selected,
+    /// that the parallel and blues views build their scales on.
+    fn tonic(&self) -> u8 {
+        match self.selected.and_then(|(col, row)| grid_chord(col, row)) {
+            Some(chord) => chord.root,
+            None => self.key.root,
+        }
+    }
+
+    /// The blues scales on the tonic, with their blue notes, the ♭3 and ♭5
+    /// added to the pentatonic scales, in blue.
+    fn draw_blues(&mut self, canvas: &mut Canvas) {
+        let tonic = self.tonic();
+        let (minor, major) = theory::blues_scale(tonic);
+        // (name, scale, intervals of blue notes)
+        let scales = [
+            ("Minor blues", minor, &[6][..]),
+            ("Major blues", major, &[3]),
+            ("Composite", theory::country_scale(tonic), &[3, 6]),
+        ];
+        // spelled like the minor key on the tonic, as in C E♭ F G♭ G B♭
+        let spelling = AccidentalPreference::KeyOf((tonic + 3) % 12);
+
+        const NAME: i32 = 12;
+        // the composite scale has nine notes, each with a space after it
+        let naming = self.naming;
+        let columns = NAME + 9 * (naming.widest_note() + 1);
+        canvas.shrink_to_fit(columns, 1 + scales.len() as i32);
+        let (font_width, font_height) = (canvas.visuals.font_width(), canvas.visuals.font_height());
+
+        canvas.center(
+            columns * font_width,
+            (1 + scales.len() as i32) * font_height,
+            |canvas| {
+                canvas.cut_top(font_height, |canvas| {
+                    let tonic = naming.note(NoteName::new(tonic, spelling));
+                    canvas.text_fmt(format_args!("Blues scales on {tonic}"));
+                });
+                for (name, scale, blue_notes) in &scales {
+                    canvas.cut_top(font_height, |canvas| {
+                        canvas.visuals.dir = CutDir::Horizontal;
+                        canvas.cut(NAME * font_width, font_height, |canvas| canvas.text(name));
+                        for (&interval, pc) in scale.intervals.iter().zip(scale.notes()) {
+                            let color = canvas.visuals.color;
+                            if blue_notes.contains(&interval) {
+                                canvas.visuals.color = [96, 160, 255, 255];
+                            }
+                            let name = naming.note(NoteName::new(pc, spelling));
+                            canvas.text_fmt(format_args!("{name} "));
+                            canvas.visuals.color = color;
+                        }
+                    });
+                }
+            },
+        );
+    }
+
+    /// The twelve-tone matrix of the row that starts with the notes of the
+    /// selected chord and goes on up the chromatic scale from its root, or of
+    /// the chromatic scale on the key when none is selected.
+    fn draw_tone_row(&mut self, canvas: &mut Canvas) {
+        let tonic = self.tonic();
+        let chord = self.selected.and_then(|(col, row)| grid_chord(col, row));
+        let chromatic = (0..12).map(|i| (tonic + i) % 12);
+        let mut notes = Vec::with_capacity(12);
+        for pc in chord.iter().flat_map(Chord::notes).chain(chromatic) {
+            if !notes.contains(&pc) {
+                notes.push(pc);
+            }
+        }
+        let notes = notes.try_into().expect("the chromatic scale has 12 notes");
+        let row = ToneRow::new(notes).expect("every pitch class is in the row once");
+        ToneRowMatrix {
+            row: &row,
+            spelling: self.spelling(),
+            naming: self.naming,
+        }
+        .draw(canvas);
+    }
+
+    /// The scale typed as a formula of degrees, on the root of the selected
+    /// chord or the key, with its notes and the mode it is if it is one. The
+    /// scale is read again as the formula is typed, and always has its
+    /// root.
+    fn draw_scale_formula(&mut self, canvas: &mut Canvas) {
+        let tonic = self.tonic();
+        let naming = self.naming;
+        let spelling = self.spelling();
+        let letter = NoteName::new(tonic, AccidentalPreference::KeyOf(tonic)).letter;
+        let width = 40.max(self.scale_formula.chars().count() as i32 + 2);
+        canvas.shrink_to_fit(width, 4);
+        let (font_width, font_height) = (canvas.visuals.font_width(), canvas.visuals.font_height());
+
+        canvas.center(width * font_width, 4 * font_height, |canvas| {
+            canvas.cut_top(font_height, |canvas| {
+                let tonic = naming.note(NoteName::new(tonic, spelling));
+                canvas.text_fmt(format_args!("Scale from degrees on {tonic}"));
+            });
+            canvas.cut_top(font_height, |canvas| {
+                canvas.visuals.dir = CutDir::Horizontal;
+                if self.editing_formula {
+                    self.scale_formula.extend(canvas.characters_typed());
+                    if canvas.key_pressed(KeyCode::Backspace) {
+                        self.scale_formula.pop();
+                    }
+                    let label = format!("{}_", self.scale_formula);
+                    if canvas.button(&label) || canvas.key_pressed(KeyCode::Enter) {
+                        self.editing_formula = false;
+                    }
+                } else if canvas.button(&self.scale_formula) {
+                    self.editing_formula = true;
+                }
+            });
+
+            let degrees = match theory::scale_spelling_to_degrees(&self.scale_formula) {
+                Ok(degrees) => degrees,
+                Err(err) => {
+                    canvas.cut_top(font_height, |canvas| {
+                        canvas.visuals.color = [255, 96, 96, 255];
+                        canvas.text_fmt(format_args!("{err}"));
+                    });
+                    return;
+                }
+            };
+            let mut intervals: Vec<u8> = degrees.iter().map(|d| d.rem_euclid(12) as u8).collect();
+            intervals.push(0);
+            intervals.sort_unstable();
+            intervals.dedup();
+            let scale = Scale::new(tonic, intervals);
+            let degrees = theory::scale_degrees(&scale);
+
+            // each note on the letter of its degree, as in the E♭ of ♭3
+            canvas.cut_top(font_height, |canvas| {
+                canvas.visuals.dir = CutDir::Horizontal;
+                for degree in &degrees {
+                    let name = NoteName::on_letter(letter + degree.number - 1, degree.pc);
+                    canvas.text_fmt(format_args!("{} ", naming.note(name)));
+                }
+            });
+            canvas.cut_top(font_height, |canvas| {
+                canvas.visuals.color = [128, 128, 128, 255];
+                let mode = theory::all_scale_modes()
+                    .into_iter()
+                    .find(|(_, root, mode)| *root == tonic && *mode == scale);
+                let degrees = degrees.iter().map(|d| d.to_string()).collect::<Vec<_>>();
+                match mode {
+                    Some((mode, _, _)) => {
+                        canvas.text_fmt(format_args!("{} ({})", degrees.join(" "), mode.name()))
+                    }
+                    None => canvas.text(&degrees.join(" ")),
+                }
+            });
+        });
+    }
+
+    /// The seven modes on the root of the selected chord, or on the key when
+    /// none is selected, one per row. Degrees are labeled against the major
+    /// scale, and notes shared with the clicked mode are highlighted.
+    fn draw_parallel_modes(&mut self, canvas: &mut Canvas) {
+        let tonic = self.tonic();
+        let letter = NoteName::new(tonic, AccidentalPreference::KeyOf(tonic)).letter;
+        let modes = theory::parallel_modes(tonic);
+        let selected = modes
+            .iter()
+            .find(|(mode, _)| *mode == self.parallel_mode)
+            .map(|(_, scale)| scale);
+        let shared = |pc: u8| selected.is_some_and(|scale| scale.notes().any(|n| n == pc));
+        let degrees: Vec<_> = modes
+            .iter()
+            .map(|(_, scale)| theory::scale_degrees(scale))
+            .collect();
+        let tuning = self.tunings.active();
+
+        // a name column, then a note and its degree for each of the 7 degrees
+        const NAME: i32 = 11;
+        let naming = self.naming;
+        let cell = naming.widest_note() + 2;
+        let rows = modes.len();
+        canvas.shrink_to_fit(NAME + 7 * cell, 1 + 2 * rows as i32);
+        let (font_width, font_height) = (canvas.visuals.font_width(), canvas.visuals.font_height());
+
+        canvas.center(
+            (NAME + 7 * cell) * font_width,
+            (1 + 2 * rows as i32) * font_height,
+            |canvas| {
+                canvas.cut_top(font_height, |canvas| {
+                    canvas.text_fmt(format_args!(
+                        "Parallel modes of {}",
+                        naming.note(NoteName::on_letter(letter, tonic))
+                    ));
+                });
+                canvas.visuals.dir = CutDir::Horizontal;
+                canvas.cut(NAME * font_width, canvas.rect.height, |canvas| {
+                    canvas.grid(1, rows, |canvas, _, row| {
+                        let mode = modes[row].0;
+                        if canvas.clicked() {
+                            self.parallel_mode = mode;
+                        }
+                        if mode == self.parallel_mode {
+                            canvas.fill(self.theme.selection);
+                            canvas.visuals.color = color::contrasting(self.theme.selection);
+                        } else if canvas.hover() {
+                            canvas.fill(HOVER);
+                        }
+                        canvas.text(mode.name());
+                    });
+                });
+                canvas.cut_remaining(|canvas| {
+                    canvas.grid(7, rows, |canvas, degree, row| {
+                        let (mode, scale) = &modes[row];
+                        let pc = scale.degree(degree + 1);
+                        if canvas.clicked() {
+                            self.parallel_mode = *mode;
+                        }
+                        if *mode != self.parallel_mode && shared(pc) {
+                            canvas.fill([40, 72, 40, 255]);
+                        }
+                        canvas.cut_top(font_height, |canvas| {
+                            let name = NoteName::on_letter(letter + degree as u8, pc);
+                            canvas.text_fmt(format_args!("{}", naming.note(name)));
+                        });
+                        let degree = degrees[row][degree];
+                        canvas.visuals.color = match degree.accidental {
+                            0 => canvas.visuals.color,
+                            a if a < 0 => [255, 96, 96, 255],
+                            _ => [96, 160, 255, 255],
+                        };
+                        canvas.text(&degree.display(tuning));
+                    });
+                });
+            },
+        );
+    }
+
+    fn draw_tuning(&mut self, canvas: &mut Canvas) {
+        let height = canvas.visuals.font_height();
+
+        canvas.cut_top(height, |canvas| {
+            canvas.visuals.dir = CutDir::Horizontal;
+            let back = canvas.repeat_button("◀");
+            if back > 0 {
+                self.tunings.cycle(-(back as isize));
+                self.renaming = None;
+                self.entering_ratio = None;
+            }
+            // clicking the name renames the tuning, until Enter or another click
+            match &mut self.renaming {
+                Some(name) => {
+                    name.extend(canvas.characters_typed());
+                    if canvas.key_pressed(KeyCode::Backspace) {
+                        name.pop();
+                    }
+                    let label = format!("{name}_");
+                    if canvas.button(&label) || canvas.key_pressed(KeyCode::Enter) {
+                        let index = self.tunings.active_index();
+                        if let Err(err) = self.tunings.rename(index, name) {
+                            notify::warn!("could not rename tuning: {err}");
+                        }
+                        self.renaming = None;
+                    }
+                }
+                None => {
+                    if canvas.button(&self.tunings.active_label()) {
+                        self.renaming = Some(self.tunings.active().name.clone());
+                    }
+                }
+            }
+            let forward = canvas.repeat_button("▶");
+            if forward > 0 {
+                self.tunings.cycle(forward as isize);
+                self.renaming = None;
+                self.entering_ratio = None;
+            }
+        });
+        canvas.cut_top(height, |canvas| {
+            canvas.visuals.dir = CutDir::Horizontal;
+            if canvas.button("Duplicate") {
+                self.tunings.duplicate();
+                self.renaming = None;
+                self.entering_ratio = None;
+            }
+            if canvas.button("Delete") {
+                self.tunings.delete(self.tunings.active_index());
+                self.renaming = None;
+                self.entering_ratio = None;
+            }
+            if canvas.button("Save") {
+                match self.tunings.save() {
+                    Ok(()) => notify::info!("saved tunings"),
+                    Err(err) => notify::error!("could not save tunings: {err}"),
+                }
+            }
+        });
+
+        // scrolling over a degree nudges it by a cent per step
+        let degrees = self.tunings.active().degrees.len();
+        let rows = degrees.div_ceil(2);
+        let mut nudge = None;
+        let (mut delete, mut ratio_entered) = (None, None);
+        let (naming, notation, octaves) = (self.naming, self.notation, self.octaves);
+        let one = self.tunings.active().one();
+        let show_offsets = self.display.show_cent_offsets;
+        canvas.cut_top(height * rows as i32, |canvas| {
+            canvas.grid(2, rows, |canvas, col, row| {
+                let Some(degree) = self.tunings.active().degrees.get(row * 2 + col) else {
+                    return;
+                };
+                let index = row * 2 + col;
+                if canvas.hover() {
+                    canvas.fill(HOVER);
+                    if canvas.scroll() != 0.0 {
+                        nudge = Some((index, canvas.scroll().round() as f64));
+                    }
+                }
+                // ratios are given above the first degree, so it has none
+                canvas.context_menu(WidgetId::new(("degree menu", index)), |menu| {
+                    if menu.item("Reset to 12-TET value") {
+                        let semitones = (degree.cents / 100.0).round() * 100.0;
+                        nudge = Some((index, semitones - degree.cents));
+                    }
+                    if menu.item_enabled("Enter as ratio", index > 0) {
+                        self.entering_ratio = Some((index, String::new()));
+                        self.renaming = None;
+                    }
+                    menu.separator();
+                    if menu.item_enabled("Delete degree", degrees > 1) {
+                        delete = Some(index);
+                    }
+                });
+                // degrees named after a note are shown in the naming scheme,
+                // and in Sagittal and HEJI so are the ones named after their
+                // pitch, from C at 300 cents above the reference
+                let cents = degree.cents - 300.0;
+                let note = match NamingScheme::English.parse_note(&degree.name) {
+                    Some((note, "")) => Some(note),
+                    _ => None,
+                };
+                let named_by_pitch =
+                    note.is_some() || degree.name == tuning::degree_name(cents, Notation::Arrows);
+                let renamed = match notation {
+                    _ if !named_by_pitch => None,
+                    Notation::Arrows => None,
+                    Notation::Sagittal => Some(match note {
+                        Some(note) => tuning::sagittal_name(note, cents),
+                        None => tuning::degree_name(cents, notation),
+                    }),
+                    Notation::Heji => {
+                        let heji = degree.ratio.and_then(|ratio| tuning::heji_name(ratio, one));
+                        Some(match (heji, note) {
+                            (Some(heji), _) => heji,
+                            (None, Some(note)) => tuning::cents_name(note, cents),
+                            (None, None) => tuning::degree_name(cents, notation),
+                        })
+                    }
+                };
+                if canvas.hover() {
+                    // in the display octave, from the octave of its nearest
+                    // semitone above C
+                    let label = renamed.as_deref().unwrap_or(&degree.name);
+                    let mut label = naming.localize(label).to_string();
+                    if named_by_pitch {
+                        label += &octaves.octave(DISPLAY_OCTAVE).to_string();
+                    }
+                    let octave = ((cents / 100.0).round() as i32).div_euclid(12);
+                    let cents = degree.cents - 1200.0 * (octave + 5 - DISPLAY_OCTAVE) as f64;
+                    let text = describe(&label, cents, self.tunings.active());
+                    canvas.status(&text);
+                    if canvas.tooltip_register(WidgetId::new(("degree", index))) {
+                        canvas.tooltip_show(&text);
+                    }
+                }
+                let name = match (renamed, note) {
+                    (Some(name), _) => naming.localize(&name).to_string(),
+                    (None, Some(note)) if show_offsets => {
+                        let off = (cents - note.pc() as f64 * 100.0 + 600.0).rem_euclid(1200.0);
+                        let off = tuning::superscript_cents(off - 600.0);
+                        format!("{}{off}", naming.note(note))
+                    }
+                    (None, Some(note)) if naming != NamingScheme::English => {
+                        naming.note(note).to_string()
+                    }
+                    _ => degree.name.clone(),
+                };
+                match &mut self.entering_ratio {
+                    Some((entering, text)) if *entering == index => {
+                        text.extend(canvas.characters_typed());
+                        if canvas.key_pressed(KeyCode::Backspace) {
+                            text.pop();
+                        }
+                        if canvas.key_pressed(KeyCode::Enter) {
+                            ratio_entered = Some((index, text.parse::<Ratio>()));
+                        }
+                        canvas.text(&format!("{name} {text}_"));
+                    }
+                    _ => canvas.text(&format!("{name} {:>4.0}", degree.cents)),
+                }
+            });
+        });
+        if let Some((i, ratio)) = ratio_entered {
+            self.entering_ratio = None;
+            match ratio {
+                Ok(ratio) => self.tunings.edit(|tuning| {
+                    tuning.degrees[i].cents = tuning.degrees[0].cents + ratio.cents();
+                    tuning.degrees[i].ratio = Some(ratio);
+                }),
+                Err(err) => notify::warn!("could not set the degree: {err}"),
+            }
+        }
+        if let Some(i) = delete {
+            self.tunings.edit(|tuning| {
+                tuning.degrees.remove(i);
+            });
+            self.entering_ratio = None;
+        }
+        if let Some((i, cents)) = nudge {
+            // a nudged degree is no longer at its ratio
+            self.tunings.edit(|tuning| {
+                tuning.degrees[i].cents += cents;
+                tuning.degrees[i].ratio = None;
+            });
+        }
+
+        canvas.cut_top(height, |canvas| {
+            canvas.visuals.dir = CutDir::Horizontal;
+            if canvas.button("Copy as .scl") {
+                self.clipboard = Some(self.tunings.active().to_scala());
+            }
+            if canvas.button("Export CSV") {
+                let tuning = self.tunings.active();
+                let path = tuning_csv_path(tuning);
+                match fs::write(&path, export::tuning_csv(tuning)) {
+                    Ok(()) => notify::info!("exported '{}'", path.display()),
+                    Err(err) => notify::error!("could not export '{}': {err}", path.display()),
+                }
+            }
+        });
+    }
+
+    /// The chord the Explore tab shows the details of: the selected chord,
+    /// or in polychord mode the selected chord over the lower one.
+    fn explored_chord(&self) -> Option<Chord> {
+        let (col, row) = self.selected?;
+        let upper = grid_chord(col, row)?;
+        let lower = self.lower.and_then(|(col, row)| grid_chord(col, row));
+        match (self.explore_mode, lower) {
+            (ExploreMode::Polychord, Some(lower)) => Some(theory::polychord(&upper, &lower)),
+            _ => Some(upper),
+        }
+    }
+
+    /// Chords that could stand in for the explored one in the key, one per
+    /// row with its degree, its notes with those in common in green, and how
+    /// dissonant it is. The first is picked when the panel opens.
+    fn draw_reharmonization(&mut self, canvas: &mut Canvas) {
+        let Some(chord) = self.explored_chord() else {
+            self.reharmonizing = false;
+            return;
+        };
+        let alternatives = theory::reharmonization(&chord, &self.key);
+        let (spelling, naming) = (self.spelling(), self.naming);
+        let symbol = |chord: &Chord| naming.localize(&chord.symbol(spelling)).to_string();
+
+        // degree, symbol, up to four notes, then the meter
+        const NUMERAL: i32 = 7;
+        const METER: i32 = 8;
+        let note = naming.widest_note() + 1;
+        let columns = [NUMERAL, note + 3, 4 * note, METER];
+        let (font_width, font_height) = (canvas.visuals.font_width(), canvas.visuals.font_height());
+        // the modal's border, title, buttons and the blank lines around them
+        let rows = (canvas.rect.height / font_height.max(1) - 6).clamp(1, 12);
+        let rows = alternatives.len().min(rows as usize);
+
+        canvas.modal(columns.iter().sum(), rows as i32 + 4, |canvas| {
+            canvas.text(&format!("Reharmonize {}", symbol(&chord)));
+            canvas.cut_top(font_height, |_| {});
+            for (i, alternative) in alternatives.iter().take(rows).enumerate() {
+                canvas.cut_top(font_height, |canvas| {
+                    if canvas.clicked() {
+                        self.reharmonized = i;
+                    }
+                    if canvas.hover() {
+                        canvas.fill(HOVER);
+                    }
+                    if i == self.reharmonized {
+                        canvas.outline(canvas.visuals.color);
+                    }
+                    canvas.visuals.dir = CutDir::Horizontal;
+                    canvas.cut(columns[0] * font_width, font_height, |canvas| {
+                        canvas.text(&theory::roman_numeral(alternative, &self.key));
+                    });
+                    canvas.cut(columns[1] * font_width, font_height, |canvas| {
+                        canvas.text(&symbol(alternative));
+                    });
+                    canvas.cut(columns[2] * font_width, font_height, |canvas| {
+                        for pc in alternative.notes() {
+                            let color = canvas.visuals.color;
+                            if chord.contains(pc) {
+                                canvas.visuals.color = [96, 208, 96, 255];
+                            }
+                            let name = pitch_name(pc, spelling);
+                            canvas.text_fmt(format_args!("{} ", naming.localize(&name)));
+                            canvas.visuals.color = color;
+                        }
+                    });
+                    canvas.cut_remaining(|canvas| {
+                        VuMeter {
+                            level: theory::dissonance(alternative),
+                        }
+                        .draw(canvas);
+                    });
+                });
+            }
+            canvas.cut_top(font_height, |_| {});
+            canvas.cut_top(font_height, |canvas| {
+                canvas.visuals.dir = CutDir::Horizontal;
+                let picked = alternatives.get(self.reharmonized);
+                if let Some(picked) = picked {
+                    if canvas.button("Add to progression") {
+                        self.progression.push(picked.clone());
+                    }
+                }
+                if canvas.button("Close") {
+                    self.reharmonizing = false;
+                }
+            });
+        });
+    }
+
+    fn draw_explore(&mut self, canvas: &mut Canvas) {
+        let spelling = self.spelling();
+        let naming = self.naming;
+        let offsets = self.cent_offsets();
+        let name = |pc: u8| naming.localize(&pitch_name(pc, spelling)).to_string();
+        let (tuning, octaves) = (self.tunings.active(), self.octaves);
+
+        if canvas.action(Action::Copy) {
+            if let Some((col, row)) = self.selected {
+                self.clipboard = Some(chord_symbol(col, row));
+            }
+        }
+        if self.reharmonizing {
+            self.draw_reharmonization(canvas);
+            return;
+        }
+
+        canvas.cut_top(canvas.visuals.font_height(), |canvas| {
+            canvas.visuals.dir = CutDir::Horizontal;
+            canvas.tabs(&mut self.explore_mode);
+        });
+        let polychords = self.explore_mode == ExploreMode::Polychord;
+
+        // columns of the grid, as indices into ROOTS
+        let mut columns = [0; ROOTS.len()];
+        let mut shown = 0;
+        for (i, &(_, _, direction)) in ROOTS.iter().enumerate() {
+            if self.shows_root(direction) {
+                columns[shown] = i;
+                shown += 1;
             }
         }
+        let columns = &columns[..shown];
+
+        // the block is drawn smaller rather than past the window edges, and
+        // each cell fits a root with two accidentals and a quality, while the
+        // block stays wide enough for the buttons under it and tall enough
+        // for the most lines of details a chord has, those of a polychord,
+        // with room for its spectrum under them
+        const DETAILS: i32 = 14;
+        let text_size = canvas.visuals.text_size;
+        let width = (columns.len() as i32 * (naming.widest_note() + 2)).max(36);
+        canvas.shrink_to_fit(width, QUALITIES.len() as i32 + DETAILS);
+
+        // the keyboard moves a focus ring over the grid, and Enter selects
+        let shown_cell = |(col, row): (usize, usize)| {
+            Some((columns.iter().position(|&shown| shown == col)?, row))
+        };
+        let start = self.selected.and_then(shown_cell);
+        let rows = QUALITIES.len();
+        self.grid_nav
+            .handle_keys(&canvas.events, columns.len(), rows, rows, start);
+        // columns go when the notation hides their roots
+        let nav = &mut self.grid_nav;
+        nav.focused = nav.focused.filter(|&(col, _)| col < columns.len());
+        if let Some((col, row)) = self.grid_nav.activated(&canvas.events) {
+            self.selected = Some((columns[col], row));
+        }
+        let focused = self.grid_nav.focused.map(|(col, row)| (columns[col], row));
+
+        // the divider between the grid and the details drags to give either
+        // more room, starting with a line for each grid row
+        let grid_height = QUALITIES.len() as i32 * canvas.visuals.font_height();
+        let details_height = DETAILS * canvas.visuals.font_height();
+        let split = grid_height as f32 / (grid_height + details_height) as f32;
+        canvas.center(
+            width * canvas.visuals.font_width(),
+            grid_height + DIVIDER * text_size + details_height,
+            |canvas| {
+                canvas.split_pane(WidgetId::new("explore split"), split, |canvas, pane| {
+                    if pane == Pane::First {
+                        canvas.profile_scope("chord grid", |canvas| {
+                            let hovered = canvas.hover_grid_cell(columns.len(), QUALITIES.len());
+                            let hovered = hovered.map(|(col, row)| (columns[col], row));
+                            canvas.grid(columns.len(), QUALITIES.len(), |canvas, col, row| {
+                                let col = columns[col];
+                                let root = naming.localize(ROOTS[col].0);
+                                if canvas.hover() {
+                                    let notes = grid_chord(col, row).map(|chord| {
+                                        let names = chord.notes().map(name);
+                                        names.collect::<Vec<_>>().join(" ")
+                                    });
+                                    let mut text = notes.unwrap_or("Not in 12-TET".to_string());
+                                    if let Ok(note) = ROOTS[col].0.parse::<NoteName>() {
+                                        let root = Pitch {
+                                            note,
+                                            octave: DISPLAY_OCTAVE,
+                                        };
+                                        let root = describe_pitch(root, tuning, naming, octaves);
+                                        text = format!("{text} · {root}");
+                                    }
+                                    let symbol = QUALITIES[row].symbol();
+                                    canvas.status(&format!("{root}{symbol}: {text}"));
+                                    if canvas.tooltip_register(WidgetId::new(("explore", col, row)))
+                                    {
+                                        canvas.tooltip_show(&text);
+                                    }
+                                }
+                                if canvas.clicked() {
+                                    self.selected = Some((col, row));
+                                    self.grid_nav.focused = None;
+                                }
+                                if polychords && canvas.mouse_right() {
+                                    self.lower = Some((col, row));
+                                }
+                                // there is no playback or favorites list yet
+                                let id = WidgetId::new(("explore menu", col, row));
+                                canvas.context_menu(id, |menu| {
+                                    menu.item_enabled("Play", false);
+                                    menu.item_enabled("Add to favorites", false);
+                                    menu.separator();
+                                    let chord = grid_chord(col, row);
+                                    if menu.item_enabled("Add to progression", chord.is_some()) {
+                                        self.progression.extend(chord);
+                                    }
+                                    if menu.item("Copy symbol") {
+                                        self.clipboard = Some(chord_symbol(col, row));
+                                    }
+                                });
+
+                                let symbol = format_args!("{root}{}", QUALITIES[row].symbol());
+                                let selected = self.selected == Some((col, row));
+                                if selected {
+                                    canvas.fill(self.theme.selection);
+                                } else if hovered == Some((col, row)) {
+                                    canvas.fill(HOVER);
+                                }
+                                // outlined before the text takes up the cell
+                                if polychords && self.lower == Some((col, row)) {
+                                    canvas.outline(canvas.visuals.color);
+                                }
+                                if focused == Some((col, row)) {
+                                    canvas.outline(FOCUS);
+                                }
+                                if selected {
+                                    canvas.visuals.color = color::contrasting(self.theme.selection);
+                                }
+                                canvas.text_fmt(symbol);
+                            });
+                        });
+                        return;
+                    }
+
+                    let Some((col, row)) = self.selected else {
+                        return;
+                    };
+
+                    let mut symbol = chord_symbol(col, row);
+                    let mut chord = grid_chord(col, row);
+                    if polychords {
+                        let lower = self.lower.and_then(|(col, row)| grid_chord(col, row));
+                        if let (Some(upper), Some(lower)) = (&chord, &lower) {
+                            symbol = Chord::to_polychord_string(upper, lower);
+                            let stacked = theory::polychord(upper, lower);
+                            // the chords it sounds closest to, after the symbol
+                            let notes = stacked.notes().collect::<Vec<_>>();
+                            let closest = theory::chord_from_pitch_classes(&notes, None);
+                            let closest = closest.iter().take(3).map(|(chord, _)| {
+                                naming.localize(&chord.symbol(spelling)).to_string()
+                            });
+                            let closest = closest.collect::<Vec<_>>().join(" ");
+                            let symbol = naming.localize(&symbol);
+                            canvas.text_fmt(format_args!("{symbol} ≈ {closest}"));
+                            chord = Some(stacked);
+                        } else {
+                            canvas.text("Right click to pick the lower chord");
+                        }
+                    }
+
+                    // the tones in the tuning, for the spectrum under the buttons
+                    let mut frequencies = Vec::new();
+                    let mut names = Vec::new();
+                    if let Some(chord) = &chord {
+                        // each tone describes its pitch, from the root up
+                        let notes = chord.note_names(spelling);
+                        let root = Pitch {
+                            note: notes[0],
+                            octave: DISPLAY_OCTAVE,
+                        };
+                        let pitches: Vec<Pitch> = notes
+                            .iter()
+                            .zip(&chord.intervals)
+                            .map(|(&note, &i)| Pitch::with_note(note, root.to_midi() + i as i32))
+                            .collect();
+                        for pitch in &pitches {
+                            frequencies.push(pitch.frequency(tuning));
+                            names.push(pitch.name(naming, octaves));
+                        }
+                        canvas.cut_top(canvas.visuals.font_height(), |canvas| {
+                            canvas.visuals.dir = CutDir::Horizontal;
+                            for (i, (&note, &pitch)) in notes.iter().zip(&pitches).enumerate() {
+                                let off =
+                                    offsets[note.pc() as usize].map(tuning::superscript_cents);
+                                let mut text =
+                                    format!("{}{}", naming.note(note), off.unwrap_or_default());
+                                if i + 1 < notes.len() {
+                                    text.push(' ');
+                                }
+                                let width =
+                                    canvas.visuals.font.len(&text) * canvas.visuals.font_width();
+                                canvas.cut(width, canvas.visuals.font_height(), |canvas| {
+                                    if canvas.tooltip_register(WidgetId::new(("tone", i))) {
+                                        canvas.tooltip_show(&describe_pitch(
+                                            pitch, tuning, naming, octaves,
+                                        ));
+                                    }
+                                    canvas.text(&text);
+                                });
+                            }
+                        });
+                        // dimmed, the interval up to each tone from the one
+                        // below it as spelled, and its size in the tuning
+                        let color = canvas.visuals.color;
+                        canvas.visuals.color = [128, 128, 128, 255];
+                        canvas.text(&describe_steps(&pitches, tuning));
+                        canvas.visuals.color = color;
+                        canvas.text(&theory::chord_formula_string(chord));
+
+                        // as a pitch-class set in the steps of an equal tuning,
+                        // with a Forte number in 12
+                        let n = tuning.divisions().unwrap_or(12);
+                        let one = tuning.degrees.first().map_or(300.0, |degree| degree.cents);
+                        let step = |pc: u8| {
+                            let steps = (pc as f64 * 100.0 + 300.0 - one) * n as f64 / 1200.0;
+                            (steps.round() as i64).rem_euclid(n as i64) as u32
+                        };
+                        let set: Vec<u32> = chord.notes().map(step).collect();
+                        let vector = interval_vector(&set, n);
+                        let mut line = format!(
+                            "[{}] ({})",
+                            pcset_string(&normal_order(&set, n), n),
+                            pcset_string(&prime_form(&set, n), n)
+                        );
+                        if let Some(forte) = forte_number(&set).filter(|_| n == 12) {
+                            line += &format!(" {forte}");
+                        }
+                        line += &format!(" <{}>", digits(&vector));
+                        canvas.cut_top(canvas.visuals.font_height(), |canvas| {
+                            if canvas.tooltip_register(WidgetId::new("pcset")) {
+                                let pairs = describe_interval_vector(&vector);
+                                canvas.tooltip_show(&format!("Pairs of notes: {pairs}"));
+                            }
+                            canvas.text(&line);
+                        });
+
+                        let fit = theory::scale_compatibility(chord, &self.key);
+                        let mut fit = format!("{:.0}% in key", fit * 100.0);
+                        let avoid = theory::avoid_notes(chord, &self.key);
+                        if !avoid.is_empty() {
+                            let avoid = avoid.iter().map(|&pc| name(pc));
+                            fit += &format!(", avoid {}", avoid.collect::<Vec<_>>().join(" "));
+                        }
+                        canvas.text(&fit);
+
+                        // diatonic approaches, then the chromatic ones in grey
+                        let approaches = theory::approach_notes(chord, &self.key);
+                        let (diatonic, chromatic): (Vec<u8>, Vec<u8>) =
+                            approaches.iter().partition(|&&pc| self.key.contains(pc));
+                        let grey = [128, 128, 128, 255];
+                        let groups = [
+                            ("Diatonic", diatonic, None),
+                            ("Chromatic", chromatic, Some(grey)),
+                        ];
+                        for (label, approaches, color) in groups {
+                            if approaches.is_empty() {
+                                continue;
+                            }
+                            let approaches = approaches.iter().map(|&pc| name(pc));
+                            let line = format!(
+                                "{label} approach {}",
+                                approaches.collect::<Vec<_>>().join(" ")
+                            );
+                            let previous = canvas.visuals.color;
+                            canvas.visuals.color = color.unwrap_or(previous);
+                            canvas.text(&line);
+                            canvas.visuals.color = previous;
+                        }
+
+                        if let Some((leading, tonic)) =
+                            theory::leading_tone_resolution(chord, &self.key)
+                        {
+                            canvas.cut_top(canvas.visuals.font_height(), |canvas| {
+                                canvas.visuals.dir = CutDir::Horizontal;
+                                canvas.text(&format!("Leading tone {} ", name(leading)));
+                                let width = 2 * canvas.visuals.font_width();
+                                let height = canvas.visuals.font_height();
+                                canvas.cut(width, height, |canvas| canvas.arrow());
+                                canvas.text(&format!(" {}", name(tonic)));
+                            });
+                        }
+                    }
+
+                    if chord.is_some() {
+                        canvas.cut_top(canvas.visuals.font_height(), |canvas| {
+                            canvas.visuals.dir = CutDir::Horizontal;
+                            if canvas.button("Reharmonize") {
+                                self.reharmonizing = true;
+                                self.reharmonized = 0;
+                            }
+                        });
+                    }
+                    canvas.cut_top(canvas.visuals.font_height(), |canvas| {
+                        canvas.visuals.dir = CutDir::Horizontal;
+                        if canvas.button("Copy") {
+                            self.clipboard = Some(match &chord {
+                                Some(chord) => export::chord_text(&symbol, chord, spelling),
+                                None => symbol.clone(),
+                            });
+                        }
+                        if let Some(chord) = &chord {
+                            if canvas.button("Copy notes") {
+                                let tuning = self.tunings.active();
+                                self.clipboard = Some(notes_text(chord, spelling, tuning));
+                            }
+                        }
+                        let export =
+                            canvas.button("Export image") || canvas.action(Action::ExportImage);
+                        canvas.tabs(&mut self.export_scale);
+
+                        if export {
+                            let notes = chord.map(|chord| {
+                                let notes = chord.note_names(spelling);
+                                let notes = notes.iter().map(|note| note.to_string());
+                                notes.collect::<Vec<_>>().join(" ")
+                            });
+                            let (pixels, width, height) = chord_card(
+                                canvas.visuals.font,
+                                &symbol,
+                                notes.as_deref(),
+                                self.export_scale as i32 + 1,
+                            );
+
+                            let path = PathBuf::from(format!("{}.png", export::file_stem(&symbol)));
+                            match export::save_png(&path, &pixels, width, height) {
+                                Ok(()) => notify::info!("exported '{}'", path.display()),
+                                Err(err) => {
+                                    notify::error!("could not export '{}': {err}", path.display())
+                                }
+                            }
+                        }
+                    });
+
+                    if !frequencies.is_empty()
+                        && canvas.rect.height >= 4 * canvas.visuals.font_height()
+                    {
+                        let partials = theory::spectrum(&frequencies, SpectrumPlot::HARMONICS);
+                        SpectrumPlot {
+                            partials: &partials,
+                            names: &names,
+                        }
+                        .draw(canvas);
+                    }
+                });
+            },
+        );
+        canvas.visuals.text_size = text_size;
+    }
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+enum ExploreMode {
+    #[default]
+    Chord,
+    Polychord,
+}
+
+impl Tab for ExploreMode {
+    type Iterator = array::IntoIter<Self, 2>;
+    fn iter() -> Self::Iterator {
+        [ExploreMode::Chord, ExploreMode::Polychord].into_iter()
+    }
+    fn name(&self) -> &str {
+        match self {
+            ExploreMode::Chord => "Chord",
+            ExploreMode::Polychord => "Polychord",
+        }
+    }
+}
+
+/// Which spellings of a black key are used, in the Explore grid and for
+/// chord tones.
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+enum Spelling {
+    Sharps,
+    Flats,
+    /// Every root in the grid, with chord tones spelled like the key.
+    #[default]
+    Both,
+    /// Sharps or flats, whichever the key signature of the key uses.
+    Key,
+}
+
+impl Tab for Spelling {
+    type Iterator = array::IntoIter<Self, 4>;
+    fn iter() -> Self::Iterator {
+        [
+            Spelling::Sharps,
+            Spelling::Flats,
+            Spelling::Both,
+            Spelling::Key,
+        ]
+        .into_iter()
+    }
+    fn name(&self) -> &str {
+        match self {
+            Spelling::Sharps => "Sharps",
+            Spelling::Flats => "Flats",
+            Spelling::Both => "Both",
+            Spelling::Key => "Key",
+        }
+    }
+}
+
+/// Options for how notes are shown, beyond their names and spelling.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DisplayOptions {
+    /// Whether notes are followed by how many cents the active tuning puts
+    /// them off 12-TET, in superscript as in E⁻¹⁴.
+    pub show_cent_offsets: bool,
+    /// Whether the status bar is left out, to give its line to the tabs.
+    pub hide_status_bar: bool,
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+enum ModesView {
+    #[default]
+    Table,
+    Parallel,
+    Blues,
+    ToneRow,
+    Formula,
+}
+
+impl Tab for ModesView {
+    type Iterator = array::IntoIter<Self, 5>;
+    fn iter() -> Self::Iterator {
+        [
+            ModesView::Table,
+            ModesView::Parallel,
+            ModesView::Blues,
+            ModesView::ToneRow,
+            ModesView::Formula,
+        ]
+        .into_iter()
+    }
+    fn name(&self) -> &str {
+        match self {
+            ModesView::Table => "All modes",
+            ModesView::Parallel => "Parallel",
+            ModesView::Blues => "Blues",
+            ModesView::ToneRow => "Tone row",
+            ModesView::Formula => "Formula",
+        }
+    }
+}
+
+impl Tab for Notation {
+    type Iterator = array::IntoIter<Self, 3>;
+    fn iter() -> Self::Iterator {
+        Notation::ALL.into_iter()
+    }
+    fn name(&self) -> &str {
+        Notation::name(self)
+    }
+}
+
+impl Tab for OctaveConvention {
+    type Iterator = array::IntoIter<Self, 2>;
+    fn iter() -> Self::Iterator {
+        OctaveConvention::ALL.into_iter()
+    }
+    fn name(&self) -> &str {
+        OctaveConvention::name(self)
+    }
+}
+
+impl Tab for NamingScheme {
+    type Iterator = array::IntoIter<Self, 3>;
+    fn iter() -> Self::Iterator {
+        NamingScheme::ALL.into_iter()
+    }
+    fn name(&self) -> &str {
+        NamingScheme::name(self)
+    }
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+enum ExportScale {
+    #[default]
+    X1,
+    X2,
+    X3,
+    X4,
+}
+
+impl Tab for ExportScale {
+    type Iterator = array::IntoIter<Self, 4>;
+    fn iter() -> Self::Iterator {
+        [
+            ExportScale::X1,
+            ExportScale::X2,
+            ExportScale::X3,
+            ExportScale::X4,
+        ]
+        .into_iter()
+    }
+    fn name(&self) -> &str {
+        match self {
+            ExportScale::X1 => "1x",
+            ExportScale::X2 => "2x",
+            ExportScale::X3 => "3x",
+            ExportScale::X4 => "4x",
+        }
+    }
+}
+
+/// Least severe level shown in the log view.
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+enum LogLevel {
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+}
+
+impl LogLevel {
+    fn level(&self) -> Level {
+        match self {
+            LogLevel::Error => Level::Error,
+            LogLevel::Warn => Level::Warn,
+            LogLevel::Info => Level::Info,
+            LogLevel::Debug => Level::Debug,
+        }
+    }
+}
+
+impl Tab for LogLevel {
+    type Iterator = array::IntoIter<Self, 4>;
+    fn iter() -> Self::Iterator {
+        [
+            LogLevel::Error,
+            LogLevel::Warn,
+            LogLevel::Info,
+            LogLevel::Debug,
+        ]
+        .into_iter()
+    }
+    fn name(&self) -> &str {
+        match self {
+            LogLevel::Error => "Error",
+            LogLevel::Warn => "Warn",
+            LogLevel::Info => "Info",
+            LogLevel::Debug => "Debug",
+        }
+    }
+}
+
+/// Renders a chord's symbol and notes into a standalone image at `scale`,
+/// independent of the window size.
+pub fn chord_card(
+    font: &Font,
+    symbol: &str,
+    notes: Option<&str>,
+    scale: i32,
+) -> (Vec<Color>, i32, i32) {
+    let lines = [Some(symbol), notes];
+    let lines = lines.iter().flatten();
+    let columns = lines.clone().map(|line| font.len(line)).max().unwrap_or(0);
+
+    let padding = font.height * scale;
+    let content_width = columns * font.width * scale;
+    let content_height = lines.clone().count() as i32 * font.height * scale;
+    let (width, height) = (content_width + 2 * padding, content_height + 2 * padding);
+
+    let mut pixels = vec![[0, 0, 0, 0]; (width * height) as usize];
+    let mut canvas = Canvas {
+        pix: PixBuf {
+            buf: &mut pixels,
+            width,
+            height,
+        },
+        rect: Rect {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        },
+        visuals: Visuals {
+            font,
+            text_size: scale,
+            dir: CutDir::Vertical,
+            color: [255, 255, 255, 255],
+            line_spacing: 0,
+        },
+        events: Events::default(),
+        tooltip: TooltipState::default(),
+        hover: HoverState::default(),
+        repeat: RepeatState::default(),
+        menu: MenuState::default(),
+        panes: PaneState::default(),
+        status: StatusState::default(),
+    };
+
+    canvas.fill([0, 0, 0, 255]);
+    canvas.center(content_width, content_height, |canvas| {
+        for line in lines {
+            canvas.text(line);
+        }
+    });
+
+    (pixels, width, height)
+}
+
+/// The notes of a chord stacked from the bass up, under its symbol, spelled
+/// for the key by [`theory::spell_chord_in_key`].
+pub struct ChordVoicing<'a> {
+    pub chord: &'a Chord,
+    pub key: &'a Scale,
+    pub spelling: AccidentalPreference,
+    pub naming: NamingScheme,
+    pub octaves: OctaveConvention,
+}
+
+impl Widget for ChordVoicing<'_> {
+    fn draw(&mut self, canvas: &mut Canvas) {
+        canvas.visuals.dir = CutDir::Vertical;
+        let symbol = self.chord.symbol(self.spelling);
+        canvas.text_fmt(format_args!("{}", self.naming.localize(&symbol)));
+        // the root in the octave below middle C, as in LilyPond exports
+        let pitches = theory::voice_chord_in_key(self.chord, self.key, 3);
+        for pitch in pitches.iter().rev() {
+            canvas.text(&pitch.name(self.naming, self.octaves));
+        }
+    }
+}
+
+/// The twelve-tone matrix of a row, as in [`ToneRow::matrix`], with the
+/// primes labeled P down the left, the retrogrades R down the right, the
+/// inversions I along the top and the retrograde inversions RI along the
+/// bottom. Forms are numbered by semitones above the first note of the row,
+/// with the numbers of the columns alone under the I and over the RI.
+pub struct ToneRowMatrix<'a> {
+    pub row: &'a ToneRow,
+    pub spelling: AccidentalPreference,
+    pub naming: NamingScheme,
+}
+
+impl Widget for ToneRowMatrix<'_> {
+    fn draw(&mut self, canvas: &mut Canvas) {
+        const LABEL: i32 = 5;
+        let matrix = self.row.matrix();
+        let first = self.row.notes()[0];
+        let number = |pc: u8| theory::interval_between(first, pc);
+        let cell = self.naming.widest_note() + 1;
+        let columns = 2 * LABEL + 12 * cell;
+        canvas.shrink_to_fit(columns, 14);
+        let (font_width, font_height) = (canvas.visuals.font_width(), canvas.visuals.font_height());
+        let (label_width, cell_width) = (LABEL * font_width, cell * font_width);
+
+        let label = |canvas: &mut Canvas, width: i32, text: std::fmt::Arguments| {
+            canvas.cut(width, font_height, |canvas| {
+                canvas.visuals.color = [128, 128, 128, 255];
+                canvas.text_fmt(text);
+            });
+        };
+        let columns_labeled = |canvas: &mut Canvas, prefix: &str| {
+            canvas.cut_top(font_height, |canvas| {
+                canvas.visuals.dir = CutDir::Horizontal;
+                label(canvas, label_width, format_args!("{prefix}"));
+                for &pc in matrix[0].notes() {
+                    label(canvas, cell_width, format_args!("{}", number(pc)));
+                }
+            });
+        };
+
+        canvas.center(columns * font_width, 14 * font_height, |canvas| {
+            columns_labeled(canvas, "I");
+            for row in &matrix {
+                let n = number(row.notes()[0]);
+                canvas.cut_top(font_height, |canvas| {
+                    canvas.visuals.dir = CutDir::Horizontal;
+                    label(canvas, label_width, format_args!("P{n}"));
+                    for &pc in row.notes() {
+                        canvas.cut(cell_width, font_height, |canvas| {
+                            let name = NoteName::new(pc, self.spelling);
+                            canvas.text_fmt(format_args!("{}", self.naming.note(name)));
+                        });
+                    }
+                    label(canvas, label_width, format_args!(" R{n}"));
+                });
+            }
+            columns_labeled(canvas, "RI");
+        });
+    }
+}
+
+/// Chords side by side as voicings, with an arrow between each pair marked
+/// with how far each voice moves, and a button under each to remove it.
+pub struct Progression<'a> {
+    pub chords: &'a [Chord],
+    pub key: &'a Scale,
+    pub spelling: AccidentalPreference,
+    pub naming: NamingScheme,
+    pub octaves: OctaveConvention,
+    /// Chords can be dragged to another place in the progression.
+    pub drag: &'a mut DragReorder,
+    /// Index of the chord whose remove button was clicked.
+    pub removed: Option<usize>,
+    /// The chord dragged and the index it was dropped at.
+    pub moved: Option<(usize, usize)>,
+    annotations: Vec<ProgressionAnnotation>,
+}
+
+impl<'a> Progression<'a> {
+    /// Width of a chord column, and of the arrows between them, in characters.
+    const COLUMN: i32 = 5;
+
+    pub fn new(
+        chords: &'a [Chord],
+        key: &'a Scale,
+        spelling: AccidentalPreference,
+        naming: NamingScheme,
+        octaves: OctaveConvention,
+        drag: &'a mut DragReorder,
+    ) -> Self {
+        Self {
+            chords,
+            key,
+            spelling,
+            naming,
+            octaves,
+            drag,
+            removed: None,
+            moved: None,
+            annotations: theory::analyze_progression(chords, key),
+        }
+    }
+
+    fn draw_voicing(&self, canvas: &mut Canvas, chord: &Chord) {
+        let notes = chord.notes().count() as i32 + 1;
+        canvas.cut_top(notes * canvas.visuals.font_height(), |canvas| {
+            ChordVoicing {
+                chord,
+                key: self.key,
+                spelling: self.spelling,
+                naming: self.naming,
+                octaves: self.octaves,
+            }
+            .draw(canvas);
+        });
+    }
+
+    /// The chord's numeral, function and tendency, and how many voice
+    /// leading issues lead into it, at a smaller text size. Hovering the
+    /// issues lists them in the status bar.
+    fn draw_annotation(&self, canvas: &mut Canvas, annotation: &ProgressionAnnotation) {
+        let size = (canvas.visuals.text_size - 1).max(1);
+        let line = canvas.visuals.font.height * size;
+        canvas.cut_top(4 * line, |canvas| {
+            canvas.visuals.text_size = size;
+            let tendency = match annotation.tendency {
+                TendencyDirection::Up => "↑",
+                TendencyDirection::Down => "↓",
+                TendencyDirection::Neutral => "-",
+            };
+            let lines = [
+                annotation.roman_numeral.as_str(),
+                annotation.function.name(),
+                tendency,
+            ];
+            for text in lines {
+                canvas.cut_top(line, |canvas| canvas.text_truncated(text));
+            }
+
+            let issues = &annotation.voice_leading_issues;
+            if issues.is_empty() {
+                return;
+            }
+            if canvas.hover() {
+                canvas.status(&issues.join("; "));
+            }
+            canvas.visuals.color = [255, 96, 96, 255];
+            let s = if issues.len() == 1 { "" } else { "s" };
+            canvas.text_truncated(&format!("{} issue{s}", issues.len()));
+        });
+    }
+}
+
+impl Widget for Progression<'_> {
+    fn draw(&mut self, canvas: &mut Canvas) {
+        let width = Self::COLUMN * canvas.visuals.font_width();
+        let height = canvas.rect.height;
+        // a chord and the arrow after it
+        let (stride, len) = (2 * width, self.chords.len());
+        let (left, top) = (canvas.rect.x, canvas.rect.y);
+        self.moved = self.drag.update(canvas, |(x, _)| x, stride, len);
+
+        // the chords in the order they would be in if the lifted one dropped
+        let order: Vec<_> = (0..len)
+            .map(|slot| self.drag.item_at(slot, stride, len))
+            .collect();
+        canvas.visuals.dir = CutDir::Horizontal;
+        for (slot, &index) in order.iter().enumerate() {
+            if slot > 0 {
+                canvas.cut(width, height, |canvas| {
+                    let (Some(a), Some(b)) = (order[slot - 1], index) else {
+                        return;
+                    };
+                    let pair = [self.chords[a].clone(), self.chords[b].clone()];
+                    let smoothness = theory::voice_leading_smoothness(&pair);
+                    canvas.visuals.dir = CutDir::Vertical;
+                    canvas.text(&format!("{smoothness:.2}"));
+                    canvas.cut_top(canvas.visuals.font_height(), |canvas| canvas.arrow());
+                });
+            }
+            canvas.cut(width, height, |canvas| {
+                let Some(i) = index else {
+                    // the gap the lifted chord would drop into
+                    return;
+                };
+                canvas.visuals.dir = CutDir::Vertical;
+                self.drag.press_on(canvas, i, |(x, _)| x);
+                self.draw_voicing(canvas, &self.chords[i]);
+                self.draw_annotation(canvas, &self.annotations[i]);
+                if canvas.button("×") {
+                    self.removed = Some(i);
+                    self.drag.cancel();
+                }
+            });
+        }
+
+        if let Some(from) = self.drag.lifted() {
+            let pad = canvas.visuals.text_size * 2;
+            let notes = self.chords[from].notes().count() as i32 + 1;
+            let rect = Rect {
+                x: left + from as i32 * stride + self.drag.offset(),
+                y: top + pad,
+                width: width + 2 * pad,
+                height: notes * canvas.visuals.font_height() + 2 * pad,
+            };
+            canvas.overlay(rect, |canvas| {
+                canvas.clear_rect();
+                canvas.outline(canvas.visuals.color);
+                canvas.padded(pad, pad, |canvas| {
+                    self.draw_voicing(canvas, &self.chords[from]);
+                });
+            });
+        }
+    }
+}
+
+/// The harmonics of each tone of a chord as lines on a log-frequency axis,
+/// taller for lower harmonics and one color per tone, with a few reference
+/// frequencies labelled under them. Pairs of lines that beat roughly, as by
+/// [`theory::rough_pairs`], are red. Hovering a line tells which tone and
+/// harmonic it is.
+pub struct SpectrumPlot<'a> {
+    /// As by [`theory::spectrum`].
+    pub partials: &'a [Partial],
+    /// Names of the tones the partials are harmonics of, by index.
+    pub names: &'a [String],
+}
+
+impl SpectrumPlot<'_> {
+    /// Harmonics shown of each tone.
+    pub const HARMONICS: u32 = 12;
+
+    /// Colors of the lines of the tones, from the root up, repeating.
+    const COLORS: [Color; 6] = [
+        [96, 160, 255, 255],
+        [96, 208, 96, 255],
+        [255, 192, 0, 255],
+        [208, 128, 255, 255],
+        [0, 208, 208, 255],
+        [255, 160, 96, 255],
+    ];
+
+    /// Frequencies labelled on the axis, when they fall in the plot.
+    const REFERENCES: [(f64, &'static str); 7] = [
+        (100.0, "100"),
+        (200.0, "200"),
+        (500.0, "500"),
+        (1000.0, "1k"),
+        (2000.0, "2k"),
+        (5000.0, "5k"),
+        (10000.0, "10k"),
+    ];
+}
+
+impl Widget for SpectrumPlot<'_> {
+    fn draw(&mut self, canvas: &mut Canvas) {
+        let (Some(lowest), Some(highest)) = (self.partials.first(), self.partials.last()) else {
+            return;
+        };
+        // a sixth of an octave of room on either side
+        let margin = 2f64.powf(1.0 / 6.0);
+        let (low, high) = (lowest.frequency / margin, highest.frequency * margin);
+        let font_height = canvas.visuals.font_height();
+        let plot = Rect {
+            height: canvas.rect.height - font_height,
+            ..canvas.rect
+        };
+        let x_of = |hz: f64| {
+            let along = (hz / low).ln() / (high / low).ln();
+            plot.x + (along * (plot.width - 1) as f64).round() as i32
+        };
+        let bottom = plot.y + plot.height - 1;
+        let dim = [128, 128, 128, 255];
+        canvas
+            .pix
+            .draw_line(plot.x, bottom, plot.x + plot.width - 1, bottom, dim);
+
+        // labels centered under their ticks, leaving out those that overlap
+        let mut labelled_to = i32::MIN;
+        for (hz, label) in Self::REFERENCES {
+            if !(low..=high).contains(&hz) {
+                continue;
+            }
+            let x = x_of(hz);
+            let width = canvas.visuals.font.len(label) * canvas.visuals.font_width();
+            let left = (x - width / 2).clamp(plot.x, plot.x + plot.width - width);
+            if left <= labelled_to {
+                continue;
+            }
+            labelled_to = left + width;
+            let tick = canvas.visuals.text_size * 2;
+            canvas.pix.draw_line(x, bottom, x, bottom - tick, dim);
+            let rect = Rect {
+                x: left,
+                y: plot.y + plot.height,
+                width,
+                height: font_height,
+            };
+            canvas.with_rect(rect, |canvas| {
+                canvas.visuals.color = dim;
+                canvas.text(label);
+            });
+        }
+
+        let mut rough = vec![false; self.partials.len()];
+        for (a, b) in theory::rough_pairs(self.partials) {
+            (rough[a], rough[b]) = (true, true);
+        }
+        let cursor = canvas.hover_cursor();
+        let mut hovered = None;
+        for (i, partial) in self.partials.iter().enumerate() {
+            let x = x_of(partial.frequency);
+            let tallest = plot.height - 1 - canvas.visuals.text_size * 2;
+            let height = tallest as f64 / (partial.harmonic as f64).sqrt();
+            let color = match rough[i] {
+                true => [255, 96, 96, 255],
+                false => Self::COLORS[partial.note % Self::COLORS.len()],
+            };
+            let top = bottom - height.round() as i32;
+            canvas.pix.draw_line(x, bottom, x, top, color);
+            if cursor.is_some_and(|(cx, _)| (cx - x).abs() <= canvas.visuals.text_size) {
+                hovered = hovered.or(Some(i));
+            }
+        }
+
+        if let Some(i) = hovered {
+            let partial = self.partials[i];
+            let name = self.names.get(partial.note).map_or("?", String::as_str);
+            let text = format!(
+                "{name} harmonic {}, {:.1} Hz",
+                partial.harmonic, partial.frequency
+            );
+            canvas.status(&text);
+            if canvas.tooltip_register(WidgetId::new(("partial", i))) {
+                canvas.tooltip_show(&text);
+            }
+        }
+    }
+}
+
+/// Shown while a file is dragged over the window.
+pub struct DropOverlay(pub FileKind);
+
+impl Widget for DropOverlay {
+    fn draw(&mut self, canvas: &mut Canvas) {
+        let label = format!("Drop to load {}", self.0.name());
+        canvas.fill([0, 0, 0, 255]);
+        canvas.center(
+            canvas.visuals.font.len(&label) * canvas.visuals.font_width(),
+            canvas.visuals.font_height(),
+            |canvas| canvas.text(&label),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn copied_notes_give_the_cents_of_the_tuning() {
+        let major = Chord::new(0, ChordQuality::Major);
+        let twelve = Tuning::default();
+        assert_eq!(
+            notes_text(&major, AccidentalPreference::Sharps, &twelve),
+            "C – E – G (0¢, +400¢, +700¢)"
+        );
+        let just = Tuning::from_scala("just", "just\n3\n5/4\n3/2\n2/1\n").unwrap();
+        assert_eq!(
+            notes_text(&major, AccidentalPreference::Sharps, &just),
+            "C – E – G (0¢, +386¢, +702¢)"
+        );
+        let minor = Chord::new(1, ChordQuality::Minor);
+        assert_eq!(
+            notes_text(&minor, AccidentalPreference::Sharps, &twelve),
+            "C♯ – E – G♯ (0¢, +300¢, +700¢)"
+        );
+    }
+
+    #[test]
+    fn intervals_are_described_by_the_nearest_ratio() {
+        assert_eq!(describe_interval(0.0), "0¢ ≈ 1/1");
+        assert_eq!(
+            describe_interval(Ratio::new(5, 4).unwrap().cents()),
+            "386¢ ≈ 5/4"
+        );
+        assert_eq!(describe_interval(400.0), "400¢ ≈ 5/4 +14¢");
+        assert_eq!(describe_interval(700.0), "700¢ ≈ 3/2 -2¢");
+        assert_eq!(describe_interval(1900.0), "1900¢ ≈ 3/1 -2¢");
+    }
+
+    #[test]
+    fn pitches_are_described_alike_everywhere() {
+        let e4 = Pitch {
+            note: NoteName::on_letter(2, 4),
+            octave: 4,
+        };
+        let twelve = Tuning::default();
+        let text = describe_pitch(
+            e4,
+            &twelve,
+            NamingScheme::English,
+            OctaveConvention::MiddleC4,
+        );
+        assert_eq!(text, "E4, 329.63 Hz, 400¢ ≈ 5/4 +14¢ above 1/1");
+        // as the tuning row of E gives it, 500 cents below A4
+        assert_eq!(describe("E4", -500.0, &twelve), text);
+
+        let just = Tuning::from_scala("just", "just\n3\n5/4\n3/2\n2/1\n").unwrap();
+        let text = describe_pitch(e4, &just, NamingScheme::German, OctaveConvention::MiddleC3);
+        assert_eq!(text, "E3, 327.03 Hz, 386¢ ≈ 5/4 above 1/1");
+    }
+
+    #[test]
+    fn sets_are_put_in_normal_order() {
+        assert_eq!(normal_order(&[4, 7, 0], 12), [0, 4, 7]);
+        assert_eq!(normal_order(&[7, 11, 2, 5], 12), [11, 2, 5, 7]);
+        assert_eq!(normal_order(&[0, 12, 4, 7], 12), [0, 4, 7]);
+        assert_eq!(normal_order(&[9, 0, 3, 6], 12), [0, 3, 6, 9]);
+        assert!(normal_order(&[], 12).is_empty());
+    }
+
+    #[test]
+    fn sets_are_reduced_to_prime_form() {
+        assert_eq!(prime_form(&[0, 4, 7], 12), [0, 3, 7]);
+        assert_eq!(prime_form(&[0, 3, 7], 12), [0, 3, 7]);
+        assert_eq!(prime_form(&[7, 11, 2, 5], 12), [0, 2, 5, 8]);
+        assert_eq!(prime_form(&[0, 4, 7, 11], 12), [0, 1, 5, 8]);
+        assert_eq!(prime_form(&[0, 4, 8], 12), [0, 4, 8]);
+        assert_eq!(prime_form(&[0, 1, 3, 7, 8], 12), [0, 1, 5, 6, 8]);
+    }
+
+    #[test]
+    fn interval_vectors_count_each_class() {
+        assert_eq!(interval_vector(&[0, 4, 7], 12), [0, 0, 1, 1, 1, 0]);
+        assert_eq!(
+            interval_vector(&[0, 2, 4, 5, 7, 9, 11], 12),
+            [2, 5, 4, 3, 6, 1]
+        );
+        assert_eq!(interval_vector(&[0, 3, 6, 9], 12), [0, 0, 4, 0, 0, 2]);
+        assert_eq!(
+            interval_vector(&[0, 2, 4, 6, 8, 10], 12),
+            [0, 6, 0, 6, 0, 3]
+        );
+    }
+
+    #[test]
+    fn sets_are_named_by_forte_number() {
+        assert_eq!(
+            forte_number(&prime_form(&[0, 4, 7], 12)).as_deref(),
+            Some("3-11")
+        );
+        assert_eq!(
+            forte_number(&prime_form(&[0, 3, 7], 12)).as_deref(),
+            Some("3-11")
+        );
+        assert_eq!(
+            forte_number(&prime_form(&[7, 11, 2, 5], 12)).as_deref(),
+            Some("4-27")
+        );
+        assert_eq!(
+            forte_number(&prime_form(&[0, 1, 5, 6, 8], 12)).as_deref(),
+            Some("5-20")
+        );
+        let diatonic = prime_form(&[0, 2, 4, 5, 7, 9, 11], 12);
+        assert_eq!(forte_number(&diatonic).as_deref(), Some("7-35"));
+        assert_eq!(forte_number(&[0, 4]), None);
+        assert_eq!(forte_number(&[0, 2, 4, 6, 8, 10]), None);
+    }
+
+    #[test]
+    fn sets_in_other_edos_keep_their_size() {
+        // a 31-EDO major triad, 10 and 18 steps above the root
+        assert_eq!(prime_form(&[0, 10, 18], 31), [0, 8, 18]);
+        let vector = interval_vector(&[0, 10, 18], 31);
+        assert_eq!(vector.len(), 15);
+        let ones: Vec<usize> = (0..vector.len()).filter(|&i| vector[i] == 1).collect();
+        assert_eq!(ones, [7, 9, 12]);
+        assert_eq!(vector.iter().sum::<u32>(), 3);
+    }
+
+    #[test]
+    fn tunings_are_exported_next_to_the_app_whatever_their_name() {
+        for name in ["../31-EDO", "tunings/just", "/etc/passwd", "C:\\meantone"] {
+            let tuning = Tuning {
+                name: name.to_string(),
+                ..Tuning::default()
+            };
+            let path = tuning_csv_path(&tuning);
+            assert_eq!(path.components().count(), 1, "{name}");
+            assert!(path.to_str().unwrap().ends_with(".csv"), "{name}");
+            assert!(!path.to_str().unwrap().contains(['/', '\\', ':']), "{name}");
+        }
+    }
+
+    #[test]
+    fn steps_follow_the_spelling_of_the_chord() {
+        let pitches = |names: &[&str]| -> Vec<Pitch> {
+            names.iter().map(|name| name.parse().unwrap()).collect()
+        };
+        let twelve = Tuning::default();
+        // C7♭9
+        let c7b9 = pitches(&["C4", "E4", "G4", "B♭4", "D♭5"]);
+        assert_eq!(
+            describe_steps(&c7b9, &twelve),
+            "M3 400¢  m3 300¢  m3 300¢  m3 300¢"
+        );
+        // the diminished seventh on the leading tone of A harmonic minor,
+        // and the same sizes spelled with an augmented second
+        let dim7 = pitches(&["G♯3", "B3", "D4", "F4"]);
+        assert_eq!(describe_steps(&dim7, &twelve), "m3 300¢  m3 300¢  m3 300¢");
+        let spelled = pitches(&["G♯3", "B3", "D4", "E♯4"]);
+        assert_eq!(
+            describe_steps(&spelled, &twelve),
+            "m3 300¢  m3 300¢  A2 300¢"
+        );
+        // the augmented III of A harmonic minor
+        let aug = pitches(&["C4", "E4", "G♯4"]);
+        assert_eq!(describe_steps(&aug, &twelve), "M3 400¢  M3 400¢");
+        // a lower pitch has no interval up to it
+        let skipped = pitches(&["E4", "C4", "G4"]);
+        assert_eq!(describe_steps(&skipped, &twelve), "P5 700¢");
+
+        // sizes come from the tuning, whatever the name
+        let just = Tuning::from_scala("just", "just\n3\n5/4\n3/2\n2/1\n").unwrap();
+        let triad = pitches(&["C4", "E4", "G4"]);
+        assert_eq!(describe_steps(&triad, &just), "M3 386¢  m3 316¢");
+    }
+
+    /// The status text of the [`SpectrumPlot`] of `partials` over a 120 by
+    /// 40 frame, with the cursor at `x` halfway down.
+    fn hover_spectrum(partials: &[Partial], x: i32) -> Option<String> {
+        let font = Font::from_bdf_bytes(crate::font::COZETTE, 6, 13).unwrap();
+        let (width, height) = (120, 40);
+        let mut frame = vec![0; (width * height * 4) as usize];
+        let mut canvas = Canvas {
+            pix: PixBuf::from_pixels_frame(&mut frame, width, height),
+            rect: Rect {
+                x: 0,
+                y: 0,
+                width,
+                height,
+            },
+            visuals: Visuals {
+                font: &font,
+                text_size: 1,
+                dir: CutDir::Vertical,
+                color: [255, 255, 255, 255],
+                line_spacing: 0,
+            },
+            events: Events {
+                cursor: Some((x, height / 2)),
+                ..Events::default()
+            },
+            tooltip: TooltipState::default(),
+            hover: HoverState::default(),
+            repeat: RepeatState::default(),
+            menu: MenuState::default(),
+            panes: PaneState::default(),
+            status: StatusState::default(),
+        };
+        let names = ["C4".to_string()];
+        SpectrumPlot {
+            partials,
+            names: &names,
+        }
+        .draw(&mut canvas);
+        canvas.status.text().map(str::to_string)
+    }
+
+    #[test]
+    fn hovering_a_partial_names_its_tone_and_harmonic() {
+        // a sixth of an octave of room on either side of the octave from
+        // 100 to 200 Hz puts them an eighth of the way in from each edge
+        let partials = theory::spectrum(&[100.0], 2);
+        let status = |x| hover_spectrum(&partials, x);
+        assert_eq!(status(15).as_deref(), Some("C4 harmonic 1, 100.0 Hz"));
+        assert_eq!(status(105).as_deref(), Some("C4 harmonic 2, 200.0 Hz"));
+        assert_eq!(status(60), None);
+        assert_eq!(hover_spectrum(&[], 60), None);
+    }
+
+    #[test]
+    fn the_progression_and_scrolls_survive_a_restart() {
+        let main = Main {
+            progression: vec![
+                Chord::new(9, ChordQuality::Minor),
+                Chord::new(4, ChordQuality::Dominant7),
+            ],
+            help_scroll: 3,
+            log_scroll: Some(5),
+            ..Main::default()
+        };
+        let text = main.session().serialize();
+
+        let mut restarted = Main::default();
+        restarted.restore(&Session::parse(&text).unwrap());
+        assert_eq!(restarted.progression, main.progression);
+        assert_eq!((restarted.help_scroll, restarted.log_scroll), (3, Some(5)));
+        // chords out of range are left out
+        let session = Session {
+            progression: vec![
+                Chord::new(0, ChordQuality::Major),
+                Chord {
+                    root: 12,
+                    intervals: vec![0, 4, 7],
+                    spelling: None,
+                },
+            ],
+            ..Session::default()
+        };
+        restarted.restore(&session);
+        assert_eq!(restarted.progression, [Chord::new(0, ChordQuality::Major)]);
+    }
+
+    #[test]
+    fn unsaved_tuning_edits_survive_a_restart() {
+        let mut main = Main::default();
+        main.tunings.edit(|tuning| tuning.reference_hz = 415.0);
+        main.tunings.duplicate();
+        let text = main.session().serialize();
+
+        let mut restarted = Main::default();
+        restarted.restore(&Session::parse(&text).unwrap());
+        assert_eq!(restarted.tunings.active_label(), "12-TET (2)*");
+        assert_eq!(restarted.tunings.active().reference_hz, 415.0);
+        assert_eq!(restarted.tunings.entries()[0].tuning.reference_hz, 415.0);
+        assert!(restarted.tunings.entries()[0].modified);
+    }
+
+    #[test]
+    fn pasting_a_chord_selects_it() {
+        let mut main = Main::default();
+        main.set_tab(MainTabs::Tuning);
+        assert!(main.paste(" C7\n"));
+        let session = main.session();
+        assert_eq!(session.selected, Some((0, 1)));
+        assert_eq!(session.tab.as_deref(), Some("Explore"));
+        // read in the naming scheme shown
+        main.set_naming(NamingScheme::German);
+        assert!(main.paste("Cism"));
+        assert_eq!(main.session().selected, Some((1, 2)));
+    }
+
+    #[test]
+    fn the_spelling_picks_the_roots_of_the_grid() {
+        let mut main = Main::default();
+        let shown = |main: &Main| (main.shows_root(-1), main.shows_root(1));
+        main.spelling = Spelling::Sharps;
+        assert_eq!(shown(&main), (false, true));
+        main.spelling = Spelling::Flats;
+        assert_eq!(shown(&main), (true, false));
+        main.spelling = Spelling::Both;
+        assert_eq!(shown(&main), (true, true));
+
+        // in A♭ major the key's flats win, and in E major its sharps
+        main.spelling = Spelling::Key;
+        main.key = ScaleMode::Ionian.scale(8);
+        assert_eq!(main.spelling().resolve(), AccidentalPreference::Flats);
+        assert_eq!(shown(&main), (true, false));
+        main.key = ScaleMode::Ionian.scale(4);
+        assert_eq!(main.spelling().resolve(), AccidentalPreference::Sharps);
+        assert_eq!(shown(&main), (false, true));
+    }
+
+    #[test]
+    fn pasting_other_text_keeps_the_selection() {
+        let mut main = Main::default();
+        assert!(main.paste("C"));
+        assert!(!main.paste("hello"));
+        assert!(!main.paste("Em"));
+        assert_eq!(main.session().selected, Some((0, 0)));
     }
 }
@@ -0,0 +1,198 @@
+use std::{fs::File, io::BufWriter, path::Path};
+
+use crate::{
+    theory::{AccidentalPreference, Chord},
+    tuning::Tuning,
+    Color,
+};
+
+/// Writes `pixels`, a `width` by `height` image in row-major order, as an RGBA PNG.
+pub fn save_png(
+    path: &Path,
+    pixels: &[Color],
+    width: i32,
+    height: i32,
+) -> Result<(), png::EncodingError> {
+    let file = BufWriter::new(File::create(path)?);
+    let mut encoder = png::Encoder::new(file, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(pixels.as_flattened())?;
+    writer.finish()
+}
+
+/// Turns a chord symbol into a file name stem, like "C♯m7♭5" into "Csharp-m7b5".
+pub fn file_stem(symbol: &str) -> String {
+    let quality_start = symbol
+        .char_indices()
+        .skip(1)
+        .find(|(_, c)| !matches!(c, '♯' | '#' | '♭' | 'b' | '♮' | '𝄪' | '𝄫' | '𝄲' | '𝄳'))
+        .map_or(symbol.len(), |(i, _)| i);
+    let (root, quality) = symbol.split_at(quality_start);
+
+    let mut stem = String::new();
+    for c in root.chars() {
+        match c {
+            '♯' | '#' => stem.push_str("sharp"),
+            '♭' | 'b' => stem.push_str("flat"),
+            '♮' => stem.push_str("natural"),
+            '𝄪' => stem.push_str("doublesharp"),
+            '𝄫' => stem.push_str("doubleflat"),
+            '𝄲' => stem.push_str("halfsharp"),
+            '𝄳' => stem.push_str("halfflat"),
+            c if c.is_ascii_alphanumeric() => stem.push(c),
+            _ => stem.push('_'),
+        }
+    }
+    if !quality.is_empty() {
+        stem.push('-');
+        for c in quality.chars() {
+            match c {
+                '♭' => stem.push('b'),
+                '♯' | '#' => stem.push_str("sharp"),
+                c if c.is_ascii_alphanumeric() => stem.push(c),
+                _ => stem.push('_'),
+            }
+        }
+    }
+    stem
+}
+
+/// Quotes a CSV field if it contains a comma, quote or line break.
+pub fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// A CSV table of the tuning's degrees with their cents, ratio to the
+/// reference pitch, and frequency.
+pub fn tuning_csv(tuning: &Tuning) -> String {
+    let mut csv = String::from("degree,cents,ratio,frequency\n");
+    for degree in &tuning.degrees {
+        csv.push_str(&format!(
+            "{},{:.2},{:.6},{:.3}\n",
+            csv_field(&degree.name),
+            degree.cents,
+            Tuning::ratio(degree.cents),
+            tuning.frequency(degree.cents),
+        ));
+    }
+    csv
+}
+
+/// A readable block describing a chord: its symbol, notes, intervals in
+/// semitones and the same intervals in 12-TET cents.
+pub fn chord_text(symbol: &str, chord: &Chord, spelling: AccidentalPreference) -> String {
+    let notes = chord.note_names(spelling);
+    let notes = notes.iter().map(|note| note.to_string());
+    let intervals = chord.intervals.iter().map(|i| i.to_string());
+    let cents = chord
+        .intervals
+        .iter()
+        .map(|i| (*i as u32 * 100).to_string());
+    format!(
+        "{symbol}\nNotes: {}\nIntervals: {}\nCents: {}\n",
+        notes.collect::<Vec<_>>().join(" "),
+        intervals.collect::<Vec<_>>().join(" "),
+        cents.collect::<Vec<_>>().join(" "),
+    )
+}
+
+/// A progression as a plain text chart with one chord per bar, as in
+/// "| Cmaj7 | Am7 | Dm7 | G7 |".
+pub fn progression_chart(chords: &[Chord], spelling: AccidentalPreference) -> String {
+    let mut chart = String::from("|");
+    for chord in chords {
+        chart.push_str(&format!(" {} |", chord.symbol(spelling)));
+    }
+    chart.push('\n');
+    chart
+}
+
+/// A progression as a Markdown table, with the bars numbered above the
+/// chords.
+pub fn progression_markdown(chords: &[Chord], spelling: AccidentalPreference) -> String {
+    let bars = (1..=chords.len()).map(|bar| format!(" {bar} |"));
+    let rule = chords.iter().map(|_| "---|");
+    format!(
+        "|{}\n|{}\n{}",
+        bars.collect::<String>(),
+        rule.collect::<String>(),
+        progression_chart(chords, spelling)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_stems_spell_out_accidentals() {
+        assert_eq!(file_stem("C♯m7♭5"), "Csharp-m7b5");
+        assert_eq!(file_stem("C#m7b5"), "Csharp-m7b5");
+        assert_eq!(file_stem("Bb7/C#"), "Bflat-7_Csharp");
+        assert_eq!(file_stem("C𝄲"), "Chalfsharp");
+    }
+
+    #[test]
+    fn csv_fields_are_quoted_only_when_needed() {
+        assert_eq!(csv_field("C"), "C");
+        assert_eq!(csv_field("E, just"), "\"E, just\"");
+        assert_eq!(csv_field("the \"fifth\""), "\"the \"\"fifth\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+    }
+
+    #[test]
+    fn tuning_csv_quotes_degree_names() {
+        let degree = |name: &str, cents| crate::tuning::Degree {
+            name: name.to_string(),
+            cents,
+            ratio: None,
+        };
+        let tuning = Tuning {
+            name: "Test".to_string(),
+            reference_hz: 440.0,
+            degrees: vec![degree("A", 0.0), degree("E, just", 701.955)],
+        };
+        assert_eq!(
+            tuning_csv(&tuning),
+            "degree,cents,ratio,frequency\n\
+             A,0.00,1.000000,440.000\n\
+             \"E, just\",701.96,1.500000,660.000\n"
+        );
+    }
+
+    #[test]
+    fn chord_text_lists_notes_intervals_and_cents() {
+        let chord = Chord::new(7, crate::theory::ChordQuality::Dominant7);
+        assert_eq!(
+            chord_text("G7", &chord, AccidentalPreference::Sharps),
+            "G7\nNotes: G B D F\nIntervals: 0 4 7 10\nCents: 0 400 700 1000\n"
+        );
+    }
+
+    #[test]
+    fn progression_charts_have_one_chord_per_bar() {
+        use crate::theory::ChordQuality::*;
+        let chords = [
+            Chord::new(0, Major),
+            Chord::new(9, Minor),
+            Chord::new(2, Minor),
+            Chord::new(7, Dominant7),
+        ];
+        let spelling = AccidentalPreference::Sharps;
+        assert_eq!(
+            progression_chart(&chords, spelling),
+            "| C | Am | Dm | G7 |\n"
+        );
+        assert_eq!(
+            progression_markdown(&chords, spelling),
+            "| 1 | 2 | 3 | 4 |\n|---|---|---|---|\n| C | Am | Dm | G7 |\n"
+        );
+    }
+}
@@ -0,0 +1,180 @@
+use std::{fmt, fs, io, path::Path};
+
+use crate::tuning::{ScalaError, Tuning, TuningLibrary};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    Scala,
+    Tuning,
+    Font,
+    Midi,
+}
+
+impl FileKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            FileKind::Scala => "Scala tuning",
+            FileKind::Tuning => "tuning",
+            FileKind::Font => "font",
+            FileKind::Midi => "MIDI file",
+        }
+    }
+
+    /// Guesses what a file is from its name, falling back to its first bytes.
+    pub fn detect(path: &Path) -> Option<Self> {
+        Self::from_name(path).or_else(|| {
+            let mut head = [0; 16];
+            let len = io::Read::read(&mut fs::File::open(path).ok()?, &mut head).ok()?;
+            Self::from_contents(&head[..len])
+        })
+    }
+
+    pub fn from_name(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?.to_ascii_lowercase();
+        if name.ends_with(".tuning.toml") {
+            Some(FileKind::Tuning)
+        } else if name.ends_with(".scl") {
+            Some(FileKind::Scala)
+        } else if name.ends_with(".bdf") {
+            Some(FileKind::Font)
+        } else if name.ends_with(".mid") || name.ends_with(".midi") {
+            Some(FileKind::Midi)
+        } else {
+            None
+        }
+    }
+
+    pub fn from_contents(head: &[u8]) -> Option<Self> {
+        if head.starts_with(b"STARTFONT") {
+            Some(FileKind::Font)
+        } else if head.starts_with(b"MThd") {
+            Some(FileKind::Midi)
+        } else if head.starts_with(b"!") {
+            Some(FileKind::Scala)
+        } else {
+            None
+        }
+    }
+}
+
+/// Why a dropped file could not be loaded.
+#[derive(Debug)]
+pub enum LoadError {
+    Io(io::Error),
+    Scala(ScalaError),
+    /// A tunings file that does not read as one.
+    Tunings,
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Io(err) => err.fmt(f),
+            LoadError::Scala(err) => err.fmt(f),
+            LoadError::Tunings => write!(f, "not a tunings file"),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+/// The tunings in a Scala file, named after it, or in a tunings file like
+/// the one the library is saved to.
+pub fn read_tunings(kind: FileKind, path: &Path) -> Result<Vec<Tuning>, LoadError> {
+    let text = fs::read_to_string(path).map_err(LoadError::Io)?;
+    match kind {
+        FileKind::Scala => {
+            let name = path
+                .file_stem()
+                .map_or("Scala".into(), |stem| stem.to_string_lossy());
+            let tuning = Tuning::from_scala(&name, &text).map_err(LoadError::Scala)?;
+            Ok(vec![tuning])
+        }
+        _ => {
+            let library = TuningLibrary::parse(&text).ok_or(LoadError::Tunings)?;
+            let entries = library.entries().iter();
+            Ok(entries.map(|entry| entry.tuning.clone()).collect())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    /// A file in the temporary directory that is removed when dropped.
+    struct TempFile(PathBuf);
+
+    impl TempFile {
+        fn new(name: &str, contents: &[u8]) -> Self {
+            let path =
+                std::env::temp_dir().join(format!("files-test-{}-{name}", std::process::id()));
+            fs::write(&path, contents).unwrap();
+            Self(path)
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    #[test]
+    fn detects_files_by_name() {
+        let kind = |name: &str| FileKind::from_name(Path::new(name));
+        assert_eq!(kind("dir/31edo.SCL"), Some(FileKind::Scala));
+        assert_eq!(kind("mine.tuning.toml"), Some(FileKind::Tuning));
+        assert_eq!(kind("other.toml"), None);
+        assert_eq!(kind("terminus.bdf"), Some(FileKind::Font));
+        assert_eq!(kind("song.mid"), Some(FileKind::Midi));
+        assert_eq!(kind("song.midi"), Some(FileKind::Midi));
+        assert_eq!(kind("notes.txt"), None);
+    }
+
+    #[test]
+    fn detects_files_by_contents() {
+        assert_eq!(
+            FileKind::from_contents(b"STARTFONT 2.1"),
+            Some(FileKind::Font)
+        );
+        assert_eq!(
+            FileKind::from_contents(b"MThd\0\0\0\x06"),
+            Some(FileKind::Midi)
+        );
+        assert_eq!(
+            FileKind::from_contents(b"! 31edo.scl"),
+            Some(FileKind::Scala)
+        );
+        assert_eq!(FileKind::from_contents(b""), None);
+
+        // the name wins, and the contents are only sniffed without one
+        let font = TempFile::new("font", b"STARTFONT 2.1\n");
+        assert_eq!(FileKind::detect(&font.0), Some(FileKind::Font));
+        let named = TempFile::new("named.scl", b"STARTFONT 2.1\n");
+        assert_eq!(FileKind::detect(&named.0), Some(FileKind::Scala));
+        assert_eq!(FileKind::detect(Path::new("/no/such/file")), None);
+    }
+
+    #[test]
+    fn reads_dropped_tunings() {
+        let scala = TempFile::new("fifths.scl", b"fifths\n2\n3/2\n2/1\n");
+        let tunings = read_tunings(FileKind::Scala, &scala.0).unwrap();
+        let name = format!("files-test-{}-fifths", std::process::id());
+        assert_eq!(tunings[0].name, name);
+        assert_eq!(tunings[0].degrees.len(), 2);
+
+        let library = TempFile::new("lib.tuning.toml", b"[a]\nC = 300\n\n[b]\nC = 310\n");
+        let tunings = read_tunings(FileKind::Tuning, &library.0).unwrap();
+        let names = tunings.iter().map(|tuning| tuning.name.as_str());
+        assert_eq!(names.collect::<Vec<_>>(), ["a", "b"]);
+
+        let broken = TempFile::new("broken.scl", b"broken\nmany\n");
+        assert!(matches!(
+            read_tunings(FileKind::Scala, &broken.0),
+            Err(LoadError::Scala(ScalaError::InvalidCount(2)))
+        ));
+    }
+}
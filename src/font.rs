@@ -1,22 +1,30 @@
-use std::{collections::HashMap, io::BufRead};
+use crate::{
+    tuning::{SAGITTAL, SEPTIMAL_COMMA},
+    widget::Rect,
+    Color, PixBuf,
+};
 
-use tap::TapOptional;
-
-use crate::{Color, PixBuf};
+/// The built-in Cozette font, with 6 by 13 character cells.
+pub const COZETTE: &[u8] = include_bytes!("../cozette.bdf");
+use std::{
+    cmp::Reverse,
+    collections::HashMap,
+    fmt,
+    io::{BufRead, Cursor},
+    iter::Peekable,
+};
 
 struct Chunks<'a>(&'a str, usize);
 
 impl<'a> Iterator for Chunks<'a> {
     type Item = &'a str;
 
+    /// Stops early rather than split a character, which a hex row should
+    /// not hold anyway.
     fn next(&mut self) -> Option<Self::Item> {
-        if self.0.len() >= self.1 {
-            let (next, rest) = self.0.split_at(self.1);
-            self.0 = rest;
-            Some(next)
-        } else {
-            None
-        }
+        let (next, rest) = self.0.split_at_checked(self.1)?;
+        self.0 = rest;
+        Some(next)
     }
 }
 
@@ -24,24 +32,292 @@ fn chunks(s: &str, n: usize) -> impl Iterator<Item = &str> {
     Chunks(s, n)
 }
 
+/// Skips to the line of the current glyph starting with `keyword`. The next
+/// glyph is left alone, so a glyph missing a section does not take the
+/// next one down with it.
+fn find_line(
+    lines: &mut Peekable<impl Iterator<Item = (usize, String)>>,
+    keyword: &str,
+) -> Result<(usize, String), BdfError> {
+    loop {
+        match lines.peek() {
+            None => return Err(BdfError::UnexpectedEof),
+            Some((number, line)) if line.starts_with("ENCODING") || line.starts_with("ENDCHAR") => {
+                return Err(BdfError::MissingSection(*number, keyword.to_string()));
+            }
+            Some((_, line)) if line.starts_with(keyword) => return Ok(lines.next().unwrap()),
+            Some(_) => {
+                lines.next();
+            }
+        }
+    }
+}
+
+/// Reads the glyph after its `ENCODING` line, the line numbered `number`, up
+/// to and including its bitmap.
+fn parse_glyph(
+    (number, encoding): (usize, &str),
+    lines: &mut Peekable<impl Iterator<Item = (usize, String)>>,
+) -> Result<(char, CharData), BdfError> {
+    let char = encoding
+        .split_whitespace()
+        .nth(1)
+        .and_then(|n| n.parse().ok())
+        .and_then(char::from_u32)
+        .ok_or_else(|| BdfError::InvalidEncoding(number, encoding.to_string()))?;
+
+    // get bounding box
+    let (number, bbx) = find_line(lines, "BBX")?;
+    // sizes and offsets past i16 are nonsense, and would overflow when drawn
+    let mut fields = bbx
+        .split_whitespace()
+        .skip(1)
+        .map(|n| n.parse::<i16>().ok());
+    let mut field = || {
+        fields
+            .next()
+            .flatten()
+            .map(i32::from)
+            .ok_or_else(|| BdfError::InvalidBoundingBox(number, bbx.clone()))
+    };
+    let width = field()?;
+    let height = field()?;
+    let xo = field()?;
+    let yo = field()?;
+    if width < 0 || height < 0 {
+        return Err(BdfError::InvalidBoundingBox(number, bbx));
+    }
+
+    // get data
+    find_line(lines, "BITMAP")?;
+    let data_width = (width as usize + 7) >> 3;
+    let mut data = Vec::with_capacity(data_width * height as usize);
+    for _ in 0..height {
+        let (number, line) = match lines.peek() {
+            None => return Err(BdfError::UnexpectedEof),
+            Some((number, line)) if line.starts_with("ENCODING") => {
+                return Err(BdfError::InvalidBitmap(*number, line.clone()));
+            }
+            Some(_) => lines.next().unwrap(),
+        };
+        // rows may be padded past the glyph width, but not cut short
+        let row = chunks(&line, 2)
+            .take(data_width)
+            .map(|s| u8::from_str_radix(s, 16));
+        let len = data.len();
+        for byte in row {
+            data.push(byte.map_err(|_| BdfError::InvalidBitmap(number, line.clone()))?);
+        }
+        if data.len() - len < data_width {
+            return Err(BdfError::InvalidBitmap(number, line));
+        }
+    }
+
+    let data = CharData {
+        width,
+        height,
+        xo,
+        yo,
+        data,
+        is_combining: false,
+    };
+    Ok((char, data))
+}
+
+/// Why a BDF font or glyph could not be read. Each variant but
+/// `UnexpectedEof` holds the number of the line at fault, counted from 1,
+/// and that line or the keyword missing on it.
+#[derive(Debug, PartialEq, Eq)]
+pub enum BdfError {
+    UnexpectedEof,
+    InvalidEncoding(usize, String),
+    InvalidBoundingBox(usize, String),
+    InvalidBitmap(usize, String),
+    /// A glyph ended before its `BBX` or `BITMAP` line.
+    MissingSection(usize, String),
+}
+
+impl fmt::Display for BdfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BdfError::UnexpectedEof => write!(f, "unexpected end of file"),
+            BdfError::InvalidEncoding(number, line) => {
+                write!(f, "line {number}: invalid encoding: {line}")
+            }
+            BdfError::InvalidBoundingBox(number, line) => {
+                write!(f, "line {number}: invalid bounding box: {line}")
+            }
+            BdfError::InvalidBitmap(number, line) => {
+                write!(f, "line {number}: invalid bitmap row: {line}")
+            }
+            BdfError::MissingSection(number, keyword) => {
+                write!(f, "line {number}: glyph ends with no {keyword} line")
+            }
+        }
+    }
+}
+
+impl std::error::Error for BdfError {}
+
 #[derive(Debug)]
 pub struct Font {
     pub width: i32,
     pub height: i32,
     pub chars: HashMap<char, CharData>,
     pub ligatures: HashMap<(char, char), CharData>,
+    /// Three-character ligatures, which take precedence over two-character
+    /// ones. BDF has no way to describe these, so they are only ever inserted.
+    pub ligatures3: HashMap<(char, char, char), CharData>,
+    /// How far characters narrower than a cell move the pen on, in
+    /// unscaled pixels. Every other glyph takes a whole cell.
+    pub advances: HashMap<char, i32>,
+    /// The glyphs pre-rendered by [`Font::with_atlas`], if they were.
+    atlas: Option<Atlas>,
 }
 
+/// The glyphs of [`Font::chars`] rendered at one scale by [`Font::atlas`],
+/// for [`Font::draw`] to copy rather than draw bit by bit.
 #[derive(Debug)]
+struct Atlas {
+    scale: i32,
+    width: i32,
+    pixels: Vec<Color>,
+    glyphs: PackedGlyphs,
+}
+
+/// Where each glyph is in an atlas, and where that starts in the box of the
+/// glyph in scaled pixels, as trimming moved it.
+type PackedGlyphs = HashMap<char, (Rect, (i32, i32))>;
+
+#[derive(Debug, Clone)]
 pub struct CharData {
     pub width: i32,
     pub height: i32,
     pub xo: i32,
     pub yo: i32,
     pub data: Vec<u8>,
+    /// Whether this is a combining mark, drawn over the glyph before it
+    /// instead of in a cell of its own.
+    pub is_combining: bool,
 }
 
 impl CharData {
+    /// Whether the pixel `x` from the left and `y` from the top is set.
+    fn pixel(&self, x: i32, y: i32) -> bool {
+        let data_width = (self.width as usize + 7) >> 3;
+        let byte = self.data[y as usize * data_width + x as usize / 8];
+        byte & (0x80 >> (x % 8)) != 0
+    }
+
+    /// The smallest rect around the pixels that are set, from the top left
+    /// of the glyph, or an empty one at the origin when none are.
+    fn set_bounds(&self) -> Rect {
+        let pixels = (0..self.height).flat_map(|y| (0..self.width).map(move |x| (x, y)));
+        let mut set = pixels.filter(|&(x, y)| self.pixel(x, y));
+        let Some((x, y)) = set.next() else {
+            return Rect {
+                x: 0,
+                y: 0,
+                width: 0,
+                height: 0,
+            };
+        };
+        let (mut left, mut top, mut right, mut bottom) = (x, y, x, y);
+        for (x, y) in set {
+            (left, top) = (left.min(x), top.min(y));
+            (right, bottom) = (right.max(x), bottom.max(y));
+        }
+        Rect {
+            x: left,
+            y: top,
+            width: right - left + 1,
+            height: bottom - top + 1,
+        }
+    }
+
+    /// An arrow `width` pixels wide and `height` tall on the baseline: a line
+    /// down the middle column under a head as wide as the arrow.
+    fn arrow(width: i32, height: i32, up: bool) -> CharData {
+        let middle = width / 2;
+        let data_width = (width as usize + 7) >> 3;
+        let mut data = vec![0; data_width * height as usize];
+        for y in 0..height {
+            let from_tip = if up { y } else { height - 1 - y };
+            let mut set =
+                |x: i32| data[y as usize * data_width + x as usize / 8] |= 0x80 >> (x % 8);
+            set(middle);
+            if from_tip <= middle {
+                set(middle - from_tip);
+                set(middle + from_tip);
+            }
+        }
+        CharData {
+            width,
+            height,
+            xo: 0,
+            yo: 0,
+            data,
+            is_combining: false,
+        }
+    }
+
+    /// This glyph at half its size, with a pixel set wherever one of the
+    /// two by two pixels it stands for is.
+    fn shrunk(&self) -> CharData {
+        let (width, height) = ((self.width + 1) / 2, (self.height + 1) / 2);
+        let data_width = (width as usize + 7) >> 3;
+        let mut data = vec![0; data_width * height as usize];
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if self.pixel(x, y) {
+                    let (x, y) = ((x / 2) as usize, (y / 2) as usize);
+                    data[y * data_width + x / 8] |= 0x80 >> (x % 8);
+                }
+            }
+        }
+        CharData {
+            width,
+            height,
+            xo: self.xo / 2,
+            yo: self.yo / 2,
+            data,
+            is_combining: false,
+        }
+    }
+
+    /// This glyph and `mark` drawn on top of each other, with `mark`
+    /// centered horizontally and kept at its own height above the baseline.
+    fn overlay(&self, mark: &CharData) -> CharData {
+        let mark_xo = self.xo + (self.width - mark.width) / 2;
+        let left = self.xo.min(mark_xo);
+        let right = (self.xo + self.width).max(mark_xo + mark.width);
+        let bottom = self.yo.min(mark.yo);
+        let top = (self.yo + self.height).max(mark.yo + mark.height);
+
+        let (width, height) = (right - left, top - bottom);
+        let data_width = (width as usize + 7) >> 3;
+        let mut data = vec![0; data_width * height as usize];
+        for (glyph, xo) in [(self, self.xo), (mark, mark_xo)] {
+            for y in 0..glyph.height {
+                for x in 0..glyph.width {
+                    if glyph.pixel(x, y) {
+                        let x = (x + xo - left) as usize;
+                        let y = (y + top - glyph.yo - glyph.height) as usize;
+                        data[y * data_width + x / 8] |= 0x80 >> (x % 8);
+                    }
+                }
+            }
+        }
+        CharData {
+            width,
+            height,
+            xo: left,
+            yo: bottom,
+            data,
+            is_combining: false,
+        }
+    }
+
     fn draw(&self, buf: &mut PixBuf, pos: (i32, i32), color: Color, scale: i32) {
         let mut data = self.data.as_slice();
 
@@ -54,7 +330,7 @@ impl CharData {
             for (x8, mut byte) in line.iter().copied().enumerate() {
                 for x in (x8 as i32 * 8..x8 as i32 * 8 + 8).rev() {
                     let pixel = byte & 1 == 1;
-                    byte = byte >> 1;
+                    byte >>= 1;
 
                     if pixel {
                         buf.set_scaled_pixel(
@@ -71,93 +347,480 @@ impl CharData {
 }
 
 impl Font {
-    pub fn parse_bdf(bdf: impl BufRead, width: i32, height: i32) -> Option<Self> {
-        let mut lines = bdf.lines().filter_map(|line| line.ok());
+    /// Parses a BDF font. A glyph that cannot be read is skipped with a
+    /// warning, as is a glyph cut off by the end of the file, so one bad
+    /// glyph does not lose the whole font. Only when no glyph could be read
+    /// at all is the first problem returned.
+    pub fn parse_bdf(bdf: impl BufRead, width: i32, height: i32) -> Result<Self, BdfError> {
+        let lines = bdf.lines().map_while(Result::ok).enumerate();
+        let mut lines = lines.map(|(i, line)| (i + 1, line)).peekable();
 
         let mut font = Self {
             chars: HashMap::new(),
             ligatures: HashMap::new(),
+            ligatures3: HashMap::new(),
+            advances: HashMap::new(),
             width,
             height,
+            atlas: None,
         };
+        let mut first_error = None;
 
-        loop {
-            // get next character
-            let char = loop {
-                if let Some(next) = lines.next() {
-                    if next.starts_with("ENCODING") {
-                        break next;
-                    }
-                } else {
-                    // no more characters
-                    return Some(font);
-                }
-            };
-            let char = char.split_whitespace().skip(1).next().unwrap();
-            let char = char::from_u32(u32::from_str_radix(char, 10).ok()?)?;
-
-            // get bounding box
-            let bbx = loop {
-                let next = lines.next()?;
-                if next.starts_with("BBX") {
-                    break next;
+        // get next character
+        let encoding = |(_, line): &(usize, String)| line.starts_with("ENCODING");
+        while let Some((number, line)) = lines.by_ref().find(encoding) {
+            match parse_glyph((number, &line), &mut lines) {
+                Ok((char, data)) => {
+                    font.chars.insert(char, data);
                 }
-            };
-            let mut bbx = bbx.split_whitespace().skip(1);
-            let width = i32::from_str_radix(bbx.next()?, 10).ok()?;
-            let height = i32::from_str_radix(bbx.next()?, 10).ok()?;
-            let xo = i32::from_str_radix(bbx.next()?, 10).ok()?;
-            let yo = i32::from_str_radix(bbx.next()?, 10).ok()?;
-
-            // get data
-            loop {
-                let next = lines.next()?;
-                if next.starts_with("BITMAP") {
-                    break;
+                Err(err) => {
+                    log::warn!("skipping glyph: {err}");
+                    first_error.get_or_insert(err);
                 }
             }
+        }
 
-            let mut data = Vec::new();
-            for _ in 0..height {
-                let line = lines.next()?;
-                let bytes = chunks(&line, 2).map(|s| u8::from_str_radix(s, 16));
-                for byte in bytes {
-                    data.push(byte.ok()?);
-                }
-            }
+        match first_error {
+            Some(err) if font.chars.is_empty() => Err(err),
+            _ => Ok(font),
+        }
+    }
+
+    /// Parses an in-memory BDF file, such as one embedded with `include_bytes!`.
+    pub fn from_bdf_bytes(bytes: &[u8], width: i32, height: i32) -> Result<Self, BdfError> {
+        Self::parse_bdf(Cursor::new(bytes), width, height)
+    }
+
+    /// Adds the accidentals missing from most bitmap fonts: double sharp and
+    /// flat, half sharp and flat, the three halves sharp and flat ligatures,
+    /// the arrows of [`Font::with_arrow_accidentals`], the Sagittal
+    /// accidentals of [`Font::with_sagittal_accidentals`], the septimal
+    /// commas of [`Font::with_heji_accidentals`] and the superscripts of
+    /// [`Font::with_superscript_digits`] for cent offsets.
+    pub fn with_custom_music_glyphs(mut self) -> Self {
+        // double sharp
+        self.chars.insert(
+            '𝄪',
+            CharData {
+                width: 5,
+                height: 5,
+                xo: 1,
+                yo: 0,
+                data: vec![0b11011000, 0b11011000, 0b00100000, 0b11011000, 0b11011000],
+                is_combining: false,
+            },
+        );
+        // double flat
+        self.chars.insert(
+            '𝄫',
+            CharData {
+                width: 5,
+                height: 7,
+                xo: 1,
+                yo: 0,
+                data: vec![
+                    0b10100000, 0b10100000, 0b10100000, 0b11111000, 0b10101000, 0b10101000,
+                    0b11110000,
+                ],
+                is_combining: false,
+            },
+        );
+        // half sharp
+        self.chars.insert(
+            '𝄲',
+            CharData {
+                width: 3,
+                height: 7,
+                xo: 2,
+                yo: -1,
+                data: vec![
+                    0b01000000, 0b01100000, 0b11000000, 0b01000000, 0b01100000, 0b11000000,
+                    0b01000000,
+                ],
+                is_combining: false,
+            },
+        );
+        // half flat
+        self.chars.insert(
+            '𝄳',
+            CharData {
+                width: 3,
+                height: 7,
+                xo: 2,
+                yo: 0,
+                data: vec![
+                    0b00100000, 0b00100000, 0b00100000, 0b11100000, 0b10100000, 0b10100000,
+                    0b01100000,
+                ],
+                is_combining: false,
+            },
+        );
+        // three halves sharp
+        self.ligatures.insert(
+            ('𝄲', '♯'),
+            CharData {
+                width: 5,
+                height: 9,
+                xo: 1,
+                yo: -1,
+                data: vec![
+                    0b00001000, 0b00101000, 0b10111000, 0b11101000, 0b10101000, 0b10111000,
+                    0b11101000, 0b10100000, 0b10000000,
+                ],
+                is_combining: false,
+            },
+        );
+        // three halves flat
+        self.ligatures.insert(
+            ('𝄳', '♭'),
+            CharData {
+                width: 5,
+                height: 7,
+                xo: 1,
+                yo: 0,
+                data: vec![
+                    0b00100000, 0b00100000, 0b00100000, 0b11111000, 0b10101000, 0b10101000,
+                    0b01110000,
+                ],
+                is_combining: false,
+            },
+        );
+        self.with_arrow_accidentals()
+            .with_sagittal_accidentals()
+            .with_heji_accidentals()
+            .with_superscript_digits()
+    }
+
+    /// Adds ↑ and ↓ drawn to fit the character cell, and a ligature of every
+    /// accidental the font has followed by an arrow, as the accidental with a
+    /// small arrow on top, for the notation of tunings finer than quarter
+    /// tones.
+    pub fn with_arrow_accidentals(mut self) -> Self {
+        // an odd width gives the arrow a middle column
+        let width = (self.width - 1) | 1;
+        let height = self.height / 2 + 1;
+        let xo = (self.width - width) / 2;
+        for (arrow, up) in [('↑', true), ('↓', false)] {
+            let glyph = CharData::arrow(width, height, up);
+            self.chars.insert(arrow, CharData { xo, ..glyph });
+        }
+
+        for accidental in ['♮', '♯', '♭', '𝄪', '𝄫', '𝄲', '𝄳'] {
+            let Some(glyph) = self.chars.get(&accidental) else {
+                continue;
+            };
+            // both go above, as there is little room under the baseline
+            let arrow = |up| CharData {
+                yo: glyph.yo + glyph.height + 1,
+                ..CharData::arrow(5, 3, up)
+            };
+            let (up, down) = (glyph.overlay(&arrow(true)), glyph.overlay(&arrow(false)));
+            self.ligatures.insert((accidental, '↑'), up);
+            self.ligatures.insert((accidental, '↓'), down);
+        }
+        self
+    }
 
-            // add character
-            font.chars.insert(
-                char,
+    /// Adds the accidentals of [`SAGITTAL`], each a shaft with its flags at
+    /// the top, and the same upside down for the lowering form.
+    pub fn with_sagittal_accidentals(mut self) -> Self {
+        // the shaft is the middle column, flags before it are on the left
+        const FLAGS: [[u8; 3]; 6] = [
+            // |(
+            [0b00101000, 0b00110000, 0b00100000],
+            // /|
+            [0b00100000, 0b01100000, 0b10100000],
+            // |)
+            [0b00110000, 0b00101000, 0b00101000],
+            // |\
+            [0b00100000, 0b00110000, 0b00101000],
+            // /|)
+            [0b00110000, 0b01101000, 0b10101000],
+            // /|\
+            [0b00100000, 0b01110000, 0b10101000],
+        ];
+        for ((_, up, down), flags) in SAGITTAL.into_iter().zip(FLAGS) {
+            let mut data = vec![0b00100000; 7];
+            data[..3].copy_from_slice(&flags);
+            let glyph = CharData {
+                width: 5,
+                height: 7,
+                xo: 1,
+                yo: 0,
+                data,
+                is_combining: false,
+            };
+            let mut flipped = glyph.data.clone();
+            flipped.reverse();
+            self.chars.insert(
+                down,
                 CharData {
-                    width,
-                    height,
-                    xo,
-                    yo,
-                    data,
+                    data: flipped,
+                    ..glyph.clone()
                 },
             );
+            self.chars.insert(up, glyph);
         }
+        self
     }
 
-    pub fn len(&self, s: &str) -> i32 {
-        let mut len = 0;
-        let mut chars = s.chars().peekable();
-        loop {
-            let Some(n) = chars.next() else {
-                break;
+    /// Adds the Helmholtz–Ellis septimal comma accidentals of
+    /// [`SEPTIMAL_COMMA`], a 7 for the lowering one and the same upside down
+    /// for the raising one. The syntonic comma is an arrow, which
+    /// [`Font::with_arrow_accidentals`] adds.
+    pub fn with_heji_accidentals(mut self) -> Self {
+        let down = vec![
+            0b11110000, 0b00010000, 0b00100000, 0b00100000, 0b01000000, 0b01000000,
+        ];
+        let mut up = down.clone();
+        up.reverse();
+        for (c, data) in [(SEPTIMAL_COMMA.0, up), (SEPTIMAL_COMMA.1, down)] {
+            let glyph = CharData {
+                width: 4,
+                height: 6,
+                xo: 1,
+                yo: 0,
+                data,
+                is_combining: false,
+            };
+            self.chars.insert(c, glyph);
+        }
+        self
+    }
+
+    /// Gives the superscript digits and signs advances of their own width
+    /// and a pixel, so offsets such as "⁻¹⁴" are set close together, and
+    /// adds the ones the font lacks as its digits and signs shrunk to half
+    /// their size at the height of the top of the digit.
+    pub fn with_superscript_digits(mut self) -> Self {
+        const SUPERSCRIPTS: [(char, char); 12] = [
+            ('⁰', '0'),
+            ('¹', '1'),
+            ('²', '2'),
+            ('³', '3'),
+            ('⁴', '4'),
+            ('⁵', '5'),
+            ('⁶', '6'),
+            ('⁷', '7'),
+            ('⁸', '8'),
+            ('⁹', '9'),
+            ('⁺', '+'),
+            ('⁻', '-'),
+        ];
+        for (superscript, normal) in SUPERSCRIPTS {
+            if !self.chars.contains_key(&superscript) {
+                let Some(glyph) = self.chars.get(&normal) else {
+                    continue;
+                };
+                let small = glyph.shrunk();
+                let yo = glyph.yo + glyph.height - small.height;
+                self.chars.insert(superscript, CharData { yo, ..small });
+            }
+            let glyph = self.chars.get_mut(&superscript).unwrap();
+            glyph.xo = 0;
+            let advance = (glyph.width + 1).min(self.width);
+            self.advances.insert(superscript, advance);
+        }
+        self
+    }
+
+    /// Adds `c` as a combining mark, which [`Font::draw`] draws over the glyph
+    /// before it without moving on.
+    pub fn insert_combining(&mut self, c: char, data: CharData) {
+        self.chars.insert(
+            c,
+            CharData {
+                is_combining: true,
+                ..data
+            },
+        );
+    }
+
+    /// A single glyph for `base` with `diacritic` on it: their ligature if
+    /// there is one, or else both glyphs drawn over each other.
+    pub fn combine_diacritics(&self, base: char, diacritic: char) -> Option<CharData> {
+        if let Some(ligature) = self.ligatures.get(&(base, diacritic)) {
+            return Some(ligature.clone());
+        }
+        let (base, mark) = (self.chars.get(&base)?, self.chars.get(&diacritic)?);
+        Some(base.overlay(mark))
+    }
+
+    /// Glyph cells across a row of the image [`Font::atlas`] packs.
+    pub const ATLAS_COLUMNS: i32 = 64;
+
+    /// Width in pixels of the image [`Font::atlas`] packs at `scale`.
+    pub fn atlas_width(&self, scale: i32) -> i32 {
+        Self::ATLAS_COLUMNS * self.width * scale
+    }
+
+    /// Every glyph of [`Font::chars`] at `scale`, white on transparent and
+    /// packed into one image [`Font::atlas_width`] pixels wide, with the
+    /// rect each char takes in it, trimmed to the pixels that are set.
+    /// Glyphs go on shelves from the tallest to the shortest, left to right
+    /// until a shelf is full. Ligatures are not in it.
+    pub fn atlas(&self, scale: i32) -> (Vec<Color>, HashMap<char, Rect>) {
+        let (pixels, glyphs) = self.pack(scale);
+        let rects = glyphs.into_iter().map(|(c, (rect, _))| (c, rect));
+        (pixels, rects.collect())
+    }
+
+    /// [`Font::atlas`], with where each rect starts in the box of its glyph.
+    fn pack(&self, scale: i32) -> (Vec<Color>, PackedGlyphs) {
+        let width = self.atlas_width(scale);
+        let mut glyphs: Vec<(char, &CharData, Rect)> = self
+            .chars
+            .iter()
+            .map(|(&c, data)| (c, data, data.set_bounds()))
+            .collect();
+        // by char too, so a font packs the same way every time
+        glyphs.sort_by_key(|&(c, _, bounds)| (Reverse(bounds.height), c));
+
+        let mut packed = HashMap::with_capacity(glyphs.len());
+        let (mut x, mut y, mut shelf) = (0, 0, 0);
+        for &(c, _, bounds) in &glyphs {
+            let (width_scaled, height_scaled) = (bounds.width * scale, bounds.height * scale);
+            if x + width_scaled > width {
+                (x, y, shelf) = (0, y + shelf, 0);
+            }
+            let rect = Rect {
+                x,
+                y,
+                width: width_scaled,
+                height: height_scaled,
             };
-            if chars
-                .peek()
-                .copied()
-                .and_then(|snd| self.ligatures.get(&(n, snd)))
-                .is_some()
-            {
-                chars.next();
+            packed.insert(c, (rect, (bounds.x * scale, bounds.y * scale)));
+            x += rect.width;
+            shelf = shelf.max(rect.height);
+        }
+
+        let mut pixels = vec![[0; 4]; (width * (y + shelf)) as usize];
+        for (c, data, bounds) in glyphs {
+            let (rect, _) = packed[&c];
+            for gy in 0..bounds.height {
+                for gx in 0..bounds.width {
+                    if !data.pixel(bounds.x + gx, bounds.y + gy) {
+                        continue;
+                    }
+                    for row in 0..scale {
+                        let y = rect.y + gy * scale + row;
+                        let start = (y * width + rect.x + gx * scale) as usize;
+                        pixels[start..start + scale as usize].fill([255; 4]);
+                    }
+                }
+            }
+        }
+        (pixels, packed)
+    }
+
+    /// Renders the glyphs of [`Font::chars`] at `scale` once, as by
+    /// [`Font::atlas`], for [`Font::draw`] to copy from when it draws at
+    /// that scale. Glyphs added or changed afterwards are drawn from the
+    /// atlas as they were, so this goes after them.
+    pub fn with_atlas(mut self, scale: i32) -> Self {
+        let (pixels, glyphs) = self.pack(scale);
+        self.atlas = Some(Atlas {
+            scale,
+            width: self.atlas_width(scale),
+            pixels,
+            glyphs,
+        });
+        self
+    }
+
+    /// Draws `glyph` at `pos` like [`CharData::draw`], copying it from the
+    /// atlas when there is one at `scale` holding `c`, the char it is the
+    /// glyph of unless it is a ligature.
+    fn draw_glyph(
+        &self,
+        buf: &mut PixBuf,
+        (glyph, c): (&CharData, Option<char>),
+        pos: (i32, i32),
+        color: Color,
+        scale: i32,
+    ) {
+        let atlas = self.atlas.as_ref().filter(|atlas| atlas.scale == scale);
+        match atlas
+            .zip(c)
+            .and_then(|(atlas, c)| Some((atlas, *atlas.glyphs.get(&c)?)))
+        {
+            Some((atlas, (rect, (dx, dy)))) => {
+                let x = (pos.0 / scale + glyph.xo) * scale + dx;
+                let y = (pos.1 / scale - glyph.height - glyph.yo) * scale + dy;
+                buf.blit(&atlas.pixels, atlas.width, rect, (x, y), color);
             }
-            len += 1;
+            None => glyph.draw(buf, pos, color, scale),
         }
-        len
+    }
+
+    /// The glyph at the start of `s` and how many bytes of it it covers,
+    /// preferring the longest ligature.
+    fn glyph(&self, s: &str) -> (Option<&CharData>, usize) {
+        let mut chars = s.chars();
+        let bytes = |n: usize| s.chars().take(n).map(char::len_utf8).sum();
+        match (chars.next(), chars.next(), chars.next()) {
+            (Some(a), Some(b), Some(c)) if self.ligatures3.contains_key(&(a, b, c)) => {
+                (self.ligatures3.get(&(a, b, c)), bytes(3))
+            }
+            (Some(a), Some(b), _) if self.ligatures.contains_key(&(a, b)) => {
+                (self.ligatures.get(&(a, b)), bytes(2))
+            }
+            (Some(a), ..) => (self.chars.get(&a), bytes(1)),
+            (None, ..) => (None, 0),
+        }
+    }
+
+    /// How far the glyph at the start of `s`, covering `bytes` of it, moves
+    /// the pen on, in unscaled pixels.
+    fn advance(&self, s: &str, bytes: usize) -> i32 {
+        match s.chars().next() {
+            Some(c) if c.len_utf8() == bytes => self.advances.get(&c).copied(),
+            _ => None,
+        }
+        .unwrap_or(self.width)
+    }
+
+    /// Width of `s` in character cells, counting a cell that narrower
+    /// glyphs only fill part of as a whole one.
+    pub fn len(&self, s: &str) -> i32 {
+        let mut rest = s;
+        let mut pixels = 0;
+        while !rest.is_empty() {
+            let (char, bytes) = self.glyph(rest);
+            if !char.is_some_and(|c| c.is_combining) {
+                pixels += self.advance(rest, bytes);
+            }
+            rest = &rest[bytes..];
+        }
+        (pixels + self.width - 1) / self.width
+    }
+
+    /// The gaps between the glyphs of `s` as the number of chars before each
+    /// and its distance from the start in unscaled pixels, from the start of
+    /// the text to its end. Ligatures are one glyph, and combining marks go
+    /// with the glyph before them, so no gap splits either.
+    pub fn glyph_edges<'s>(&'s self, s: &'s str) -> impl Iterator<Item = (usize, i32)> + 's {
+        let (mut rest, mut chars, mut pixels) = (s, 0, 0);
+        let mut next = Some((0, 0));
+        std::iter::from_fn(move || {
+            let mut edge = next.take()?;
+            while !rest.is_empty() {
+                let (char, bytes) = self.glyph(rest);
+                chars += rest[..bytes].chars().count();
+                let combining = char.is_some_and(|c| c.is_combining);
+                if combining {
+                    edge.0 = chars;
+                } else {
+                    pixels += self.advance(rest, bytes);
+                }
+                rest = &rest[bytes..];
+                if !combining {
+                    next = Some((chars, pixels));
+                    break;
+                }
+            }
+            Some(edge)
+        })
     }
 
     pub fn draw(
@@ -168,26 +831,280 @@ impl Font {
         color: Color,
         scale: i32,
     ) -> i32 {
-        let mut len = 0;
-        let mut chars = s.chars().peekable();
-        loop {
-            let Some(n) = chars.next() else {
-                break;
-            };
-            if let Some(char) = chars
-                .peek()
-                .copied()
-                .and_then(|snd| self.ligatures.get(&(n, snd)))
-                .tap_some(|_| {
-                    chars.next();
-                })
-                .or_else(|| self.chars.get(&n))
-            {
-                char.draw(buf, pos, color, scale);
-            }
-            pos.0 += self.width * scale;
-            len += 1;
-        }
-        len
+        let (mut rest, start) = (s, pos.0);
+        let mut previous = pos.0 - self.width * scale;
+        while !rest.is_empty() {
+            let (char, bytes) = self.glyph(rest);
+            let advance = self.advance(rest, bytes);
+            let single = rest.chars().next().filter(|c| c.len_utf8() == bytes);
+            rest = &rest[bytes..];
+            match char {
+                // combining marks go over the previous glyph
+                Some(char) if char.is_combining => {
+                    self.draw_glyph(buf, (char, single), (previous, pos.1), color, scale);
+                    continue;
+                }
+                Some(char) => self.draw_glyph(buf, (char, single), pos, color, scale),
+                None => {}
+            }
+            previous = pos.0;
+            pos.0 += advance * scale;
+        }
+        let cell = self.width * scale;
+        (pos.0 - start + cell - 1) / cell
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    const BDF: &str = "STARTFONT 2.1
+FONT -test
+SIZE 8 75 75
+FONTBOUNDINGBOX 6 8 0 -2
+CHARS 3
+STARTCHAR A
+ENCODING 65
+SWIDTH 500 0
+DWIDTH 6 0
+BBX 5 6 0 0
+BITMAP
+70
+88
+88
+F8
+88
+88
+ENDCHAR
+STARTCHAR B
+ENCODING 66
+BBX 5 6 0 0
+BITMAP
+F0
+88
+F0
+88
+88
+F0
+ENDCHAR
+STARTCHAR g
+ENCODING 103
+BBX 4 6 0 -2
+BITMAP
+70
+90
+90
+70
+10
+60
+ENDCHAR
+ENDFONT
+";
+
+    fn parse(bdf: &str) -> Result<Font, BdfError> {
+        Font::from_bdf_bytes(bdf.as_bytes(), 6, 8)
+    }
+
+    /// Checks that every glyph holds as many rows as it is tall, and draws
+    /// all of them.
+    fn assert_well_formed(font: &Font) {
+        for (char, glyph) in &font.chars {
+            let data_width = (glyph.width as usize + 7) >> 3;
+            assert_eq!(
+                glyph.data.len(),
+                data_width * glyph.height as usize,
+                "{char:?}"
+            );
+        }
+        let mut frame = vec![0; 64 * 16 * 4];
+        let mut buf = PixBuf::from_pixels_frame(&mut frame, 64, 16);
+        let text = font.chars.keys().collect::<String>();
+        font.draw(&mut buf, &text, (0, 12), [255; 4], 1);
+    }
+
+    #[test]
+    fn parses_every_glyph() {
+        let font = parse(BDF).unwrap();
+        assert_eq!(font.chars.len(), 3);
+        let g = &font.chars[&'g'];
+        assert_eq!((g.width, g.height, g.xo, g.yo), (4, 6, 0, -2));
+        assert_eq!(g.data, [0x70, 0x90, 0x90, 0x70, 0x10, 0x60]);
+        assert_well_formed(&font);
+    }
+
+    #[test]
+    fn skips_a_bad_glyph() {
+        for (from, to) in [
+            ("F8", "F"),
+            ("F8", "XY"),
+            ("F8\n88\n88\n", ""),
+            ("BBX 5 6 0 0\nBITMAP\n70", "BITMAP\n70"),
+            ("ENCODING 65", "ENCODING 1114112"),
+        ] {
+            let font = parse(&BDF.replacen(from, to, 1)).unwrap();
+            assert!(!font.chars.contains_key(&'A'), "{to:?}");
+            assert!(font.chars.contains_key(&'B') && font.chars.contains_key(&'g'));
+            assert_well_formed(&font);
+        }
+    }
+
+    #[test]
+    fn fails_without_any_glyph() {
+        assert!(matches!(parse(""), Ok(font) if font.chars.is_empty()));
+        let truncated = &BDF[..BDF.find("F8").unwrap()];
+        let truncated = truncated
+            .replace("ENCODING 66", "")
+            .replace("ENCODING 103", "");
+        assert!(matches!(parse(&truncated), Err(BdfError::UnexpectedEof)));
+    }
+
+    #[test]
+    fn errors_give_the_line() {
+        let one_glyph = &BDF[..BDF.find("STARTCHAR B").unwrap()];
+        let error = |from, to| parse(&one_glyph.replacen(from, to, 1)).unwrap_err();
+        assert_eq!(
+            error("ENCODING 65", "ENCODING x"),
+            BdfError::InvalidEncoding(7, "ENCODING x".to_string())
+        );
+        assert_eq!(
+            error("BBX 5 6", "BBX 5 -6"),
+            BdfError::InvalidBoundingBox(10, "BBX 5 -6 0 0".to_string())
+        );
+        assert_eq!(
+            error("F8", "XY"),
+            BdfError::InvalidBitmap(15, "XY".to_string())
+        );
+        assert_eq!(
+            error("BITMAP\n", ""),
+            BdfError::MissingSection(17, "BITMAP".to_string())
+        );
+        assert_eq!(
+            error("F8", "XY").to_string(),
+            "line 15: invalid bitmap row: XY"
+        );
+    }
+
+    #[test]
+    fn arrows_are_drawn_to_the_cell() {
+        let font = parse(BDF).unwrap().with_arrow_accidentals();
+        let up = &font.chars[&'↑'];
+        // an odd width, so the shaft is in the middle
+        assert_eq!((up.width, up.height, up.xo), (5, 5, 0));
+        #[rustfmt::skip]
+        assert_eq!(up.data, [
+            0b0010_0000,
+            0b0111_0000,
+            0b1010_1000,
+            0b0010_0000,
+            0b0010_0000,
+        ]);
+        let down = &font.chars[&'↓'];
+        let flipped = up.data.iter().rev().copied().collect::<Vec<_>>();
+        assert_eq!(down.data, flipped);
+        // the test font has no accidentals to put arrows on
+        assert!(font.ligatures.is_empty());
+        assert_well_formed(&font);
+    }
+
+    #[test]
+    fn accidentals_and_arrows_are_one_glyph() {
+        let font = Font::from_bdf_bytes(COZETTE, 6, 13)
+            .unwrap()
+            .with_custom_music_glyphs();
+        for accidental in ['♮', '♯', '♭', '𝄪', '𝄫', '𝄲', '𝄳'] {
+            for arrow in ['↑', '↓'] {
+                assert!(font.ligatures.contains_key(&(accidental, arrow)));
+                let name = format!("C{accidental}{arrow}");
+                assert_eq!(font.len(&name), 2, "{name}");
+            }
+        }
+        // arrows on their own, and a second one, take a cell each
+        assert_eq!(font.len("C↑"), 2);
+        assert_eq!(font.len("C♯↑↑"), 3);
+    }
+
+    #[test]
+    fn sagittal_and_heji_accidentals_are_one_cell() {
+        let font = Font::from_bdf_bytes(COZETTE, 6, 13)
+            .unwrap()
+            .with_custom_music_glyphs();
+        let sagittal = SAGITTAL.iter().flat_map(|&(_, up, down)| [up, down]);
+        for symbol in sagittal.chain([SEPTIMAL_COMMA.0, SEPTIMAL_COMMA.1]) {
+            let glyph = &font.chars[&symbol];
+            assert!(glyph.width <= font.width && glyph.height <= font.height);
+            assert_eq!(font.len(&format!("C{symbol}")), 2, "{symbol:?}");
+        }
+        // raising and lowering forms are told apart
+        for (_, up, down) in SAGITTAL {
+            assert_ne!(font.chars[&up].data, font.chars[&down].data);
+        }
+    }
+
+    #[test]
+    fn superscript_offsets_are_narrower() {
+        let font = Font::from_bdf_bytes(COZETTE, 6, 13)
+            .unwrap()
+            .with_custom_music_glyphs();
+        for c in "⁰¹²³⁴⁵⁶⁷⁸⁹⁺⁻".chars() {
+            let glyph = &font.chars[&c];
+            assert!(glyph.height < font.height / 2 + 2, "{c}");
+            assert!(font.advances[&c] < font.width, "{c}");
+        }
+        // set closer than full cells, which whole-cell widths round up
+        assert_eq!(font.len("E⁻¹⁴"), 4);
+        let widths: Vec<_> = font.glyph_edges("⁻¹⁴").map(|(_, edge)| edge).collect();
+        assert_eq!(widths, [0, 4, 8, 13]);
+    }
+
+    #[test]
+    fn every_accidental_is_one_glyph() {
+        let font = Font::from_bdf_bytes(COZETTE, 6, 13)
+            .unwrap()
+            .with_custom_music_glyphs();
+        for accidental in crate::theory::Accidental::ALL {
+            let glyph = accidental.glyph();
+            assert_eq!(font.len(glyph), 1, "{glyph}");
+            assert_eq!(font.glyph_edges(glyph).count(), 2, "{glyph}");
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn truncated_fonts_parse(at in 0..BDF.len()) {
+            if let Ok(font) = parse(&BDF[..at]) {
+                assert_well_formed(&font);
+            }
+        }
+
+        #[test]
+        fn corrupted_lines_parse(at in 0..40usize, line in ".{0,12}") {
+            let mut lines = BDF.lines().collect::<Vec<_>>();
+            lines[at] = &line;
+            if let Ok(font) = parse(&lines.join("\n")) {
+                assert_well_formed(&font);
+            }
+        }
+
+        #[test]
+        fn reordered_lines_parse(a in 0..40usize, b in 0..40usize) {
+            let mut lines = BDF.lines().collect::<Vec<_>>();
+            lines.swap(a, b);
+            if let Ok(font) = parse(&lines.join("\n")) {
+                assert_well_formed(&font);
+            }
+        }
+
+        #[test]
+        fn shuffled_glyphs_parse(order in Just(vec![0, 1, 2]).prop_shuffle()) {
+            let glyphs = BDF.split("STARTCHAR").collect::<Vec<_>>();
+            let shuffled = order.iter().map(|&i| glyphs[i + 1]);
+            let bdf = format!("{}STARTCHAR{}", glyphs[0], shuffled.collect::<Vec<_>>().join("STARTCHAR"));
+            let font = parse(&bdf).unwrap();
+            prop_assert_eq!(font.chars.len(), 3);
+            assert_well_formed(&font);
+        }
     }
 }
@@ -0,0 +1,469 @@
+//! A small immediate-mode widget toolkit with a BDF font renderer, and the
+//! chord explorer built on top of it.
+//!
+//! The binary only opens the window and runs the event loop; everything it
+//! draws lives here.
+
+#[cfg(feature = "count-allocations")]
+pub mod alloc_count;
+pub mod color;
+pub mod explorer;
+pub mod export;
+pub mod files;
+pub mod font;
+pub mod logging;
+pub mod notify;
+#[cfg(feature = "profiling")]
+pub mod profiling;
+pub mod ratio;
+pub mod session;
+pub mod shortcuts;
+pub mod theory;
+pub mod tuning;
+pub mod widget;
+
+use std::{fmt, fs, io, path::Path};
+
+use widget::Rect;
+
+/// An RGBA color.
+pub type Color = [u8; 4];
+
+/// The color with every channel, alpha included, flipped.
+pub fn invert(c: Color) -> Color {
+    [255 - c[0], 255 - c[1], 255 - c[2], 255 - c[3]]
+}
+
+/// A mutable view of an RGBA frame that the widgets draw on.
+pub struct PixBuf<'a> {
+    buf: &'a mut [Color],
+    width: i32,
+    height: i32,
+}
+
+impl<'a> PixBuf<'a> {
+    /// Wraps an RGBA frame as returned by `Pixels::frame_mut`.
+    pub fn from_pixels_frame(frame: &'a mut [u8], width: i32, height: i32) -> Self {
+        assert_eq!(frame.len(), (width * height * 4) as usize);
+        Self {
+            buf: frame.as_chunks_mut().0,
+            width,
+            height,
+        }
+    }
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+    /// Fills the whole buffer with `color`.
+    pub fn clear(&mut self, color: Color) {
+        self.buf.fill(color);
+    }
+    /// Sets one pixel, ignoring pixels outside the buffer.
+    pub fn set_pixel(&mut self, x: i32, y: i32, color: Color) {
+        if x >= 0 && y >= 0 && x < self.width && y < self.height {
+            self.buf[(x + y * self.width) as usize] = color;
+        }
+    }
+    /// The pixel at `(x, y)`, or `None` outside the buffer.
+    pub fn pixel_at(&self, x: i32, y: i32) -> Option<Color> {
+        if x >= 0 && y >= 0 && x < self.width && y < self.height {
+            Some(self.buf[(x + y * self.width) as usize])
+        } else {
+            None
+        }
+    }
+    /// The pixels of row `y` from left to right, or `None` outside the
+    /// buffer.
+    pub fn row(&self, y: i32) -> Option<&[Color]> {
+        if y >= 0 && y < self.height {
+            let start = (y * self.width) as usize;
+            Some(&self.buf[start..start + self.width as usize])
+        } else {
+            None
+        }
+    }
+    pub(crate) fn set_scaled_pixel(&mut self, x: i32, y: i32, scale: i32, color: Color) {
+        for y in y * scale..y * scale + scale {
+            // TODO: set slice range?
+            for x in x * scale..x * scale + scale {
+                self.set_pixel(x, y, color);
+            }
+        }
+    }
+    /// Draws `rect` of `src`, an image `src_width` pixels wide, as a stencil
+    /// with its top left corner at `(x, y)`: wherever `src` is not
+    /// transparent the pixel is set to `color`, as for a glyph atlas. Pixels
+    /// outside the buffer, or outside `src`, are left out.
+    pub fn blit(
+        &mut self,
+        src: &[Color],
+        src_width: i32,
+        rect: Rect,
+        (x, y): (i32, i32),
+        color: Color,
+    ) {
+        // the part of `rect` inside `src`, with the corner it goes to
+        let src_height = src.len() as i32 / src_width.max(1);
+        let (src_left, src_top) = (rect.x.max(0), rect.y.max(0));
+        let src_right = (rect.x + rect.width).min(src_width);
+        let src_bottom = (rect.y + rect.height).min(src_height);
+        let (x, y) = (x + src_left - rect.x, y + src_top - rect.y);
+        let rect = Rect {
+            x: src_left,
+            y: src_top,
+            width: src_right - src_left,
+            height: src_bottom - src_top,
+        };
+        if rect.width <= 0 || rect.height <= 0 {
+            return;
+        }
+
+        let (left, right) = ((-x).max(0), rect.width.min(self.width - x));
+        if right <= left {
+            return;
+        }
+        for row in (-y).max(0)..rect.height.min(self.height - y) {
+            let from = ((rect.y + row) * src_width + rect.x) as usize;
+            let to = ((y + row) * self.width + x) as usize;
+            let (from, to) = (from + left as usize, to + left as usize);
+            let len = (right - left) as usize;
+            let pixels = self.buf[to..to + len].iter_mut();
+            for (pixel, &source) in pixels.zip(&src[from..from + len]) {
+                if source[3] != 0 {
+                    *pixel = color;
+                }
+            }
+        }
+    }
+    pub fn draw_line(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, color: Color) {
+        // Bresenham's line algorithm
+        let (dx, dy) = ((x1 - x0).abs(), -(y1 - y0).abs());
+        let (sx, sy) = ((x1 - x0).signum(), (y1 - y0).signum());
+        let (mut x, mut y, mut err) = (x0, y0, dx + dy);
+        loop {
+            self.set_pixel(x, y, color);
+            if x == x1 && y == y1 {
+                break;
+            }
+            let e2 = 2 * err;
+            if e2 >= dy {
+                err += dy;
+                x += sx;
+            }
+            if e2 <= dx {
+                err += dx;
+                y += sy;
+            }
+        }
+    }
+    /// Fills a polygon with the scanline algorithm. Pixels are filled when
+    /// their center is inside, by the even-odd rule, so concave and
+    /// self-intersecting polygons work and shared edges are not drawn twice.
+    pub fn fill_polygon(&mut self, vertices: &[(i32, i32)], color: Color) {
+        let Some(top) = vertices.iter().map(|v| v.1).min() else {
+            return;
+        };
+        let bottom = vertices.iter().map(|v| v.1).max().unwrap();
+
+        let mut crossings = Vec::new();
+        for y in top.max(0)..bottom.min(self.height) {
+            let center = y as f32 + 0.5;
+            crossings.clear();
+            // each edge covers the rows from its upper end up to but not
+            // including its lower end, so horizontal edges cover none and
+            // vertices on the scan line count once
+            let edges = vertices.iter().zip(vertices.iter().cycle().skip(1));
+            for (&(x0, y0), &(x1, y1)) in edges {
+                let (lo, hi) = (y0.min(y1) as f32, y0.max(y1) as f32);
+                if center >= lo && center < hi {
+                    let t = (center - y0 as f32) / (y1 - y0) as f32;
+                    crossings.push(x0 as f32 + t * (x1 - x0) as f32);
+                }
+            }
+            crossings.sort_by(f32::total_cmp);
+
+            for span in crossings.chunks_exact(2) {
+                let start = ((span[0] - 0.5).ceil() as i32).max(0);
+                let end = ((span[1] - 0.5).ceil() as i32).min(self.width);
+                for x in start..end {
+                    self.set_pixel(x, y, color);
+                }
+            }
+        }
+    }
+    /// The buffer as a binary (P6) PPM image. PPM has no alpha, so it is
+    /// dropped.
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let mut ppm = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        ppm.extend(self.buf.iter().flat_map(|c| [c[0], c[1], c[2]]));
+        ppm
+    }
+    pub fn save_ppm(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_ppm())
+    }
+    /// Reads a binary PPM image as pixels in row-major order, its width and
+    /// its height. Every pixel is opaque, and channels are scaled up to 255
+    /// when the image has a lower maximum.
+    pub fn from_ppm(bytes: &[u8]) -> Result<(Vec<Color>, i32, i32), PpmError> {
+        let mut rest = bytes;
+        let magic = ppm_token(&mut rest)?;
+        if magic != b"P6" {
+            return Err(PpmError::NotP6);
+        }
+        let mut number = || {
+            let token = ppm_token(&mut rest)?;
+            let number = std::str::from_utf8(token)
+                .ok()
+                .and_then(|t| t.parse::<i32>().ok());
+            number
+                .filter(|&n| n > 0)
+                .ok_or_else(|| PpmError::InvalidHeader(String::from_utf8_lossy(token).into()))
+        };
+        let (width, height, max) = (number()?, number()?, number()?);
+        if max > 255 {
+            return Err(PpmError::UnsupportedDepth(max));
+        }
+        // a single whitespace byte separates the header from the pixels
+        let data = rest.get(1..).ok_or(PpmError::UnexpectedEof)?;
+        let len = (width as usize) * (height as usize) * 3;
+        let data = data.get(..len).ok_or(PpmError::UnexpectedEof)?;
+
+        let scale = |c: u8| (c as u32 * 255 / max as u32) as u8;
+        let pixels = data
+            .chunks_exact(3)
+            .map(|rgb| [scale(rgb[0]), scale(rgb[1]), scale(rgb[2]), 255])
+            .collect();
+        Ok((pixels, width, height))
+    }
+    /// Fills the pixels whose center lies within `radius` of `(cx, cy)`.
+    pub fn fill_circle(&mut self, cx: i32, cy: i32, radius: f32, color: Color) {
+        let r = radius.ceil() as i32;
+        for y in cy - r..=cy + r {
+            for x in cx - r..=cx + r {
+                let (dx, dy) = ((x - cx) as f32, (y - cy) as f32);
+                if dx.hypot(dy) <= radius {
+                    self.set_pixel(x, y, color);
+                }
+            }
+        }
+    }
+    /// Draws a line ending in a filled arrowhead at `(x1, y1)`, with sides
+    /// `head_size` long at 30° to the line.
+    pub fn draw_arrow(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, head_size: i32, color: Color) {
+        self.draw_line(x0, y0, x1, y1, color);
+
+        let (dx, dy) = ((x1 - x0) as f32, (y1 - y0) as f32);
+        let len = dx.hypot(dy);
+        if len == 0.0 {
+            return;
+        }
+        let (ux, uy) = (dx / len, dy / len);
+        let back = head_size as f32 * 30f32.to_radians().cos();
+        let side = head_size as f32 * 30f32.to_radians().sin();
+        let (bx, by) = (x1 as f32 - ux * back, y1 as f32 - uy * back);
+        self.fill_polygon(
+            &[
+                (x1, y1),
+                (
+                    (bx - uy * side).round() as i32,
+                    (by + ux * side).round() as i32,
+                ),
+                (
+                    (bx + uy * side).round() as i32,
+                    (by - ux * side).round() as i32,
+                ),
+            ],
+            color,
+        );
+    }
+    /// Plots `steps + 1` points evenly spaced in `t` along the cubic Bézier
+    /// curve from `p0` to `p3` with control points `p1` and `p2`. Too few
+    /// steps leave gaps where the curve is long, but the pixels only depend
+    /// on the arguments, for output that has to be reproducible.
+    pub fn draw_bezier(
+        &mut self,
+        p0: (i32, i32),
+        p1: (i32, i32),
+        p2: (i32, i32),
+        p3: (i32, i32),
+        steps: u32,
+        color: Color,
+    ) {
+        // de Casteljau's algorithm in 16.16 fixed point
+        const ONE: i64 = 1 << 16;
+        let fixed = |(x, y): (i32, i32)| ((x as i64) << 16, (y as i64) << 16);
+        let lerp = |a: (i64, i64), b: (i64, i64), t: i64| {
+            (
+                a.0 + (((b.0 - a.0) * t) >> 16),
+                a.1 + (((b.1 - a.1) * t) >> 16),
+            )
+        };
+        let points = [p0, p1, p2, p3].map(fixed);
+        let steps = steps.max(1) as i64;
+        for step in 0..=steps {
+            let t = step * ONE / steps;
+            let [a, b, c] = [0, 1, 2].map(|i| lerp(points[i], points[i + 1], t));
+            let (d, e) = (lerp(a, b, t), lerp(b, c, t));
+            let (x, y) = lerp(d, e, t);
+            let round = |v: i64| ((v + ONE / 2) >> 16) as i32;
+            self.set_pixel(round(x), round(y), color);
+        }
+    }
+    /// Draws the cubic Bézier curve from `p0` to `p3` with control points
+    /// `p1` and `p2` without gaps. The curve is no longer than its control
+    /// polygon, so taking a step per pixel of the polygon moves less than a
+    /// pixel at a time.
+    pub fn draw_bezier_interpolated(
+        &mut self,
+        p0: (i32, i32),
+        p1: (i32, i32),
+        p2: (i32, i32),
+        p3: (i32, i32),
+        color: Color,
+    ) {
+        let side =
+            |(x0, y0): (i32, i32), (x1, y1): (i32, i32)| ((x1 - x0) as f32).hypot((y1 - y0) as f32);
+        let length = side(p0, p1) + side(p1, p2) + side(p2, p3);
+        self.draw_bezier(p0, p1, p2, p3, length.ceil() as u32, color);
+    }
+    /// Draws the lines of a grid of `cell_w` by `cell_h` cells over `rect`,
+    /// for diagrams to draw on. Lines fall on the rect's left and top edges
+    /// and every cell after them, moved by `offset` so the grid can scroll
+    /// with what is drawn over it.
+    pub fn draw_grid(
+        &mut self,
+        rect: Rect,
+        cell_w: i32,
+        cell_h: i32,
+        offset: (i32, i32),
+        color: Color,
+    ) {
+        if rect.is_empty() || cell_w <= 0 || cell_h <= 0 {
+            return;
+        }
+        let (right, bottom) = (rect.x + rect.width - 1, rect.y + rect.height - 1);
+        let first_x = rect.x + offset.0.rem_euclid(cell_w);
+        for x in (first_x..=right).step_by(cell_w as usize) {
+            self.draw_line(x, rect.y, x, bottom, color);
+        }
+        let first_y = rect.y + offset.1.rem_euclid(cell_h);
+        for y in (first_y..=bottom).step_by(cell_h as usize) {
+            self.draw_line(rect.x, y, right, y, color);
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum PpmError {
+    UnexpectedEof,
+    NotP6,
+    InvalidHeader(String),
+    /// Maximum channel values above 255 take two bytes per channel.
+    UnsupportedDepth(i32),
+}
+
+impl fmt::Display for PpmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PpmError::UnexpectedEof => write!(f, "unexpected end of file"),
+            PpmError::NotP6 => write!(f, "not a binary PPM (P6) image"),
+            PpmError::InvalidHeader(token) => write!(f, "invalid header value: {token}"),
+            PpmError::UnsupportedDepth(max) => write!(f, "unsupported maximum value: {max}"),
+        }
+    }
+}
+
+impl std::error::Error for PpmError {}
+
+/// Takes the next header token off `rest`, skipping whitespace and comments.
+fn ppm_token<'a>(rest: &mut &'a [u8]) -> Result<&'a [u8], PpmError> {
+    loop {
+        match rest.first() {
+            Some(b) if b.is_ascii_whitespace() => *rest = &rest[1..],
+            Some(b'#') => {
+                let end = rest.iter().position(|&b| b == b'\n').unwrap_or(rest.len());
+                *rest = &rest[end..];
+            }
+            Some(_) => break,
+            None => return Err(PpmError::UnexpectedEof),
+        }
+    }
+    let end = rest
+        .iter()
+        .position(|b| b.is_ascii_whitespace() || *b == b'#')
+        .unwrap_or(rest.len());
+    let (token, tail) = rest.split_at(end);
+    *rest = tail;
+    Ok(token)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 3 by 2 stencil with every pixel set.
+    const SRC: [Color; 6] = [[255; 4]; 6];
+
+    fn blit(rect: Rect, at: (i32, i32)) -> Vec<u8> {
+        let mut frame = vec![0; 4 * 4 * 4];
+        let mut buf = PixBuf::from_pixels_frame(&mut frame, 4, 4);
+        buf.blit(&SRC, 3, rect, at, [1, 1, 1, 1]);
+        frame.chunks(4).map(|pixel| pixel[0]).collect()
+    }
+
+    fn rect(x: i32, y: i32, width: i32, height: i32) -> Rect {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn blits_inside_both_buffers() {
+        #[rustfmt::skip]
+        assert_eq!(blit(rect(0, 0, 3, 2), (1, 1)), [
+            0, 0, 0, 0,
+            0, 1, 1, 1,
+            0, 1, 1, 1,
+            0, 0, 0, 0,
+        ]);
+    }
+
+    #[test]
+    fn clips_to_the_destination() {
+        #[rustfmt::skip]
+        assert_eq!(blit(rect(0, 0, 3, 2), (-2, 3)), [
+            0, 0, 0, 0,
+            0, 0, 0, 0,
+            0, 0, 0, 0,
+            1, 0, 0, 0,
+        ]);
+    }
+
+    #[test]
+    fn clips_to_the_source() {
+        // past the right and bottom edges of the source
+        #[rustfmt::skip]
+        assert_eq!(blit(rect(2, 1, 4, 4), (0, 0)), [
+            1, 0, 0, 0,
+            0, 0, 0, 0,
+            0, 0, 0, 0,
+            0, 0, 0, 0,
+        ]);
+        // before its left and top edges, which stay where they would be
+        #[rustfmt::skip]
+        assert_eq!(blit(rect(-1, -1, 3, 3), (0, 0)), [
+            0, 0, 0, 0,
+            0, 1, 1, 0,
+            0, 1, 1, 0,
+            0, 0, 0, 0,
+        ]);
+        // wholly outside it
+        assert!(blit(rect(5, 5, 2, 2), (0, 0)).iter().all(|&p| p == 0));
+    }
+}
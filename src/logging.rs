@@ -0,0 +1,146 @@
+use std::{collections::VecDeque, sync::Mutex};
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+/// Number of records kept for the log view.
+pub const CAPACITY: usize = 500;
+
+static BUFFER: Mutex<RingBuffer> = Mutex::new(RingBuffer::new(CAPACITY));
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+}
+
+impl Entry {
+    pub fn line(&self) -> String {
+        format!("{:<5} {}: {}", self.level, self.target, self.message)
+    }
+}
+
+/// The most recent log entries, dropping the oldest once full.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RingBuffer {
+    entries: VecDeque<Entry>,
+    capacity: usize,
+}
+
+impl RingBuffer {
+    pub const fn new(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    pub fn push(&mut self, entry: Entry) {
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+
+    /// Entries at `level` or more severe, oldest first.
+    pub fn filtered(&self, level: Level) -> impl Iterator<Item = &Entry> {
+        self.entries
+            .iter()
+            .filter(move |entry| entry.level <= level)
+    }
+}
+
+/// Passes records on to env_logger and keeps them for the log view too.
+struct TeeLogger {
+    inner: env_logger::Logger,
+}
+
+impl Log for TeeLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= Level::Info || self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if self.inner.matches(record) {
+            self.inner.log(record);
+        }
+        if self.enabled(record.metadata()) {
+            BUFFER.lock().unwrap().push(Entry {
+                level: record.level(),
+                target: record.target().to_string(),
+                message: record.args().to_string(),
+            });
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+/// Installs env_logger, configured from `RUST_LOG` as usual, and keeps at
+/// least info records for the log view whatever it is set to.
+pub fn init() {
+    let inner = env_logger::Builder::from_default_env().build();
+    log::set_max_level(inner.filter().max(LevelFilter::Info));
+    log::set_boxed_logger(Box::new(TeeLogger { inner })).expect("logger already installed");
+}
+
+/// Snapshot of the kept entries at `level` or more severe.
+pub fn entries(level: Level) -> Vec<Entry> {
+    BUFFER.lock().unwrap().filtered(level).cloned().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(level: Level, message: &str) -> Entry {
+        Entry {
+            level,
+            target: "chord_explorer::font".to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    fn messages<'a>(entries: impl Iterator<Item = &'a Entry>) -> Vec<&'a str> {
+        entries.map(|entry| entry.message.as_str()).collect()
+    }
+
+    #[test]
+    fn drops_the_oldest_once_full() {
+        let mut buffer = RingBuffer::new(3);
+        for message in ["a", "b", "c", "d", "e"] {
+            buffer.push(entry(Level::Info, message));
+        }
+        assert_eq!(messages(buffer.filtered(Level::Trace)), ["c", "d", "e"]);
+    }
+
+    #[test]
+    fn filters_by_severity() {
+        let mut buffer = RingBuffer::new(CAPACITY);
+        for level in [
+            Level::Trace,
+            Level::Error,
+            Level::Debug,
+            Level::Warn,
+            Level::Info,
+        ] {
+            buffer.push(entry(level, level.as_str()));
+        }
+        assert_eq!(messages(buffer.filtered(Level::Error)), ["ERROR"]);
+        assert_eq!(
+            messages(buffer.filtered(Level::Info)),
+            ["ERROR", "WARN", "INFO"]
+        );
+        assert_eq!(buffer.filtered(Level::Trace).count(), 5);
+    }
+
+    #[test]
+    fn lines_line_up() {
+        assert_eq!(
+            entry(Level::Warn, "bad glyph").line(),
+            "WARN  chord_explorer::font: bad glyph"
+        );
+    }
+}
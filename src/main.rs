@@ -1,72 +1,110 @@
-use std::io::Cursor;
-use std::slice::from_raw_parts_mut;
-use std::vec;
+use std::fs;
+use std::path::Path;
+use std::process;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
 
+use arboard::Clipboard;
+use args::{BufferMode, Command, LetterboxScale};
+use chord_explorer::explorer::{DropOverlay, Main};
+use chord_explorer::files::{self, FileKind};
+use chord_explorer::font::{Font, COZETTE};
+use chord_explorer::notify::{self, Toasts};
+use chord_explorer::session::{self, Session};
+use chord_explorer::shortcuts::Action;
+use chord_explorer::tuning::TuningLibrary;
+use chord_explorer::widget::{
+    BoxedWidget, Canvas, CutDir, Events, HoverState, MenuState, PaneState, Rect, RepeatState,
+    Spinner, StatusState, TooltipState, Visuals, Widget,
+};
+use chord_explorer::{logging, PixBuf};
 use error_iter::ErrorIter;
-use explorer::Main;
-use log::error;
-use pixels::{Error, Pixels, SurfaceTexture};
-use widget::{Canvas, CutDir, Events, Rect, Visuals, Widget};
-use winit::dpi::LogicalSize;
-use winit::event::{Event, WindowEvent};
-use winit::event_loop::EventLoop;
-use winit::keyboard::KeyCode;
-use winit::window::{WindowBuilder, WindowLevel};
+use log::{error, info, warn};
+use pixels::{PixelsBuilder, SurfaceTexture};
+use winit::dpi::{LogicalSize, PhysicalPosition, PhysicalSize};
+use winit::event::{ElementState, Event, StartCause, WindowEvent};
+use winit::event_loop::{ControlFlow, EventLoop};
+use winit::keyboard::{Key, PhysicalKey};
+use winit::window::{Fullscreen, Icon, Window, WindowBuilder};
 use winit_input_helper::WinitInputHelper;
 
-use crate::font::{CharData, Font};
-
-mod explorer;
-mod font;
-mod widget;
+mod args;
 
 const WIDTH: usize = 320;
 const HEIGHT: usize = 240;
 
-#[inline]
-pub fn as_chunks_mut<T, const N: usize>(s: &mut [T]) -> (&mut [[T; N]], &mut [T]) {
-    assert_ne!(N, 0);
-    let len = s.len() / N;
-    let (multiple_of_n, remainder) = s.split_at_mut(len * N);
-    // SAFETY: We cast a slice of `len * N` elements into
-    // a slice of `len` many `N` elements chunks.
-    let array_slice: &mut [[T; N]] =
-        unsafe { from_raw_parts_mut(multiple_of_n.as_mut_ptr().cast(), len) };
-    (array_slice, remainder)
-}
+const TITLE: &str = "Chord Explorer";
 
-type Color = [u8; 4];
+/// Time between two steps of the spinner shown while saving.
+const SPINNER_FRAME: Duration = Duration::from_millis(100);
 
-fn invert(c: Color) -> Color {
-    [255 - c[0], 255 - c[1], 255 - c[2], 255 - c[3]]
-}
+/// Time between frames while a tooltip waits out its delay, which is counted
+/// in frames.
+const TOOLTIP_FRAME: Duration = Duration::from_millis(50);
 
-struct PixBuf<'a> {
-    buf: &'a mut [Color],
-    width: i32,
-    height: i32,
-}
+/// Time between frames while toasts slide into place or count down.
+const TOAST_FRAME: Duration = Duration::from_millis(50);
+
+/// Time between frames while a control is held down to repeat, as fast as
+/// it repeats at most.
+const REPEAT_FRAME: Duration = Duration::from_millis(20);
+
+/// Text size the glyphs are pre-rendered at, that of the default zoom.
+const ATLAS_SCALE: i32 = 2;
 
-impl<'a> PixBuf<'a> {
-    fn set_pixel(&mut self, x: i32, y: i32, color: Color) {
-        if x >= 0 && y >= 0 && x < self.width && y < self.height {
-            self.buf[(x + y * self.width) as usize] = color;
+/// Longest time between the presses of a double click.
+const DOUBLE_CLICK_TIME: Duration = Duration::from_millis(400);
+
+/// Farthest the cursor can move between the presses of a double click, in
+/// buffer pixels.
+const DOUBLE_CLICK_DISTANCE: i32 = 4;
+
+/// Consecutive failed frames after which the app gives up.
+const MAX_RENDER_FAILURES: u32 = 3;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    logging::init();
+
+    let args = match args::parse(std::env::args().skip(1)) {
+        Ok(Command::Run(args)) => args,
+        Ok(Command::Help) => {
+            println!("{}", args::help());
+            return Ok(());
         }
-    }
-    fn set_scaled_pixel(&mut self, x: i32, y: i32, scale: i32, color: Color) {
-        for y in y * scale..y * scale + scale {
-            // TODO: set slice range?
-            for x in x * scale..x * scale + scale {
-                self.set_pixel(x, y, color);
-            }
+        Ok(Command::Version) => {
+            println!("chord-explorer {}", env!("CARGO_PKG_VERSION"));
+            return Ok(());
         }
-    }
-}
+        Err(err) => {
+            eprintln!("error: {err}\n\n{}", args::help());
+            process::exit(2);
+        }
+    };
 
-const COZETTE: &'static [u8; 342005] = include_bytes!("../cozette.bdf");
+    let mut font = match &args.font {
+        Some(path) => load_font(path, args.font_size).unwrap_or_else(|err| {
+            eprintln!("error: could not load font '{}': {err}", path.display());
+            process::exit(1);
+        }),
+        None => Font::from_bdf_bytes(COZETTE, 6, 13)?,
+    }
+    .with_custom_music_glyphs()
+    .with_atlas(ATLAS_SCALE);
 
-fn main() -> Result<(), Error> {
-    env_logger::init();
+    // set up before the window opens, so bad arguments do not flash one
+    let mut explorer = Main::default();
+    explorer.set_tunings(TuningLibrary::load());
+    if let Some(session) = Session::load() {
+        if args.fresh || session.is_stale() {
+            explorer.offer_restore(session);
+        } else {
+            explorer.restore(&session);
+        }
+    }
+    if let Err(err) = args.apply(&mut explorer) {
+        eprintln!("error: {err}");
+        process::exit(1);
+    }
 
     let event_loop = EventLoop::new().unwrap();
     let mut input = WinitInputHelper::new();
@@ -74,7 +112,8 @@ fn main() -> Result<(), Error> {
     let window = {
         let size = LogicalSize::new(WIDTH as f64, HEIGHT as f64);
         WindowBuilder::new()
-            .with_title("Hello Pixels")
+            .with_title(TITLE)
+            .with_window_icon(window_icon())
             .with_inner_size(size)
             .with_min_inner_size(size)
             .build(&event_loop)
@@ -84,93 +123,63 @@ fn main() -> Result<(), Error> {
     let mut pixels = {
         let window_size = window.inner_size();
         let surface_texture = SurfaceTexture::new(window_size.width, window_size.height, &window);
-        Pixels::new(WIDTH as u32, HEIGHT as u32, surface_texture)?
+        let (width, height) = match (args.letterbox, args.letterbox_scale) {
+            (true, LetterboxScale::Integer) => letterbox_size(args.aspect),
+            // scaled into a buffer the size of the window by hand
+            (true, LetterboxScale::Fractional) => (window_size.width, window_size.height),
+            (false, _) => buffer_size(window_size, window.scale_factor(), args.buffer),
+        };
+        PixelsBuilder::new(width.max(1), height.max(1), surface_texture)
+            .enable_vsync(args.vsync)
+            .build()?
     };
-
-    let mut font = Font::parse_bdf(Cursor::new(COZETTE), 6, 13).unwrap();
-
-    // double sharp
-    font.chars.insert(
-        '𝄪',
-        CharData {
-            width: 5,
-            height: 5,
-            xo: 1,
-            yo: 0,
-            data: vec![0b11011000, 0b11011000, 0b00100000, 0b11011000, 0b11011000],
-        },
-    );
-    // double flat
-    font.chars.insert(
-        '𝄫',
-        CharData {
-            width: 5,
-            height: 7,
-            xo: 1,
-            yo: 0,
-            data: vec![
-                0b10100000, 0b10100000, 0b10100000, 0b11111000, 0b10101000, 0b10101000, 0b11110000,
-            ],
-        },
-    );
-    // half sharp
-    font.chars.insert(
-        '𝄲',
-        CharData {
-            width: 3,
-            height: 7,
-            xo: 2,
-            yo: -1,
-            data: vec![
-                0b01000000, 0b01100000, 0b11000000, 0b01000000, 0b01100000, 0b11000000, 0b01000000,
-            ],
-        },
-    );
-    // half flat
-    font.chars.insert(
-        '𝄳',
-        CharData {
-            width: 3,
-            height: 7,
-            xo: 2,
-            yo: 0,
-            data: vec![
-                0b00100000, 0b00100000, 0b00100000, 0b11100000, 0b10100000, 0b10100000, 0b01100000,
-            ],
-        },
-    );
-    // three halves sharp
-    font.ligatures.insert(
-        ('𝄲', '♯'),
-        CharData {
-            width: 5,
-            height: 9,
-            xo: 1,
-            yo: -1,
-            data: vec![
-                0b00001000, 0b00101000, 0b10111000, 0b11101000, 0b10101000, 0b10111000, 0b11101000,
-                0b10100000, 0b10000000,
-            ],
-        },
-    );
-    // three halves flat
-    font.ligatures.insert(
-        ('𝄳', '♭'),
-        CharData {
-            width: 5,
-            height: 7,
-            xo: 1,
-            yo: 0,
-            data: vec![
-                0b00100000, 0b00100000, 0b00100000, 0b11111000, 0b10101000, 0b10101000, 0b01110000,
-            ],
-        },
+    let pacing = format!(
+        "{}, {}",
+        if args.vsync { "vsync" } else { "immediate" },
+        match args.fps {
+            0 => "no frame limit".to_string(),
+            fps => format!("at most {fps} fps"),
+        }
     );
+    info!("frame pacing: {pacing}");
+    let frame_time = (args.fps > 0).then(|| Duration::from_secs_f64(1.0 / args.fps as f64));
+    let started = Instant::now();
+    let mut last_redraw = None;
+    let mut redraw_at: Option<Instant> = None;
+    // whether the next frame may look different from the last one drawn,
+    // and where the cursor was in that one
+    let mut dirty = true;
+    let mut drawn_cursor = None;
+    let mut overlay_hover = HoverState::default();
+    let mut scale_factor_changed = false;
 
-    let mut width = WIDTH as i32;
-    let mut height = HEIGHT as i32;
+    let size = match args.letterbox {
+        true => letterbox_size(args.aspect),
+        false => (pixels.texture().width(), pixels.texture().height()),
+    };
+    let (mut width, mut height) = (size.0 as i32, size.1 as i32);
+    // Only the letterbox is scaled, and only it by a fractional factor, for
+    // which it is drawn here first
+    let scale = match args.letterbox {
+        true => args.letterbox_scale,
+        false => LetterboxScale::Integer,
+    };
+    let mut letterboxed =
+        (scale == LetterboxScale::Fractional).then(|| vec![0; (width * height * 4) as usize]);
 
-    let mut explorer = Main::default();
+    let mut saved_session = explorer.session();
+    let mut last_autosave = Instant::now();
+    let mut autosave: Option<JoinHandle<()>> = None;
+    let mut spinner = Spinner::new();
+    let mut render_failures = 0;
+    let mut events = Events::default();
+    let mut last_click: Option<(Instant, (i32, i32))> = None;
+    let mut hovered_file = None;
+    let mut windowed = None;
+    let mut title = TITLE.to_string();
+    let mut clipboard = Clipboard::new()
+        .map_err(|err| warn!("clipboard unavailable: {err}"))
+        .ok();
 
     event_loop
         .run(move |event, target| {
@@ -180,12 +189,19 @@ fn main() -> Result<(), Error> {
                 event: WindowEvent::RedrawRequested,
             } = event
             {
+                last_redraw = Some(Instant::now());
+                dirty = false;
+                drawn_cursor = events.cursor;
+                events.time = started.elapsed();
                 let mut canvas = Canvas {
-                    pix: PixBuf {
-                        buf: as_chunks_mut(pixels.frame_mut()).0,
+                    pix: PixBuf::from_pixels_frame(
+                        match &mut letterboxed {
+                            Some(frame) => frame,
+                            None => pixels.frame_mut(),
+                        },
                         width,
                         height,
-                    },
+                    ),
                     rect: Rect {
                         x: 0,
                         y: 0,
@@ -197,50 +213,386 @@ fn main() -> Result<(), Error> {
                         text_size: 2,
                         dir: CutDir::Vertical,
                         color: [255, 255, 255, 255],
+                        line_spacing: 0,
                     },
-                    events: Events {
-                        mouse_left: input.mouse_held(0),
-                        mouse_middle: input.mouse_held(2),
-                        mouse_right: input.mouse_held(1),
-                        cursor: input.cursor().map(|(x, y)| (x as i32, y as i32)),
-                    },
+                    events: events.clone(),
+                    tooltip: TooltipState::default(),
+                    hover: HoverState::default(),
+                    repeat: RepeatState::default(),
+                    menu: MenuState::default(),
+                    panes: PaneState::default(),
+                    status: StatusState::default(),
                 };
-                canvas.clear();
+                canvas.clear_frame();
+                #[cfg(feature = "count-allocations")]
+                let allocations = chord_explorer::alloc_count::allocations();
+                #[cfg(feature = "profiling")]
+                chord_explorer::profiling::clear();
                 explorer.draw(&mut canvas);
+                #[cfg(feature = "profiling")]
+                let mut report = chord_explorer::profiling::Report::take();
+                #[cfg(feature = "count-allocations")]
+                log::debug!(
+                    "{} allocations while drawing",
+                    chord_explorer::alloc_count::allocations() - allocations
+                );
 
-                if let Err(err) = pixels.render() {
-                    log_error("pixels.render", err);
-                    target.exit();
-                    return;
+                let mut overlays: Vec<BoxedWidget> = vec![Box::new(Toasts)];
+                if let Some(kind) = hovered_file {
+                    overlays.push(Box::new(DropOverlay(kind)));
+                }
+                std::mem::swap(&mut canvas.hover, &mut overlay_hover);
+                for overlay in &mut overlays {
+                    overlay.draw(&mut canvas);
+                }
+                canvas.hover.end_frame();
+                std::mem::swap(&mut canvas.hover, &mut overlay_hover);
+                events.overlays = std::mem::take(&mut canvas.events.next_overlays);
+
+                // The frame pacing and the slowest widgets of the frame in the
+                // top right, and all of them in the log on F9
+                #[cfg(feature = "profiling")]
+                {
+                    let lines = report.offenders().len().min(5) as i32 + 1;
+                    let rect = Rect {
+                        x: width - 24 * font.width,
+                        y: 0,
+                        width: 24 * font.width,
+                        height: lines * font.height,
+                    };
+                    canvas.absolute(rect, |canvas| {
+                        canvas.visuals.text_size = 1;
+                        canvas.clear_rect();
+                        canvas.cut_top(font.height, |canvas| canvas.text(&pacing));
+                        report.draw(canvas);
+                    });
+                    if events.keys_pressed.contains(&winit::keyboard::KeyCode::F9) {
+                        report.log();
+                    }
+                }
+
+                // Spin in the bottom left corner while work is done in the background
+                autosave.take_if(|handle| handle.is_finished());
+                if autosave.is_some() {
+                    let size = canvas.visuals.font_height();
+                    let rect = Rect {
+                        x: 0,
+                        y: height - size,
+                        width: size,
+                        height: size,
+                    };
+                    canvas.absolute(rect, |canvas| spinner.draw(canvas));
+                }
+
+                if let Some(frame) = &letterboxed {
+                    let size =
+                        PhysicalSize::new(pixels.texture().width(), pixels.texture().height());
+                    blit_letterboxed(frame, (width, height), pixels.frame_mut(), size, scale);
+                }
+
+                // Per-frame input has been handled
+                events.clicked = false;
+                events.released = false;
+                events.double_clicked = false;
+                if !events.mouse_left {
+                    events.press_origin = None;
+                }
+                events.scroll = 0.0;
+                events.keys_pressed.clear();
+                events.characters_typed.clear();
+
+                if let Some(text) = explorer.take_clipboard() {
+                    match clipboard.as_mut() {
+                        Some(clipboard) => {
+                            if let Err(err) = clipboard.set_text(text) {
+                                notify::warn!("could not copy to clipboard: {err}");
+                            }
+                        }
+                        None => {
+                            notify::warn!("could not copy to clipboard: no clipboard available")
+                        }
+                    }
+                }
+
+                // Only touch the title when it changes, as it is a call to the OS
+                let new_title = format!("{TITLE} — {}", explorer.title_fragment());
+                if new_title != title {
+                    window.set_title(&new_title);
+                    title = new_title;
+                }
+
+                if explorer.fullscreen() != window.fullscreen().is_some() {
+                    set_fullscreen(&window, explorer.fullscreen(), &mut windowed);
+                }
+
+                // Autosave in the background whenever the session changed
+                if last_autosave.elapsed() >= session::AUTOSAVE_INTERVAL {
+                    last_autosave = Instant::now();
+                    let mut session = explorer.session();
+                    if session != saved_session {
+                        saved_session = session.clone();
+                        session.saved_at = session::now();
+                        autosave = Some(thread::spawn(move || {
+                            if let Err(err) = session.save() {
+                                warn!("could not save session: {err}");
+                            }
+                        }));
+                    }
+                }
+
+                // Keep the spinner turning until the work is done
+                if autosave.is_some() {
+                    let spin = Instant::now() + SPINNER_FRAME;
+                    redraw_at = Some(redraw_at.map_or(spin, |at| at.min(spin)));
+                }
+                if explorer.tooltip_pending() {
+                    let tooltip = Instant::now() + TOOLTIP_FRAME;
+                    redraw_at = Some(redraw_at.map_or(tooltip, |at| at.min(tooltip)));
+                }
+                if notify::animating() {
+                    let toast = Instant::now() + TOAST_FRAME;
+                    redraw_at = Some(redraw_at.map_or(toast, |at| at.min(toast)));
+                }
+                if explorer.holding() {
+                    let repeat = Instant::now() + REPEAT_FRAME;
+                    redraw_at = Some(redraw_at.map_or(repeat, |at| at.min(repeat)));
+                }
+
+                // A failed frame is only fatal if the next ones fail too
+                match pixels.render() {
+                    Ok(()) => render_failures = 0,
+                    Err(err) => {
+                        render_failures += 1;
+                        log_error("pixels.render", &err);
+                        if render_failures >= MAX_RENDER_FAILURES {
+                            target.exit();
+                            return;
+                        }
+                        notify::error!("could not draw the window: {err}");
+                    }
+                }
+
+                target.set_control_flow(next_wakeup(redraw_at));
+            }
+
+            if let Event::LoopExiting = event {
+                let mut session = explorer.session();
+                session.saved_at = session::now();
+                if let Err(err) = session.save() {
+                    warn!("could not save session: {err}");
+                }
+                return;
+            }
+
+            // Anything but the cursor moving may change the next frame, as may
+            // the timers waking the loop up
+            match &event {
+                Event::NewEvents(StartCause::ResumeTimeReached { .. }) => dirty = true,
+                Event::WindowEvent {
+                    event: WindowEvent::CursorMoved { .. } | WindowEvent::RedrawRequested,
+                    ..
+                } => {}
+                Event::WindowEvent { .. } => dirty = true,
+                _ => {}
+            }
+
+            // Files and scale changes are handled one event at a time, so multiple drops load in order
+            if let Event::WindowEvent {
+                window_id: _,
+                event: file_event,
+            } = &event
+            {
+                match file_event {
+                    // Picked up with the other resizes below, as the new size may not
+                    // come with its own resize event
+                    WindowEvent::ScaleFactorChanged { .. } => {
+                        scale_factor_changed = true;
+                    }
+                    WindowEvent::HoveredFile(path) => hovered_file = FileKind::detect(path),
+                    WindowEvent::HoveredFileCancelled => hovered_file = None,
+                    WindowEvent::DroppedFile(path) => {
+                        hovered_file = None;
+                        match FileKind::detect(path) {
+                            Some(FileKind::Font) => match load_font(path, args.font_size) {
+                                Ok(loaded) => {
+                                    font =
+                                        loaded.with_custom_music_glyphs().with_atlas(ATLAS_SCALE);
+                                    notify::info!("loaded font '{}'", path.display());
+                                }
+                                Err(err) => {
+                                    notify::error!(
+                                        "could not load font '{}': {err}",
+                                        path.display()
+                                    )
+                                }
+                            },
+                            Some(kind @ (FileKind::Scala | FileKind::Tuning)) => {
+                                match files::read_tunings(kind, path) {
+                                    Ok(tunings) => {
+                                        for tuning in tunings {
+                                            notify::info!("loaded tuning '{}'", tuning.name);
+                                            explorer.add_tuning(tuning);
+                                        }
+                                    }
+                                    Err(err) => notify::error!(
+                                        "could not load {} '{}': {err}",
+                                        kind.name(),
+                                        path.display()
+                                    ),
+                                }
+                            }
+                            Some(kind) => notify::warn!(
+                                "cannot load {} '{}' yet",
+                                kind.name(),
+                                path.display()
+                            ),
+                            None => notify::warn!("unrecognized file '{}'", path.display()),
+                        }
+                        window.request_redraw();
+                    }
+                    _ => {}
+                }
+            }
+
+            // Keys are collected manually, as the input helper cannot list them
+            if let Event::WindowEvent {
+                window_id: _,
+                event: WindowEvent::KeyboardInput { event: key, .. },
+            } = &event
+            {
+                if let (ElementState::Pressed, false, PhysicalKey::Code(code)) =
+                    (key.state, key.repeat, key.physical_key)
+                {
+                    events.keys_pressed.push(code);
+                }
+
+                // Text input repeats while the key is held, but shortcuts are not text
+                if let (ElementState::Pressed, Key::Character(text)) = (key.state, &key.logical_key)
+                {
+                    if !events.control {
+                        let text = text.chars().filter(|c| !c.is_control());
+                        events.characters_typed.extend(text);
+                    }
                 }
             }
 
             // Handle input events
             if input.update(&event) {
+                // Collect input for the next frame, which is drawn in a later iteration
+                events.mouse_left = input.mouse_held(0);
+                events.mouse_middle = input.mouse_held(2);
+                events.mouse_right = input.mouse_held(1);
+                // Positions over the letterbox bars have no pixel and are ignored
+                events.cursor = input
+                    .cursor()
+                    .and_then(|pos| cursor_pixel(pos, window.inner_size(), (width, height), scale));
+                // Most cursor movement changes nothing on screen, which the last
+                // frame can tell from what it hovered
+                let held = events.mouse_left || events.mouse_middle || events.mouse_right;
+                let (from, to) = (drawn_cursor, events.cursor);
+                dirty |= from != to
+                    && (held
+                        || explorer.cursor_moved_redraws(from, to)
+                        || overlay_hover.cursor_moved(from, to));
+                if input.mouse_pressed(0) {
+                    events.clicked = true;
+                    events.press_origin = events.cursor;
+                    // a third press starts over rather than double clicking again
+                    let now = Instant::now();
+                    let near = |(x, y): (i32, i32), (cx, cy): (i32, i32)| {
+                        (x - cx).abs().max((y - cy).abs()) <= DOUBLE_CLICK_DISTANCE
+                    };
+                    let double =
+                        last_click
+                            .zip(events.cursor)
+                            .is_some_and(|((at, pos), cursor)| {
+                                now - at <= DOUBLE_CLICK_TIME && near(pos, cursor)
+                            });
+                    events.double_clicked |= double;
+                    last_click = match double {
+                        true => None,
+                        false => events.cursor.map(|cursor| (now, cursor)),
+                    };
+                }
+                events.released |= input.mouse_released(0);
+                events.scroll += input.scroll_diff().1;
+                events.control = input.held_control();
+                events.alt = input.held_alt();
+
+                // Pasted text goes to the field being typed in, or selects a chord.
+                // Checked on the input helper, as keys pile up in `events` until
+                // the next redraw
+                let mut paste = Action::Paste.bindings().iter();
+                let paste =
+                    input.held_control() && paste.any(|binding| input.key_pressed(binding.key));
+                if paste {
+                    match clipboard.as_mut().map(Clipboard::get_text) {
+                        Some(Ok(text)) if explorer.typing() => {
+                            let text = text.chars().filter(|c| !c.is_control());
+                            events.characters_typed.extend(text);
+                        }
+                        Some(Ok(text)) => {
+                            explorer.paste(&text);
+                        }
+                        Some(Err(err)) => notify::warn!("could not paste from clipboard: {err}"),
+                        None => {
+                            notify::warn!("could not paste from clipboard: no clipboard available")
+                        }
+                    }
+                }
+
                 // Close
-                if input.key_pressed(KeyCode::Escape) || input.close_requested() {
+                // an open context menu or a drag takes Escape instead
+                let quit = Action::Quit.bindings().iter();
+                let quit = !explorer.takes_escape()
+                    && quit.clone().any(|binding| input.key_pressed(binding.key));
+                if quit || input.close_requested() {
                     target.exit();
                     return;
                 }
 
-                // Resize the window
-                if let Some(size) = input.window_resized() {
-                    width = size.width as i32;
-                    height = size.height as i32;
+                // Resize the window, keeping the buffer size when letterboxed
+                let rescaled =
+                    std::mem::take(&mut scale_factor_changed).then(|| window.inner_size());
+                if let Some(size) = input.window_resized().or(rescaled) {
                     if let Err(err) = pixels.resize_surface(size.width, size.height) {
-                        log_error("pixels.resize_surface", err);
-                        target.exit();
-                        return;
+                        log_error("pixels.resize_surface", &err);
+                        notify::error!("could not resize the window: {err}");
                     }
-                    if let Err(err) = pixels.resize_buffer(size.width, size.height) {
-                        log_error("pixels.resize_buffer", err);
-                        target.exit();
-                        return;
+                    let buffer = match (args.letterbox, scale) {
+                        (false, _) => Some(buffer_size(size, window.scale_factor(), args.buffer)),
+                        (true, LetterboxScale::Fractional) => {
+                            Some((size.width.max(1), size.height.max(1)))
+                        }
+                        (true, LetterboxScale::Integer) => None,
+                    };
+                    if let Some((w, h)) = buffer {
+                        match pixels.resize_buffer(w, h) {
+                            Ok(()) if !args.letterbox => {
+                                width = w as i32;
+                                height = h as i32;
+                            }
+                            Ok(()) => {}
+                            Err(err) => {
+                                log_error("pixels.resize_buffer", &err);
+                                notify::error!("could not resize the window: {err}");
+                            }
+                        }
                     }
                 }
 
-                // Request redraw
-                window.request_redraw();
+                // Request redraw if anything changed, or put it off until a frame
+                // time has passed
+                let next = last_redraw.zip(frame_time).map(|(last, time)| last + time);
+                match next.filter(|&next| next > Instant::now()) {
+                    _ if !dirty => {}
+                    Some(next) => redraw_at = Some(next),
+                    None => {
+                        redraw_at = None;
+                        window.request_redraw();
+                    }
+                }
+                target.set_control_flow(next_wakeup(redraw_at));
             }
         })
         .unwrap();
@@ -248,9 +600,356 @@ fn main() -> Result<(), Error> {
     Ok(())
 }
 
-fn log_error<E: std::error::Error + 'static>(method_name: &str, err: E) {
+/// Waits for a put-off redraw or the next toast to expire, whichever comes
+/// first.
+fn next_wakeup(redraw_at: Option<Instant>) -> ControlFlow {
+    match redraw_at.into_iter().chain(notify::next_expiry()).min() {
+        Some(time) => ControlFlow::WaitUntil(time),
+        None => ControlFlow::Wait,
+    }
+}
+
+/// Buffer size for a window of physical `size`. Logical buffers are scaled
+/// up by the scale factor rounded to a whole number, so every buffer pixel
+/// covers the same number of screen pixels and stays crisp.
+fn buffer_size(size: PhysicalSize<u32>, scale_factor: f64, mode: BufferMode) -> (u32, u32) {
+    match mode {
+        BufferMode::Physical => (size.width.max(1), size.height.max(1)),
+        BufferMode::Logical => {
+            let scale = (scale_factor.round() as u32).max(1);
+            ((size.width / scale).max(1), (size.height / scale).max(1))
+        }
+    }
+}
+
+/// Buffer size for a fixed `(width, height)` aspect ratio, as many rows tall
+/// as the default window. It is scaled up as [`letterbox_fit`] says, by
+/// `pixels` for whole factors, and the rest is filled with bars.
+fn letterbox_size((width, height): (u32, u32)) -> (u32, u32) {
+    let rows = HEIGHT as u32;
+    ((rows * width + height / 2) / height, rows)
+}
+
+/// The scale and offset, in physical pixels, of `buffer` drawn centered in
+/// the window: by the largest whole factor that fits, as `pixels` draws it,
+/// or by as much as fits at all.
+fn letterbox_fit(
+    window: PhysicalSize<u32>,
+    buffer: (i32, i32),
+    scale: LetterboxScale,
+) -> (f64, (i32, i32)) {
+    let (width, height) = (window.width as i32, window.height as i32);
+    let scale = match scale {
+        LetterboxScale::Integer => (width / buffer.0).min(height / buffer.1).max(1) as f64,
+        LetterboxScale::Fractional => {
+            let scale = (width as f64 / buffer.0 as f64).min(height as f64 / buffer.1 as f64);
+            if scale > 0.0 {
+                scale
+            } else {
+                1.0
+            }
+        }
+    };
+    let offset = |window: i32, buffer: i32| ((window as f64 - buffer as f64 * scale) / 2.0) as i32;
+    (scale, (offset(width, buffer.0), offset(height, buffer.1)))
+}
+
+/// The buffer pixel under the cursor at physical `pos`, with the buffer
+/// placed by [`letterbox_fit`], or `None` over the bars around it.
+fn cursor_pixel(
+    pos: (f32, f32),
+    window: PhysicalSize<u32>,
+    buffer: (i32, i32),
+    scale: LetterboxScale,
+) -> Option<(i32, i32)> {
+    let (scale, (left, top)) = letterbox_fit(window, buffer, scale);
+    let pixel = |pos: f32, offset: i32| ((pos.floor() as f64 - offset as f64) / scale).floor();
+    let (x, y) = (pixel(pos.0, left) as i32, pixel(pos.1, top) as i32);
+    ((0..buffer.0).contains(&x) && (0..buffer.1).contains(&y)).then_some((x, y))
+}
+
+/// Draws the letterboxed `buffer` of `size` into a `frame` the size of the
+/// `window`, each pixel showing the one [`cursor_pixel`] maps it to and the
+/// bars around it black.
+fn blit_letterboxed(
+    buffer: &[u8],
+    size: (i32, i32),
+    frame: &mut [u8],
+    window: PhysicalSize<u32>,
+    scale: LetterboxScale,
+) {
+    let width = window.width.max(1) as usize;
+    for (i, pixel) in frame.chunks_exact_mut(4).enumerate() {
+        let pos = ((i % width) as f32, (i / width) as f32);
+        let color = match cursor_pixel(pos, window, size, scale) {
+            Some((x, y)) => {
+                let at = ((y * size.0 + x) * 4) as usize;
+                &buffer[at..at + 4]
+            }
+            None => &[0, 0, 0, 255],
+        };
+        pixel.copy_from_slice(color);
+    }
+}
+
+/// How the window looked before it went fullscreen.
+struct Windowed {
+    position: Option<PhysicalPosition<i32>>,
+    size: PhysicalSize<u32>,
+    maximized: bool,
+}
+
+/// Switches between windowed and borderless fullscreen on the current
+/// monitor, putting the window back the way it was when leaving. The
+/// resulting resize goes through the usual resize path.
+fn set_fullscreen(window: &Window, fullscreen: bool, windowed: &mut Option<Windowed>) {
+    if fullscreen {
+        *windowed = Some(Windowed {
+            position: window.outer_position().ok(),
+            size: window.inner_size(),
+            maximized: window.is_maximized(),
+        });
+        window.set_fullscreen(Some(Fullscreen::Borderless(None)));
+        return;
+    }
+
+    window.set_fullscreen(None);
+    match windowed.take() {
+        Some(Windowed {
+            maximized: true, ..
+        }) => window.set_maximized(true),
+        Some(Windowed { position, size, .. }) => {
+            let _ = window.request_inner_size(size);
+            if let Some(position) = position {
+                window.set_outer_position(position);
+            }
+        }
+        None => {}
+    }
+}
+
+/// Size of the window icon, in pixels.
+const ICON_SIZE: i32 = 32;
+
+/// The window icon: a beamed pair of eighth notes from Cozette on a dark
+/// square, drawn at startup like the rest of the UI.
+fn window_icon() -> Option<Icon> {
+    let font = Font::from_bdf_bytes(COZETTE, 6, 13).ok()?;
+    let mut rgba = vec![0; (ICON_SIZE * ICON_SIZE * 4) as usize];
+    let mut canvas = Canvas {
+        pix: PixBuf::from_pixels_frame(&mut rgba, ICON_SIZE, ICON_SIZE),
+        rect: Rect {
+            x: 0,
+            y: 0,
+            width: ICON_SIZE,
+            height: ICON_SIZE,
+        },
+        visuals: Visuals {
+            font: &font,
+            text_size: 2,
+            dir: CutDir::Vertical,
+            color: [255, 255, 255, 255],
+            line_spacing: 0,
+        },
+        events: Events::default(),
+        tooltip: TooltipState::default(),
+        hover: HoverState::default(),
+        repeat: RepeatState::default(),
+        menu: MenuState::default(),
+        panes: PaneState::default(),
+        status: StatusState::default(),
+    };
+    canvas.fill([48, 48, 96, 255]);
+    let (width, height) = (2 * font.len("♫") * font.width, 2 * font.height);
+    canvas.center(width, height, |canvas| canvas.text("♫"));
+    drop(canvas);
+    Icon::from_rgba(rgba, ICON_SIZE as u32, ICON_SIZE as u32)
+        .map_err(|err| log_error("Icon::from_rgba", &err))
+        .ok()
+}
+
+fn load_font(path: &Path, (width, height): (i32, i32)) -> Result<Font, String> {
+    let bytes = fs::read(path).map_err(|err| err.to_string())?;
+    Font::from_bdf_bytes(&bytes, width, height).map_err(|err| err.to_string())
+}
+
+fn log_error<E: std::error::Error + 'static>(method_name: &str, err: &E) {
     error!("{method_name}() failed: {err}");
     for source in err.sources().skip(1) {
         error!("  Caused by: {source}");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(width: u32, height: u32) -> PhysicalSize<u32> {
+        PhysicalSize::new(width, height)
+    }
+
+    #[test]
+    fn letterboxed_buffers_keep_the_aspect() {
+        assert_eq!(letterbox_size((4, 3)), (320, 240));
+        assert_eq!(letterbox_size((16, 9)), (427, 240));
+        assert_eq!(letterbox_size((1, 1)), (240, 240));
+    }
+
+    #[test]
+    fn maps_the_cursor_through_the_letterbox() {
+        let buffer = (320, 240);
+        // scaled by 2 with bars of 80 and 60 pixels
+        let at = |x, y| cursor_pixel((x, y), window(800, 600), buffer, LetterboxScale::Integer);
+        assert_eq!(at(80.0, 60.0), Some((0, 0)));
+        assert_eq!(at(719.9, 539.0), Some((319, 239)));
+        assert_eq!(at(79.0, 300.0), None);
+        assert_eq!(at(720.0, 300.0), None);
+        assert_eq!(at(400.0, 59.0), None);
+
+        // scaled by 4 to fit the height, with bars at the sides
+        let at = |x, y| cursor_pixel((x, y), window(1920, 1080), buffer, LetterboxScale::Integer);
+        assert_eq!(at(320.0, 60.0), Some((0, 0)));
+        assert_eq!(at(1599.0, 1019.0), Some((319, 239)));
+        assert_eq!(at(323.0, 63.0), Some((0, 0)));
+        assert_eq!(at(324.0, 63.0), Some((1, 0)));
+        assert_eq!(at(100.0, 500.0), None);
+
+        // too narrow to scale up
+        let at = |x, y| cursor_pixel((x, y), window(1000, 240), buffer, LetterboxScale::Integer);
+        assert_eq!(at(340.0, 0.0), Some((0, 0)));
+        assert_eq!(at(339.0, 0.0), None);
+    }
+
+    #[test]
+    fn fractional_letterboxes_fill_the_window_as_far_as_they_fit() {
+        let buffer = (320, 240);
+        let fit = |width, height| {
+            letterbox_fit(window(width, height), buffer, LetterboxScale::Fractional)
+        };
+        assert_eq!(fit(800, 600), (2.5, (0, 0)));
+        assert_eq!(fit(1920, 1080), (4.5, (240, 0)));
+        assert_eq!(fit(640, 720), (2.0, (0, 120)));
+        // whole factors are left as they are
+        assert_eq!(
+            letterbox_fit(window(1920, 1080), buffer, LetterboxScale::Integer),
+            (4.0, (320, 60))
+        );
+    }
+
+    #[test]
+    fn maps_the_cursor_through_a_fractional_letterbox() {
+        let buffer = (320, 240);
+        let at = |x, y| {
+            cursor_pixel(
+                (x, y),
+                window(1920, 1080),
+                buffer,
+                LetterboxScale::Fractional,
+            )
+        };
+        assert_eq!(at(240.0, 0.0), Some((0, 0)));
+        assert_eq!(at(244.0, 4.0), Some((0, 0)));
+        assert_eq!(at(245.0, 5.0), Some((1, 1)));
+        assert_eq!(at(1679.0, 1079.0), Some((319, 239)));
+        assert_eq!(at(239.0, 500.0), None);
+        assert_eq!(at(1680.0, 500.0), None);
+
+        // scaled down to fit a window narrower than the aspect
+        let at = |x, y| {
+            cursor_pixel(
+                (x, y),
+                window(320, 240),
+                (427, 240),
+                LetterboxScale::Fractional,
+            )
+        };
+        assert_eq!(at(0.0, 30.0), Some((0, 0)));
+        // a row and a column are lost to scaling down
+        assert_eq!(at(319.0, 209.0), Some((425, 238)));
+        assert_eq!(at(0.0, 29.0), None);
+        assert_eq!(at(0.0, 210.0), None);
+    }
+
+    #[test]
+    fn fractional_letterboxes_are_drawn_where_the_cursor_maps() {
+        let (red, blue) = ([255, 0, 0, 255], [0, 0, 255, 255]);
+        let buffer = [red, blue].concat();
+        let mut frame = vec![0; 5 * 4 * 4];
+        let size = window(5, 4);
+        blit_letterboxed(
+            &buffer,
+            (2, 1),
+            &mut frame,
+            size,
+            LetterboxScale::Fractional,
+        );
+        let pixels: Vec<&[u8]> = frame.chunks(4).collect();
+        // 2.5 screen pixels to a buffer pixel, with a bar below
+        for row in pixels.chunks(5).take(3) {
+            assert_eq!(row, [&red[..], &red, &red, &blue, &blue]);
+        }
+        assert!(pixels[15..].iter().all(|&pixel| pixel == [0, 0, 0, 255]));
+        for (i, pixel) in pixels.iter().enumerate() {
+            let pos = ((i % 5) as f32, (i / 5) as f32);
+            let mapped = cursor_pixel(pos, size, (2, 1), LetterboxScale::Fractional);
+            assert_eq!(mapped.is_some(), pixel != &[0, 0, 0, 255]);
+        }
+    }
+
+    #[test]
+    fn a_window_smaller_than_the_buffer_shows_its_middle() {
+        let at = |x, y| {
+            cursor_pixel(
+                (x, y),
+                window(200, 100),
+                (320, 240),
+                LetterboxScale::Integer,
+            )
+        };
+        assert_eq!(at(0.0, 0.0), Some((60, 70)));
+        assert_eq!(at(199.0, 99.0), Some((259, 169)));
+    }
+
+    #[test]
+    fn logical_buffers_scale_by_whole_factors() {
+        let size = window(640, 480);
+        assert_eq!(buffer_size(size, 1.0, BufferMode::Logical), (640, 480));
+        // 1.5 rounds up, which keeps every pixel the same size
+        assert_eq!(buffer_size(size, 1.5, BufferMode::Logical), (320, 240));
+        assert_eq!(buffer_size(size, 2.0, BufferMode::Logical), (320, 240));
+        for scale_factor in [1.0, 1.5, 2.0] {
+            assert_eq!(
+                buffer_size(size, scale_factor, BufferMode::Physical),
+                (640, 480)
+            );
+        }
+        assert_eq!(buffer_size(window(1, 0), 2.0, BufferMode::Logical), (1, 1));
+    }
+
+    #[test]
+    fn maps_physical_cursor_positions_at_each_scale_factor() {
+        // a 320 by 240 logical window, in physical pixels
+        for (scale_factor, size) in [
+            (1.0, window(320, 240)),
+            (1.5, window(480, 360)),
+            (2.0, window(640, 480)),
+        ] {
+            let buffer = buffer_size(size, scale_factor, BufferMode::Logical);
+            let buffer = (buffer.0 as i32, buffer.1 as i32);
+            let at = |x: f32, y: f32| cursor_pixel((x, y), size, buffer, LetterboxScale::Integer);
+            let last = (size.width as f32 - 1.0, size.height as f32 - 1.0);
+            assert_eq!(at(0.0, 0.0), Some((0, 0)), "{scale_factor}");
+            assert_eq!(at(last.0, last.1), Some((buffer.0 - 1, buffer.1 - 1)));
+            // the middle of the window is the middle of the buffer
+            let middle = at(size.width as f32 / 2.0, size.height as f32 / 2.0);
+            assert_eq!(middle, Some((buffer.0 / 2, buffer.1 / 2)), "{scale_factor}");
+
+            let physical = buffer_size(size, scale_factor, BufferMode::Physical);
+            let physical = (physical.0 as i32, physical.1 as i32);
+            assert_eq!(
+                cursor_pixel(last, size, physical, LetterboxScale::Integer),
+                Some((physical.0 - 1, physical.1 - 1))
+            );
+        }
+    }
+}
@@ -0,0 +1,671 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    widget::{Canvas, CutDir, Rect, Widget, WrappedText, HOVER},
+    Color,
+};
+
+/// How long info and warning toasts stay up, not counting the time the
+/// cursor is over them. Errors stay until closed.
+pub const TOAST_DURATION: Duration = Duration::from_secs(4);
+
+/// Most toasts shown at once; older ones are dropped first.
+const MAX_TOASTS: usize = 5;
+
+/// Characters on a line of a toast before its message wraps.
+pub const TOAST_COLUMNS: i32 = 30;
+
+static QUEUE: Mutex<Notifications> = Mutex::new(Notifications {
+    queue: Vec::new(),
+    next_id: 0,
+    activated: Vec::new(),
+});
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    Info,
+    Warn,
+    Error,
+}
+
+impl Level {
+    fn color(&self) -> Color {
+        match self {
+            Level::Info => [48, 48, 96, 255],
+            Level::Warn => [112, 88, 16, 255],
+            Level::Error => [128, 24, 24, 255],
+        }
+    }
+
+    /// The brighter color of the stripe and countdown of its toasts.
+    fn accent(&self) -> Color {
+        match self {
+            Level::Info => [112, 112, 224, 255],
+            Level::Warn => [240, 192, 48, 255],
+            Level::Error => [240, 72, 72, 255],
+        }
+    }
+}
+
+/// Tells toasts apart, for the code that pushed one with an action to
+/// know when its button was clicked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ToastId(u64);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+    pub level: Level,
+    pub message: String,
+    pub created: Instant,
+    /// Label of a button on the toast, such as "Undo".
+    pub action: Option<String>,
+    id: ToastId,
+    /// When the cursor came over the toast, which holds off its expiry.
+    paused: Option<Instant>,
+    /// Where the toast is drawn, sliding towards its place in the stack.
+    y: Option<i32>,
+    /// Whether it has yet to reach its place.
+    sliding: bool,
+}
+
+impl Notification {
+    pub fn new(level: Level, message: String, created: Instant) -> Self {
+        Self {
+            level,
+            message,
+            created,
+            action: None,
+            id: ToastId(0),
+            paused: None,
+            y: None,
+            sliding: false,
+        }
+    }
+
+    pub fn with_action(mut self, label: &str) -> Self {
+        self.action = Some(label.to_string());
+        self
+    }
+
+    pub fn id(&self) -> ToastId {
+        self.id
+    }
+
+    pub fn expires_at(&self) -> Option<Instant> {
+        (self.level != Level::Error && self.paused.is_none())
+            .then_some(self.created + TOAST_DURATION)
+    }
+
+    /// The fraction of its time the toast has left at `now`, or `None` if
+    /// it does not expire.
+    pub fn remaining(&self, now: Instant) -> Option<f32> {
+        if self.level == Level::Error {
+            return None;
+        }
+        let shown = self
+            .paused
+            .unwrap_or(now)
+            .saturating_duration_since(self.created);
+        Some(1.0 - (shown.as_secs_f32() / TOAST_DURATION.as_secs_f32()).min(1.0))
+    }
+
+    /// Holds off the expiry while `hovered`, and moves it on by as long as
+    /// it was held once the cursor leaves.
+    fn hold(&mut self, hovered: bool, now: Instant) {
+        match (hovered, self.paused) {
+            (true, None) => self.paused = Some(now),
+            (false, Some(since)) => {
+                self.created += now.saturating_duration_since(since);
+                self.paused = None;
+            }
+            _ => {}
+        }
+    }
+
+    /// Moves the toast `step` pixels, or a third of the way if that is
+    /// more, towards `target`, where it starts out, and returns where it is.
+    fn slide(&mut self, target: i32, step: i32) -> i32 {
+        let y = match self.y {
+            Some(y) => {
+                let distance = target - y;
+                let by = step.max(distance.abs() / 3).min(distance.abs());
+                y + by * distance.signum()
+            }
+            None => target,
+        };
+        self.y = Some(y);
+        self.sliding = y != target;
+        y
+    }
+}
+
+/// Notifications in the order they were pushed.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Notifications {
+    queue: Vec<Notification>,
+    next_id: u64,
+    /// Toasts whose action button was clicked, until taken.
+    activated: Vec<ToastId>,
+}
+
+impl Notifications {
+    pub fn push(&mut self, level: Level, message: String, now: Instant) -> ToastId {
+        self.push_notification(Notification::new(level, message, now))
+    }
+
+    pub fn push_notification(&mut self, mut notification: Notification) -> ToastId {
+        notification.id = ToastId(self.next_id);
+        self.next_id += 1;
+        self.queue.push(notification);
+        if self.queue.len() > MAX_TOASTS {
+            self.queue.remove(0);
+        }
+        ToastId(self.next_id - 1)
+    }
+
+    /// Drops every notification that expired by `now`.
+    pub fn expire(&mut self, now: Instant) {
+        self.queue
+            .retain(|n| n.expires_at().is_none_or(|expires| expires > now));
+    }
+
+    pub fn dismiss(&mut self, index: usize) {
+        if index < self.queue.len() {
+            self.queue.remove(index);
+        }
+    }
+
+    /// When the next notification expires, if any will.
+    pub fn next_expiry(&self) -> Option<Instant> {
+        self.queue.iter().filter_map(Notification::expires_at).min()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Notification> {
+        self.queue.iter()
+    }
+
+    /// Whether a toast is sliding into place or counting down, which takes
+    /// more frames to show.
+    pub fn animating(&self) -> bool {
+        let counting = |n: &Notification| n.level != Level::Error && n.paused.is_none();
+        self.queue.iter().any(|n| n.sliding || counting(n))
+    }
+
+    /// Takes the toasts whose action button was clicked since last asked.
+    pub fn take_activated(&mut self) -> Vec<ToastId> {
+        std::mem::take(&mut self.activated)
+    }
+
+    /// Rects of the toasts stacked up from the bottom right corner of `area`,
+    /// newest at the bottom. `size` maps a notification to the width and
+    /// height of its toast.
+    pub fn layout(&self, area: Rect, size: impl Fn(&Notification) -> (i32, i32)) -> Vec<Rect> {
+        let mut bottom = area.y + area.height;
+        let mut rects: Vec<Rect> = self
+            .queue
+            .iter()
+            .rev()
+            .map(|n| {
+                let (width, height) = size(n);
+                let width = width.min(area.width);
+                bottom -= height;
+                Rect {
+                    x: area.x + area.width - width,
+                    y: bottom,
+                    width,
+                    height,
+                }
+            })
+            .collect();
+        rects.reverse();
+        rects
+    }
+}
+
+/// Queues a notification to be shown as a toast.
+pub fn push(level: Level, message: String) {
+    QUEUE.lock().unwrap().push(level, message, Instant::now());
+}
+
+/// Queues a notification with a button labelled `action`, which shows up
+/// in [`take_activated`] when clicked.
+pub fn push_action(level: Level, message: String, action: &str) -> ToastId {
+    let notification = Notification::new(level, message, Instant::now()).with_action(action);
+    QUEUE.lock().unwrap().push_notification(notification)
+}
+
+/// The toasts whose action button was clicked since last asked.
+pub fn take_activated() -> Vec<ToastId> {
+    QUEUE.lock().unwrap().take_activated()
+}
+
+/// When the toasts need to be drawn again to drop an expired one.
+pub fn next_expiry() -> Option<Instant> {
+    QUEUE.lock().unwrap().next_expiry()
+}
+
+/// Whether the toasts need more frames to slide into place or count down.
+pub fn animating() -> bool {
+    QUEUE.lock().unwrap().animating()
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! notify_info {
+    ($($arg:tt)*) => {{
+        let message = format!($($arg)*);
+        $crate::notify::log::info!("{message}");
+        $crate::notify::push($crate::notify::Level::Info, message);
+    }};
+}
+#[doc(hidden)]
+#[macro_export]
+macro_rules! notify_warn {
+    ($($arg:tt)*) => {{
+        let message = format!($($arg)*);
+        $crate::notify::log::warn!("{message}");
+        $crate::notify::push($crate::notify::Level::Warn, message);
+    }};
+}
+#[doc(hidden)]
+#[macro_export]
+macro_rules! notify_error {
+    ($($arg:tt)*) => {{
+        let message = format!($($arg)*);
+        $crate::notify::log::error!("{message}");
+        $crate::notify::push($crate::notify::Level::Error, message);
+    }};
+}
+/// `info!`, `warn!` and `error!` log a message like their `log` namesakes
+/// and also queue it as a toast.
+// renamed on export, as a plain `warn` clashes with the attribute
+pub use crate::{notify_error as error, notify_info as info, notify_warn as warn};
+#[doc(hidden)]
+pub use log;
+
+fn toast_rects(queue: &Notifications, canvas: &Canvas) -> Vec<Rect> {
+    queue.layout(canvas.rect, |n| Toast::size(n, canvas))
+}
+
+/// One notification as a toast: a stripe in the color of its level, the
+/// message wrapped to [`TOAST_COLUMNS`], its action button if it has one
+/// and a close button, with a bar along the bottom running down to when it
+/// expires.
+pub struct Toast<'a> {
+    pub notification: &'a Notification,
+    /// The time the countdown is drawn at.
+    pub now: Instant,
+    /// Whether the toast was clicked anywhere but its action button.
+    pub closed: bool,
+    /// Whether its action button was clicked.
+    pub activated: bool,
+}
+
+impl<'a> Toast<'a> {
+    /// Height of the countdown bar, in pixels of text.
+    const BAR: i32 = 2;
+
+    pub fn new(notification: &'a Notification, now: Instant) -> Self {
+        Self {
+            notification,
+            now,
+            closed: false,
+            activated: false,
+        }
+    }
+
+    /// The width and height of the toast of `notification`.
+    pub fn size(notification: &Notification, canvas: &Canvas) -> (i32, i32) {
+        let font = canvas.visuals.font;
+        let lines = Self::lines(notification, canvas);
+        let columns = lines.iter().map(|line| font.len(line)).max().unwrap_or(0);
+        let action = notification
+            .action
+            .as_ref()
+            .map_or(0, |label| font.len(label) + 2);
+        // the stripe and a space before the text, the close button after
+        let width = (columns.max(action) + 4) * canvas.visuals.font_width();
+        let lines = lines.len() as i32 + notification.action.is_some() as i32;
+        let bar = Self::BAR * canvas.visuals.text_size;
+        (width, lines * canvas.visuals.font_line_height() + bar)
+    }
+
+    fn lines(notification: &Notification, canvas: &Canvas) -> Vec<String> {
+        let font = canvas.visuals.font;
+        WrappedText::new(&notification.message).lines(TOAST_COLUMNS, |s| font.len(s))
+    }
+}
+
+impl Widget for Toast<'_> {
+    fn draw(&mut self, canvas: &mut Canvas) {
+        let notification = self.notification;
+        let (font_width, font_height) = (canvas.visuals.font_width(), canvas.visuals.font_height());
+        let accent = notification.level.accent();
+        let rect = canvas.rect;
+        canvas.fill(notification.level.color());
+
+        let bar = Self::BAR * canvas.visuals.text_size;
+        if let Some(remaining) = notification.remaining(self.now) {
+            let countdown = Rect {
+                y: rect.y + rect.height - bar,
+                width: (rect.width as f32 * remaining).round() as i32,
+                height: bar,
+                ..rect
+            };
+            canvas.with_rect(countdown, |canvas| canvas.fill(accent));
+        }
+        let close = Rect {
+            x: rect.x + rect.width - 2 * font_width,
+            width: 2 * font_width,
+            height: font_height,
+            ..rect
+        };
+        canvas.with_rect(close, |canvas| {
+            if canvas.hover() {
+                canvas.fill(HOVER);
+            }
+            canvas.center(font_width, font_height, |canvas| canvas.text("×"));
+        });
+
+        // cut from a copy of the rect, which is left whole to take the
+        // clicks on the rest of the toast
+        canvas.with_rect(rect, |canvas| {
+            canvas.visuals.dir = CutDir::Horizontal;
+            canvas.cut(font_width / 2, rect.height, |canvas| canvas.fill(accent));
+            canvas.cut(font_width / 2, rect.height, |_| {});
+            canvas.cut_remaining(|canvas| {
+                canvas.visuals.dir = CutDir::Vertical;
+                for line in Self::lines(notification, canvas) {
+                    canvas.cut_top(canvas.visuals.font_line_height(), |canvas| {
+                        canvas.text(&line)
+                    });
+                }
+                if let Some(label) = &notification.action {
+                    canvas.cut_top(canvas.visuals.font_line_height(), |canvas| {
+                        canvas.visuals.dir = CutDir::Horizontal;
+                        if canvas.button(label) {
+                            self.activated = true;
+                            canvas.consume_click();
+                        }
+                    });
+                }
+            });
+        });
+        // the close button and the rest of the toast close it alike
+        canvas.with_rect(rect, |canvas| {
+            if canvas.clicked() {
+                self.closed = true;
+                canvas.consume_click();
+            }
+        });
+    }
+}
+
+/// The queued notifications as toasts in the bottom right corner, stacked
+/// up from the newest. They slide up as new ones come in, and hold off
+/// expiring while the cursor is over them.
+pub struct Toasts;
+
+impl Widget for Toasts {
+    fn draw(&mut self, canvas: &mut Canvas) {
+        let now = Instant::now();
+        let mut queue = QUEUE.lock().unwrap();
+        queue.expire(now);
+        let step = 2 * canvas.visuals.text_size;
+        let (mut closed, mut activated) = (None, None);
+        for (i, target) in toast_rects(&queue, canvas).into_iter().enumerate() {
+            let notification = &mut queue.queue[i];
+            let rect = Rect {
+                y: notification.slide(target.y, step),
+                ..target
+            };
+            canvas.overlay(rect, |canvas| {
+                notification.hold(canvas.hover(), now);
+                let mut toast = Toast::new(notification, now);
+                toast.draw(canvas);
+                if toast.activated {
+                    activated = Some(i);
+                } else if toast.closed {
+                    closed = Some(i);
+                }
+            });
+        }
+        if let Some(index) = activated {
+            let id = queue.queue[index].id;
+            queue.activated.push(id);
+            queue.dismiss(index);
+        } else if let Some(index) = closed {
+            queue.dismiss(index);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        font::{Font, COZETTE},
+        widget::{
+            Events, HoverState, MenuState, PaneState, RepeatState, StatusState, TooltipState,
+            Visuals,
+        },
+        PixBuf,
+    };
+
+    fn messages(queue: &Notifications) -> Vec<&str> {
+        queue.iter().map(|n| n.message.as_str()).collect()
+    }
+
+    fn queue(levels: &[Level], now: Instant) -> Notifications {
+        let mut queue = Notifications::default();
+        for (i, &level) in levels.iter().enumerate() {
+            queue.push(level, i.to_string(), now);
+        }
+        queue
+    }
+
+    #[test]
+    fn keeps_the_order_pushed() {
+        let now = Instant::now();
+        let queue = queue(&[Level::Error, Level::Info, Level::Warn], now);
+        assert_eq!(messages(&queue), ["0", "1", "2"]);
+        let ids: Vec<_> = queue.iter().map(Notification::id).collect();
+        assert!(ids[0] != ids[1] && ids[1] != ids[2]);
+    }
+
+    #[test]
+    fn drops_the_oldest_past_the_limit() {
+        let queue = queue(&[Level::Info; MAX_TOASTS + 2], Instant::now());
+        assert_eq!(messages(&queue), ["2", "3", "4", "5", "6"]);
+    }
+
+    #[test]
+    fn expires_all_but_errors() {
+        let now = Instant::now();
+        let mut queue = queue(&[Level::Info, Level::Error, Level::Warn], now);
+        assert_eq!(queue.next_expiry(), Some(now + TOAST_DURATION));
+
+        queue.expire(now + TOAST_DURATION - Duration::from_millis(1));
+        assert_eq!(messages(&queue), ["0", "1", "2"]);
+        queue.expire(now + TOAST_DURATION);
+        assert_eq!(messages(&queue), ["1"]);
+        queue.expire(now + 100 * TOAST_DURATION);
+        assert_eq!(messages(&queue), ["1"]);
+        assert_eq!(queue.next_expiry(), None);
+    }
+
+    #[test]
+    fn hovering_holds_off_expiry() {
+        let now = Instant::now();
+        let mut queue = queue(&[Level::Info], now);
+        let second = Duration::from_secs(1);
+        queue.queue[0].hold(true, now + second);
+        queue.expire(now + 2 * TOAST_DURATION);
+        assert_eq!(queue.iter().count(), 1);
+        assert_eq!(
+            queue.queue[0].remaining(now + 2 * TOAST_DURATION),
+            Some(0.75)
+        );
+
+        // the time over it is added on once the cursor leaves
+        queue.queue[0].hold(false, now + 3 * second);
+        assert_eq!(queue.next_expiry(), Some(now + TOAST_DURATION + 2 * second));
+    }
+
+    #[test]
+    fn stacks_up_from_the_corner() {
+        let queue = queue(&[Level::Info, Level::Info, Level::Info], Instant::now());
+        let area = Rect {
+            x: 0,
+            y: 0,
+            width: 100,
+            height: 100,
+        };
+        let rects = queue.layout(area, |n| (if n.message == "1" { 200 } else { 40 }, 10));
+        let corners: Vec<_> = rects.iter().map(|r| (r.x, r.y, r.width)).collect();
+        // newest at the bottom, and no wider than the area
+        assert_eq!(corners, [(60, 70, 40), (0, 80, 100), (60, 90, 40)]);
+    }
+
+    #[test]
+    fn a_click_dismisses_the_toast_under_it() {
+        let mut queue = queue(&[Level::Error, Level::Error, Level::Error], Instant::now());
+        let area = Rect {
+            x: 0,
+            y: 0,
+            width: 100,
+            height: 100,
+        };
+        let hit = |queue: &Notifications, x, y| {
+            let rects = queue.layout(area, |_| (40, 10));
+            rects.iter().position(|rect| rect.contains(x, y))
+        };
+        assert_eq!(hit(&queue, 50, 95), None);
+        assert_eq!(hit(&queue, 60, 70), Some(0));
+        assert_eq!(hit(&queue, 99, 89), Some(1));
+        assert_eq!(hit(&queue, 60, 69), None);
+
+        queue.dismiss(hit(&queue, 80, 85).unwrap());
+        assert_eq!(messages(&queue), ["0", "2"]);
+        // the rest move down into its place
+        assert_eq!(hit(&queue, 80, 85), Some(0));
+        assert_eq!(hit(&queue, 80, 75), None);
+    }
+
+    #[test]
+    fn toasts_slide_into_their_place() {
+        let mut toast = Notification::new(Level::Info, String::new(), Instant::now());
+        // a new toast shows up where it belongs
+        assert_eq!(toast.slide(100, 2), 100);
+        assert!(!toast.sliding);
+        // and then moves a third of the way, or at least a step
+        assert_eq!(toast.slide(70, 2), 90);
+        assert!(toast.sliding);
+        assert_eq!(toast.slide(70, 2), 84);
+        let frames = std::iter::repeat_with(|| toast.slide(70, 2));
+        assert_eq!(frames.take_while(|&y| y != 70).count(), 5);
+        assert!(!toast.sliding);
+        assert_eq!(toast.slide(70, 2), 70);
+    }
+
+    /// Draws with `f` on a blank 120 by 60 frame with `events`.
+    fn draw_on<R>(events: Events, f: impl FnOnce(&mut Canvas) -> R) -> R {
+        let font = Font::from_bdf_bytes(COZETTE, 6, 13).unwrap();
+        let (width, height) = (120, 60);
+        let mut frame = vec![0; (width * height * 4) as usize];
+        let mut canvas = Canvas {
+            pix: PixBuf::from_pixels_frame(&mut frame, width, height),
+            rect: Rect {
+                x: 0,
+                y: 0,
+                width,
+                height,
+            },
+            visuals: Visuals {
+                font: &font,
+                text_size: 1,
+                dir: CutDir::Vertical,
+                color: [255, 255, 255, 255],
+                line_spacing: 0,
+            },
+            events,
+            tooltip: TooltipState::default(),
+            hover: HoverState::default(),
+            repeat: RepeatState::default(),
+            menu: MenuState::default(),
+            panes: PaneState::default(),
+            status: StatusState::default(),
+        };
+        f(&mut canvas)
+    }
+
+    /// Whether the toast of `notification`, drawn in the top left corner,
+    /// is activated or closed when clicked at `click`.
+    fn click(notification: &Notification, click: Option<(i32, i32)>) -> (bool, bool) {
+        let events = Events {
+            cursor: click,
+            press_origin: click,
+            clicked: click.is_some(),
+            mouse_left: click.is_some(),
+            ..Events::default()
+        };
+        draw_on(events, |canvas| {
+            let (width, height) = Toast::size(notification, canvas);
+            let rect = Rect {
+                x: 0,
+                y: 0,
+                width,
+                height,
+            };
+            let mut toast = Toast::new(notification, notification.created);
+            canvas.with_rect(rect, |canvas| toast.draw(canvas));
+            (toast.activated, toast.closed)
+        })
+    }
+
+    #[test]
+    fn toasts_fit_their_message_and_button() {
+        let size = |n: &Notification| draw_on(Events::default(), |canvas| Toast::size(n, canvas));
+        let now = Instant::now();
+        // the stripe and space, five characters and the close button, and
+        // a line over the countdown bar
+        let saved = Notification::new(Level::Info, "saved".to_string(), now);
+        assert_eq!(size(&saved), (54, 15));
+        // the button is two characters wider than its label "Undo"
+        assert_eq!(size(&saved.with_action("Undo")), (60, 28));
+        // long messages wrap
+        let (width, height) = size(&Notification::new(Level::Info, "word ".repeat(12), now));
+        assert!(width <= (TOAST_COLUMNS + 4) * 6);
+        assert_eq!(height, 2 * 13 + 2);
+    }
+
+    #[test]
+    fn the_action_button_activates_and_the_rest_closes() {
+        let undo = Notification::new(Level::Info, "deleted".to_string(), Instant::now())
+            .with_action("Undo");
+        assert_eq!(click(&undo, None), (false, false));
+        // the button, under the message
+        assert_eq!(click(&undo, Some((10, 19))), (true, false));
+        // the close button and the message
+        assert_eq!(click(&undo, Some((55, 5))), (false, true));
+        assert_eq!(click(&undo, Some((20, 5))), (false, true));
+        // outside the toast
+        assert_eq!(click(&undo, Some((100, 50))), (false, false));
+    }
+
+    #[test]
+    fn clicked_actions_are_taken_once() {
+        let mut queue = queue(&[Level::Info], Instant::now());
+        let id = queue.push_notification(
+            Notification::new(Level::Info, "deleted".to_string(), Instant::now())
+                .with_action("Undo"),
+        );
+        assert_eq!(queue.iter().map(Notification::id).last(), Some(id));
+        queue.activated.push(id);
+        assert_eq!(queue.take_activated(), [id]);
+        assert!(queue.take_activated().is_empty());
+    }
+}
@@ -0,0 +1,112 @@
+//! Draw times per widget, recorded by [`Canvas::profile_scope`], to find the
+//! widget that makes a frame slow. Only compiled with the `profiling`
+//! feature; without it `profile_scope` calls its closure and nothing else.
+
+use std::{cell::RefCell, time::Duration, time::Instant};
+
+use crate::widget::{Canvas, CutDir, Widget};
+
+thread_local! {
+    static PROFILER: RefCell<Profiler> = const {
+        RefCell::new(Profiler {
+            open: Vec::new(),
+            scopes: Vec::new(),
+        })
+    };
+}
+
+struct Profiler {
+    /// Index into `scopes` and start time of every scope not yet ended.
+    open: Vec<(usize, Instant)>,
+    scopes: Vec<Scope>,
+}
+
+/// One scope drawn in a frame.
+#[derive(Debug, Clone)]
+pub struct Scope {
+    pub name: &'static str,
+    /// Scopes open around this one.
+    pub depth: usize,
+    /// Time spent in the scope, nested scopes included.
+    pub elapsed: Duration,
+}
+
+pub(crate) fn begin(name: &'static str) {
+    PROFILER.with_borrow_mut(|profiler| {
+        let index = profiler.scopes.len();
+        profiler.scopes.push(Scope {
+            name,
+            depth: profiler.open.len(),
+            elapsed: Duration::ZERO,
+        });
+        profiler.open.push((index, Instant::now()));
+    });
+}
+
+pub(crate) fn end() {
+    PROFILER.with_borrow_mut(|profiler| {
+        if let Some((index, start)) = profiler.open.pop() {
+            profiler.scopes[index].elapsed = start.elapsed();
+        }
+    });
+}
+
+/// Forgets the scopes recorded so far, to start a frame.
+pub fn clear() {
+    PROFILER.with_borrow_mut(|profiler| profiler.scopes.clear());
+}
+
+/// The scopes of one frame, in the order they were entered.
+#[derive(Debug, Default)]
+pub struct Report {
+    pub scopes: Vec<Scope>,
+}
+
+impl Report {
+    /// Takes the scopes recorded since [`clear`].
+    pub fn take() -> Self {
+        let scopes = PROFILER.with_borrow_mut(|profiler| std::mem::take(&mut profiler.scopes));
+        Self { scopes }
+    }
+
+    /// Total time per scope name, slowest first. Scopes nested in one of the
+    /// same name are not counted twice.
+    pub fn offenders(&self) -> Vec<(&'static str, Duration)> {
+        let mut totals: Vec<(&'static str, Duration)> = Vec::new();
+        let mut outer: Vec<&'static str> = Vec::new();
+        for scope in &self.scopes {
+            outer.truncate(scope.depth);
+            if !outer.contains(&scope.name) {
+                match totals.iter_mut().find(|(name, _)| *name == scope.name) {
+                    Some((_, total)) => *total += scope.elapsed,
+                    None => totals.push((scope.name, scope.elapsed)),
+                }
+            }
+            outer.push(scope.name);
+        }
+        totals.sort_by_key(|&(_, total)| std::cmp::Reverse(total));
+        totals
+    }
+
+    /// Writes the frame as an indented tree to the log.
+    pub fn log(&self) {
+        for scope in &self.scopes {
+            let indent = "  ".repeat(scope.depth);
+            log::info!("{indent}{} {:?}", scope.name, scope.elapsed);
+        }
+    }
+}
+
+/// Draws the slowest scopes of the frame, one per line.
+impl Widget for Report {
+    fn draw(&mut self, canvas: &mut Canvas) {
+        canvas.clear_rect();
+        canvas.visuals.dir = CutDir::Vertical;
+        let height = canvas.visuals.font_height();
+        for (name, total) in self.offenders() {
+            canvas.cut_top(height, |canvas| {
+                canvas.text_fmt(format_args!("{name} {:.2}ms", total.as_secs_f64() * 1000.0));
+            });
+        }
+    }
+}
@@ -0,0 +1,144 @@
+//! Frequency ratios of just intonation, which tuning degrees can be given as.
+
+use std::{fmt, str::FromStr};
+
+/// The primes up to 11, which [`Ratio::monzo`] factors ratios into.
+pub const PRIMES: [u64; 5] = [2, 3, 5, 7, 11];
+
+/// Common just intervals up to the octave, from the unison, that
+/// [`Ratio::nearest`] picks from.
+pub const JUST_INTERVALS: [Ratio; 23] = {
+    const fn r(num: u64, den: u64) -> Ratio {
+        Ratio { num, den }
+    }
+    [
+        r(1, 1),
+        r(16, 15),
+        r(10, 9),
+        r(9, 8),
+        r(8, 7),
+        r(7, 6),
+        r(6, 5),
+        r(5, 4),
+        r(9, 7),
+        r(4, 3),
+        r(11, 8),
+        r(7, 5),
+        r(10, 7),
+        r(3, 2),
+        r(14, 9),
+        r(8, 5),
+        r(5, 3),
+        r(12, 7),
+        r(7, 4),
+        r(16, 9),
+        r(9, 5),
+        r(15, 8),
+        r(2, 1),
+    ]
+};
+
+/// A frequency ratio such as 5/4, in lowest terms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Ratio {
+    pub num: u64,
+    pub den: u64,
+}
+
+impl Ratio {
+    /// `num`/`den` in lowest terms, or `None` if either is zero.
+    pub fn new(num: u64, den: u64) -> Option<Self> {
+        if num == 0 || den == 0 {
+            return None;
+        }
+        let gcd = gcd(num, den);
+        Some(Self {
+            num: num / gcd,
+            den: den / gcd,
+        })
+    }
+
+    pub fn cents(&self) -> f64 {
+        1200.0 * (self.num as f64 / self.den as f64).log2()
+    }
+
+    /// The exponents of [`PRIMES`] that multiply to the ratio, as in
+    /// `[-2, 0, 1, 0, 0]` for 5/4, or `None` if it has a larger prime
+    /// factor.
+    pub fn monzo(&self) -> Option<[i32; 5]> {
+        let mut exponents = [0; 5];
+        let (mut num, mut den) = (self.num, self.den);
+        for (exponent, prime) in exponents.iter_mut().zip(PRIMES) {
+            while num % prime == 0 {
+                num /= prime;
+                *exponent += 1;
+            }
+            while den % prime == 0 {
+                den /= prime;
+                *exponent -= 1;
+            }
+        }
+        (num == 1 && den == 1).then_some(exponents)
+    }
+
+    /// The interval of [`JUST_INTERVALS`] closest to `cents`, in the same
+    /// octave, so 400 cents gives 5/4 and 1600 cents 5/2. Below the unison
+    /// the ratio is inverted, so -386 cents gives 4/5.
+    pub fn nearest(cents: f64) -> Ratio {
+        if cents < 0.0 {
+            let Ratio { num, den } = Ratio::nearest(-cents);
+            return Ratio { num: den, den: num };
+        }
+        let octaves = (cents / 1200.0).floor();
+        let within = cents - octaves * 1200.0;
+        let nearest = JUST_INTERVALS
+            .into_iter()
+            .min_by(|a, b| {
+                (a.cents() - within)
+                    .abs()
+                    .total_cmp(&(b.cents() - within).abs())
+            })
+            .unwrap();
+        let octaves = 1u64 << (octaves as u32).min(32);
+        Ratio::new(nearest.num * octaves, nearest.den).unwrap()
+    }
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+impl fmt::Display for Ratio {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.num, self.den)
+    }
+}
+
+/// A ratio written as "5/4", or a whole number such as "3" for 3/1.
+impl FromStr for Ratio {
+    type Err = InvalidRatio;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (num, den) = s.trim().split_once('/').unwrap_or((s.trim(), "1"));
+        let ratio = match (num.trim().parse(), den.trim().parse()) {
+            (Ok(num), Ok(den)) => Ratio::new(num, den),
+            _ => None,
+        };
+        ratio.ok_or_else(|| InvalidRatio(s.to_string()))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidRatio(pub String);
+
+impl fmt::Display for InvalidRatio {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "not a ratio: {}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidRatio {}
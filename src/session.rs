@@ -0,0 +1,300 @@
+use std::{
+    env, fs, io,
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::theory::Chord;
+
+/// Format version written to the session file. Files with any other
+/// version are ignored.
+pub const VERSION: u32 = 1;
+
+pub const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(120);
+
+/// Sessions older than this are only restored when the user asks for it.
+pub const STALE_AFTER: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// UI state that survives a restart. Tabs and modes are stored by name so
+/// reordering them does not invalidate old sessions.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Session {
+    /// Seconds since the Unix epoch.
+    pub saved_at: u64,
+    pub tab: Option<String>,
+    pub explore_mode: Option<String>,
+    pub selected: Option<(usize, usize)>,
+    pub lower: Option<(usize, usize)>,
+    pub modes_scroll: usize,
+    pub help_scroll: usize,
+    /// First visible log line, or `None` to follow new lines.
+    pub log_scroll: Option<usize>,
+    /// Chords of the progression being built, without their spellings.
+    pub progression: Vec<Chord>,
+    pub export_scale: Option<String>,
+    /// Name of the active tuning.
+    pub tuning: Option<String>,
+    /// Tunings changed but not saved to the library, as by
+    /// [`TuningLibrary::unsaved`](crate::tuning::TuningLibrary::unsaved).
+    pub unsaved_tunings: Option<String>,
+    pub fullscreen: bool,
+    pub zoom: Option<i32>,
+    /// Name of the note naming scheme.
+    pub naming: Option<String>,
+    /// Name of the accidental spelling preference.
+    pub spelling: Option<String>,
+    /// Name of the notation for microtones.
+    pub notation: Option<String>,
+    /// Name of the octave numbering of middle C.
+    pub octaves: Option<String>,
+    /// Whether notes show their cent offsets in the active tuning.
+    pub cent_offsets: bool,
+    /// Whether the status bar is hidden.
+    pub status_bar_hidden: bool,
+    /// Colors of the theme as hex codes.
+    pub accent: Option<String>,
+    pub selection: Option<String>,
+}
+
+impl Session {
+    pub fn age(&self) -> Duration {
+        Duration::from_secs(now().saturating_sub(self.saved_at))
+    }
+
+    /// Whether the session is too old to restore without asking.
+    pub fn is_stale(&self) -> bool {
+        self.age() > STALE_AFTER
+    }
+
+    pub fn serialize(&self) -> String {
+        let mut out = format!("version = {VERSION}\nsaved_at = {}\n", self.saved_at);
+        let mut line = |key: &str, value: Option<String>| {
+            if let Some(value) = value {
+                out.push_str(&format!("{key} = {value}\n"));
+            }
+        };
+        let cell = |cell: Option<(usize, usize)>| cell.map(|(col, row)| format!("{col},{row}"));
+
+        line("tab", self.tab.clone());
+        line("explore_mode", self.explore_mode.clone());
+        line("selected", cell(self.selected));
+        line("lower", cell(self.lower));
+        line("modes_scroll", Some(self.modes_scroll.to_string()));
+        line("help_scroll", Some(self.help_scroll.to_string()));
+        line(
+            "log_scroll",
+            self.log_scroll.map(|scroll| scroll.to_string()),
+        );
+        // each chord as its root and intervals, as in "7:0,4,7,10"
+        let chords = self.progression.iter().map(|chord| {
+            let intervals = chord.intervals.iter().map(u8::to_string);
+            format!("{}:{}", chord.root, intervals.collect::<Vec<_>>().join(","))
+        });
+        let chords = chords.collect::<Vec<_>>().join(" ");
+        line("progression", (!chords.is_empty()).then_some(chords));
+        line("export_scale", self.export_scale.clone());
+        line("tuning", self.tuning.clone());
+        line("fullscreen", Some(self.fullscreen.to_string()));
+        line("zoom", self.zoom.map(|zoom| zoom.to_string()));
+        line("naming", self.naming.clone());
+        line("spelling", self.spelling.clone());
+        line("notation", self.notation.clone());
+        line("octaves", self.octaves.clone());
+        line("cent_offsets", Some(self.cent_offsets.to_string()));
+        line(
+            "status_bar_hidden",
+            Some(self.status_bar_hidden.to_string()),
+        );
+        line("accent", self.accent.clone());
+        line("selection", self.selection.clone());
+        // a line of the library text each, which has lines of its own
+        let unsaved = self.unsaved_tunings.iter().flat_map(|text| text.lines());
+        for unsaved in unsaved.filter(|line| !line.trim().is_empty()) {
+            out.push_str(&format!("unsaved_tuning = {unsaved}\n"));
+        }
+        out
+    }
+
+    /// Parses a serialized session, or returns `None` if it is malformed or
+    /// from another version.
+    pub fn parse(s: &str) -> Option<Self> {
+        let mut session = Session::default();
+        let mut version = None;
+
+        let cell = |value: &str| {
+            let (col, row) = value.split_once(',')?;
+            Some((col.trim().parse().ok()?, row.trim().parse().ok()?))
+        };
+        let chord = |value: &str| {
+            let (root, intervals) = value.split_once(':')?;
+            let intervals = intervals.split(',').map(|i| i.parse().ok());
+            Some(Chord {
+                root: root.parse().ok()?,
+                intervals: intervals.collect::<Option<_>>()?,
+                spelling: None,
+            })
+        };
+
+        for line in s.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            let (key, value) = line.split_once('=')?;
+            let value = value.trim();
+            match key.trim() {
+                "version" => version = value.parse::<u32>().ok(),
+                "saved_at" => session.saved_at = value.parse().ok()?,
+                "tab" => session.tab = Some(value.to_string()),
+                "explore_mode" => session.explore_mode = Some(value.to_string()),
+                "selected" => session.selected = Some(cell(value)?),
+                "lower" => session.lower = Some(cell(value)?),
+                "modes_scroll" => session.modes_scroll = value.parse().ok()?,
+                "help_scroll" => session.help_scroll = value.parse().ok()?,
+                "log_scroll" => session.log_scroll = Some(value.parse().ok()?),
+                "progression" => {
+                    let chords = value.split_whitespace().map(chord);
+                    session.progression = chords.collect::<Option<_>>()?;
+                }
+                "export_scale" => session.export_scale = Some(value.to_string()),
+                "tuning" => session.tuning = Some(value.to_string()),
+                "fullscreen" => session.fullscreen = value.parse().ok()?,
+                "zoom" => session.zoom = Some(value.parse().ok()?),
+                "naming" => session.naming = Some(value.to_string()),
+                "spelling" => session.spelling = Some(value.to_string()),
+                "notation" => session.notation = Some(value.to_string()),
+                "octaves" => session.octaves = Some(value.to_string()),
+                "cent_offsets" => session.cent_offsets = value.parse().ok()?,
+                "status_bar_hidden" => session.status_bar_hidden = value.parse().ok()?,
+                "accent" => session.accent = Some(value.to_string()),
+                "selection" => session.selection = Some(value.to_string()),
+                "unsaved_tuning" => {
+                    let text = session.unsaved_tunings.get_or_insert_default();
+                    text.push_str(value);
+                    text.push('\n');
+                }
+                // keys from newer builds of the same version
+                _ => {}
+            }
+        }
+
+        (version == Some(VERSION)).then_some(session)
+    }
+
+    /// Loads the saved session, if there is a valid one.
+    pub fn load() -> Option<Self> {
+        Self::parse(&fs::read_to_string(path()?).ok()?)
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let path = path().ok_or(io::ErrorKind::NotFound)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.serialize())
+    }
+}
+
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs())
+}
+
+/// The per-user configuration directory of the app.
+pub fn config_dir() -> Option<PathBuf> {
+    let base = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("APPDATA").map(PathBuf::from))
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("chord-explorer"))
+}
+
+fn path() -> Option<PathBuf> {
+    Some(config_dir()?.join("session.txt"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::theory::ChordQuality;
+
+    fn full() -> Session {
+        Session {
+            saved_at: 1_700_000_000,
+            tab: Some("Explore".into()),
+            explore_mode: Some("Compare".into()),
+            selected: Some((3, 1)),
+            lower: Some((0, 2)),
+            modes_scroll: 4,
+            help_scroll: 7,
+            log_scroll: Some(12),
+            progression: vec![
+                Chord::new(2, ChordQuality::Minor),
+                Chord::new(7, ChordQuality::Dominant7),
+                Chord::new(0, ChordQuality::Major),
+            ],
+            export_scale: Some("D dorian".into()),
+            tuning: Some("31-EDO".into()),
+            unsaved_tunings: Some("[31-EDO]\nreference_hz = 432\nC = 300\nD = 5/4\n".into()),
+            fullscreen: true,
+            zoom: Some(3),
+            naming: Some("German".into()),
+            spelling: Some("Flats".into()),
+            notation: Some("HEJI".into()),
+            octaves: Some("C3".into()),
+            cent_offsets: true,
+            status_bar_hidden: true,
+            accent: Some("#ff8800".into()),
+            selection: Some("#224466".into()),
+        }
+    }
+
+    #[test]
+    fn round_trips() {
+        for session in [Session::default(), full()] {
+            assert_eq!(Session::parse(&session.serialize()), Some(session));
+        }
+    }
+
+    #[test]
+    fn ignores_other_versions() {
+        let text = full().serialize().replace("version = 1", "version = 2");
+        assert_eq!(Session::parse(&text), None);
+        let text = full().serialize().replace("version = 1\n", "");
+        assert_eq!(Session::parse(&text), None);
+    }
+
+    #[test]
+    fn rejects_malformed_values() {
+        for (key, value) in [
+            ("selected", "3"),
+            ("selected", "a,b"),
+            ("modes_scroll", "-1"),
+            ("fullscreen", "yes"),
+            ("zoom", "big"),
+            ("log_scroll", "end"),
+            ("progression", "2:0,3,7 7"),
+            ("progression", "2:0,x,7"),
+        ] {
+            let text = format!("version = 1\n{key} = {value}\n");
+            assert_eq!(Session::parse(&text), None, "{key} = {value}");
+        }
+        assert_eq!(Session::parse("version = 1\nno equals sign\n"), None);
+    }
+
+    #[test]
+    fn skips_unknown_keys() {
+        let text = format!("{}from_the_future = 42\n", full().serialize());
+        assert_eq!(Session::parse(&text), Some(full()));
+    }
+
+    #[test]
+    fn goes_stale_after_a_day() {
+        let at = |ago: Duration| Session {
+            saved_at: now() - ago.as_secs(),
+            ..Session::default()
+        };
+        assert!(!at(Duration::ZERO).is_stale());
+        assert!(!at(STALE_AFTER - Duration::from_secs(60)).is_stale());
+        assert!(at(STALE_AFTER + Duration::from_secs(60)).is_stale());
+        assert!(Session::default().is_stale());
+    }
+}
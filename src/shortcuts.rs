@@ -0,0 +1,363 @@
+//! Keyboard shortcuts: the actions bound anywhere in the app, and the
+//! registry of every key the help overlay lists.
+
+use std::borrow::Cow;
+
+use winit::keyboard::KeyCode;
+
+use crate::{
+    explorer,
+    widget::{self, DragReorder, Events, GridNav, MenuState},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Modifier {
+    /// The key on its own, whatever modifiers are held.
+    None,
+    Control,
+    Alt,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Binding {
+    pub modifier: Modifier,
+    pub key: KeyCode,
+}
+
+const fn key(key: KeyCode) -> Binding {
+    Binding {
+        modifier: Modifier::None,
+        key,
+    }
+}
+const fn ctrl(key: KeyCode) -> Binding {
+    Binding {
+        modifier: Modifier::Control,
+        key,
+    }
+}
+const fn alt(key: KeyCode) -> Binding {
+    Binding {
+        modifier: Modifier::Alt,
+        key,
+    }
+}
+
+impl Binding {
+    pub fn pressed(&self, events: &Events) -> bool {
+        let modifier = match self.modifier {
+            Modifier::None => true,
+            Modifier::Control => events.control,
+            Modifier::Alt => events.alt,
+        };
+        modifier && events.keys_pressed.contains(&self.key)
+    }
+
+    /// The binding as written in help texts, as in "Ctrl+C".
+    pub fn label(&self) -> String {
+        let key = match self.key {
+            KeyCode::Equal => "Plus".to_string(),
+            KeyCode::Minus => "Minus".to_string(),
+            KeyCode::Escape => "Esc".to_string(),
+            KeyCode::ArrowLeft => "Left".to_string(),
+            KeyCode::ArrowRight => "Right".to_string(),
+            KeyCode::ArrowUp => "Up".to_string(),
+            KeyCode::ArrowDown => "Down".to_string(),
+            key => {
+                let name = format!("{key:?}");
+                let name = name.strip_prefix("Key").unwrap_or(&name);
+                name.strip_prefix("Digit").unwrap_or(name).to_string()
+            }
+        };
+        match self.modifier {
+            Modifier::None => key,
+            Modifier::Control => format!("Ctrl+{key}"),
+            Modifier::Alt => format!("Alt+{key}"),
+        }
+    }
+}
+
+/// Everything that can be done from the keyboard. Both the bindings and the
+/// help texts listing them come from here.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Copy,
+    Paste,
+    ExportImage,
+    ZoomIn,
+    ZoomOut,
+    ZoomReset,
+    Fullscreen,
+    Log,
+    Help,
+    Quit,
+}
+
+impl Action {
+    pub const ALL: [Action; 10] = [
+        Action::Copy,
+        Action::Paste,
+        Action::ExportImage,
+        Action::ZoomIn,
+        Action::ZoomOut,
+        Action::ZoomReset,
+        Action::Fullscreen,
+        Action::Log,
+        Action::Help,
+        Action::Quit,
+    ];
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Action::Copy => "Copy the selected chord",
+            Action::Paste => "Paste a chord to select it, or text while typing",
+            Action::ExportImage => "Export the selected chord as an image",
+            Action::ZoomIn => "Zoom in, also Ctrl+scroll",
+            Action::ZoomOut => "Zoom out",
+            Action::ZoomReset => "Reset the zoom",
+            Action::Fullscreen => "Toggle fullscreen",
+            Action::Log => "Toggle the log view",
+            Action::Help => "Toggle the list of keys",
+            Action::Quit => "Quit",
+        }
+    }
+
+    /// Bindings of the action, any of which triggers it.
+    pub fn bindings(&self) -> &'static [Binding] {
+        match self {
+            Action::Copy => const { &[ctrl(KeyCode::KeyC)] },
+            Action::Paste => const { &[ctrl(KeyCode::KeyV)] },
+            Action::ExportImage => const { &[ctrl(KeyCode::KeyE)] },
+            Action::ZoomIn => const { &[ctrl(KeyCode::Equal), ctrl(KeyCode::NumpadAdd)] },
+            Action::ZoomOut => const { &[ctrl(KeyCode::Minus), ctrl(KeyCode::NumpadSubtract)] },
+            Action::ZoomReset => const { &[ctrl(KeyCode::Digit0), ctrl(KeyCode::Numpad0)] },
+            Action::Fullscreen => const { &[key(KeyCode::F11), alt(KeyCode::Enter)] },
+            Action::Log => const { &[key(KeyCode::F12)] },
+            Action::Help => const { &[key(KeyCode::F1)] },
+            Action::Quit => const { &[key(KeyCode::Escape)] },
+        }
+    }
+
+    pub fn category(&self) -> Category {
+        match self {
+            Action::Copy | Action::Paste | Action::ExportImage => Category::Chords,
+            Action::ZoomIn
+            | Action::ZoomOut
+            | Action::ZoomReset
+            | Action::Fullscreen
+            | Action::Log => Category::View,
+            Action::Help | Action::Quit => Category::General,
+        }
+    }
+
+    pub fn pressed(&self, events: &Events) -> bool {
+        self.bindings()
+            .iter()
+            .any(|binding| binding.pressed(events))
+    }
+
+    /// Every binding of the action that is not a numpad duplicate, joined
+    /// for help texts.
+    pub fn label(&self) -> String {
+        let bindings = self
+            .bindings()
+            .iter()
+            .filter(|binding| !format!("{:?}", binding.key).starts_with("Numpad"));
+        let labels = bindings.map(Binding::label).collect::<Vec<_>>();
+        labels.join(", ")
+    }
+}
+
+/// What a shortcut is for, to group the help overlay by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Category {
+    General,
+    View,
+    Chords,
+    Navigation,
+    Editing,
+}
+
+impl Category {
+    pub const ALL: [Category; 5] = [
+        Category::General,
+        Category::View,
+        Category::Chords,
+        Category::Navigation,
+        Category::Editing,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Category::General => "General",
+            Category::View => "View",
+            Category::Chords => "Chords",
+            Category::Navigation => "Navigation",
+            Category::Editing => "Editing",
+        }
+    }
+}
+
+/// A key or keys and what they do, as listed in the help overlay.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Shortcut {
+    pub category: Category,
+    /// The keys as written in help texts, as in "Ctrl+C" or "Home, End".
+    pub keys: Cow<'static, str>,
+    pub description: &'static str,
+}
+
+impl Shortcut {
+    /// A shortcut listed under `category`, for the constants widgets keep
+    /// next to the keys they handle.
+    pub const fn new(category: Category, keys: &'static str, description: &'static str) -> Self {
+        Self {
+            category,
+            keys: Cow::Borrowed(keys),
+            description,
+        }
+    }
+}
+
+impl From<Action> for Shortcut {
+    fn from(action: Action) -> Self {
+        Self {
+            category: action.category(),
+            keys: Cow::Owned(action.label()),
+            description: action.description(),
+        }
+    }
+}
+
+/// The `SHORTCUTS` of each widget that handles keys itself.
+const WIDGET_SHORTCUTS: [&[Shortcut]; 5] = [
+    widget::TAB_SHORTCUTS,
+    GridNav::SHORTCUTS,
+    MenuState::SHORTCUTS,
+    DragReorder::SHORTCUTS,
+    explorer::TYPING_SHORTCUTS,
+];
+
+/// Every shortcut of the app. The actions are registered here, and the keys
+/// widgets handle themselves come from a `SHORTCUTS` constant kept next to
+/// the code handling them, so a key added there is listed without
+/// touching the help overlay.
+#[derive(Debug, Clone)]
+pub struct ShortcutRegistry {
+    shortcuts: Vec<Shortcut>,
+}
+
+impl Default for ShortcutRegistry {
+    fn default() -> Self {
+        let mut registry = Self {
+            shortcuts: Vec::new(),
+        };
+        for action in Action::ALL {
+            registry.register(action.into());
+        }
+        for shortcut in WIDGET_SHORTCUTS.into_iter().flatten() {
+            registry.register(shortcut.clone());
+        }
+        registry
+    }
+}
+
+impl ShortcutRegistry {
+    pub fn register(&mut self, shortcut: Shortcut) {
+        self.shortcuts.push(shortcut);
+    }
+
+    /// Every shortcut in the order registered.
+    pub fn shortcuts(&self) -> &[Shortcut] {
+        &self.shortcuts
+    }
+
+    /// Each category that has shortcuts with its shortcuts, in the order of
+    /// [`Category::ALL`].
+    pub fn by_category(&self) -> impl Iterator<Item = (Category, Vec<&Shortcut>)> {
+        Category::ALL.into_iter().filter_map(|category| {
+            let shortcuts = self.shortcuts.iter();
+            let shortcuts: Vec<_> = shortcuts.filter(|s| s.category == category).collect();
+            (!shortcuts.is_empty()).then_some((category, shortcuts))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_shortcut_has_keys_and_a_description() {
+        let registry = ShortcutRegistry::default();
+        for shortcut in registry.shortcuts() {
+            assert!(!shortcut.keys.trim().is_empty(), "{shortcut:?}");
+            assert!(!shortcut.description.trim().is_empty(), "{shortcut:?}");
+        }
+        for action in Action::ALL {
+            let listed = Shortcut::from(action);
+            assert!(registry.shortcuts().contains(&listed), "{action:?}");
+        }
+    }
+
+    #[test]
+    fn no_two_actions_share_a_binding() {
+        let bindings = Action::ALL.iter().flat_map(|action| {
+            let bindings = action.bindings().iter();
+            bindings.map(move |&binding| (binding, action))
+        });
+        let mut seen = std::collections::HashMap::new();
+        for (binding, action) in bindings {
+            if let Some(other) = seen.insert(binding, action) {
+                panic!("{} is bound to {other:?} and {action:?}", binding.label());
+            }
+        }
+    }
+
+    #[test]
+    fn no_widget_lists_a_key_twice() {
+        // the same keys do different things in different widgets, such as
+        // Home and End in the tab bar and the chord grid, but not in one
+        for shortcuts in WIDGET_SHORTCUTS {
+            let mut keys: Vec<&str> = shortcuts
+                .iter()
+                .flat_map(|shortcut| shortcut.keys.split(", "))
+                .collect();
+            let count = keys.len();
+            keys.sort();
+            keys.dedup();
+            assert_eq!(keys.len(), count, "{shortcuts:?}");
+        }
+    }
+
+    #[test]
+    fn bindings_are_labelled_as_in_help_texts() {
+        assert_eq!(ctrl(KeyCode::KeyC).label(), "Ctrl+C");
+        assert_eq!(ctrl(KeyCode::Digit0).label(), "Ctrl+0");
+        assert_eq!(alt(KeyCode::Enter).label(), "Alt+Enter");
+        assert_eq!(key(KeyCode::Escape).label(), "Esc");
+        // numpad keys are left out as duplicates
+        assert_eq!(Action::ZoomIn.label(), "Ctrl+Plus");
+        assert_eq!(Action::Fullscreen.label(), "F11, Alt+Enter");
+    }
+
+    #[test]
+    fn categories_are_listed_in_order() {
+        let registry = ShortcutRegistry::default();
+        let categories: Vec<_> = registry.by_category().map(|(c, _)| c).collect();
+        assert_eq!(categories, Category::ALL);
+        let listed: usize = registry.by_category().map(|(_, s)| s.len()).sum();
+        assert_eq!(listed, registry.shortcuts().len());
+    }
+
+    #[test]
+    fn bindings_need_their_modifier() {
+        let events = |control| Events {
+            keys_pressed: vec![KeyCode::KeyC],
+            control,
+            ..Events::default()
+        };
+        assert!(Action::Copy.pressed(&events(true)));
+        assert!(!Action::Copy.pressed(&events(false)));
+    }
+}
@@ -0,0 +1,2747 @@
+use std::{fmt, str::FromStr, sync::OnceLock};
+
+use crate::{ratio::Ratio, tuning::Tuning};
+
+const MAJOR: [u8; 7] = [0, 2, 4, 5, 7, 9, 11];
+const HARMONIC_MINOR: [u8; 7] = [0, 2, 3, 5, 7, 8, 11];
+
+/// How to spell pitch classes that fall between two natural notes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AccidentalPreference {
+    Sharps,
+    Flats,
+    /// Both spellings, sharp first, as in "C♯/D♭".
+    BothSharps,
+    /// Both spellings, flat first, as in "D♭/C♯".
+    BothFlats,
+    /// The spelling of the key signature of the major key on this pitch class.
+    KeyOf(u8),
+}
+
+impl AccidentalPreference {
+    /// Resolves [`AccidentalPreference::KeyOf`] to sharps or flats.
+    pub fn resolve(self) -> Self {
+        match self {
+            // F, B♭, E♭, A♭, D♭ and G♭ major are written with flats
+            AccidentalPreference::KeyOf(key) => match key % 12 {
+                5 | 10 | 3 | 8 | 1 | 6 => AccidentalPreference::Flats,
+                _ => AccidentalPreference::Sharps,
+            },
+            preference => preference,
+        }
+    }
+}
+
+/// A note spelled as a letter and an accidental, so that enharmonic notes
+/// such as C♯ and D♭ are told apart, and optionally raised or lowered by
+/// arrows for the finer steps of tunings such as 72-EDO.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NoteName {
+    /// Index into C D E F G A B.
+    pub letter: u8,
+    /// Semitones above the natural letter, negative for flats.
+    pub accidental: i8,
+    /// Steps of 72-EDO, a sixth of a semitone, above the accidental, written
+    /// as ↑ or ↓ after it.
+    pub arrows: i8,
+}
+
+impl NoteName {
+    /// Spells `pc` on `letter`, with whichever accidental is closest.
+    pub fn on_letter(letter: u8, pc: u8) -> Self {
+        let letter = letter % 7;
+        let diff = (pc as i8 - MAJOR[letter as usize] as i8).rem_euclid(12);
+        Self {
+            letter,
+            accidental: if diff > 6 { diff - 12 } else { diff },
+            arrows: 0,
+        }
+    }
+
+    /// Spells `pc` by `preference`. With a key, notes of the key's major
+    /// scale get the letter of their degree, so B is C♭ in G♭ major.
+    pub fn new(pc: u8, preference: AccidentalPreference) -> Self {
+        let pc = pc % 12;
+        if let AccidentalPreference::KeyOf(key) = preference {
+            let key = key % 12;
+            if let Some(degree) = MAJOR.iter().position(|&i| (key + i) % 12 == pc) {
+                let tonic = NoteName::new(key, preference.resolve());
+                return NoteName::on_letter(tonic.letter + degree as u8, pc);
+            }
+        }
+
+        let natural = |pc: u8| MAJOR.iter().position(|&i| i == pc % 12);
+        let letter = match natural(pc) {
+            Some(letter) => letter,
+            None => match preference.resolve() {
+                AccidentalPreference::Flats | AccidentalPreference::BothFlats => {
+                    natural(pc + 1).unwrap()
+                }
+                _ => natural(pc + 11).unwrap(),
+            },
+        };
+        NoteName::on_letter(letter as u8, pc)
+    }
+
+    /// The pitch class, leaving out the arrows.
+    pub fn pc(&self) -> u8 {
+        (MAJOR[self.letter as usize] as i8 + self.accidental).rem_euclid(12) as u8
+    }
+}
+
+impl fmt::Display for NoteName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        NamingScheme::English.note(*self).fmt(f)
+    }
+}
+
+/// An English note name, with ♯ or #, ♭ or b, 𝄪 and 𝄫 for accidentals and ↑
+/// or ^ and ↓ or v for arrows, as in "C#^" or "E♭↓". A half sharp 𝄲 or half
+/// flat 𝄳 after the letter is read as three arrows.
+impl FromStr for NoteName {
+    type Err = InvalidNote;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match NamingScheme::English.parse_note(s.trim()) {
+            Some((note, "")) => Ok(note),
+            _ => Err(InvalidNote(s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidNote(pub String);
+
+impl fmt::Display for InvalidNote {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "not a note: {}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidNote {}
+
+/// Which octave number middle C has.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OctaveConvention {
+    /// Scientific pitch notation, where middle C is C4 and MIDI note 0 is C-1.
+    #[default]
+    MiddleC4,
+    /// Where middle C is C3 and MIDI note 0 is C-2, as in many MIDI tools.
+    MiddleC3,
+}
+
+impl OctaveConvention {
+    pub const ALL: [OctaveConvention; 2] = [OctaveConvention::MiddleC4, OctaveConvention::MiddleC3];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            OctaveConvention::MiddleC4 => "C4",
+            OctaveConvention::MiddleC3 => "C3",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|convention| convention.name() == name)
+    }
+
+    /// This convention's number for the octave numbered `octave` in
+    /// scientific pitch notation.
+    pub fn octave(self, octave: i32) -> i32 {
+        match self {
+            OctaveConvention::MiddleC4 => octave,
+            OctaveConvention::MiddleC3 => octave - 1,
+        }
+    }
+}
+
+/// A note in an octave. The octave is that of the letter, so B♯3 and C4 are
+/// the same pitch, as are C♭4 and B3. Octaves are numbered as in scientific
+/// pitch notation, with middle C in octave 4, and can be written in another
+/// [`OctaveConvention`] with [`Pitch::name`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Pitch {
+    pub note: NoteName,
+    pub octave: i32,
+}
+
+impl Pitch {
+    /// `note` spelled at MIDI note `midi`, in whichever octave that puts
+    /// its letter, so B♯ at 60 is B♯3.
+    pub fn with_note(note: NoteName, midi: i32) -> Self {
+        let natural = MAJOR[note.letter as usize % 7] as i32 + note.accidental as i32;
+        Self {
+            note,
+            octave: (midi - natural).div_euclid(12) - 1,
+        }
+    }
+
+    /// MIDI note `midi` spelled by `preference`.
+    pub fn from_midi(midi: i32, preference: AccidentalPreference) -> Self {
+        let note = NoteName::new(midi.rem_euclid(12) as u8, preference);
+        Self::with_note(note, midi)
+    }
+
+    /// The MIDI note, 60 for middle C, leaving out the arrows.
+    pub fn to_midi(&self) -> i32 {
+        let natural = MAJOR[self.note.letter as usize % 7] as i32 + self.note.accidental as i32;
+        12 * (self.octave + 1) + natural
+    }
+
+    /// Frequency in `tuning`, from the degree nearest the pitch class as by
+    /// [`Tuning::cents_off`], in 12-TET if there is none, with A4 at the
+    /// reference pitch.
+    ///
+    /// [`Tuning::cents_off`]: crate::tuning::Tuning::cents_off
+    pub fn frequency(&self, tuning: &Tuning) -> f64 {
+        let off = tuning.cents_off(self.note.pc()).unwrap_or(0.0);
+        let arrows = self.note.arrows as f64 * 100.0 / 6.0;
+        tuning.frequency((self.to_midi() - 69) as f64 * 100.0 + off + arrows)
+    }
+
+    /// The pitch as `scheme` writes it with the octave numbered by
+    /// `convention`, as in "Fis2".
+    pub fn name(&self, scheme: NamingScheme, convention: OctaveConvention) -> String {
+        format!(
+            "{}{}",
+            scheme.note(self.note),
+            convention.octave(self.octave)
+        )
+    }
+
+    /// Reads an English pitch such as "B♭2", "Bb2" or "C𝄲5", with the octave
+    /// numbered by `convention`.
+    pub fn parse(s: &str, convention: OctaveConvention) -> Result<Self, InvalidNote> {
+        let invalid = || InvalidNote(s.to_string());
+        let (note, octave) = NamingScheme::English
+            .parse_note(s.trim())
+            .ok_or_else(invalid)?;
+        let octave: i32 = octave.parse().map_err(|_| invalid())?;
+        Ok(Self {
+            note,
+            octave: octave - convention.octave(0),
+        })
+    }
+}
+
+/// Lower pitches first. Enharmonic pitches such as B♯3 and C4 are ordered
+/// by octave and then by letter, so that only equal pitches compare equal.
+impl Ord for Pitch {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        let key = |pitch: &Pitch| {
+            let sixths = pitch.to_midi() * 6 + pitch.note.arrows as i32;
+            (
+                sixths,
+                pitch.octave,
+                pitch.note.letter,
+                pitch.note.accidental,
+            )
+        };
+        key(self).cmp(&key(other))
+    }
+}
+
+impl PartialOrd for Pitch {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// The English name and scientific octave, as in "F♯3".
+impl fmt::Display for Pitch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.note, self.octave)
+    }
+}
+
+impl FromStr for Pitch {
+    type Err = InvalidNote;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Pitch::parse(s, OctaveConvention::MiddleC4)
+    }
+}
+
+/// The quality of an [`Interval`], between the diminished and augmented
+/// forms of its number. Neutral intervals are a quarter tone between minor
+/// and major, as the third of 24-EDO at 350 cents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IntervalQuality {
+    Diminished,
+    Minor,
+    Neutral,
+    Major,
+    Perfect,
+    Augmented,
+}
+
+impl IntervalQuality {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            IntervalQuality::Diminished => "d",
+            IntervalQuality::Minor => "m",
+            IntervalQuality::Neutral => "n",
+            IntervalQuality::Major => "M",
+            IntervalQuality::Perfect => "P",
+            IntervalQuality::Augmented => "A",
+        }
+    }
+}
+
+/// An interval between two spelled pitches, named by how many letters it
+/// spans, so C to D♯ is an augmented second and C to E♭ a minor third.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Interval {
+    /// 1 for a unison, 8 for an octave.
+    pub number: u8,
+    pub quality: IntervalQuality,
+    /// Steps of 72-EDO left over above the quality, written as arrows.
+    pub arrows: i8,
+}
+
+impl Interval {
+    /// The interval from `lower` up to `upper`, or `None` if `upper` is on
+    /// a lower letter.
+    pub fn between(lower: Pitch, upper: Pitch) -> Option<Self> {
+        let letters = |pitch: Pitch| pitch.note.letter as i32 + 7 * pitch.octave;
+        let steps = u8::try_from(letters(upper) - letters(lower)).ok()?;
+        let sixths = |pitch: Pitch| pitch.to_midi() * 6 + pitch.note.arrows as i32;
+        let major = MAJOR[steps as usize % 7] as i32 + 12 * (steps as i32 / 7);
+        // sixths of a semitone above the major or perfect interval
+        let off = sixths(upper) - sixths(lower) - 6 * major;
+        let qualities: &[(IntervalQuality, i32)] = match steps % 7 {
+            0 | 3 | 4 => &[
+                (IntervalQuality::Perfect, 0),
+                (IntervalQuality::Augmented, 6),
+                (IntervalQuality::Diminished, -6),
+            ],
+            _ => &[
+                (IntervalQuality::Major, 0),
+                (IntervalQuality::Minor, -6),
+                (IntervalQuality::Augmented, 6),
+                (IntervalQuality::Diminished, -12),
+                (IntervalQuality::Neutral, -3),
+            ],
+        };
+        // the first of the closest, so the plainer quality wins ties
+        let &(quality, base) = qualities
+            .iter()
+            .min_by_key(|(_, base)| (off - base).abs())
+            .unwrap();
+        Some(Self {
+            number: steps + 1,
+            quality,
+            arrows: (off - base) as i8,
+        })
+    }
+}
+
+/// The quality and number with any arrows, as in "m3", "A2" or "M3↓".
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.quality.symbol(), self.number)?;
+        write_arrows(f, self.arrows)
+    }
+}
+
+fn write_accidental(f: &mut fmt::Formatter<'_>, accidental: i8) -> fmt::Result {
+    let symbol = match accidental {
+        2 => return f.write_str(Accidental::DoubleSharp.glyph()),
+        -2 => return f.write_str(Accidental::DoubleFlat.glyph()),
+        n if n > 0 => "♯",
+        _ => "♭",
+    };
+    for _ in 0..accidental.unsigned_abs() {
+        f.write_str(symbol)?;
+    }
+    Ok(())
+}
+
+fn write_arrows(f: &mut fmt::Formatter<'_>, arrows: i8) -> fmt::Result {
+    let arrow = if arrows > 0 { "↑" } else { "↓" };
+    for _ in 0..arrows.unsigned_abs() {
+        f.write_str(arrow)?;
+    }
+    Ok(())
+}
+
+/// An accidental in steps of a quarter tone, from double flat up to double
+/// sharp, as drawn with the music glyphs of [`Font`](crate::font::Font).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Accidental {
+    DoubleFlat,
+    ThreeHalvesFlat,
+    Flat,
+    HalfFlat,
+    Natural,
+    HalfSharp,
+    Sharp,
+    ThreeHalvesSharp,
+    DoubleSharp,
+}
+
+impl Accidental {
+    pub const ALL: [Accidental; 9] = [
+        Accidental::DoubleFlat,
+        Accidental::ThreeHalvesFlat,
+        Accidental::Flat,
+        Accidental::HalfFlat,
+        Accidental::Natural,
+        Accidental::HalfSharp,
+        Accidental::Sharp,
+        Accidental::ThreeHalvesSharp,
+        Accidental::DoubleSharp,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Accidental::DoubleFlat => "Double flat",
+            Accidental::ThreeHalvesFlat => "Three-halves flat",
+            Accidental::Flat => "Flat",
+            Accidental::HalfFlat => "Half flat",
+            Accidental::Natural => "Natural",
+            Accidental::HalfSharp => "Half sharp",
+            Accidental::Sharp => "Sharp",
+            Accidental::ThreeHalvesSharp => "Three-halves sharp",
+            Accidental::DoubleSharp => "Double sharp",
+        }
+    }
+
+    /// How far the accidental raises a note, in cents of 12-TET.
+    pub fn semitones_cents(&self) -> f64 {
+        let quarter_tones = *self as i8 - Accidental::Natural as i8;
+        f64::from(quarter_tones) * 50.0
+    }
+
+    /// The accidental as the font draws it. The three-halves accidentals are
+    /// a half accidental followed by a whole one, which the font joins into
+    /// a single glyph, so this is a string rather than a `char`.
+    pub fn glyph(&self) -> &'static str {
+        match self {
+            Accidental::DoubleFlat => "𝄫",
+            Accidental::ThreeHalvesFlat => "𝄳♭",
+            Accidental::Flat => "♭",
+            Accidental::HalfFlat => "𝄳",
+            Accidental::Natural => "♮",
+            Accidental::HalfSharp => "𝄲",
+            Accidental::Sharp => "♯",
+            Accidental::ThreeHalvesSharp => "𝄲♯",
+            Accidental::DoubleSharp => "𝄪",
+        }
+    }
+}
+
+/// How notes are named: with English letters, with German letters, where B
+/// is B♭ and H is B, or with fixed-do solfège syllables.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum NamingScheme {
+    #[default]
+    English,
+    German,
+    Solfege,
+}
+
+impl NamingScheme {
+    pub const ALL: [NamingScheme; 3] = [
+        NamingScheme::English,
+        NamingScheme::German,
+        NamingScheme::Solfege,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            NamingScheme::English => "English",
+            NamingScheme::German => "German",
+            NamingScheme::Solfege => "Solfège",
+        }
+    }
+
+    /// Name of the natural note on `letter`, an index into C D E F G A B.
+    pub fn letter(&self, letter: u8) -> &'static str {
+        let names = match self {
+            NamingScheme::English => ["C", "D", "E", "F", "G", "A", "B"],
+            NamingScheme::German => ["C", "D", "E", "F", "G", "A", "H"],
+            NamingScheme::Solfege => ["Do", "Ré", "Mi", "Fa", "Sol", "La", "Si"],
+        };
+        names[letter as usize % 7]
+    }
+
+    /// Characters in the longest name of a note with up to two accidentals,
+    /// such as "Cisis" in German.
+    pub fn widest_note(&self) -> i32 {
+        match self {
+            NamingScheme::English => 2,
+            NamingScheme::German => 5,
+            NamingScheme::Solfege => 4,
+        }
+    }
+
+    /// `note` as this scheme writes it.
+    pub fn note(self, note: NoteName) -> Localized<'static> {
+        Localized {
+            scheme: self,
+            letter: Some(note.letter),
+            accidental: note.accidental,
+            arrows: note.arrows,
+            rest: "",
+        }
+    }
+
+    /// English note names, such as "C♯", "C♯↑", "C𝄲", "B♭m/C" or "C E G", as this
+    /// scheme writes them. Only the notes at the start and after a space, a
+    /// slash or a brace are named again, so the text should hold nothing but
+    /// notes and chord symbols. ASCII # and b are read as ♯ and ♭, as
+    /// [`pitch_name`] writes them. Accidentals other than these, ♯ ♭ 𝄪 and
+    /// 𝄫, such as quarter tones, are kept behind the scheme's name of the
+    /// letter.
+    pub fn localize(self, name: &str) -> Localized<'_> {
+        let unchanged = Localized {
+            scheme: self,
+            letter: None,
+            accidental: 0,
+            arrows: 0,
+            rest: name,
+        };
+        let Some(letter) = name.chars().next().and_then(|c| "CDEFGAB".find(c)) else {
+            return unchanged;
+        };
+        let after = &name[1..];
+        let end = after
+            .find(|c| !"♯♭#b𝄪𝄫𝄲𝄳♮↑↓".contains(c))
+            .unwrap_or(after.len());
+        let (symbols, rest) = after.split_at(end);
+        let (mut accidental, mut arrows) = (0, 0);
+        let mut known = true;
+        for c in symbols.chars() {
+            match c {
+                '♯' | '#' => accidental += 1,
+                '♭' | 'b' => accidental -= 1,
+                '𝄪' => accidental += 2,
+                '𝄫' => accidental -= 2,
+                '↑' => arrows += 1,
+                '↓' => arrows -= 1,
+                _ => known = false,
+            }
+        }
+        match known {
+            true => Localized {
+                letter: Some(letter as u8),
+                accidental,
+                arrows,
+                rest,
+                ..unchanged
+            },
+            false => Localized {
+                letter: Some(letter as u8),
+                rest: after,
+                ..unchanged
+            },
+        }
+    }
+
+    /// Reads a note in this scheme, such as "C♯", "Cis", "Es", "Do#" or
+    /// "C#^", from the start of `s`, and returns it with the rest of `s`.
+    pub fn parse_note(self, s: &str) -> Option<(NoteName, &str)> {
+        // names that are not a letter's name, with their accidental
+        let aliases: &[(&str, u8, i8)] = match self {
+            NamingScheme::English => &[],
+            NamingScheme::German => &[("Es", 2, -1), ("As", 5, -1), ("B", 6, -1)],
+            NamingScheme::Solfege => &[("Re", 1, 0)],
+        };
+        let names = (0..7).map(|letter| (self.letter(letter), letter, 0));
+        let (letter, mut accidental, mut rest) =
+            aliases
+                .iter()
+                .copied()
+                .chain(names)
+                .find_map(|(name, letter, accidental)| {
+                    Some((letter, accidental, s.strip_prefix(name)?))
+                })?;
+
+        let suffixes: &[(&str, i8)] = match self {
+            NamingScheme::German => &[("is", 1), ("es", -1)],
+            _ => &[],
+        };
+        let symbols = [
+            ("♯", 1),
+            ("#", 1),
+            ("♭", -1),
+            ("b", -1),
+            ("𝄪", 2),
+            ("𝄫", -2),
+        ];
+        while let Some((step, after)) = symbols
+            .iter()
+            .chain(suffixes)
+            .find_map(|&(symbol, step)| Some((step, rest.strip_prefix(symbol)?)))
+        {
+            accidental += step;
+            rest = after;
+        }
+
+        // a half sharp or flat is three steps of 72-EDO
+        let arrows = [
+            ("↑", 1),
+            ("^", 1),
+            ("↓", -1),
+            ("v", -1),
+            ("𝄲", 3),
+            ("𝄳", -3),
+        ];
+        let mut up = 0;
+        while let Some((step, after)) = arrows
+            .iter()
+            .find_map(|&(arrow, step)| Some((step, rest.strip_prefix(arrow)?)))
+        {
+            up += step;
+            rest = after;
+        }
+        let note = NoteName {
+            letter,
+            accidental,
+            arrows: up,
+        };
+        Some((note, rest))
+    }
+}
+
+/// A note or note names as a [`NamingScheme`] writes them, from
+/// [`NamingScheme::note`] or [`NamingScheme::localize`].
+#[derive(Debug, Clone, Copy)]
+pub struct Localized<'a> {
+    scheme: NamingScheme,
+    letter: Option<u8>,
+    accidental: i8,
+    arrows: i8,
+    /// Text after the note, written as it is up to the next note.
+    rest: &'a str,
+}
+
+impl fmt::Display for Localized<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(letter) = self.letter {
+            match (self.scheme, letter, self.accidental) {
+                (NamingScheme::German, 6, -1) => f.write_str("B")?,
+                // Es and As rather than Ees and Aes
+                (NamingScheme::German, 2 | 5, accidental) if accidental < 0 => {
+                    f.write_str(if letter == 2 { "Es" } else { "As" })?;
+                    for _ in 1..accidental.unsigned_abs() {
+                        f.write_str("es")?;
+                    }
+                }
+                (NamingScheme::German, letter, accidental) => {
+                    f.write_str(self.scheme.letter(letter))?;
+                    let suffix = if accidental > 0 { "is" } else { "es" };
+                    for _ in 0..accidental.unsigned_abs() {
+                        f.write_str(suffix)?;
+                    }
+                }
+                (scheme, letter, accidental) => {
+                    f.write_str(scheme.letter(letter))?;
+                    write_accidental(f, accidental)?;
+                }
+            }
+            write_arrows(f, self.arrows)?;
+        }
+        match self.rest.find([' ', '/', '{']) {
+            Some(end) => {
+                f.write_str(&self.rest[..=end])?;
+                self.scheme.localize(&self.rest[end + 1..]).fmt(f)
+            }
+            None => f.write_str(self.rest),
+        }
+    }
+}
+
+/// Reads a chord symbol such as "C♯m", "Cism" or "Do#m". Symbols are read
+/// in `scheme` first and then in the others, so "B" is B♭ in German but B
+/// otherwise, and "H" is B in every scheme.
+pub fn parse_chord_symbol(s: &str, scheme: NamingScheme) -> Option<Chord> {
+    let others = NamingScheme::ALL
+        .into_iter()
+        .filter(|&other| other != scheme);
+    std::iter::once(scheme).chain(others).find_map(|scheme| {
+        let (root, rest) = scheme.parse_note(s.trim())?;
+        let quality = ChordQuality::ALL
+            .into_iter()
+            .find(|quality| quality.symbol() == rest)?;
+        Some(Chord::new(root.pc(), quality))
+    })
+}
+
+/// The name of `pc` spelled as [`NoteName::new`] spells it, with ASCII
+/// accidentals: # and b, x for a double sharp and bb for a double flat. The
+/// both spellings preferences give one name for a natural note.
+pub fn pitch_name(pc: u8, preference: AccidentalPreference) -> String {
+    let ascii = |note: NoteName| {
+        let name = note.to_string().replace('𝄪', "x").replace('𝄫', "bb");
+        name.replace('♯', "#").replace('♭', "b")
+    };
+    let sharp = NoteName::new(pc, AccidentalPreference::Sharps);
+    let flat = NoteName::new(pc, AccidentalPreference::Flats);
+    match preference {
+        AccidentalPreference::BothSharps if sharp != flat => {
+            format!("{}/{}", ascii(sharp), ascii(flat))
+        }
+        AccidentalPreference::BothFlats if sharp != flat => {
+            format!("{}/{}", ascii(flat), ascii(sharp))
+        }
+        _ => ascii(NoteName::new(pc, preference)),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ScaleMode {
+    Ionian,
+    Dorian,
+    Phrygian,
+    Lydian,
+    Mixolydian,
+    Aeolian,
+    Locrian,
+}
+
+impl ScaleMode {
+    pub const ALL: [ScaleMode; 7] = [
+        ScaleMode::Ionian,
+        ScaleMode::Dorian,
+        ScaleMode::Phrygian,
+        ScaleMode::Lydian,
+        ScaleMode::Mixolydian,
+        ScaleMode::Aeolian,
+        ScaleMode::Locrian,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ScaleMode::Ionian => "Ionian",
+            ScaleMode::Dorian => "Dorian",
+            ScaleMode::Phrygian => "Phrygian",
+            ScaleMode::Lydian => "Lydian",
+            ScaleMode::Mixolydian => "Mixolydian",
+            ScaleMode::Aeolian => "Aeolian",
+            ScaleMode::Locrian => "Locrian",
+        }
+    }
+
+    /// Semitone offsets of each degree above the root.
+    pub fn intervals(&self) -> [u8; 7] {
+        let rotation = *self as usize;
+        let offset = MAJOR[rotation];
+        std::array::from_fn(|i| (MAJOR[(i + rotation) % 7] + 12 - offset) % 12)
+    }
+
+    pub fn scale(&self, root: u8) -> Scale {
+        Scale::new(root, self.intervals().to_vec())
+    }
+
+    /// Tendency of each degree, from the root up. Unstable degrees lean
+    /// toward the tonic triad tone a half step away; those without one are
+    /// neutral.
+    pub fn tendencies(&self) -> [TendencyDirection; 7] {
+        use TendencyDirection::{Down as D, Neutral as N, Up as U};
+        match self {
+            ScaleMode::Ionian => [N, N, N, D, N, N, U],
+            ScaleMode::Dorian => [N, U, N, N, N, N, N],
+            ScaleMode::Phrygian => [N, D, N, N, N, D, N],
+            ScaleMode::Lydian => [N, N, N, U, N, N, U],
+            ScaleMode::Mixolydian => [N, N, N, D, N, N, N],
+            ScaleMode::Aeolian => [N, U, N, N, N, D, N],
+            ScaleMode::Locrian => [N, D, N, U, N, N, N],
+        }
+    }
+
+    /// Semitones each degree is raised above the same degree of the major
+    /// scale, negative where it is lowered, as in the ♭3 of Dorian.
+    pub fn alterations(&self) -> [i8; 7] {
+        let intervals = self.intervals();
+        std::array::from_fn(|i| intervals[i] as i8 - MAJOR[i] as i8)
+    }
+
+    /// Root of the major scale that this mode on `root` is a rotation of.
+    pub fn parent_major(&self, root: u8) -> u8 {
+        (root + 12 - MAJOR[*self as usize]) % 12
+    }
+}
+
+/// Direction a scale degree conventionally resolves in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TendencyDirection {
+    Up,
+    Down,
+    Neutral,
+}
+
+/// A set of pitch classes built on a root, stored as ascending semitone
+/// offsets from that root (the first offset is always 0).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Scale {
+    pub root: u8,
+    pub intervals: Vec<u8>,
+}
+
+impl Scale {
+    pub fn new(root: u8, intervals: Vec<u8>) -> Self {
+        Self {
+            root: root % 12,
+            intervals,
+        }
+    }
+
+    /// Pitch classes of the scale, starting at the root.
+    pub fn notes(&self) -> impl Iterator<Item = u8> + '_ {
+        self.intervals.iter().map(|i| (self.root + i) % 12)
+    }
+
+    pub fn contains(&self, pc: u8) -> bool {
+        self.notes().any(|n| n == pc % 12)
+    }
+
+    /// The mode of this scale starting on the 1-based scale `degree`.
+    pub fn mode(&self, degree: usize) -> Scale {
+        let start = self.intervals[(degree.max(1) - 1) % self.intervals.len()];
+        let mut intervals = self
+            .intervals
+            .iter()
+            .map(|i| (i + 12 - start) % 12)
+            .collect::<Vec<_>>();
+        intervals.sort_unstable();
+        Scale::new(self.degree(degree), intervals)
+    }
+
+    /// Pitch class of the 1-based scale `degree`, wrapping past the octave.
+    pub fn degree(&self, degree: usize) -> u8 {
+        let i = (degree.max(1) - 1) % self.intervals.len();
+        (self.root + self.intervals[i]) % 12
+    }
+
+    /// Semitones from each note to the next, the last back up to the root
+    /// an octave higher, so the major scale steps 2 2 1 2 2 2 1.
+    pub fn steps(&self) -> Vec<u8> {
+        let next = self.intervals.iter().skip(1).chain([&12]);
+        self.intervals
+            .iter()
+            .zip(next)
+            .map(|(a, b)| b - a)
+            .collect()
+    }
+
+    /// The mode of this scale that every mode of it shares, to group them
+    /// by: the one whose steps start with the largest, the next largest
+    /// after that and so on. The modes of the major scale all give Lydian.
+    pub fn parent_scale(&self) -> Scale {
+        let steps = self.steps();
+        let rotated = |start: usize| steps.iter().cycle().skip(start).take(steps.len());
+        let start = (0..steps.len()).max_by(|&a, &b| rotated(a).cmp(rotated(b)).then(b.cmp(&a)));
+        match start {
+            Some(start) => self.mode(start + 1),
+            None => self.clone(),
+        }
+    }
+}
+
+/// A note of a scale as its degree number and how far it is from that
+/// degree of the major scale, as in ♭3 or ♯4.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ScaleDegree {
+    /// From 1 for the root up to 7.
+    pub number: u8,
+    /// Semitones above the degree of the major scale, negative for flats.
+    pub accidental: i8,
+    /// Pitch class of the note, to find it in a tuning.
+    pub pc: u8,
+}
+
+impl ScaleDegree {
+    /// The degree with an arrow up or down for every step of 72-EDO that the
+    /// degree of `tuning` nearest the note is off 12-TET, as in ♭3↑ for the
+    /// just minor third.
+    pub fn display(&self, tuning: &Tuning) -> String {
+        let off = tuning.cents_off(self.pc).unwrap_or(0.0);
+        let arrows = (off * 6.0 / 100.0).round() as i32;
+        let arrow = if arrows > 0 { "↑" } else { "↓" };
+        format!("{self}{}", arrow.repeat(arrows.unsigned_abs() as usize))
+    }
+}
+
+/// The degree in 12-TET, as in "♭7".
+impl fmt::Display for ScaleDegree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_accidental(f, self.accidental)?;
+        write!(f, "{}", self.number)
+    }
+}
+
+/// The degree of every note of `scale` above its root. Scales of seven notes
+/// number their notes in order, so the fourth note of the Lydian mode is ♯4
+/// and not ♭5. Other scales number each interval after the major scale, or
+/// the degree below when it falls between two, as in the 1 ♭3 4 5 ♭7 of the
+/// minor pentatonic scale or the ♯4 of the blues scale.
+pub fn scale_degrees(scale: &Scale) -> Vec<ScaleDegree> {
+    let heptatonic = scale.intervals.len() == 7;
+    scale
+        .intervals
+        .iter()
+        .enumerate()
+        .map(|(index, &interval)| {
+            let number = match heptatonic {
+                true => index as u8 + 1,
+                false => [1, 2, 2, 3, 3, 4, 4, 5, 6, 6, 7, 7][interval as usize % 12],
+            };
+            let major = MAJOR[number as usize - 1] as i8;
+            ScaleDegree {
+                number,
+                accidental: (interval % 12) as i8 - major,
+                pc: (scale.root + interval) % 12,
+            }
+        })
+        .collect()
+}
+
+/// The semitones above the root of every degree of a formula such as
+/// "1 2 b3 4 5 b6 b7", in the order they are written. Each degree is a
+/// number counted up the major scale, after any number of flats (b or ♭) or
+/// sharps (# or ♯), or a 𝄫 or 𝄪, so "#4" gives 6 and "bb7" 9. Degrees past
+/// the octave, up to the 15th two octaves up, are an octave higher, so "9"
+/// gives 14.
+pub fn scale_spelling_to_degrees(spelling: &str) -> Result<Vec<i32>, ParseError> {
+    let mut degrees = Vec::new();
+    let mut rest = spelling;
+    while let Some(start) = rest.find(|c: char| !c.is_whitespace()) {
+        let token = &rest[start..];
+        let token = &token[..token.find(char::is_whitespace).unwrap_or(token.len())];
+        let position = spelling.len() - rest.len() + start;
+        let error = || ParseError {
+            token: token.to_string(),
+            position,
+        };
+
+        let number = token.trim_start_matches(['b', '♭', '#', '♯', '𝄫', '𝄪']);
+        let accidentals = &token[..token.len() - number.len()];
+        let accidental: i32 = accidentals
+            .chars()
+            .map(|c| match c {
+                'b' | '♭' => -1,
+                '#' | '♯' => 1,
+                '𝄫' => -2,
+                _ => 2,
+            })
+            .sum();
+        if !number.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(error());
+        }
+        let number = match number.parse::<usize>() {
+            Ok(number @ 1..=15) => number,
+            _ => return Err(error()),
+        };
+        let (octave, degree) = ((number - 1) / 7, (number - 1) % 7);
+        degrees.push(12 * octave as i32 + MAJOR[degree] as i32 + accidental);
+        rest = &rest[start + token.len()..];
+    }
+    Ok(degrees)
+}
+
+/// A token of a scale formula that is not a degree, for
+/// [`scale_spelling_to_degrees`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub token: String,
+    /// Byte offset of the token in the formula.
+    pub position: usize,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "not a scale degree: {} at {}", self.token, self.position)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ChordQuality {
+    Major,
+    Dominant7,
+    Minor,
+}
+
+impl ChordQuality {
+    pub const COUNT: usize = 3;
+    pub const ALL: [ChordQuality; Self::COUNT] = [
+        ChordQuality::Major,
+        ChordQuality::Dominant7,
+        ChordQuality::Minor,
+    ];
+
+    /// Every quality, in the order of [`ChordQuality::ALL`].
+    pub fn iter() -> impl Iterator<Item = ChordQuality> {
+        Self::ALL.into_iter()
+    }
+
+    /// Suffix written after the root, as in "C7".
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            ChordQuality::Major => "",
+            ChordQuality::Dominant7 => "7",
+            ChordQuality::Minor => "m",
+        }
+    }
+
+    /// Semitone offsets of each chord tone above the root.
+    pub fn intervals(&self) -> &'static [u8] {
+        match self {
+            ChordQuality::Major => &[0, 4, 7],
+            ChordQuality::Dominant7 => &[0, 4, 7, 10],
+            ChordQuality::Minor => &[0, 3, 7],
+        }
+    }
+}
+
+/// Chord tones built on a root, stored like a [`Scale`] as semitone offsets
+/// from the root.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Chord {
+    pub root: u8,
+    pub intervals: Vec<u8>,
+    /// How each note is spelled, from the root up, once the chord has been
+    /// respelled by [`enharmonic_respelling`].
+    pub spelling: Option<Vec<NoteName>>,
+}
+
+impl Chord {
+    pub fn new(root: u8, quality: ChordQuality) -> Self {
+        Self {
+            root: root % 12,
+            intervals: quality.intervals().to_vec(),
+            spelling: None,
+        }
+    }
+
+    /// How each note is spelled, from the root up: as the chord was
+    /// respelled, or as [`enharmonic_respelling`] spells it by `preference`
+    /// if it was not.
+    pub fn note_names(&self, preference: AccidentalPreference) -> Vec<NoteName> {
+        match &self.spelling {
+            Some(names) => names.clone(),
+            None => enharmonic_respelling(self, preference).note_names(preference),
+        }
+    }
+
+    /// Pitch classes of the chord, starting at the root.
+    pub fn notes(&self) -> impl Iterator<Item = u8> + '_ {
+        self.intervals.iter().map(|i| (self.root + i) % 12)
+    }
+
+    pub fn contains(&self, pc: u8) -> bool {
+        self.notes().any(|n| n == pc % 12)
+    }
+
+    /// Number of distinct pitch classes in the chord.
+    pub fn note_count(&self) -> usize {
+        let mut notes = self.notes().collect::<Vec<_>>();
+        notes.sort_unstable();
+        notes.dedup();
+        notes.len()
+    }
+
+    pub fn quality(&self) -> Option<ChordQuality> {
+        ChordQuality::ALL
+            .into_iter()
+            .find(|quality| quality.intervals() == self.intervals)
+    }
+
+    /// The chord symbol, or the notes in braces if it has no known quality.
+    pub fn symbol(&self, spelling: AccidentalPreference) -> String {
+        match self.quality() {
+            Some(quality) => format!("{}{}", pitch_name(self.root, spelling), quality.symbol()),
+            None => {
+                let notes = self.notes().map(|pc| pitch_name(pc, spelling));
+                format!("{{{}}}", notes.collect::<Vec<_>>().join(" "))
+            }
+        }
+    }
+
+    /// Writes a polychord as "upper/lower".
+    pub fn to_polychord_string(upper: &Chord, lower: &Chord) -> String {
+        let spelling = AccidentalPreference::Sharps;
+        format!("{}/{}", upper.symbol(spelling), lower.symbol(spelling))
+    }
+}
+
+/// Degree names of the twelve semitones above a root, as they are usually
+/// written in chord formulas.
+const FORMULA_DEGREES: [&str; 12] = [
+    "1", "b2", "2", "b3", "3", "4", "b5", "5", "#5", "6", "b7", "7",
+];
+
+/// The chord as an interval formula, as in "1-3-5-b7". A tritone next to a
+/// perfect fifth is written "#11" and a minor sixth next to one "b13", as
+/// neither alters the fifth then.
+pub fn chord_formula_string(chord: &Chord) -> String {
+    let has_fifth = chord.intervals.contains(&7);
+    let degrees = chord.intervals.iter().map(|&i| match i % 12 {
+        6 if has_fifth => "#11",
+        8 if has_fifth => "b13",
+        i => FORMULA_DEGREES[i as usize],
+    });
+    degrees.collect::<Vec<_>>().join("-")
+}
+
+/// Parses an interval formula as written by [`chord_formula_string`] into a
+/// chord on `root`.
+pub fn chord_from_formula(root: u8, formula: &str) -> Option<Chord> {
+    let mut intervals = formula
+        .split('-')
+        .map(|degree| match degree.trim() {
+            "#11" => Some(6),
+            "b13" => Some(8),
+            degree => FORMULA_DEGREES
+                .iter()
+                .position(|&d| d == degree)
+                .map(|i| i as u8),
+        })
+        .collect::<Option<Vec<_>>>()?;
+    intervals.sort_unstable();
+    intervals.dedup();
+    Some(Chord {
+        root: root % 12,
+        intervals,
+        spelling: None,
+    })
+}
+
+/// `chord` with its notes spelled from its root by `preference`, in
+/// [`Chord::spelling`], each chord tone on the letter of its degree above
+/// the root, so the third of A♭m is C♭ and not B. Which degree an interval
+/// is follows [`chord_formula_string`].
+pub fn enharmonic_respelling(chord: &Chord, preference: AccidentalPreference) -> Chord {
+    let root = NoteName::new(chord.root, preference);
+    let has_fifth = chord.intervals.contains(&7);
+    let spelling = chord
+        .intervals
+        .iter()
+        .map(|&i| NoteName::on_letter(root.letter + degree_steps(i, has_fifth), chord.root + i))
+        .collect();
+    Chord {
+        spelling: Some(spelling),
+        ..chord.clone()
+    }
+}
+
+/// The notes of `chord` spelled for `key`, their letters stacked in thirds
+/// from the root as by [`enharmonic_respelling`], so G7 in C major is G B D
+/// F and not G B D G♭. A root in the key is spelled as the key spells it.
+/// Tones left on a letter twice or with a double accidental move to the
+/// letter next to it with the simplest accidental, so the sharp ninth of
+/// C7♯9 is D♯. A root outside the key is spelled as a flat or a sharp,
+/// whichever moves fewer tones, then leaves fewer notes spelled unlike the
+/// key, then fewer accidentals, and then the flat. So ♭III of C major is
+/// E♭ G B♭, and vii°7 of its dominant F♯ A C E♭.
+pub fn spell_chord_in_key(chord: &Chord, key: &Scale) -> Vec<NoteName> {
+    let preference = AccidentalPreference::KeyOf(key.root);
+    let tonic = NoteName::new(key.root, preference);
+    // scales of seven notes have a letter per degree
+    let heptatonic = key.intervals.len() == 7;
+    let key_notes: Vec<NoteName> = key
+        .notes()
+        .enumerate()
+        .map(|(degree, pc)| match heptatonic {
+            true => NoteName::on_letter(tonic.letter + degree as u8, pc),
+            false => NoteName::new(pc, preference),
+        })
+        .collect();
+
+    let roots = match key_notes.iter().find(|note| note.pc() == chord.root) {
+        Some(&root) => vec![root],
+        None => [AccidentalPreference::Flats, AccidentalPreference::Sharps]
+            .map(|preference| NoteName::new(chord.root, preference))
+            .to_vec(),
+    };
+    let cost = |(notes, moved): &(Vec<NoteName>, usize)| {
+        let outside = notes.iter().filter(|note| !key_notes.contains(note));
+        let accidentals = notes.iter().map(|n| n.accidental.unsigned_abs() as u32);
+        (*moved, outside.count(), accidentals.sum::<u32>())
+    };
+    let stacks = roots.into_iter().map(|root| stack_in_thirds(chord, root));
+    stacks.min_by_key(cost).unwrap().0
+}
+
+/// The notes of `chord` spelled for `key` as by [`spell_chord_in_key`], as
+/// pitches with the root in `octave` and every interval above it.
+pub fn voice_chord_in_key(chord: &Chord, key: &Scale, octave: i32) -> Vec<Pitch> {
+    let notes = spell_chord_in_key(chord, key);
+    let root = Pitch::with_note(notes[0], 12 * (octave + 1) + chord.root as i32);
+    let bass = root.to_midi();
+    notes
+        .into_iter()
+        .zip(&chord.intervals)
+        .map(|(note, &i)| Pitch::with_note(note, bass + i as i32))
+        .collect()
+}
+
+/// The tones of `chord` on letters stacked from `root` by their degree,
+/// moving tones that share a letter with a tone before them or need a
+/// double accidental to the letter next to it, and how many moved.
+fn stack_in_thirds(chord: &Chord, root: NoteName) -> (Vec<NoteName>, usize) {
+    let has_fifth = chord.intervals.contains(&7);
+    // the sixth of a diminished seventh chord is its seventh
+    let diminished_seventh = !has_fifth && chord.intervals.contains(&6);
+    let steps = |i: u8| match i % 12 {
+        9 if diminished_seventh => 6,
+        _ => degree_steps(i, has_fifth),
+    };
+    let mut notes: Vec<NoteName> = chord
+        .intervals
+        .iter()
+        .map(|&i| NoteName::on_letter(root.letter + steps(i), chord.root + i))
+        .collect();
+    let mut moved = 0;
+    for i in 1..notes.len() {
+        let note = notes[i];
+        let free = |letter: u8| notes[..i].iter().all(|below| below.letter != letter);
+        if free(note.letter) && note.accidental.abs() < 2 {
+            continue;
+        }
+        let simplest = [note.letter + 6, note.letter + 1]
+            .into_iter()
+            .filter(|&letter| free(letter % 7))
+            .map(|letter| NoteName::on_letter(letter, note.pc()))
+            .filter(|n| n.accidental.abs() < note.accidental.abs().max(2))
+            .min_by_key(|n| n.accidental.abs());
+        if let Some(simplest) = simplest {
+            notes[i] = simplest;
+            moved += 1;
+        }
+    }
+    (notes, moved)
+}
+
+/// Letters from the root up to the degree of interval `i` within the octave.
+fn degree_steps(i: u8, has_fifth: bool) -> u8 {
+    match i % 12 {
+        6 if has_fifth => 3,
+        8 if has_fifth => 5,
+        i => [0, 1, 1, 2, 2, 3, 4, 4, 4, 5, 6, 6][i as usize],
+    }
+}
+
+/// The notes of `chord` as a LilyPond chord of `duration` (4 for a quarter,
+/// 2 for a half and so on), as in `<c e g bes>4`. Notes are voiced for
+/// `key` as by [`voice_chord_in_key`], so they are spelled as the chord is
+/// shown, with the root in the octave below middle C and an apostrophe for
+/// every octave above it.
+pub fn chord_to_lily(chord: &Chord, key: &Scale, duration: u8) -> String {
+    let notes = voice_chord_in_key(chord, key, 3).into_iter().map(|pitch| {
+        let letter = ['c', 'd', 'e', 'f', 'g', 'a', 'b'][pitch.note.letter as usize];
+        let accidental = match pitch.note.accidental {
+            n if n > 0 => "is".repeat(n as usize),
+            n => "es".repeat(-n as usize),
+        };
+        // LilyPond's c is C3
+        let octave = match pitch.octave - 3 {
+            n if n >= 0 => "'".repeat(n as usize),
+            n => ",".repeat(-n as usize),
+        };
+        format!("{letter}{accidental}{octave}")
+    });
+    format!("<{}>{duration}", notes.collect::<Vec<_>>().join(" "))
+}
+
+/// A whole progression of `(chord, duration)` in `key` as LilyPond chords
+/// separated by spaces.
+pub fn progression_to_lily(chords: &[(Chord, u8)], key: &Scale) -> String {
+    let chords = chords
+        .iter()
+        .map(|(chord, duration)| chord_to_lily(chord, key, *duration));
+    chords.collect::<Vec<_>>().join(" ")
+}
+
+/// Every mode on every root, sorted by mode and then by root.
+pub fn all_scale_modes() -> Vec<(ScaleMode, u8, Scale)> {
+    ScaleMode::ALL
+        .iter()
+        .flat_map(|mode| (0..12).map(|root| (*mode, root, mode.scale(root))))
+        .collect()
+}
+
+/// The seven modes on `tonic`, such as C Ionian up to C Locrian, in the
+/// order of [`ScaleMode::ALL`].
+pub fn parallel_modes(tonic: u8) -> Vec<(ScaleMode, Scale)> {
+    ScaleMode::ALL
+        .iter()
+        .map(|mode| (*mode, mode.scale(tonic)))
+        .collect()
+}
+
+/// The six-note scale of whole steps.
+pub fn whole_tone(root: u8) -> Scale {
+    Scale::new(root, vec![0, 2, 4, 6, 8, 10])
+}
+
+/// The eight-note scale of alternating half and whole steps, starting with a
+/// half step if `start_half_step` is set.
+pub fn diminished(root: u8, start_half_step: bool) -> Scale {
+    let intervals = match start_half_step {
+        true => vec![0, 1, 3, 4, 6, 7, 9, 10],
+        false => vec![0, 2, 3, 5, 6, 8, 9, 11],
+    };
+    Scale::new(root, intervals)
+}
+
+/// The six-note scale of alternating minor thirds and half steps.
+pub fn augmented(root: u8) -> Scale {
+    Scale::new(root, vec![0, 3, 4, 7, 8, 11])
+}
+
+/// The major (1 2 3 5 6) and minor (1 ♭3 4 5 ♭7) pentatonic scales.
+pub fn pentatonic_scales(root: u8) -> (Scale, Scale) {
+    (
+        Scale::new(root, vec![0, 2, 4, 7, 9]),
+        Scale::new(root, vec![0, 3, 5, 7, 10]),
+    )
+}
+
+/// The minor blues (1 ♭3 4 ♭5 5 ♭7) and major blues (1 2 ♭3 3 5 6) scales,
+/// the pentatonic scales with a ♭5 and a ♭3 added.
+pub fn blues_scale(root: u8) -> (Scale, Scale) {
+    (
+        Scale::new(root, vec![0, 3, 5, 6, 7, 10]),
+        Scale::new(root, vec![0, 2, 3, 4, 7, 9]),
+    )
+}
+
+/// The composite blues scale (1 2 ♭3 3 4 ♭5 5 6 ♭7), every note of both
+/// blues scales.
+pub fn country_scale(root: u8) -> Scale {
+    let (minor, major) = blues_scale(root);
+    let mut intervals = [minor.intervals, major.intervals].concat();
+    intervals.sort_unstable();
+    intervals.dedup();
+    Scale::new(root, intervals)
+}
+
+/// The five rotations of a pentatonic scale, starting with the scale itself.
+pub fn pentatonic_modes(pentatonic: &Scale) -> [Scale; 5] {
+    std::array::from_fn(|i| pentatonic.mode(i + 1))
+}
+
+/// Every symmetric scale on every root with its name, sorted by scale and
+/// then by root.
+pub fn symmetric_scales() -> Vec<(&'static str, u8, Scale)> {
+    type Build = fn(u8) -> Scale;
+    let scales: [(&str, Build); 4] = [
+        ("Whole tone", whole_tone),
+        ("Half-whole", |root| diminished(root, true)),
+        ("Whole-half", |root| diminished(root, false)),
+        ("Augmented", augmented),
+    ];
+    scales
+        .into_iter()
+        .flat_map(|(name, scale)| (0..12).map(move |root| (name, root, scale(root))))
+        .collect()
+}
+
+/// The named six-note scales on `root`: the whole tone and augmented
+/// scales, the Prometheus scale (1 2 3 ♯4 6 ♭7), the tritone scale
+/// (1 ♭2 3 ♭5 5 ♭7), the two major triads a tritone apart, and the
+/// two-semitone tritone scale (1 ♭2 2 ♯4 5 ♭6), two chromatic clusters a
+/// tritone apart.
+pub fn hexatonic_scales(root: u8) -> Vec<(String, Scale)> {
+    let scales = [
+        ("Whole tone", whole_tone(root)),
+        ("Augmented", augmented(root)),
+        ("Prometheus", Scale::new(root, vec![0, 2, 4, 6, 9, 10])),
+        ("Tritone", Scale::new(root, vec![0, 1, 4, 6, 7, 10])),
+        ("2-st tritone", Scale::new(root, vec![0, 1, 2, 6, 7, 8])),
+    ];
+    scales
+        .into_iter()
+        .map(|(name, scale)| (name.to_string(), scale))
+        .collect()
+}
+
+/// The bebop scales, each a seven-note scale with a chromatic passing tone
+/// added so that chord tones fall on the beat in runs of eighth notes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BebopType {
+    /// Mixolydian with a major seventh between ♭7 and the root.
+    Dominant,
+    /// Ionian with a ♯5 between 5 and 6.
+    Major,
+    /// Dorian with a major third between ♭3 and 4.
+    Dorian,
+    /// Locrian with a perfect fifth between ♭5 and ♭6.
+    HalfDiminished,
+}
+
+impl BebopType {
+    pub const ALL: [BebopType; 4] = [
+        BebopType::Dominant,
+        BebopType::Major,
+        BebopType::Dorian,
+        BebopType::HalfDiminished,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BebopType::Dominant => "Bebop dom",
+            BebopType::Major => "Bebop maj",
+            BebopType::Dorian => "Bebop dor",
+            BebopType::HalfDiminished => "Bebop m7♭5",
+        }
+    }
+
+    /// The seven-note mode the passing tone is added to.
+    pub fn mode(&self) -> ScaleMode {
+        match self {
+            BebopType::Dominant => ScaleMode::Mixolydian,
+            BebopType::Major => ScaleMode::Ionian,
+            BebopType::Dorian => ScaleMode::Dorian,
+            BebopType::HalfDiminished => ScaleMode::Locrian,
+        }
+    }
+
+    /// The 1-based degree of [`BebopType::mode`] that the passing tone is
+    /// inserted after, a half step above it.
+    pub fn passing_tone_position(&self) -> u8 {
+        match self {
+            BebopType::Dominant => 7,
+            BebopType::Major => 5,
+            BebopType::Dorian => 3,
+            BebopType::HalfDiminished => 5,
+        }
+    }
+}
+
+/// The eight-note bebop scale of `scale_type` on `root`.
+pub fn bebop_scale(root: u8, scale_type: BebopType) -> Scale {
+    let mut intervals = scale_type.mode().intervals().to_vec();
+    let position = scale_type.passing_tone_position() as usize;
+    intervals.insert(position, intervals[position - 1] + 1);
+    Scale::new(root, intervals)
+}
+
+/// Every bebop scale on every root, sorted by scale and then by root.
+pub fn bebop_scales() -> Vec<(BebopType, u8, Scale)> {
+    BebopType::ALL
+        .iter()
+        .flat_map(|kind| (0..12).map(|root| (*kind, root, bebop_scale(root, *kind))))
+        .collect()
+}
+
+/// The just intonation ratio each of the twelve semitones above a root
+/// approximates, in 5-limit where there is a common one.
+pub const JUST_RATIOS: [(u32, u32); 12] = [
+    (1, 1),
+    (16, 15),
+    (9, 8),
+    (6, 5),
+    (5, 4),
+    (4, 3),
+    (45, 32),
+    (3, 2),
+    (8, 5),
+    (5, 3),
+    (9, 5),
+    (15, 8),
+];
+
+/// Tenney harmonic distance between pitch classes `a` and `b`: `log2(p * q)`
+/// of the simplest just ratio `p/q` of the interval between them, so a fifth
+/// is closer than a major second and both are closer than a tritone. That is
+/// the simplest of the ratios up from `a` to `b` and from `b` to `a` and of
+/// their inversions `2q/p`, so the distance is the same both ways round even
+/// where the ratios are not each other's inversions, as the minor seventh
+/// 9/5 is not that of the major second 9/8.
+pub fn harmonic_distance(a: u8, b: u8) -> f64 {
+    let tenney = |p: u64, q: u64| ((p * q) as f64).log2();
+    let up = |a: u8, b: u8| {
+        let (p, q) = JUST_RATIOS[interval_between(a, b) as usize];
+        let inversion = Ratio::new(2 * q as u64, p as u64).unwrap();
+        tenney(p as u64, q as u64).min(tenney(inversion.num, inversion.den))
+    };
+    up(a, b).min(up(b, a))
+}
+
+/// Whether one of `a` and `b` is a mode of the other, on any root: whether
+/// the steps of `a` are those of `b` starting from one of its notes, as for
+/// C Dorian and D Aeolian.
+pub fn scale_rotation_equivalence(a: &Scale, b: &Scale) -> bool {
+    let (a, b) = (a.steps(), b.steps());
+    a.len() == b.len()
+        && (a.is_empty()
+            || (0..b.len()).any(|start| a.iter().eq(b[start..].iter().chain(&b[..start]))))
+}
+
+/// Fraction of the pitch classes of `chord` that are in `scale`, 1.0 for a
+/// chord that is fully diatonic to it.
+pub fn scale_compatibility(chord: &Chord, scale: &Scale) -> f64 {
+    let mut common_tones = chord
+        .notes()
+        .filter(|&pc| scale.notes().any(|n| n == pc))
+        .collect::<Vec<_>>();
+    common_tones.sort_unstable();
+    common_tones.dedup();
+    common_tones.len() as f64 / chord.note_count().max(1) as f64
+}
+
+/// Chord tones that are avoid notes over `scale`: those outside the scale,
+/// and those a half step above a scale degree that the chord also holds,
+/// like the fourth over a major third. In the order of the chord.
+pub fn avoid_notes(chord: &Chord, scale: &Scale) -> Vec<u8> {
+    let in_scale = |pc: u8| scale.notes().any(|n| n == pc % 12);
+    let mut avoid = Vec::new();
+    for pc in chord.notes() {
+        let clashes = in_scale(pc + 11) && chord.contains(pc + 11);
+        if (!in_scale(pc) || clashes) && !avoid.contains(&pc) {
+            avoid.push(pc);
+        }
+    }
+    avoid
+}
+
+/// Approach notes to `target`: the pitch classes a half step below one of
+/// its tones, and those a whole step below one that are in `scale`, that
+/// are not chord tones themselves. In the order of the tones they lead to.
+/// Those in `scale` are diatonic approaches and the others chromatic ones,
+/// which [`Scale::contains`] tells apart. Unlike tensions these are passing
+/// tones of a melody, not part of the chord.
+pub fn approach_notes(target: &Chord, scale: &Scale) -> Vec<u8> {
+    let mut approaches = Vec::new();
+    for pc in target.notes() {
+        let (half, whole) = ((pc + 11) % 12, (pc + 10) % 12);
+        let below = [Some(half), scale.contains(whole).then_some(whole)];
+        for below in below.into_iter().flatten() {
+            if !target.contains(below) && !approaches.contains(&below) {
+                approaches.push(below);
+            }
+        }
+    }
+    approaches
+}
+
+/// Every degree of `scale` with the direction it tends to resolve in, from
+/// the root up. Degrees of scales that are neither one of the seven modes nor
+/// harmonic minor are all neutral.
+pub fn tendency_tones(scale: &Scale) -> Vec<(u8, TendencyDirection)> {
+    use TendencyDirection::{Down as D, Neutral as N, Up as U};
+    const HARMONIC_MINOR_TENDENCIES: [TendencyDirection; 7] = [N, U, N, N, N, D, U];
+
+    let tendencies = ScaleMode::ALL
+        .into_iter()
+        .find(|mode| mode.intervals() == scale.intervals[..])
+        .map(|mode| mode.tendencies())
+        .or_else(|| (scale.intervals == HARMONIC_MINOR).then_some(HARMONIC_MINOR_TENDENCIES));
+
+    scale
+        .notes()
+        .enumerate()
+        .map(|(i, pc)| (pc, tendencies.map_or(N, |tendencies| tendencies[i])))
+        .collect()
+}
+
+/// The leading tone in `chord` and the tonic it resolves up to, if `chord`
+/// contains the seventh degree of `scale` and it lies a half step below the
+/// tonic. A seventh degree a whole step below (a subtonic) does not count.
+pub fn leading_tone_resolution(chord: &Chord, scale: &Scale) -> Option<(u8, u8)> {
+    if scale.intervals.len() < 7 {
+        return None;
+    }
+
+    let leading_tone = scale.degree(7);
+    let tonic = scale.root;
+    ((leading_tone + 1) % 12 == tonic && chord.contains(leading_tone))
+        .then_some((leading_tone, tonic))
+}
+
+/// Semitones up from pitch class `a` to pitch class `b`, from 0 to 11.
+pub fn interval_between(a: u8, b: u8) -> i32 {
+    (b as i32 - a as i32).rem_euclid(12)
+}
+
+/// Semitones from pitch class `a` to pitch class `b` the shorter way round,
+/// from -6 to 6: negative when going down is shorter, and 6 for a tritone.
+pub fn interval_between_signed(a: u8, b: u8) -> i32 {
+    let up = interval_between(a, b);
+    if up > 6 {
+        up - 12
+    } else {
+        up
+    }
+}
+
+/// Semitones between pitch classes `a` and `b` the shorter way round.
+fn pc_distance(a: u8, b: u8) -> u32 {
+    interval_between_signed(a, b).unsigned_abs()
+}
+
+/// Least total number of semitones the voices move going from `a` to `b`,
+/// with each voice moving to the nearest octave. Every note of the smaller
+/// chord is led to a different note of the larger one, and the notes of the
+/// larger chord left over are doubled from their nearest note.
+pub fn voice_leading_distance(a: &Chord, b: &Chord) -> u32 {
+    let (a, b) = (a.notes().collect::<Vec<_>>(), b.notes().collect::<Vec<_>>());
+    match a.len() <= b.len() {
+        true => least_motion(&a, &b),
+        false => least_motion(&b, &a),
+    }
+}
+
+/// The motion of [`voice_leading_distance`] from the pitch classes `small`
+/// into the at least as many of `large`. Rather than try every way to lead
+/// one into the other, this finds the least motion into each subset of
+/// `large` in turn, so it takes time exponential in the size of `large`
+/// rather than factorial.
+fn least_motion(small: &[u8], large: &[u8]) -> u32 {
+    let doubled = |pc: u8| small.iter().map(|&n| pc_distance(pc, n)).min();
+    // the least motion leading as many notes of `small` as the mask has
+    // bits into the notes of `large` it has, which every smaller subset
+    // comes before
+    let mut least = vec![u32::MAX; 1 << large.len()];
+    least[0] = 0;
+    let mut best = u32::MAX;
+    for mask in 0..least.len() {
+        let (motion, led) = (least[mask], mask.count_ones() as usize);
+        if motion == u32::MAX {
+            continue;
+        }
+        if led == small.len() {
+            let left_over = (0..large.len()).filter(|i| mask & 1 << i == 0);
+            let doubled = left_over.map(|i| doubled(large[i]).unwrap_or(0));
+            best = best.min(motion + doubled.sum::<u32>());
+            continue;
+        }
+        for (i, &pc) in large.iter().enumerate() {
+            if mask & 1 << i == 0 {
+                let next = &mut least[mask | 1 << i];
+                *next = (*next).min(motion + pc_distance(small[led], pc));
+            }
+        }
+    }
+    best
+}
+
+/// The ways of leading the pitch classes `from` into `to` with the least
+/// motion, as by [`voice_leading_distance`], up to `limit` of them. Each is
+/// a list of pairs of an index into `from` and one into `to` that every note
+/// of both is in at least once.
+fn least_motion_leadings(from: &[u8], to: &[u8], limit: usize) -> Vec<Vec<(usize, usize)>> {
+    struct Search<'a> {
+        small: &'a [u8],
+        large: &'a [u8],
+        least: u32,
+        /// The least the notes of `small` from each index on could move.
+        bound: Vec<u32>,
+        /// The note of `small` led into each note of `large` so far.
+        led: Vec<Option<usize>>,
+        found: Vec<Vec<(usize, usize)>>,
+        limit: usize,
+    }
+
+    fn search(s: &mut Search, index: usize, motion: u32) {
+        if s.found.len() >= s.limit {
+            return;
+        }
+        if index == s.small.len() {
+            let mut motion = motion;
+            let mut pairs = Vec::with_capacity(s.large.len());
+            for (i, led) in s.led.iter().enumerate() {
+                let from = led.unwrap_or_else(|| {
+                    let distance = |&n: &usize| pc_distance(s.large[i], s.small[n]);
+                    let nearest = (0..s.small.len()).min_by_key(distance).unwrap();
+                    motion += distance(&nearest);
+                    nearest
+                });
+                pairs.push((from, i));
+            }
+            if motion == s.least {
+                s.found.push(pairs);
+            }
+            return;
+        }
+        for i in 0..s.large.len() {
+            let next = motion + pc_distance(s.small[index], s.large[i]);
+            if s.led[i].is_none() && next + s.bound[index + 1] <= s.least {
+                s.led[i] = Some(index);
+                search(s, index + 1, next);
+                s.led[i] = None;
+            }
+        }
+    }
+
+    let flip = from.len() > to.len();
+    let (small, large) = if flip { (to, from) } else { (from, to) };
+    if small.is_empty() {
+        return Vec::new();
+    }
+    let mut bound = vec![0; small.len() + 1];
+    for (index, &pc) in small.iter().enumerate().rev() {
+        let nearest = large.iter().map(|&n| pc_distance(pc, n)).min().unwrap();
+        bound[index] = bound[index + 1] + nearest;
+    }
+    let mut s = Search {
+        small,
+        large,
+        least: least_motion(small, large),
+        bound,
+        led: vec![None; large.len()],
+        found: Vec::new(),
+        limit,
+    };
+    search(&mut s, 0, 0);
+    if flip {
+        for pairs in &mut s.found {
+            pairs.iter_mut().for_each(|(a, b)| std::mem::swap(a, b));
+        }
+    }
+    s.found
+}
+
+/// Mean motion per voice and per chord change of `progression`, in
+/// semitones, so lower is smoother. Each change counts as many voices as its
+/// larger chord has notes. Progressions of fewer than two chords score 0.
+pub fn voice_leading_smoothness(progression: &[Chord]) -> f64 {
+    if progression.len() < 2 {
+        return 0.0;
+    }
+    let per_voice = progression.windows(2).map(|pair| {
+        let voices = pair[0].notes().count().max(pair[1].notes().count()).max(1);
+        voice_leading_distance(&pair[0], &pair[1]) as f64 / voices as f64
+    });
+    per_voice.sum::<f64>() / (progression.len() - 1) as f64
+}
+
+/// Stacks `upper` over `lower`: the union of their pitch classes, rooted on
+/// the root of `lower` so that it sounds in the bass.
+pub fn polychord(upper: &Chord, lower: &Chord) -> Chord {
+    let mut intervals = lower
+        .notes()
+        .chain(upper.notes())
+        .map(|pc| (pc + 12 - lower.root) % 12)
+        .collect::<Vec<_>>();
+    intervals.sort_unstable();
+    intervals.dedup();
+
+    Chord {
+        root: lower.root,
+        intervals,
+        spelling: None,
+    }
+}
+
+/// The pitch classes two chords share over the pitch classes in either, 1.0
+/// for chords of the same notes and 0.0 for chords with none in common.
+pub fn chord_similarity(a: &Chord, b: &Chord) -> f64 {
+    let mut a_notes = a.notes().collect::<Vec<_>>();
+    a_notes.sort_unstable();
+    a_notes.dedup();
+    let shared = a_notes.iter().filter(|&&pc| b.contains(pc)).count();
+    let total = a_notes.len() + b.note_count() - shared;
+    if total == 0 {
+        return 0.0;
+    }
+    shared as f64 / total as f64
+}
+
+/// Names a set of pitch classes: every chord of a known quality on every
+/// root, or only on `root_hint`, scored by [`chord_similarity`] to `pcs`, so
+/// that each omitted or added note counts against it. Best first, without
+/// chords that share no notes.
+pub fn chord_from_pitch_classes(pcs: &[u8], root_hint: Option<u8>) -> Vec<(Chord, f64)> {
+    let notes = Chord {
+        root: 0,
+        intervals: pcs.iter().map(|pc| pc % 12).collect(),
+        spelling: None,
+    };
+    let mut matches = chord_catalog()
+        .iter()
+        .filter(|(_, chord)| root_hint.is_none_or(|root| chord.root == root % 12))
+        .map(|(_, chord)| (chord.clone(), chord_similarity(&notes, chord)))
+        .filter(|&(_, similarity)| similarity > 0.0)
+        .collect::<Vec<_>>();
+    // stable, so ties stay in root order and then in the order of qualities
+    matches.sort_by(|(a, x), (b, y)| y.total_cmp(x).then(a.root.cmp(&b.root)));
+    matches
+}
+
+/// Length of [`chord_catalog`].
+pub const CHORD_CATALOG_LEN: usize = ChordQuality::COUNT * 12;
+
+/// A chord of every quality on every root, by quality and then root, so the
+/// chord of `quality` on `root` is the `quality as usize * 12 + root`th.
+pub fn generate_chord_catalog() -> Vec<(ChordQuality, Chord)> {
+    ChordQuality::iter()
+        .flat_map(|quality| (0u8..12).map(move |root| (quality, Chord::new(root, quality))))
+        .collect()
+}
+
+/// The [`generate_chord_catalog`] of every chord, computed the first time it
+/// is asked for.
+pub fn chord_catalog() -> &'static [(ChordQuality, Chord)] {
+    static CATALOG: OnceLock<Vec<(ChordQuality, Chord)>> = OnceLock::new();
+    CATALOG.get_or_init(generate_chord_catalog)
+}
+
+/// The chord of `quality` on `root` in [`chord_catalog`].
+pub fn catalog_chord(quality: ChordQuality, root: u8) -> &'static Chord {
+    &chord_catalog()[quality as usize * 12 + root as usize % 12].1
+}
+
+/// A twelve-tone row: every pitch class once, in the order it is played.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ToneRow([u8; 12]);
+
+impl ToneRow {
+    /// The row of `notes`, if they are the twelve pitch classes from 0 to 11
+    /// in some order.
+    pub fn new(notes: [u8; 12]) -> Result<Self, ToneRowError> {
+        let mut seen = [false; 12];
+        for pc in notes {
+            match seen.get_mut(pc as usize) {
+                None => return Err(ToneRowError::OutOfRange(pc)),
+                Some(true) => return Err(ToneRowError::Repeated(pc)),
+                Some(seen) => *seen = true,
+            }
+        }
+        Ok(Self(notes))
+    }
+
+    pub fn notes(&self) -> &[u8; 12] {
+        &self.0
+    }
+
+    /// The row moved up `n` semitones.
+    pub fn transpose(&self, n: u8) -> Self {
+        Self(self.0.map(|pc| (pc + n % 12) % 12))
+    }
+
+    /// The row with every interval turned upside down, starting on the same
+    /// note, so a fourth up becomes a fourth down.
+    pub fn inversion(&self) -> Self {
+        let first = self.0[0];
+        Self(self.0.map(|pc| (2 * first + 12 - pc) % 12))
+    }
+
+    /// The row backwards.
+    pub fn retrograde(&self) -> Self {
+        let mut notes = self.0;
+        notes.reverse();
+        Self(notes)
+    }
+
+    /// The inversion backwards.
+    pub fn retrograde_inversion(&self) -> Self {
+        self.inversion().retrograde()
+    }
+
+    /// The twelve-tone matrix of the row: the row in the first row, and
+    /// below it the row transposed to start on each note of the inversion in
+    /// turn. Primes read left to right, retrogrades right to left, and
+    /// inversions and retrograde inversions down and up the columns, for all
+    /// 48 forms of the row.
+    pub fn matrix(&self) -> [ToneRow; 12] {
+        let first = self.0[0];
+        self.inversion()
+            .0
+            .map(|pc| self.transpose(interval_between(first, pc) as u8))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToneRowError {
+    /// A note that is not a pitch class from 0 to 11.
+    OutOfRange(u8),
+    /// A pitch class that is in the row more than once, so another is
+    /// missing.
+    Repeated(u8),
+}
+
+impl fmt::Display for ToneRowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ToneRowError::OutOfRange(pc) => write!(f, "not a pitch class: {pc}"),
+            ToneRowError::Repeated(pc) => write!(f, "pitch class {pc} is in the row twice"),
+        }
+    }
+}
+
+impl std::error::Error for ToneRowError {}
+
+/// The triad on every degree of `scale`, stacked from every other note of
+/// it, in the order of the degrees.
+pub fn diatonic_chords(scale: &Scale) -> Vec<Chord> {
+    let len = scale.intervals.len();
+    (0..len)
+        .map(|degree| {
+            let root = scale.intervals[degree];
+            let intervals = [0, 2, 4].map(|step| {
+                let note = scale.intervals[(degree + step) % len];
+                (note + 12 - root) % 12
+            });
+            Chord {
+                root: (scale.root + root) % 12,
+                intervals: intervals.to_vec(),
+                spelling: None,
+            }
+        })
+        .collect()
+}
+
+/// The chord's degree in `scale` as a Roman numeral, upper case for a major
+/// third and lower case otherwise, with ° for a diminished triad and 7 for
+/// a minor seventh. Roots outside the scale are written as the degree above
+/// with a ♭, or the one below with a ♯, as in ♭II7.
+pub fn roman_numeral(chord: &Chord, scale: &Scale) -> String {
+    const NUMERALS: [&str; 7] = ["I", "II", "III", "IV", "V", "VI", "VII"];
+    let degree_of = |pc: u8| scale.notes().position(|n| n == pc % 12);
+    let (accidental, degree) = match degree_of(chord.root) {
+        Some(degree) => ("", degree),
+        None => match degree_of(chord.root + 1) {
+            Some(degree) => ("♭", degree),
+            None => ("♯", degree_of(chord.root + 11).unwrap_or(0)),
+        },
+    };
+    let numeral = NUMERALS.get(degree).copied().unwrap_or("?");
+
+    let has = |interval: u8| chord.intervals.contains(&interval);
+    let numeral = match has(4) {
+        true => numeral.to_string(),
+        false => numeral.to_lowercase(),
+    };
+    let diminished = if has(3) && has(6) && !has(7) {
+        "°"
+    } else {
+        ""
+    };
+    let seventh = if has(10) { "7" } else { "" };
+    format!("{accidental}{numeral}{diminished}{seventh}")
+}
+
+/// The part a chord plays in a key, as told by [`analyze_progression`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HarmonicFunction {
+    /// I, iii and vi: at rest.
+    Tonic,
+    /// ii and IV: leading away from the tonic towards the dominant.
+    PreDominant,
+    /// V and vii°: pulling back to the tonic.
+    Dominant,
+    /// A dominant borrowed from another key, resolving a fifth down to a
+    /// degree other than the tonic, as the V/V.
+    Secondary,
+    /// Any other chord with notes outside the key, as borrowed from a
+    /// parallel mode.
+    Modal,
+}
+
+impl HarmonicFunction {
+    pub const ALL: [HarmonicFunction; 5] = [
+        HarmonicFunction::Tonic,
+        HarmonicFunction::PreDominant,
+        HarmonicFunction::Dominant,
+        HarmonicFunction::Secondary,
+        HarmonicFunction::Modal,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            HarmonicFunction::Tonic => "Tonic",
+            HarmonicFunction::PreDominant => "Predominant",
+            HarmonicFunction::Dominant => "Dominant",
+            HarmonicFunction::Secondary => "Secondary",
+            HarmonicFunction::Modal => "Modal",
+        }
+    }
+}
+
+/// What [`analyze_progression`] makes of one chord of a progression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProgressionAnnotation {
+    /// As by [`roman_numeral`], or as in "V7/V" for a secondary dominant.
+    pub roman_numeral: String,
+    pub function: HarmonicFunction,
+    /// Up when the chord holds the leading tone of the key or of the chord
+    /// it tonicizes, and otherwise the tendency of its root by
+    /// [`tendency_tones`].
+    pub tendency: TendencyDirection,
+    /// Parallel fifths, parallel octaves, and crossed and overlapping voices
+    /// going into the chord from the one before it, as sentences.
+    pub voice_leading_issues: Vec<String>,
+}
+
+/// Roman numeral, function, tendency and voice leading of every chord of
+/// `chords` in `key`. Keys other than heptatonic ones have no degrees to go
+/// by, so all their chords are modal.
+///
+/// The first chord is voiced as by [`voice_chord_in_key`], and each voice
+/// moves on to the nearest note of the next chord with the least motion
+/// overall, as by [`voice_leading_distance`]. Of the ways to do that, the
+/// one with the fewest voice leading issues is taken.
+pub fn analyze_progression(chords: &[Chord], key: &Scale) -> Vec<ProgressionAnnotation> {
+    let mut previous: Option<Vec<i32>> = None;
+    chords
+        .iter()
+        .map(|chord| {
+            let (voices, voice_leading_issues) = match &previous {
+                Some(before) => lead_voices(before, chord),
+                None => {
+                    let voices = voice_chord_in_key(chord, key, 4);
+                    (voices.iter().map(Pitch::to_midi).collect(), Vec::new())
+                }
+            };
+            previous = Some(voices);
+
+            let (function, roman_numeral, tendency) = harmonic_function(chord, key);
+            ProgressionAnnotation {
+                roman_numeral,
+                function,
+                tendency,
+                voice_leading_issues,
+            }
+        })
+        .collect()
+}
+
+/// The voicing of `chord` that `before`, as MIDI notes from the bass up,
+/// moves to with the least motion and fewest issues, with those issues.
+fn lead_voices(before: &[i32], chord: &Chord) -> (Vec<i32>, Vec<String>) {
+    let from = before.iter().map(|&n| n.rem_euclid(12) as u8);
+    let (from, to) = (from.collect::<Vec<_>>(), chord.notes().collect::<Vec<_>>());
+    let voicings = least_motion_leadings(&from, &to, 64)
+        .into_iter()
+        .map(|pairs| {
+            let mut voices = pairs
+                .into_iter()
+                .map(|(i, j)| {
+                    (
+                        before[i],
+                        before[i] + interval_between_signed(from[i], to[j]),
+                    )
+                })
+                .collect::<Vec<_>>();
+            voices.sort_unstable();
+            let issues = voice_leading_issues(&voices);
+            (voices, issues)
+        });
+    match voicings.min_by_key(|(_, issues)| issues.len()) {
+        Some((voices, issues)) => (voices.into_iter().map(|(_, to)| to).collect(), issues),
+        None => (before.to_vec(), Vec::new()),
+    }
+}
+
+/// The function, Roman numeral and tendency [`analyze_progression`] gives
+/// `chord` in `key`.
+fn harmonic_function(chord: &Chord, key: &Scale) -> (HarmonicFunction, String, TendencyDirection) {
+    use HarmonicFunction::*;
+
+    let numeral = roman_numeral(chord, key);
+    let tendency = match leading_tone_resolution(chord, key) {
+        Some(_) => TendencyDirection::Up,
+        None => tendency_tones(key)
+            .into_iter()
+            .find(|&(pc, _)| pc == chord.root % 12)
+            .map_or(TendencyDirection::Neutral, |(_, tendency)| tendency),
+    };
+    if key.intervals.len() != 7 {
+        return (Modal, numeral, tendency);
+    }
+
+    let degree = key.notes().position(|pc| pc == chord.root % 12);
+    if chord.notes().all(|pc| key.contains(pc)) {
+        let function = match degree {
+            Some(0 | 2 | 5) => Tonic,
+            Some(1 | 3) => PreDominant,
+            Some(_) => Dominant,
+            None => Modal,
+        };
+        return (function, numeral, tendency);
+    }
+
+    let has = |interval: u8| chord.intervals.contains(&interval);
+    let target = (chord.root + 5) % 12;
+    let dominant = has(4) && !has(11);
+    if !dominant || !key.contains(target) {
+        (Modal, numeral, tendency)
+    } else if target == key.root {
+        // as the major V of a minor key
+        (Dominant, numeral, TendencyDirection::Up)
+    } else {
+        let of = diatonic_chords(key)
+            .into_iter()
+            .find(|other| other.root == target)
+            .map_or_else(|| "?".to_string(), |other| roman_numeral(&other, key));
+        let seventh = if has(10) { "7" } else { "" };
+        (Secondary, format!("V{seventh}/{of}"), TendencyDirection::Up)
+    }
+}
+
+/// Parallel fifths and octaves between any two of `voices`, given as the
+/// MIDI notes they move from and to and ordered from the bass up by the
+/// first. Also finds neighbouring voices that cross, swapping their order,
+/// and ones that overlap, passing where their neighbour was without
+/// crossing it. Voices are numbered from 1 for the bass.
+fn voice_leading_issues(voices: &[(i32, i32)]) -> Vec<String> {
+    let mut issues = Vec::new();
+    for (i, &(before, after)) in voices.iter().enumerate() {
+        let motion = (after - before).signum();
+        for (j, &(other_before, other_after)) in voices.iter().enumerate().skip(i + 1) {
+            let similar = motion != 0 && motion == (other_after - other_before).signum();
+            let interval = |a: i32, b: i32| (b - a).rem_euclid(12);
+            let parallel = match (interval(before, other_before), interval(after, other_after)) {
+                (7, 7) => "fifths",
+                (0, 0) => "octaves",
+                _ => continue,
+            };
+            if similar {
+                let (a, b) = (i + 1, j + 1);
+                issues.push(format!("parallel {parallel} in voices {a} and {b}"));
+            }
+        }
+        let Some(&(above_before, above_after)) = voices.get(i + 1) else {
+            continue;
+        };
+        let (a, b) = (i + 1, i + 2);
+        if after > above_after {
+            issues.push(format!("voices {a} and {b} cross"));
+        } else if after > above_before {
+            issues.push(format!("voice {a} overlaps where voice {b} was"));
+        } else if above_after < before {
+            issues.push(format!("voice {b} overlaps where voice {a} was"));
+        }
+    }
+    issues
+}
+
+/// How dissonant the chord sounds, from 0.0 for a single note to 1.0 when
+/// every pair of notes is a tritone apart: the mean [`harmonic_distance`]
+/// of all pairs, over that of the tritone.
+pub fn dissonance(chord: &Chord) -> f64 {
+    let notes = chord.notes().collect::<Vec<_>>();
+    let pairs = notes
+        .iter()
+        .enumerate()
+        .flat_map(|(i, &a)| notes[i + 1..].iter().map(move |&b| harmonic_distance(a, b)));
+    let (sum, count) = pairs.fold((0.0, 0), |(sum, count), d| (sum + d, count + 1));
+    match count {
+        0 => 0.0,
+        _ => sum / count as f64 / harmonic_distance(0, 6),
+    }
+}
+
+/// Partials closer than this fraction of the [`critical_bandwidth`] between
+/// them beat roughly, as by [`rough_pairs`].
+pub const ROUGH_FRACTION: f64 = 0.25;
+
+/// Partials closer than this many Hz are heard as one, beating too slowly to
+/// sound rough.
+pub const COINCIDENT_HZ: f64 = 0.5;
+
+/// One harmonic of a note in the spectrum of a chord, as by [`spectrum`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Partial {
+    pub frequency: f64,
+    /// Index of the note's frequency among those given to [`spectrum`].
+    pub note: usize,
+    /// 1 for the fundamental, 2 for the octave above it and so on.
+    pub harmonic: u32,
+}
+
+/// The first `n_harmonics` harmonics of each of `frequencies`, in Hz, as
+/// whole multiples of it, from the lowest partial up.
+pub fn spectrum(frequencies: &[f64], n_harmonics: u32) -> Vec<Partial> {
+    let mut partials: Vec<Partial> = frequencies
+        .iter()
+        .enumerate()
+        .flat_map(|(note, &hz)| {
+            (1..=n_harmonics).map(move |harmonic| Partial {
+                frequency: hz * harmonic as f64,
+                note,
+                harmonic,
+            })
+        })
+        .collect();
+    partials.sort_by(|a, b| a.frequency.total_cmp(&b.frequency));
+    partials
+}
+
+/// Width in Hz of the critical band around `hz`, within which two tones
+/// interfere, by the formula of Zwicker and Terhardt.
+pub fn critical_bandwidth(hz: f64) -> f64 {
+    25.0 + 75.0 * (1.0 + 1.4 * (hz / 1000.0).powi(2)).powf(0.69)
+}
+
+/// Indices into `partials`, sorted by frequency as [`spectrum`] gives them,
+/// of the pairs of partials of different notes that sound rough: apart by
+/// less than [`ROUGH_FRACTION`] of the critical band at their mean, but by
+/// more than [`COINCIDENT_HZ`].
+pub fn rough_pairs(partials: &[Partial]) -> Vec<(usize, usize)> {
+    let mut pairs = Vec::new();
+    for (i, a) in partials.iter().enumerate() {
+        for (j, b) in partials.iter().enumerate().skip(i + 1) {
+            let apart = b.frequency - a.frequency;
+            let mean = (a.frequency + b.frequency) / 2.0;
+            if apart >= ROUGH_FRACTION * critical_bandwidth(mean) {
+                break;
+            }
+            if a.note != b.note && apart > COINCIDENT_HZ {
+                pairs.push((i, j));
+            }
+        }
+    }
+    pairs
+}
+
+/// Chords that could stand in for `chord` in `scale`: the diatonic chords
+/// that share a note with it, then for each the dominant seventh a fifth
+/// above it and that dominant's tritone substitution. Ranked by
+/// [`chord_similarity`] to `chord`, without `chord` itself or repeats.
+pub fn reharmonization(chord: &Chord, scale: &Scale) -> Vec<Chord> {
+    let diatonic = diatonic_chords(scale)
+        .into_iter()
+        .filter(|other| chord_similarity(chord, other) > 0.0);
+    let candidates = diatonic.flat_map(|other| {
+        let secondary = Chord::new((other.root + 7) % 12, ChordQuality::Dominant7);
+        let tritone = Chord::new((other.root + 1) % 12, ChordQuality::Dominant7);
+        [other, secondary, tritone]
+    });
+    let same_notes = |a: &Chord, b: &Chord| chord_similarity(a, b) == 1.0;
+    let mut unique: Vec<Chord> = Vec::new();
+    for candidate in candidates {
+        if !same_notes(&candidate, chord) && !unique.iter().any(|c| same_notes(c, &candidate)) {
+            unique.push(candidate);
+        }
+    }
+    // stable, so ties keep diatonic chords before their dominants
+    unique.sort_by(|a, b| chord_similarity(chord, b).total_cmp(&chord_similarity(chord, a)));
+    unique
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn pitch_names_follow_the_key_signature() {
+        assert_eq!(pitch_name(10, AccidentalPreference::KeyOf(5)), "Bb");
+        assert_eq!(pitch_name(10, AccidentalPreference::KeyOf(7)), "A#");
+        assert_eq!(pitch_name(6, AccidentalPreference::Sharps), "F#");
+        assert_eq!(pitch_name(6, AccidentalPreference::Flats), "Gb");
+        assert_eq!(pitch_name(1, AccidentalPreference::BothSharps), "C#/Db");
+        assert_eq!(pitch_name(1, AccidentalPreference::BothFlats), "Db/C#");
+        assert_eq!(pitch_name(2, AccidentalPreference::BothFlats), "D");
+    }
+
+    #[test]
+    fn pitch_names_agree_with_note_names() {
+        // B is C♭ in G♭ major, and E♯ in F♯ major
+        assert_eq!(pitch_name(11, AccidentalPreference::KeyOf(6)), "Cb");
+        assert_eq!(pitch_name(5, AccidentalPreference::KeyOf(6)), "F");
+        for key in 0..12 {
+            let preference = AccidentalPreference::KeyOf(key);
+            for pc in 0..12 {
+                let note = NoteName::new(pc, preference).to_string();
+                let ascii = note.replace('♯', "#").replace('♭', "b");
+                assert_eq!(pitch_name(pc, preference), ascii, "{pc} in {key}");
+            }
+        }
+    }
+
+    #[test]
+    fn ascii_pitch_names_localize() {
+        assert_eq!(NamingScheme::German.localize("Bb").to_string(), "B");
+        assert_eq!(NamingScheme::German.localize("A# Eb").to_string(), "Ais Es");
+        assert_eq!(NamingScheme::English.localize("Bb").to_string(), "B♭");
+        assert_eq!(NamingScheme::Solfege.localize("F#").to_string(), "Fa♯");
+    }
+
+    #[test]
+    fn german_b_is_b_flat_and_h_is_b() {
+        let parse = |s, scheme| parse_chord_symbol(s, scheme).map(|chord| chord.root);
+        assert_eq!(parse("B", NamingScheme::German), Some(10));
+        assert_eq!(parse("B", NamingScheme::English), Some(11));
+        assert_eq!(parse("B", NamingScheme::Solfege), Some(11));
+        for scheme in NamingScheme::ALL {
+            assert_eq!(parse("H", scheme), Some(11), "{scheme:?}");
+            assert_eq!(parse("Hm", scheme), Some(11), "{scheme:?}");
+        }
+
+        let german = |note| NamingScheme::German.note(note).to_string();
+        assert_eq!(german(NoteName::on_letter(6, 10)), "B");
+        assert_eq!(german(NoteName::on_letter(6, 11)), "H");
+        assert_eq!(german(NoteName::on_letter(6, 9)), "Heses");
+        assert_eq!(german(NoteName::on_letter(6, 0)), "His");
+        assert_eq!(NamingScheme::German.localize("Bbm/B").to_string(), "Bm/H");
+    }
+
+    #[test]
+    fn parses_chord_symbols_in_every_scheme() {
+        let chord = |root, quality| Some(Chord::new(root, quality));
+        for scheme in NamingScheme::ALL {
+            let parse = |s| parse_chord_symbol(s, scheme);
+            assert_eq!(parse("C♯m"), chord(1, ChordQuality::Minor), "{scheme:?}");
+            assert_eq!(parse("Cism"), chord(1, ChordQuality::Minor), "{scheme:?}");
+            assert_eq!(parse("Do#m"), chord(1, ChordQuality::Minor), "{scheme:?}");
+            assert_eq!(parse(" Re7 "), chord(2, ChordQuality::Dominant7));
+            assert_eq!(parse("Es"), chord(3, ChordQuality::Major), "{scheme:?}");
+            assert_eq!(parse("As7"), chord(8, ChordQuality::Dominant7));
+            assert_eq!(parse("Eses"), chord(2, ChordQuality::Major));
+            assert_eq!(parse("Sol"), chord(7, ChordQuality::Major));
+            assert_eq!(parse("Cmaj9"), None);
+            assert_eq!(parse("Xm"), None);
+        }
+    }
+
+    #[test]
+    fn note_names_read_back_in_their_scheme() {
+        for scheme in NamingScheme::ALL {
+            for letter in 0..7 {
+                for accidental in -2..=2 {
+                    for arrows in -1..=1 {
+                        let note = NoteName {
+                            letter,
+                            accidental,
+                            arrows,
+                        };
+                        let name = scheme.note(note).to_string();
+                        let read = scheme.parse_note(&name);
+                        assert_eq!(read, Some((note, "")), "{name} in {scheme:?}");
+                    }
+                }
+            }
+        }
+        // quarter tones stay behind the name of the letter
+        assert_eq!(NamingScheme::German.localize("C𝄲").to_string(), "C𝄲");
+        assert_eq!(NamingScheme::Solfege.localize("B𝄳").to_string(), "Si𝄳");
+    }
+
+    #[test]
+    fn a_flat_or_g_sharp_by_preference() {
+        let chord = Chord::new(8, ChordQuality::Minor);
+        let notes = |preference| {
+            let names = chord
+                .note_names(preference)
+                .into_iter()
+                .map(|note| note.to_string());
+            names.collect::<Vec<_>>().join(" ")
+        };
+        let cases = [
+            (AccidentalPreference::Sharps, "G#m", "G♯ B D♯"),
+            (AccidentalPreference::Flats, "Abm", "A♭ C♭ E♭"),
+            (AccidentalPreference::BothSharps, "G#/Abm", "G♯ B D♯"),
+            (AccidentalPreference::BothFlats, "Ab/G#m", "A♭ C♭ E♭"),
+            // the key overrides either way
+            (AccidentalPreference::KeyOf(4), "G#m", "G♯ B D♯"),
+            (AccidentalPreference::KeyOf(3), "Abm", "A♭ C♭ E♭"),
+        ];
+        for (preference, symbol, names) in cases {
+            assert_eq!(chord.symbol(preference), symbol, "{preference:?}");
+            assert_eq!(notes(preference), names, "{preference:?}");
+        }
+        // naturals are the same under every preference
+        let chord = Chord::new(9, ChordQuality::Minor);
+        assert_eq!(chord.symbol(AccidentalPreference::Flats), "Am");
+        assert_eq!(chord.symbol(AccidentalPreference::BothSharps), "Am");
+    }
+
+    #[test]
+    fn harmonic_distances_of_every_interval() {
+        // the simplest of the interval's ratio, that of its inversion, and
+        // the inversions of both
+        let expected = [
+            (1, 1),   // unison
+            (15, 8),  // minor second, as its inversion
+            (9, 5),   // major second, as the minor seventh
+            (5, 3),   // minor third, as its inversion
+            (5, 4),   // major third
+            (3, 2),   // fourth, as its inversion
+            (45, 32), // tritone
+            (3, 2),   // fifth
+            (5, 4),   // minor sixth, as its inversion
+            (5, 3),   // major sixth
+            (9, 5),   // minor seventh
+            (15, 8),  // major seventh
+        ];
+        for (interval, (p, q)) in expected.into_iter().enumerate() {
+            let tenney = ((p * q) as f64).log2();
+            for root in 0..12 {
+                let distance = harmonic_distance(root, (root + interval as u8) % 12);
+                assert!((distance - tenney).abs() < 1e-9, "{interval}: {distance}");
+            }
+        }
+        for (a, b) in (0..12).flat_map(|a| (0..12).map(move |b| (a, b))) {
+            assert_eq!(harmonic_distance(a, b), harmonic_distance(b, a), "{a} {b}");
+        }
+        assert!((harmonic_distance(0, 2) - 5.49).abs() < 0.01);
+        assert!((harmonic_distance(0, 10) - 5.49).abs() < 0.01);
+        assert!(harmonic_distance(0, 7) < harmonic_distance(0, 2));
+        assert!(harmonic_distance(0, 2) < harmonic_distance(0, 6));
+    }
+
+    /// Every way of leading the smaller chord into the larger one, as the
+    /// reference for [`least_motion`].
+    fn brute_force_motion(small: &[u8], large: &[u8]) -> u32 {
+        fn best(small: &[u8], rest: &[u8], large: &[u8], used: &mut Vec<bool>) -> u32 {
+            let Some((&note, rest)) = rest.split_first() else {
+                let left_over = large.iter().zip(used.iter()).filter(|(_, &used)| !used);
+                let doubled = left_over.map(|(&pc, _)| {
+                    let nearest = small.iter().map(|&n| pc_distance(pc, n)).min();
+                    nearest.unwrap_or(0)
+                });
+                return doubled.sum();
+            };
+            let mut min = u32::MAX;
+            for i in 0..large.len() {
+                if !used[i] {
+                    used[i] = true;
+                    min = min.min(pc_distance(note, large[i]) + best(small, rest, large, used));
+                    used[i] = false;
+                }
+            }
+            min
+        }
+        best(small, small, large, &mut vec![false; large.len()])
+    }
+
+    #[test]
+    fn voice_leading_distances() {
+        use ChordQuality::*;
+        let distance = |a, b| voice_leading_distance(&a, &b);
+        assert_eq!(distance(Chord::new(0, Major), Chord::new(0, Major)), 0);
+        // G to A
+        assert_eq!(distance(Chord::new(0, Major), Chord::new(9, Minor)), 2);
+        // B to C, F to E, and D doubled from C
+        assert_eq!(distance(Chord::new(7, Dominant7), Chord::new(0, Major)), 4);
+        assert_eq!(distance(Chord::new(0, Major), Chord::new(7, Dominant7)), 4);
+    }
+
+    #[test]
+    fn stepwise_triads_do_not_move_in_parallel() {
+        use ChordQuality::*;
+        let key = ScaleMode::Ionian.scale(0);
+        for (a, b) in [(0, 2), (5, 7), (7, 9), (2, 0), (10, 0)] {
+            let chords = [Chord::new(a, Major), Chord::new(b, Major)];
+            let analysis = analyze_progression(&chords, &key);
+            assert_eq!(
+                analysis[1].voice_leading_issues,
+                Vec::<String>::new(),
+                "{a} to {b}"
+            );
+        }
+        // C E G moves up to D F A with the least motion, in fifths
+        let chords = [Chord::new(0, Major), Chord::new(2, Minor)];
+        assert_eq!(
+            analyze_progression(&chords, &key)[1].voice_leading_issues,
+            ["parallel fifths in voices 1 and 3"]
+        );
+    }
+
+    #[test]
+    fn analyzes_a_cadence() {
+        use ChordQuality::*;
+        let key = ScaleMode::Ionian.scale(0);
+        let chords = [
+            Chord::new(0, Major),
+            Chord::new(5, Major),
+            Chord::new(2, Major),
+            Chord::new(7, Dominant7),
+            Chord::new(0, Major),
+        ];
+        let analysis = analyze_progression(&chords, &key);
+        let numerals = analysis.iter().map(|a| a.roman_numeral.as_str());
+        assert_eq!(numerals.collect::<Vec<_>>(), ["I", "IV", "V/V", "V7", "I"]);
+        use HarmonicFunction::*;
+        let functions = analysis.iter().map(|a| a.function);
+        assert_eq!(
+            functions.collect::<Vec<_>>(),
+            [Tonic, PreDominant, Secondary, Dominant, Tonic]
+        );
+        assert_eq!(analysis[3].tendency, TendencyDirection::Up);
+        assert!(analysis.iter().all(|a| a.voice_leading_issues.is_empty()));
+    }
+
+    #[test]
+    fn finds_voice_leading_issues() {
+        // C and G up to D and A
+        assert_eq!(
+            voice_leading_issues(&[(60, 62), (67, 69)]),
+            ["parallel fifths in voices 1 and 2"]
+        );
+        assert_eq!(
+            voice_leading_issues(&[(48, 50), (60, 62)]),
+            ["parallel octaves in voices 1 and 2"]
+        );
+        // in contrary motion, fifths are not parallel
+        assert!(voice_leading_issues(&[(60, 55), (67, 74)]).is_empty());
+        assert_eq!(
+            voice_leading_issues(&[(60, 65), (64, 62)]),
+            ["voices 1 and 2 cross"]
+        );
+        assert_eq!(
+            voice_leading_issues(&[(60, 65), (64, 67)]),
+            ["voice 1 overlaps where voice 2 was"]
+        );
+        assert_eq!(
+            voice_leading_issues(&[(60, 60), (64, 59)]),
+            ["voices 1 and 2 cross"]
+        );
+        assert_eq!(
+            voice_leading_issues(&[(60, 55), (64, 57)]),
+            ["voice 2 overlaps where voice 1 was"]
+        );
+    }
+
+    #[test]
+    fn respells_chords_by_degree() {
+        use ChordQuality::*;
+        let names = |chord: &Chord| {
+            let names = chord.spelling.iter().flatten().map(|note| note.to_string());
+            names.collect::<Vec<_>>()
+        };
+        // the minor v of D♭ major keeps C♭ on its third
+        let chord = enharmonic_respelling(&Chord::new(8, Minor), AccidentalPreference::KeyOf(1));
+        assert_eq!(names(&chord), ["A♭", "C♭", "E♭"]);
+        assert_eq!((chord.root, &chord.intervals[..]), (8, &[0, 3, 7][..]));
+        let chord = enharmonic_respelling(&Chord::new(8, Major), AccidentalPreference::Sharps);
+        assert_eq!(names(&chord), ["G♯", "B♯", "D♯"]);
+        let chord = Chord::new(6, Dominant7);
+        assert_eq!(
+            names(&enharmonic_respelling(&chord, AccidentalPreference::Sharps)),
+            ["F♯", "A♯", "C♯", "E"]
+        );
+        for preference in [AccidentalPreference::Flats, AccidentalPreference::KeyOf(11)] {
+            let respelled = enharmonic_respelling(&chord, preference);
+            let names = respelled.note_names(preference);
+            assert!(names.iter().map(NoteName::pc).eq(chord.notes()));
+            assert_eq!(
+                chord.note_names(preference),
+                respelled.note_names(preference)
+            );
+            // a respelled chord keeps its spelling
+            let other = respelled.note_names(AccidentalPreference::Sharps);
+            assert_eq!(other, respelled.note_names(preference));
+        }
+    }
+
+    #[test]
+    fn approach_notes_lead_up_to_chord_tones() {
+        let c_major = ScaleMode::Ionian.scale(0);
+        let c7 = Chord::new(0, ChordQuality::Dominant7);
+        // B to C, D♯ and D to E, F♯ and F to G, A to B♭
+        assert_eq!(approach_notes(&c7, &c_major), [11, 3, 2, 6, 5, 9]);
+        let chromatic = approach_notes(&c7, &c_major)
+            .into_iter()
+            .filter(|&pc| !c_major.contains(pc));
+        assert_eq!(chromatic.collect::<Vec<_>>(), [3, 6]);
+    }
+
+    #[test]
+    fn accidentals_are_quarter_tones_apart() {
+        let cents = Accidental::ALL.map(|accidental| accidental.semitones_cents());
+        assert_eq!(
+            cents,
+            [-200.0, -150.0, -100.0, -50.0, 0.0, 50.0, 100.0, 150.0, 200.0]
+        );
+        let glyphs = Accidental::ALL.map(|accidental| accidental.glyph());
+        assert_eq!(glyphs, ["𝄫", "𝄳♭", "♭", "𝄳", "♮", "𝄲", "♯", "𝄲♯", "𝄪"]);
+    }
+
+    #[test]
+    fn chords_to_lily() {
+        use ChordQuality::*;
+        let c_major = ScaleMode::Ionian.scale(0);
+        assert_eq!(
+            chord_to_lily(&Chord::new(0, Major), &c_major, 4),
+            "<c e g>4"
+        );
+        assert_eq!(
+            chord_to_lily(&Chord::new(0, Dominant7), &c_major, 4),
+            "<c e g bes>4"
+        );
+        assert_eq!(
+            chord_to_lily(&Chord::new(10, Dominant7), &c_major, 2),
+            "<bes d' f' aes'>2"
+        );
+        assert_eq!(
+            chord_to_lily(&Chord::new(8, Major), &c_major, 1),
+            "<aes c' ees'>1"
+        );
+        // spelled as the chord is shown, and not as G♭7
+        assert_eq!(
+            chord_to_lily(&Chord::new(6, Dominant7), &c_major, 4),
+            "<fis ais cis' e'>4"
+        );
+        // the third of A♭m is C♭
+        let a_flat_minor = ScaleMode::Aeolian.scale(8);
+        assert_eq!(
+            chord_to_lily(&Chord::new(8, Minor), &a_flat_minor, 8),
+            "<aes ces' ees'>8"
+        );
+        let g_major = ScaleMode::Ionian.scale(7);
+        assert_eq!(
+            chord_to_lily(&Chord::new(7, Major), &g_major, 4),
+            "<g b d'>4"
+        );
+    }
+
+    #[test]
+    fn progressions_to_lily() {
+        use ChordQuality::*;
+        let progression = [
+            (Chord::new(2, Minor), 2),
+            (Chord::new(7, Dominant7), 2),
+            (Chord::new(0, Major), 1),
+        ];
+        assert_eq!(
+            progression_to_lily(&progression, &ScaleMode::Ionian.scale(0)),
+            "<d f a>2 <g b d' f'>2 <c e g>1"
+        );
+    }
+
+    #[test]
+    fn spectra_are_whole_multiples_from_the_lowest_up() {
+        let partials = spectrum(&[200.0, 300.0], 3);
+        let found: Vec<_> = partials
+            .iter()
+            .map(|p| (p.frequency, p.note, p.harmonic))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (200.0, 0, 1),
+                (300.0, 1, 1),
+                (400.0, 0, 2),
+                (600.0, 0, 3),
+                (600.0, 1, 2),
+                (900.0, 1, 3)
+            ]
+        );
+        assert!(spectrum(&[], 12).is_empty());
+        assert!(spectrum(&[440.0], 0).is_empty());
+    }
+
+    #[test]
+    fn critical_bands_widen_with_frequency() {
+        assert!((critical_bandwidth(0.0) - 100.0).abs() < 1e-9);
+        assert!((critical_bandwidth(1000.0) - 162.0).abs() < 1.0);
+        assert!(critical_bandwidth(4000.0) > 600.0);
+    }
+
+    #[test]
+    fn just_triads_beat_less_than_tempered_ones() {
+        let c = 261.63;
+        let tempered = spectrum(&[c, 329.63, 392.0], 12);
+        let just = spectrum(&[c, c * 5.0 / 4.0, c * 3.0 / 2.0], 12);
+        let rough = |partials: &[Partial]| {
+            let pairs = rough_pairs(partials).into_iter();
+            let pairs = pairs.map(|(a, b)| (partials[a], partials[b]));
+            pairs
+                .map(|(a, b)| ((a.note, a.harmonic), (b.note, b.harmonic)))
+                .collect::<Vec<_>>()
+        };
+        let (tempered, just) = (rough(&tempered), rough(&just));
+        assert!(just.len() < tempered.len(), "{just:?} {tempered:?}");
+        // the fifth harmonic of C and the fourth of E, ten Hz apart when
+        // tempered and one partial when just
+        assert!(tempered.contains(&((0, 5), (1, 4))), "{tempered:?}");
+        assert!(!just
+            .iter()
+            .any(|&pair| pair == ((0, 5), (1, 4)) || pair == ((1, 4), (0, 5))));
+        // partials of one note never beat with each other
+        assert!(just.iter().chain(&tempered).all(|(a, b)| a.0 != b.0));
+    }
+
+    fn preference() -> impl Strategy<Value = AccidentalPreference> {
+        prop_oneof![
+            Just(AccidentalPreference::Sharps),
+            Just(AccidentalPreference::Flats),
+            (0..12u8).prop_map(AccidentalPreference::KeyOf),
+        ]
+    }
+
+    fn tone_row() -> impl Strategy<Value = ToneRow> {
+        Just((0..12).collect::<Vec<u8>>())
+            .prop_shuffle()
+            .prop_map(|notes| ToneRow::new(notes.try_into().unwrap()).unwrap())
+    }
+
+    proptest! {
+        #[test]
+        fn intervals_between_pitch_classes_add_up(a in 0..12u8, b in 0..12u8) {
+            let up = interval_between(a, b);
+            prop_assert!((0..12).contains(&up));
+            prop_assert_eq!((a as i32 + up) % 12, b as i32);
+            prop_assert_eq!((up + interval_between(b, a)) % 12, 0);
+
+            let signed = interval_between_signed(a, b);
+            prop_assert!((-5..=6).contains(&signed));
+            prop_assert_eq!((a as i32 + signed).rem_euclid(12), b as i32);
+        }
+
+        #[test]
+        fn pitches_round_trip(midi in 0..128i32, preference in preference()) {
+            let pitch = Pitch::from_midi(midi, preference);
+            prop_assert_eq!(pitch.to_midi(), midi);
+            prop_assert_eq!(pitch.to_string().parse::<Pitch>(), Ok(pitch));
+        }
+
+        #[test]
+        fn intervals_survive_octave_transposition(
+            lower in 0..100i32,
+            semitones in 0..24i32,
+            octaves in -2..3i32,
+            preference in preference(),
+        ) {
+            let (a, b) = (
+                Pitch::from_midi(lower, preference),
+                Pitch::from_midi(lower + semitones, preference),
+            );
+            let moved = |pitch: Pitch| Pitch {
+                octave: pitch.octave + octaves,
+                ..pitch
+            };
+            let interval = Interval::between(a, b);
+            prop_assert_eq!(Interval::between(moved(a), moved(b)), interval);
+            // one spelling never puts a higher pitch on a lower letter
+            prop_assert!(interval.is_some());
+        }
+
+        #[test]
+        fn chords_transpose_with_their_root(
+            root in 0..12u8,
+            n in 0..12u8,
+            quality in prop::sample::select(&ChordQuality::ALL[..]),
+        ) {
+            let chord = Chord::new(root, quality);
+            let moved = Chord::new(root + n, quality);
+            let notes = chord.notes().map(|pc| (pc + n) % 12);
+            prop_assert_eq!(moved.notes().collect::<Vec<_>>(), notes.collect::<Vec<_>>());
+            prop_assert_eq!(moved.quality(), Some(quality));
+        }
+
+        #[test]
+        fn least_motion_matches_every_permutation(
+            small in prop::collection::vec(0..12u8, 0..5),
+            extra in prop::collection::vec(0..12u8, 0..3),
+            shuffle in any::<prop::sample::Index>(),
+        ) {
+            let mut large = small.iter().map(|pc| (pc + shuffle.index(12) as u8) % 12).collect::<Vec<_>>();
+            large.extend(extra);
+            prop_assert_eq!(least_motion(&small, &large), brute_force_motion(&small, &large));
+            for pairs in least_motion_leadings(&small, &large, 8) {
+                let motion = pairs.iter().map(|&(i, j)| pc_distance(small[i], large[j]));
+                prop_assert_eq!(motion.sum::<u32>(), least_motion(&small, &large));
+            }
+        }
+
+        #[test]
+        fn tone_rows_transpose_back(row in tone_row(), a in 0..12u8, b in 0..12u8) {
+            prop_assert_eq!(row.transpose(a).transpose(12 - a), row);
+            prop_assert_eq!(row.transpose(a).transpose(b), row.transpose((a + b) % 12));
+            prop_assert_eq!(row.inversion().inversion(), row);
+            prop_assert_eq!(row.retrograde().retrograde(), row);
+        }
+    }
+}
@@ -0,0 +1,1006 @@
+use std::{fmt, fs, io, path::PathBuf};
+
+use crate::{
+    ratio::Ratio,
+    session::config_dir,
+    theory::{pitch_name, AccidentalPreference, NoteName},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Degree {
+    pub name: String,
+    /// Offset from the reference pitch.
+    pub cents: f64,
+    /// The just ratio above the tuning's first degree that `cents` was
+    /// given as, if it was.
+    pub ratio: Option<Ratio>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tuning {
+    pub name: String,
+    /// Frequency of the pitch at 0 cents.
+    pub reference_hz: f64,
+    pub degrees: Vec<Degree>,
+}
+
+impl Default for Tuning {
+    /// The natural notes from C5 to B5 in 12-TET, relative to A4 = 440 Hz.
+    fn default() -> Self {
+        let degrees = [
+            (0, 300.0),
+            (2, 500.0),
+            (4, 700.0),
+            (5, 800.0),
+            (7, 1000.0),
+            (9, 1200.0),
+            (11, 1400.0),
+        ];
+        Self {
+            name: "12-TET".to_string(),
+            reference_hz: 440.0,
+            degrees: degrees
+                .into_iter()
+                .map(|(pc, cents)| Degree {
+                    name: pitch_name(pc, AccidentalPreference::Sharps),
+                    cents,
+                    ratio: None,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Tuning {
+    /// One octave from C5 of `divisions` equal steps, relative to A4 = 440 Hz
+    /// like the default tuning, with each degree named by [`degree_name`].
+    pub fn edo(divisions: u32) -> Self {
+        let step = 1200.0 / divisions.max(1) as f64;
+        Self {
+            name: format!("{divisions}-EDO"),
+            reference_hz: 440.0,
+            degrees: (0..divisions)
+                .map(|i| Degree {
+                    name: degree_name(i as f64 * step, Notation::Arrows),
+                    cents: 300.0 + i as f64 * step,
+                    ratio: None,
+                })
+                .collect(),
+        }
+    }
+
+    /// Frequency ratio of `cents` above the reference pitch.
+    pub fn ratio(cents: f64) -> f64 {
+        2f64.powf(cents / 1200.0)
+    }
+
+    pub fn frequency(&self, cents: f64) -> f64 {
+        self.reference_hz * Self::ratio(cents)
+    }
+
+    /// How many cents the degree nearest pitch class `pc` of 12-TET is off
+    /// it, with C at 300 cents above the reference, or `None` if no degree
+    /// is within a quarter tone of it.
+    pub fn cents_off(&self, pc: u8) -> Option<f64> {
+        let off = |degree: &Degree| {
+            let cents = degree.cents - 300.0 - pc as f64 * 100.0;
+            (cents + 600.0).rem_euclid(1200.0) - 600.0
+        };
+        let nearest = self
+            .degrees
+            .iter()
+            .map(off)
+            .min_by(|a, b| a.abs().total_cmp(&b.abs()));
+        nearest.filter(|off| off.abs() < 50.0)
+    }
+
+    /// The number of equal steps the octave is divided into, if the degrees
+    /// are that many steps a cent or less apart from one another.
+    pub fn divisions(&self) -> Option<u32> {
+        let divisions = self.degrees.len() as u32;
+        let step = 1200.0 / divisions as f64;
+        let mut steps = self.degrees.windows(2);
+        let equal = steps.all(|pair| (pair[1].cents - pair[0].cents - step).abs() <= 1.0);
+        (divisions > 0 && equal).then_some(divisions)
+    }
+
+    /// The note of the first degree, the 1/1 that ratios are given above,
+    /// or C if it is not named after a note.
+    pub fn one(&self) -> NoteName {
+        let name = self
+            .degrees
+            .first()
+            .map_or("C", |degree| degree.name.as_str());
+        name.parse().unwrap_or(NoteName {
+            letter: 0,
+            accidental: 0,
+            arrows: 0,
+        })
+    }
+
+    /// The tuning as a Scala .scl file, with the pitches above the first
+    /// degree as ratios where they were given as ratios and otherwise in
+    /// cents, and an octave period.
+    pub fn to_scala(&self) -> String {
+        let one = self.degrees.first().map_or(0.0, |degree| degree.cents);
+        let mut scala = format!(
+            "! {}.scl\n!\n{}\n {}\n!\n",
+            self.name,
+            self.name,
+            self.degrees.len().max(1)
+        );
+        for degree in self.degrees.iter().skip(1) {
+            match degree.ratio {
+                Some(ratio) => scala.push_str(&format!(" {ratio}\n")),
+                None => scala.push_str(&format!(" {:.5}\n", degree.cents - one)),
+            }
+        }
+        scala.push_str(" 2/1\n");
+        scala
+    }
+
+    /// Reads a Scala .scl file into a tuning called `name`, starting from C5
+    /// like the default tuning. The implied 1/1 becomes the first degree and
+    /// the last pitch, the period, is left out, with each degree named by
+    /// [`degree_name`].
+    pub fn from_scala(name: &str, text: &str) -> Result<Self, ScalaError> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.starts_with('!'));
+        // the description comes first, and may be empty
+        lines.next().ok_or(ScalaError::MissingCount)?;
+        let (line, count) = lines.next().ok_or(ScalaError::MissingCount)?;
+        let count: usize = count
+            .split_whitespace()
+            .next()
+            .and_then(|count| count.parse().ok())
+            .ok_or(ScalaError::InvalidCount(line))?;
+
+        let mut degrees = vec![Degree {
+            name: degree_name(0.0, Notation::Arrows),
+            cents: 300.0,
+            ratio: None,
+        }];
+        for _ in 1..count {
+            let (line, pitch) = lines.next().ok_or(ScalaError::TooFewPitches(count))?;
+            // anything after the pitch is a comment
+            let pitch = pitch.split_whitespace().next().unwrap_or_default();
+            let (cents, ratio) = if pitch.contains('.') {
+                let cents = pitch.parse().map_err(|_| ScalaError::InvalidPitch(line))?;
+                (cents, None)
+            } else {
+                let ratio: Ratio = pitch.parse().map_err(|_| ScalaError::InvalidPitch(line))?;
+                (ratio.cents(), Some(ratio))
+            };
+            degrees.push(Degree {
+                name: degree_name(cents, Notation::Arrows),
+                cents: 300.0 + cents,
+                ratio,
+            });
+        }
+        if count > 0 && lines.next().is_none() {
+            return Err(ScalaError::TooFewPitches(count));
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            reference_hz: 440.0,
+            degrees,
+        })
+    }
+}
+
+/// Why a Scala file could not be read, with the line it was on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScalaError {
+    MissingCount,
+    InvalidCount(usize),
+    InvalidPitch(usize),
+    /// The file ended before the number of pitches it gave.
+    TooFewPitches(usize),
+}
+
+impl fmt::Display for ScalaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScalaError::MissingCount => write!(f, "missing the number of pitches"),
+            ScalaError::InvalidCount(line) => {
+                write!(f, "line {line}: not a number of pitches")
+            }
+            ScalaError::InvalidPitch(line) => {
+                write!(f, "line {line}: not a ratio or cents")
+            }
+            ScalaError::TooFewPitches(count) => {
+                write!(f, "ends before all {count} pitches")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ScalaError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RenameError {
+    Empty,
+    Taken,
+}
+
+impl fmt::Display for RenameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RenameError::Empty => write!(f, "tuning name cannot be empty"),
+            RenameError::Taken => write!(f, "a tuning with that name already exists"),
+        }
+    }
+}
+
+impl std::error::Error for RenameError {}
+
+/// How the pitches of tuning degrees between the semitones of 12-TET are
+/// written.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Notation {
+    /// Quarter tone accidentals, and arrows for steps of 72-EDO.
+    #[default]
+    Arrows,
+    /// The nearest semitone with a Sagittal accidental from [`SAGITTAL`].
+    Sagittal,
+    /// Helmholtz–Ellis accidentals for degrees given as ratios, by
+    /// [`heji_name`], and the cents off the nearest semitone for others.
+    Heji,
+}
+
+impl Notation {
+    pub const ALL: [Notation; 3] = [Notation::Arrows, Notation::Sagittal, Notation::Heji];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Notation::Arrows => "Arrows",
+            Notation::Sagittal => "Sagittal (Athenian)",
+            Notation::Heji => "HEJI",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|notation| notation.name() == name)
+    }
+}
+
+/// Single-shaft Sagittal accidentals of the Athenian subset, as the cents
+/// they raise a note by and the characters of their raising and lowering
+/// forms. Unicode has no Sagittal characters, so these are in the Private
+/// Use Area, drawn by [`Font::with_sagittal_accidentals`].
+///
+/// [`Font::with_sagittal_accidentals`]: crate::font::Font::with_sagittal_accidentals
+pub const SAGITTAL: [(f64, char, char); 6] = [
+    // |( 5:7 kleisma
+    (5.8, '\u{E300}', '\u{E301}'),
+    // /| 5 comma
+    (21.5, '\u{E302}', '\u{E303}'),
+    // |) 7 comma
+    (27.3, '\u{E304}', '\u{E305}'),
+    // |\
+    (33.1, '\u{E306}', '\u{E307}'),
+    // /|) 35 medium diesis
+    (48.8, '\u{E308}', '\u{E309}'),
+    // /|\ 11 medium diesis
+    (53.3, '\u{E30A}', '\u{E30B}'),
+];
+
+/// The Sagittal accidental closest to `cents` up or down, and how many
+/// cents it misses by. Below half the smallest accidental this is none.
+pub fn sagittal_accidental(cents: f64) -> (Option<char>, f64) {
+    let mut best = (None, cents.abs());
+    for (size, up, down) in SAGITTAL {
+        let (symbol, size) = if cents < 0.0 {
+            (down, -size)
+        } else {
+            (up, size)
+        };
+        if (cents - size).abs() < best.1 {
+            best = (Some(symbol), (cents - size).abs());
+        }
+    }
+    best
+}
+
+/// Name of the pitch `cents` above C in `notation`.
+///
+/// With arrows this is to the nearest step of 72-EDO, a sixth of a
+/// semitone: the nearest semitone with an arrow up or down for each step
+/// away from it, or a half sharp for the quarter tone halfway between two
+/// semitones. So 50 cents is C𝄲, 67 cents C♯↓↓ and 117 C♯↑.
+///
+/// With Sagittal it is the semitone below or above, whichever leaves the
+/// closer accidental of [`SAGITTAL`] for the cents left over, and that
+/// accidental. So 22 cents is C with `/|` for a 5 comma up, and 80 cents C♯
+/// with `\!` for a 5 comma down.
+pub fn degree_name(cents: f64, notation: Notation) -> String {
+    if notation == Notation::Heji {
+        let semitone = (cents / 100.0).round() as i32;
+        let note = NoteName::new(semitone.rem_euclid(12) as u8, AccidentalPreference::Sharps);
+        return cents_name(note, cents);
+    }
+    if notation == Notation::Sagittal {
+        let below = (cents / 100.0).floor();
+        let candidates = [below, below + 1.0].map(|semitone| {
+            let (symbol, error) = sagittal_accidental(cents - semitone * 100.0);
+            (semitone, symbol, error)
+        });
+        // the lower semitone wins ties
+        let (semitone, symbol, _) = candidates
+            .into_iter()
+            .min_by(|a, b| a.2.total_cmp(&b.2))
+            .unwrap();
+        let pc = (semitone as i32).rem_euclid(12) as u8;
+        let mut name = NoteName::new(pc, AccidentalPreference::Sharps).to_string();
+        name.extend(symbol);
+        return name;
+    }
+
+    let step = (cents * 72.0 / 1200.0).round() as i32;
+    let (semitone, sixths) = (step.div_euclid(6), step.rem_euclid(6));
+    let pc = semitone.rem_euclid(12) as u8;
+    match sixths {
+        3 => {
+            let below = NoteName::new(pc, AccidentalPreference::Sharps);
+            let natural = NoteName {
+                accidental: 0,
+                ..below
+            };
+            let half = if below.accidental == 0 {
+                "𝄲"
+            } else {
+                "𝄲♯"
+            };
+            format!("{natural}{half}")
+        }
+        _ => {
+            let (pc, arrows) = match sixths {
+                0..3 => (pc, sixths as i8),
+                _ => ((pc + 1) % 12, sixths as i8 - 6),
+            };
+            let note = NoteName::new(pc, AccidentalPreference::Sharps);
+            NoteName { arrows, ..note }.to_string()
+        }
+    }
+}
+
+/// `note` with the Sagittal accidental for how far `cents` above C is from
+/// the note in 12-TET, keeping the note's spelling, or [`degree_name`] when
+/// that is further than the largest accidental of [`SAGITTAL`].
+pub fn sagittal_name(note: NoteName, cents: f64) -> String {
+    let deviation = (cents - note.pc() as f64 * 100.0 + 600.0).rem_euclid(1200.0) - 600.0;
+    if deviation.abs() > SAGITTAL[SAGITTAL.len() - 1].0 {
+        return degree_name(cents, Notation::Sagittal);
+    }
+    let mut name = NoteName { arrows: 0, ..note }.to_string();
+    name.extend(sagittal_accidental(deviation).0);
+    name
+}
+
+/// Characters of the Helmholtz–Ellis septimal comma accidentals, raising
+/// and lowering, in the Private Use Area as Unicode has none.
+pub const SEPTIMAL_COMMA: (char, char) = ('\u{E310}', '\u{E311}');
+
+/// Name of the pitch `ratio` above `one` in the Helmholtz–Ellis notation,
+/// or `None` if the ratio has a prime factor above 11. The letter and
+/// accidental are those of the Pythagorean ratio the ratio differs from by
+/// commas, followed by an arrow down for every syntonic comma it is below
+/// it, a septimal comma down from [`SEPTIMAL_COMMA`] for every septimal
+/// comma and a half sharp for every undecimal quarter tone up. So above C,
+/// 5/4 is E↓, 81/64 is E and 7/4 is B♭ with a septimal comma down.
+pub fn heji_name(ratio: Ratio, one: NoteName) -> Option<String> {
+    let [_, three, five, seven, eleven] = ratio.monzo()?;
+    // 5/4 is 81/64 a syntonic comma down, 7/4 is 16/9 a septimal comma down
+    // and 11/8 is 4/3 an undecimal quarter tone up
+    let fifths = three + 4 * five - 2 * seven - eleven;
+    let letter = (one.letter as i32 + 4 * fifths).rem_euclid(7) as u8;
+    let pc = (one.pc() as i32 + 7 * fifths).rem_euclid(12) as u8;
+    let mut name = NoteName::on_letter(letter, pc).to_string();
+
+    let quarter = if eleven > 0 { '𝄲' } else { '𝄳' };
+    let syntonic = if five > 0 { '↓' } else { '↑' };
+    let septimal = if seven > 0 {
+        SEPTIMAL_COMMA.1
+    } else {
+        SEPTIMAL_COMMA.0
+    };
+    for (mark, count) in [(quarter, eleven), (syntonic, five), (septimal, seven)] {
+        name.extend(std::iter::repeat_n(mark, count.unsigned_abs() as usize));
+    }
+    Some(name)
+}
+
+/// `note` with the cents that `cents` above C is off the note in 12-TET
+/// written in superscript, as in E⁻¹⁴, or just `note` when it is on it.
+pub fn cents_name(note: NoteName, cents: f64) -> String {
+    let off = (cents - note.pc() as f64 * 100.0 + 600.0).rem_euclid(1200.0) - 600.0;
+    let mut name = NoteName { arrows: 0, ..note }.to_string();
+    name.push_str(&superscript_cents(off));
+    name
+}
+
+/// `cents` rounded and written in superscript with its sign, as in ⁻¹⁴, or
+/// nothing when it rounds to zero.
+pub fn superscript_cents(cents: f64) -> String {
+    let cents = cents.round() as i64;
+    if cents == 0 {
+        return String::new();
+    }
+    let sign = if cents > 0 { '⁺' } else { '⁻' };
+    let digits = cents.unsigned_abs().to_string();
+    let digits = digits.chars().map(|digit| {
+        let digit = digit.to_digit(10).unwrap() as usize;
+        ['⁰', '¹', '²', '³', '⁴', '⁵', '⁶', '⁷', '⁸', '⁹'][digit]
+    });
+    std::iter::once(sign).chain(digits).collect()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub tuning: Tuning,
+    /// Whether the tuning changed since the library was last saved.
+    pub modified: bool,
+}
+
+/// Named tunings, one of which is active. There is always at least one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TuningLibrary {
+    entries: Vec<Entry>,
+    active: usize,
+}
+
+impl Default for TuningLibrary {
+    fn default() -> Self {
+        Self::new(vec![Tuning::default()])
+    }
+}
+
+impl TuningLibrary {
+    /// Creates a library with the first tuning active, or the default
+    /// library if `tunings` is empty.
+    pub fn new(tunings: Vec<Tuning>) -> Self {
+        if tunings.is_empty() {
+            return Self::default();
+        }
+        Self {
+            entries: tunings
+                .into_iter()
+                .map(|tuning| Entry {
+                    tuning,
+                    modified: false,
+                })
+                .collect(),
+            active: 0,
+        }
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    pub fn active(&self) -> &Tuning {
+        &self.entries[self.active].tuning
+    }
+
+    pub fn active_index(&self) -> usize {
+        self.active
+    }
+
+    pub fn active_entry(&self) -> &Entry {
+        &self.entries[self.active]
+    }
+
+    /// Name of the active tuning with an asterisk if it has unsaved changes.
+    pub fn active_label(&self) -> String {
+        let entry = self.active_entry();
+        if entry.modified {
+            format!("{}*", entry.tuning.name)
+        } else {
+            entry.tuning.name.clone()
+        }
+    }
+
+    pub fn select(&mut self, index: usize) {
+        self.active = index.min(self.entries.len() - 1);
+    }
+
+    /// Selects the tuning `offset` places away, wrapping around.
+    pub fn cycle(&mut self, offset: isize) {
+        let len = self.entries.len() as isize;
+        self.active = (self.active as isize + offset).rem_euclid(len) as usize;
+    }
+
+    /// Changes the active tuning, marking it as modified.
+    pub fn edit(&mut self, f: impl FnOnce(&mut Tuning)) {
+        let entry = &mut self.entries[self.active];
+        f(&mut entry.tuning);
+        entry.modified = true;
+    }
+
+    /// Adds `tuning` after the others and selects it, renaming it if the
+    /// name is taken.
+    pub fn add(&mut self, mut tuning: Tuning) {
+        if self
+            .entries
+            .iter()
+            .any(|entry| entry.tuning.name == tuning.name)
+        {
+            tuning.name = self.unique_name(&tuning.name);
+        }
+        self.entries.push(Entry {
+            tuning,
+            modified: true,
+        });
+        self.active = self.entries.len() - 1;
+    }
+
+    /// Copies the active tuning under a new name and selects the copy.
+    pub fn duplicate(&mut self) {
+        let mut tuning = self.active().clone();
+        tuning.name = self.unique_name(&tuning.name);
+        self.entries.insert(
+            self.active + 1,
+            Entry {
+                tuning,
+                modified: true,
+            },
+        );
+        self.active += 1;
+    }
+
+    pub fn rename(&mut self, index: usize, name: &str) -> Result<(), RenameError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(RenameError::Empty);
+        }
+        if self
+            .entries
+            .iter()
+            .enumerate()
+            .any(|(i, entry)| i != index && entry.tuning.name == name)
+        {
+            return Err(RenameError::Taken);
+        }
+        let entry = &mut self.entries[index];
+        entry.tuning.name = name.to_string();
+        entry.modified = true;
+        Ok(())
+    }
+
+    /// Removes a tuning unless it is the last one. Deleting the active tuning
+    /// selects the one after it, or the one before if it was last.
+    pub fn delete(&mut self, index: usize) -> bool {
+        if self.entries.len() <= 1 || index >= self.entries.len() {
+            return false;
+        }
+        self.entries.remove(index);
+        if index < self.active || self.active == self.entries.len() {
+            self.active -= 1;
+        }
+        true
+    }
+
+    fn unique_name(&self, base: &str) -> String {
+        (2..)
+            .map(|n| format!("{base} ({n})"))
+            .find(|name| self.entries.iter().all(|entry| &entry.tuning.name != name))
+            .unwrap()
+    }
+
+    pub fn serialize(&self) -> String {
+        serialize_entries(self.entries.iter())
+    }
+
+    /// The tunings changed since the library was last saved, as
+    /// [`TuningLibrary::serialize`] writes them, or `None` when there are
+    /// none. The session keeps these, so closing the app does not lose them.
+    pub fn unsaved(&self) -> Option<String> {
+        let modified = self.entries.iter().filter(|entry| entry.modified);
+        let text = serialize_entries(modified);
+        (!text.is_empty()).then_some(text)
+    }
+
+    /// Puts back the tunings of [`TuningLibrary::unsaved`], still marked as
+    /// modified, each in place of the tuning of the same name or after the
+    /// others if there is none.
+    pub fn restore_unsaved(&mut self, text: &str) {
+        let Some(unsaved) = Self::parse(text) else {
+            return;
+        };
+        for tuning in unsaved.entries.into_iter().map(|entry| entry.tuning) {
+            let same_name = |e: &Entry| e.tuning.name == tuning.name;
+            let index = self.entries.iter().position(same_name);
+            let entry = Entry {
+                tuning,
+                modified: true,
+            };
+            match index {
+                Some(index) => self.entries[index] = entry,
+                None => self.entries.push(entry),
+            }
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        let mut tunings: Vec<Tuning> = Vec::new();
+        for line in s.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                tunings.push(Tuning {
+                    name: name.to_string(),
+                    reference_hz: 440.0,
+                    degrees: Vec::new(),
+                });
+                continue;
+            }
+
+            let tuning = tunings.last_mut()?;
+            let (key, value) = line.rsplit_once('=')?;
+            let value = value.trim();
+            match key.trim() {
+                "reference_hz" => tuning.reference_hz = value.parse().ok()?,
+                // ratios are above the first degree, which has to be in cents
+                name if value.contains('/') => {
+                    let ratio: Ratio = value.parse().ok()?;
+                    let one = tuning.degrees.first()?.cents;
+                    tuning.degrees.push(Degree {
+                        name: name.to_string(),
+                        cents: one + ratio.cents(),
+                        ratio: Some(ratio),
+                    });
+                }
+                name => tuning.degrees.push(Degree {
+                    name: name.to_string(),
+                    cents: value.parse().ok()?,
+                    ratio: None,
+                }),
+            }
+        }
+        (!tunings.is_empty()).then(|| Self::new(tunings))
+    }
+
+    /// Loads the saved library, or the default one if there is none.
+    pub fn load() -> Self {
+        path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|s| Self::parse(&s))
+            .unwrap_or_default()
+    }
+
+    /// Writes the library, clearing the modified flags.
+    pub fn save(&mut self) -> io::Result<()> {
+        let path = path().ok_or(io::ErrorKind::NotFound)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.serialize())?;
+        for entry in &mut self.entries {
+            entry.modified = false;
+        }
+        Ok(())
+    }
+}
+
+fn serialize_entries<'a>(entries: impl Iterator<Item = &'a Entry>) -> String {
+    let mut out = String::new();
+    for entry in entries {
+        let tuning = &entry.tuning;
+        out.push_str(&format!(
+            "[{}]\nreference_hz = {}\n",
+            tuning.name, tuning.reference_hz
+        ));
+        for degree in &tuning.degrees {
+            match degree.ratio {
+                Some(ratio) => out.push_str(&format!("{} = {ratio}\n", degree.name)),
+                None => out.push_str(&format!("{} = {}\n", degree.name, degree.cents)),
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+fn path() -> Option<PathBuf> {
+    Some(config_dir()?.join("tunings.txt"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_scala_ratios_and_cents() {
+        let scala =
+            "! meantone.scl\n!\nQuarter-comma meantone, in part\n 3\n!\n193.157 tone\n5/4\n2/1\n";
+        let tuning = Tuning::from_scala("meantone", scala).unwrap();
+        assert_eq!(tuning.name, "meantone");
+        let cents = tuning.degrees.iter().map(|degree| degree.cents);
+        let cents = cents.collect::<Vec<_>>();
+        assert_eq!(cents.len(), 3);
+        assert_eq!(cents[0], 300.0);
+        assert!((cents[1] - 493.157).abs() < 1e-9);
+        assert!((cents[2] - 686.314).abs() < 1e-3);
+        assert_eq!(tuning.degrees[1].ratio, None);
+        assert_eq!(tuning.degrees[2].ratio, Ratio::new(5, 4));
+    }
+
+    #[test]
+    fn scala_files_round_trip() {
+        let scala = "! just.scl\n!\njust\n 4\n!\n 9/8\n 386.31371\n 3/2\n 2/1\n";
+        let tuning = Tuning::from_scala("just", scala).unwrap();
+        assert_eq!(tuning.to_scala(), scala);
+        let edo = Tuning::edo(5);
+        let read = Tuning::from_scala("5-EDO", &edo.to_scala()).unwrap();
+        for (read, degree) in read.degrees.iter().zip(&edo.degrees) {
+            assert!((read.cents - degree.cents).abs() < 1e-4);
+        }
+        assert_eq!(read.degrees.len(), 5);
+    }
+
+    #[test]
+    fn reports_the_line_of_scala_errors() {
+        assert_eq!(Tuning::from_scala("x", ""), Err(ScalaError::MissingCount));
+        assert_eq!(
+            Tuning::from_scala("x", "!\ndesc\nmany\n"),
+            Err(ScalaError::InvalidCount(3))
+        );
+        assert_eq!(
+            Tuning::from_scala("x", "desc\n2\n5:4\n2/1\n"),
+            Err(ScalaError::InvalidPitch(3))
+        );
+        assert_eq!(
+            Tuning::from_scala("x", "desc\n3\n5/4\n"),
+            Err(ScalaError::TooFewPitches(3))
+        );
+    }
+
+    #[test]
+    fn names_degrees_of_72_edo_with_arrows() {
+        let tuning = Tuning::edo(72);
+        let name = |degree: usize| tuning.degrees[degree].name.as_str();
+        let names = [
+            (0, "C"),
+            (1, "C↑"),
+            (2, "C↑↑"),
+            (3, "C𝄲"),
+            (4, "C♯↓↓"),
+            (5, "C♯↓"),
+            (6, "C♯"),
+            (7, "C♯↑"),
+            (9, "C𝄲♯"),
+            (12, "D"),
+            (71, "C↓"),
+        ];
+        for (degree, expected) in names {
+            assert_eq!(name(degree), expected, "degree {degree}");
+        }
+        // ASCII arrows read back as the same notes
+        let note: NoteName = "C#^".parse().unwrap();
+        assert_eq!(note.to_string(), "C♯↑");
+        let note: NoteName = "Dvv".parse().unwrap();
+        assert_eq!((note.letter, note.accidental, note.arrows), (1, 0, -2));
+    }
+
+    #[test]
+    fn sagittal_accidentals_by_cents() {
+        let symbol = |cents| sagittal_accidental(cents).0;
+        // under half the smallest accidental is no accidental
+        assert_eq!(symbol(0.0), None);
+        assert_eq!(symbol(2.5), None);
+        assert_eq!(symbol(-2.5), None);
+        let buckets = [
+            (4.0, '\u{E300}'),
+            (6.0, '\u{E300}'),
+            (20.0, '\u{E302}'),
+            (25.0, '\u{E304}'),
+            (31.0, '\u{E306}'),
+            (45.0, '\u{E308}'),
+            (52.0, '\u{E30A}'),
+            (60.0, '\u{E30A}'),
+        ];
+        for (cents, up) in buckets {
+            assert_eq!(symbol(cents), Some(up), "{cents}");
+            let down = char::from_u32(up as u32 + 1);
+            assert_eq!(symbol(-cents), down, "{}", -cents);
+        }
+        let (_, error) = sagittal_accidental(20.0);
+        assert!((error - 1.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn names_with_sagittal_accidentals() {
+        let name = |cents| degree_name(cents, Notation::Sagittal);
+        assert_eq!(name(0.0), "C");
+        assert_eq!(name(222.0), "D\u{E302}");
+        assert_eq!(name(1222.0), "C\u{E302}");
+        assert_eq!(name(178.0), "D\u{E303}");
+        // the lower semitone wins a tie
+        assert_eq!(name(50.0), "C\u{E308}");
+
+        let e = NoteName::on_letter(2, 4);
+        assert_eq!(sagittal_name(e, 378.0), "E\u{E303}");
+        assert_eq!(sagittal_name(e, 400.0), "E");
+        // too far from E for any accidental
+        assert_eq!(sagittal_name(e, 300.0), "D♯");
+    }
+
+    #[test]
+    fn names_just_ratios_in_helmholtz_ellis() {
+        let c = NoteName::on_letter(0, 0);
+        let name = |num, den, one| heji_name(Ratio::new(num, den).unwrap(), one);
+        assert_eq!(name(3, 2, c).as_deref(), Some("G"));
+        assert_eq!(name(81, 64, c).as_deref(), Some("E"));
+        assert_eq!(name(5, 4, c).as_deref(), Some("E↓"));
+        assert_eq!(name(6, 5, c).as_deref(), Some("E♭↑"));
+        assert_eq!(name(15, 8, c).as_deref(), Some("B↓"));
+        assert_eq!(name(25, 16, c).as_deref(), Some("G♯↓↓"));
+        let seven = format!("B♭{}", SEPTIMAL_COMMA.1);
+        assert_eq!(name(7, 4, c), Some(seven));
+        assert_eq!(name(11, 8, c).as_deref(), Some("F𝄲"));
+        // above an 11 limit there is no name
+        assert_eq!(name(13, 8, c), None);
+
+        // relative to the 1/1
+        let d = NoteName::on_letter(1, 2);
+        assert_eq!(name(5, 4, d).as_deref(), Some("F♯↓"));
+        let b_flat = NoteName::on_letter(6, 10);
+        assert_eq!(name(3, 2, b_flat).as_deref(), Some("F"));
+        assert_eq!(name(5, 4, b_flat).as_deref(), Some("D↓"));
+    }
+
+    #[test]
+    fn cent_offsets_in_superscript() {
+        assert_eq!(superscript_cents(-13.7), "⁻¹⁴");
+        assert_eq!(superscript_cents(2.0), "⁺²");
+        assert_eq!(superscript_cents(105.0), "⁺¹⁰⁵");
+        assert_eq!(superscript_cents(0.4), "");
+        assert_eq!(superscript_cents(-0.4), "");
+
+        let e = NoteName::on_letter(2, 4);
+        assert_eq!(cents_name(e, 386.3), "E⁻¹⁴");
+        assert_eq!(cents_name(e, 400.0), "E");
+        // the offset is the shortest way round the octave
+        let c = NoteName::on_letter(0, 0);
+        assert_eq!(cents_name(c, 1190.0), "C⁻¹⁰");
+    }
+
+    #[test]
+    fn added_tunings_are_renamed_when_taken() {
+        let mut library = TuningLibrary::default();
+        library.add(Tuning::default());
+        assert_eq!(library.active_index(), 1);
+        assert_eq!(library.active().name, "12-TET (2)");
+        assert!(library.active_entry().modified);
+    }
+
+    fn library(names: &[&str]) -> TuningLibrary {
+        let tunings = names.iter().map(|&name| Tuning {
+            name: name.to_string(),
+            ..Tuning::default()
+        });
+        TuningLibrary::new(tunings.collect())
+    }
+
+    fn names(library: &TuningLibrary) -> Vec<&str> {
+        let entries = library.entries().iter();
+        entries.map(|entry| entry.tuning.name.as_str()).collect()
+    }
+
+    #[test]
+    fn renames_refuse_taken_and_empty_names() {
+        let mut library = library(&["12-TET", "31-EDO", "just"]);
+        assert_eq!(library.rename(0, "just"), Err(RenameError::Taken));
+        assert_eq!(library.rename(0, " 31-EDO "), Err(RenameError::Taken));
+        assert_eq!(library.rename(0, "  "), Err(RenameError::Empty));
+        assert_eq!(names(&library), ["12-TET", "31-EDO", "just"]);
+        assert!(!library.entries()[0].modified);
+
+        // keeping its own name is fine
+        assert_eq!(library.rename(2, "just"), Ok(()));
+        assert_eq!(library.rename(1, " meantone "), Ok(()));
+        assert_eq!(names(&library), ["12-TET", "meantone", "just"]);
+        assert!(library.entries()[1].modified);
+    }
+
+    #[test]
+    fn deleting_the_active_tuning_selects_a_neighbour() {
+        let mut library = library(&["a", "b", "c", "d"]);
+        library.select(1);
+        assert!(library.delete(1));
+        assert_eq!(library.active().name, "c");
+
+        library.select(2);
+        assert!(library.delete(2));
+        assert_eq!(library.active().name, "c");
+        assert_eq!(names(&library), ["a", "c"]);
+    }
+
+    #[test]
+    fn deleting_others_keeps_the_active_tuning() {
+        let mut library = library(&["a", "b", "c"]);
+        library.select(1);
+        assert!(library.delete(0));
+        assert_eq!(library.active().name, "b");
+        assert!(library.delete(1));
+        assert_eq!(library.active().name, "b");
+        // never the last one
+        assert!(!library.delete(0));
+        assert!(!library.delete(5));
+        assert_eq!(names(&library), ["b"]);
+    }
+
+    #[test]
+    fn duplicates_go_after_the_original() {
+        let mut library = library(&["a", "b"]);
+        library.select(0);
+        library.duplicate();
+        library.select(0);
+        library.duplicate();
+        assert_eq!(names(&library), ["a", "a (3)", "a (2)", "b"]);
+        assert_eq!(library.active_index(), 1);
+        assert_eq!(library.active_label(), "a (3)*");
+    }
+
+    #[test]
+    fn edits_only_change_the_active_tuning() {
+        let mut library = library(&["a", "b"]);
+        library.cycle(-1);
+        library.edit(|tuning| tuning.reference_hz = 432.0);
+        assert_eq!(library.active_label(), "b*");
+        assert_eq!(library.entries()[0].tuning.reference_hz, 440.0);
+        assert!(!library.entries()[0].modified);
+        assert_eq!(library.entries()[1].tuning.reference_hz, 432.0);
+    }
+
+    #[test]
+    fn unsaved_edits_are_put_back() {
+        let mut library = library(&["a", "b"]);
+        assert_eq!(library.unsaved(), None);
+        library.select(1);
+        library.edit(|tuning| tuning.reference_hz = 432.0);
+        library.add(Tuning::edo(31));
+        let unsaved = library.unsaved().unwrap();
+
+        // as the saved library is loaded again at the next start
+        let mut restarted = self::library(&["a", "b"]);
+        restarted.restore_unsaved(&unsaved);
+        assert_eq!(names(&restarted), ["a", "b", "31-EDO"]);
+        let modified = restarted.entries().iter().map(|entry| entry.modified);
+        assert_eq!(modified.collect::<Vec<_>>(), [false, true, true]);
+        assert_eq!(restarted.entries()[1].tuning.reference_hz, 432.0);
+        assert_eq!(restarted.entries()[2].tuning.degrees.len(), 31);
+        assert_eq!(restarted.unsaved(), Some(unsaved));
+
+        restarted.restore_unsaved("not a library");
+        assert_eq!(names(&restarted), ["a", "b", "31-EDO"]);
+    }
+
+    #[test]
+    fn libraries_round_trip() {
+        let scala = "just\n 4\n 9/8\n 386.31371\n 3/2\n 2/1\n";
+        let tunings = vec![
+            Tuning::default(),
+            Tuning::edo(31),
+            Tuning::from_scala("just", scala).unwrap(),
+        ];
+        let library = TuningLibrary::new(tunings);
+        let read = TuningLibrary::parse(&library.serialize()).unwrap();
+        assert_eq!(names(&read), names(&library));
+        for (read, entry) in read.entries().iter().zip(library.entries()) {
+            let (read, tuning) = (&read.tuning, &entry.tuning);
+            assert_eq!(read.reference_hz, tuning.reference_hz);
+            assert_eq!(read.degrees.len(), tuning.degrees.len());
+            for (read, degree) in read.degrees.iter().zip(&tuning.degrees) {
+                assert_eq!((&read.name, read.ratio), (&degree.name, degree.ratio));
+                assert!((read.cents - degree.cents).abs() < 1e-9);
+            }
+        }
+        assert_eq!(TuningLibrary::parse("reference_hz = 440\n"), None);
+        assert_eq!(TuningLibrary::parse(""), None);
+    }
+}
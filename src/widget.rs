@@ -1,4 +1,47 @@
-use crate::{font::Font, invert, Color, PixBuf};
+use std::{
+    cell::RefCell,
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    fmt::Write,
+    hash::{DefaultHasher, Hash, Hasher},
+    time::Duration,
+};
+
+use winit::keyboard::KeyCode;
+
+use crate::{
+    color::{contrasting, parse_hex, to_hex, Hsv, HUE_STEPS},
+    font::Font,
+    invert,
+    shortcuts::{Action, Category, Shortcut},
+    Color, PixBuf,
+};
+
+thread_local! {
+    /// Buffers that [`Canvas::text_fmt`] formats into, kept between frames so
+    /// labels composed every frame do not allocate. There is one for each
+    /// call in progress, as the text drawn may be formatted in turn.
+    static SCRATCH: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Formats `args` into a buffer of [`SCRATCH`] and hands it to `f`.
+fn with_scratch<R>(args: std::fmt::Arguments, f: impl FnOnce(&str) -> R) -> R {
+    let mut text = SCRATCH.with_borrow_mut(Vec::pop).unwrap_or_default();
+    text.clear();
+    let _ = text.write_fmt(args);
+    let result = f(&text);
+    SCRATCH.with_borrow_mut(|scratch| scratch.push(text));
+    result
+}
+
+/// Background of the window, and of anything cleared.
+pub const BACKGROUND: Color = [0, 0, 0, 0];
+
+/// Background of hovered rows and cells.
+pub const HOVER: Color = [48, 48, 48, 255];
+
+/// Ring around the cell with keyboard focus.
+pub const FOCUS: Color = [96, 160, 255, 255];
 
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum CutDir {
@@ -6,7 +49,7 @@ pub enum CutDir {
     Vertical,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Rect {
     pub x: i32,
     pub y: i32,
@@ -18,6 +61,85 @@ impl Rect {
     pub fn contains(&self, x: i32, y: i32) -> bool {
         x >= self.x && y >= self.y && x < self.x + self.width && y < self.y + self.height
     }
+
+    /// Same as [`Rect::contains`], named to tell it apart from
+    /// [`Rect::contains_rect`].
+    pub fn contains_point(&self, x: i32, y: i32) -> bool {
+        self.contains(x, y)
+    }
+
+    /// Whether all of `other` is inside this rect.
+    pub fn contains_rect(&self, other: Rect) -> bool {
+        self.union(other) == *self
+    }
+
+    /// Whether the two rects share any pixel.
+    pub fn overlaps(&self, other: Rect) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    /// The pixels both rects share, if they share any. Unlike
+    /// [`Rect::intersect`] this tells a miss apart from a hit.
+    pub fn intersection(&self, other: Rect) -> Option<Rect> {
+        let rect = self.intersect(other);
+        (!self.is_empty() && !rect.is_empty()).then_some(rect)
+    }
+
+    /// The smallest rect around both rects.
+    pub fn union(&self, other: Rect) -> Rect {
+        let (x0, y0) = (self.x.min(other.x), self.y.min(other.y));
+        let x1 = (self.x + self.width).max(other.x + other.width);
+        let y1 = (self.y + self.height).max(other.y + other.height);
+        Rect {
+            x: x0,
+            y: y0,
+            width: x1 - x0,
+            height: y1 - y0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width <= 0 || self.height <= 0
+    }
+
+    /// The part of this rect inside `other`. Rects that do not overlap give
+    /// an empty rect on the edge of `other` nearest to this one.
+    pub fn intersect(&self, other: Rect) -> Rect {
+        let x0 = self.x.clamp(other.x, other.x + other.width.max(0));
+        let y0 = self.y.clamp(other.y, other.y + other.height.max(0));
+        let x1 = (self.x + self.width.max(0)).clamp(x0, other.x + other.width.max(0));
+        let y1 = (self.y + self.height.max(0)).clamp(y0, other.y + other.height.max(0));
+        Rect {
+            x: x0,
+            y: y0,
+            width: x1 - x0,
+            height: y1 - y0,
+        }
+    }
+
+    /// Cell `(col, row)` of this rect divided into `cols` by `rows` equal cells.
+    /// Any remainder pixels are given to the last column and row.
+    pub fn grid(&self, cols: usize, rows: usize, col: usize, row: usize) -> Rect {
+        let (cols, rows) = (cols.max(1) as i32, rows.max(1) as i32);
+        let (col, row) = (col as i32, row as i32);
+
+        let cell_width = self.width / cols;
+        let cell_height = self.height / rows;
+        Rect {
+            x: self.x + col * cell_width,
+            y: self.y + row * cell_height,
+            width: if col == cols - 1 {
+                self.width - col * cell_width
+            } else {
+                cell_width
+            },
+            height: if row == rows - 1 {
+                self.height - row * cell_height
+            } else {
+                cell_height
+            },
+        }
+    }
 }
 
 pub struct Canvas<'a> {
@@ -25,13 +147,447 @@ pub struct Canvas<'a> {
     pub rect: Rect,
     pub visuals: Visuals<'a>,
     pub events: Events,
+    pub tooltip: TooltipState,
+    pub hover: HoverState,
+    pub repeat: RepeatState,
+    pub menu: MenuState,
+    pub panes: PaneState,
+    pub status: StatusState,
+}
+
+/// Frames a widget has to be hovered before its tooltip shows.
+pub const TOOLTIP_DELAY: u32 = 10;
+
+/// Identifies a widget across frames, for state such as [`TooltipState`]
+/// that has to know whether the cursor is still over the same one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WidgetId(u64);
+
+impl WidgetId {
+    /// The id of the widget named by `key`, such as a label or a grid cell.
+    pub fn new(key: impl Hash) -> Self {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        Self(hasher.finish())
+    }
+}
+
+/// Which widget has been hovered for how long, shared by every tooltip so
+/// only one shows at a time. It has to outlive a frame, so the owner swaps
+/// it into [`Canvas::tooltip`] while drawing and takes it back after.
+#[derive(Debug, Default)]
+pub struct TooltipState {
+    /// The widget under the cursor in the previous frame.
+    pub hover_widget: Option<WidgetId>,
+    /// Frames `hover_widget` has been hovered in a row.
+    pub frames_hovered: u32,
+    /// Whether a tooltip has been shown this frame.
+    pub visible: bool,
+    /// Whether a hovered widget registered this frame.
+    registered: bool,
+    /// Whether the tooltip was dismissed by a click, until the cursor moves
+    /// to another widget.
+    dismissed: bool,
+    text: String,
+}
+
+impl TooltipState {
+    /// Forgets what was shown in the previous frame.
+    pub fn begin_frame(&mut self) {
+        self.visible = false;
+        self.registered = false;
+    }
+    /// Resets the delay when no widget registered as hovered this frame.
+    pub fn end_frame(&mut self) {
+        if !self.registered {
+            self.hover_widget = None;
+            self.frames_hovered = 0;
+            self.dismissed = false;
+        }
+    }
+    /// Whether a widget is hovered but its tooltip is still waiting out the
+    /// delay, so more frames are needed to show it.
+    pub fn pending(&self) -> bool {
+        self.hover_widget.is_some() && !self.visible && !self.dismissed
+    }
+}
+
+/// What the status bar shows this frame, set by [`Canvas::status`]. Unlike
+/// [`TooltipState`] it lasts only a frame, with the last widget to set it
+/// winning.
+#[derive(Debug, Default)]
+pub struct StatusState {
+    text: Option<String>,
+}
+
+impl StatusState {
+    /// Forgets the text of the previous frame.
+    pub fn begin_frame(&mut self) {
+        self.text = None;
+    }
+    /// The text the status bar shows this frame, if a widget set one.
+    pub fn text(&self) -> Option<&str> {
+        self.text.as_deref()
+    }
+}
+
+/// The context menu that is open, if any, opened by
+/// [`Canvas::context_menu`]. Like [`TooltipState`] it has to outlive a
+/// frame, so the owner swaps it into [`Canvas::menu`] while drawing.
+#[derive(Debug, Default)]
+pub struct MenuState {
+    open: Option<OpenMenu>,
+}
+
+#[derive(Debug)]
+struct OpenMenu {
+    /// The widget that opened the menu.
+    owner: WidgetId,
+    /// Where the menu opened, its top left corner unless that would put it
+    /// off the screen.
+    at: (i32, i32),
+    /// The entries the owner added, kept from its last frame for the
+    /// keyboard to move over.
+    entries: Vec<MenuEntry>,
+    /// Where the menu was drawn in the previous frame, which clicks are
+    /// matched against.
+    rect: Option<Rect>,
+    /// The item focused with the arrow keys.
+    focused: Option<usize>,
+    /// The item Enter was pressed on this frame.
+    activated: Option<usize>,
+    /// Whether the owner was drawn this frame.
+    drawn: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum MenuEntry {
+    Item { label: String, enabled: bool },
+    Separator,
+}
+
+impl MenuEntry {
+    fn enabled(&self) -> bool {
+        matches!(self, MenuEntry::Item { enabled: true, .. })
+    }
+}
+
+impl MenuState {
+    pub const SHORTCUTS: &[Shortcut] = &[
+        Shortcut::new(
+            Category::Navigation,
+            "Up, Down",
+            "Move through a context menu",
+        ),
+        Shortcut::new(Category::Navigation, "Enter", "Pick the focused menu item"),
+        Shortcut::new(Category::Navigation, "Esc", "Close the context menu"),
+    ];
+
+    pub fn is_open(&self) -> bool {
+        self.open.is_some()
+    }
+    /// Takes the keys of the open menu before any widget sees them: Escape
+    /// closes it, the up and down arrows move the focus over its items and
+    /// Enter picks the focused one. A click outside it closes it too.
+    pub fn begin_frame(&mut self, events: &mut Events) {
+        let Some(open) = &mut self.open else {
+            return;
+        };
+        open.activated = None;
+        open.drawn = false;
+        let outside = events
+            .cursor
+            .is_none_or(|(x, y)| open.rect.is_none_or(|rect| !rect.contains(x, y)));
+        if events.clicked && outside || events.keys_pressed.contains(&KeyCode::Escape) {
+            events.keys_pressed.retain(|&key| key != KeyCode::Escape);
+            self.open = None;
+            return;
+        }
+
+        let enabled: Vec<usize> = (0..open.entries.len())
+            .filter(|&i| open.entries[i].enabled())
+            .collect();
+        for &key in &events.keys_pressed {
+            let position = open
+                .focused
+                .and_then(|f| enabled.iter().position(|&i| i == f));
+            let step = |forward: bool| {
+                let len = enabled.len();
+                let next = match (position, forward) {
+                    (None, true) => 0,
+                    (None, false) => len.checked_sub(1)?,
+                    (Some(p), true) => (p + 1) % len,
+                    (Some(p), false) => (p + len - 1) % len,
+                };
+                enabled.get(next).copied()
+            };
+            match key {
+                KeyCode::ArrowDown => open.focused = step(true),
+                KeyCode::ArrowUp => open.focused = step(false),
+                KeyCode::Enter => open.activated = open.focused,
+                _ => {}
+            }
+        }
+        // nothing under the menu moves or acts on these while it is open
+        events
+            .keys_pressed
+            .retain(|key| !GridNav::moves(*key) && *key != KeyCode::Enter);
+    }
+    /// Closes the menu if its owner was not drawn this frame, as when its
+    /// tab was left.
+    pub fn end_frame(&mut self) {
+        if self.open.as_ref().is_some_and(|open| !open.drawn) {
+            self.open = None;
+        }
+    }
+}
+
+/// The entries of a context menu, added by the closure passed to
+/// [`Canvas::context_menu`] in every frame the menu is open.
+pub struct Menu {
+    entries: Vec<MenuEntry>,
+    /// The entry clicked or activated with Enter this frame.
+    picked: Option<usize>,
+}
+
+impl Menu {
+    /// Adds an item and returns whether it was picked this frame, which
+    /// closes the menu.
+    pub fn item(&mut self, label: &str) -> bool {
+        self.item_enabled(label, true)
+    }
+    /// Adds an item that is greyed out and cannot be picked unless
+    /// `enabled`.
+    pub fn item_enabled(&mut self, label: &str, enabled: bool) -> bool {
+        let index = self.entries.len();
+        self.entries.push(MenuEntry::Item {
+            label: label.to_string(),
+            enabled,
+        });
+        enabled && self.picked == Some(index)
+    }
+    /// Adds a line between the items before and after it.
+    pub fn separator(&mut self) {
+        self.entries.push(MenuEntry::Separator);
+    }
+}
+
+/// How many frames in a row the cursor has been over each rect that asked
+/// with [`Canvas::hover_frames`]. Like [`TooltipState`] it has to outlive a
+/// frame, so the owner swaps it into [`Canvas::hover`] while drawing.
+#[derive(Debug, Default)]
+pub struct HoverState {
+    /// Frames each widget had been hovered up to the previous frame.
+    pub hover_counters: HashMap<WidgetId, u32>,
+    /// The counters of the widgets hovered so far this frame. A widget that
+    /// is not hovered, or does not ask, starts from zero again next frame.
+    current: RefCell<HashMap<WidgetId, u32>>,
+    /// The rects asked about with [`Canvas::hover`] in the previous frame.
+    regions: Vec<HoverRegion>,
+    /// The rects asked about so far this frame.
+    current_regions: RefCell<Vec<HoverRegion>>,
+}
+
+/// A rect a widget asked about with [`Canvas::hover`], and whether what it
+/// draws follows the cursor inside the rect rather than only whether the
+/// cursor is in.
+#[derive(Debug, Clone, Copy)]
+struct HoverRegion {
+    rect: Rect,
+    follows: bool,
+}
+
+impl HoverState {
+    /// Keeps the counters of the widgets hovered this frame and forgets the
+    /// rest.
+    pub fn end_frame(&mut self) {
+        self.hover_counters = std::mem::take(self.current.get_mut());
+        std::mem::swap(&mut self.regions, self.current_regions.get_mut());
+        self.current_regions.get_mut().clear();
+    }
+    /// Whether moving the cursor from `from` to `to` changes what the
+    /// previous frame drew: it went into or out of a rect that was asked
+    /// about, or moved in one that follows it.
+    pub fn cursor_moved(&self, from: Option<(i32, i32)>, to: Option<(i32, i32)>) -> bool {
+        let inside =
+            |rect: Rect, at: Option<(i32, i32)>| at.is_some_and(|(x, y)| rect.contains(x, y));
+        from != to
+            && self.regions.iter().any(|region| {
+                let (was, is) = (inside(region.rect, from), inside(region.rect, to));
+                was != is || (region.follows && was)
+            })
+    }
+    /// Remembers that `rect` was asked about this frame, once for the many
+    /// questions a widget asks about the same rect.
+    fn ask(&self, rect: Rect, follows: bool) {
+        if rect.width <= 0 || rect.height <= 0 {
+            return;
+        }
+        let mut regions = self.current_regions.borrow_mut();
+        match regions.last_mut() {
+            Some(last) if last.rect == rect => last.follows |= follows,
+            _ => regions.push(HoverRegion { rect, follows }),
+        }
+    }
+}
+
+/// The controls held down with [`Canvas::hold_repeat`], each with when it
+/// repeats next. Like [`TooltipState`] it has to outlive a frame, so the
+/// owner swaps it into [`Canvas::repeat`] while drawing.
+#[derive(Debug, Default)]
+pub struct RepeatState {
+    held: HashMap<WidgetId, HoldRepeat>,
+    /// The held controls that asked this frame.
+    asked: HashSet<WidgetId>,
+}
+
+impl RepeatState {
+    /// Lets go of the held controls that were not drawn this frame.
+    pub fn end_frame(&mut self) {
+        let Self { held, asked } = self;
+        held.retain(|id, _| asked.contains(id));
+        asked.clear();
+    }
+    /// Whether a control is held down and will repeat, so frames have to be
+    /// drawn while nothing else happens.
+    pub fn holding(&self) -> bool {
+        !self.held.is_empty()
+    }
+}
+
+/// Time between pressing a control and its first repeat.
+pub const REPEAT_DELAY: Duration = Duration::from_millis(400);
+
+/// Repeats per second of a held control when they start, and once they have
+/// sped up for [`REPEAT_RAMP`].
+pub const REPEAT_RATES: (f64, f64) = (10.0, 50.0);
+
+/// Time a held control takes to speed up from the first rate of
+/// [`REPEAT_RATES`] to the second.
+pub const REPEAT_RAMP: Duration = Duration::from_secs(2);
+
+/// When a control held down repeats, timed from when it was pressed: not at
+/// all for [`REPEAT_DELAY`], then ever faster over [`REPEAT_RAMP`]. Times are
+/// those of [`Events::time`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HoldRepeat {
+    pub pressed_at: Duration,
+    /// When it last repeated, `None` until the delay is over.
+    pub last_fire: Option<Duration>,
+}
+
+impl HoldRepeat {
+    pub fn new(pressed_at: Duration) -> Self {
+        Self {
+            pressed_at,
+            last_fire: None,
+        }
+    }
+
+    /// How many times the control repeats after the last call, up to and
+    /// including `now`. A control let go before the delay never repeats.
+    pub fn fires_this_frame(&mut self, now: Duration) -> u32 {
+        let mut fires = 0;
+        loop {
+            let next = match self.last_fire {
+                None => self.pressed_at + REPEAT_DELAY,
+                Some(last) => last + self.interval(last),
+            };
+            if next > now {
+                return fires;
+            }
+            self.last_fire = Some(next);
+            fires += 1;
+        }
+    }
+
+    /// Time from a repeat at `at` to the next, shrinking as the rate rises
+    /// linearly across [`REPEAT_RATES`].
+    fn interval(&self, at: Duration) -> Duration {
+        let repeating = at.saturating_sub(self.pressed_at + REPEAT_DELAY);
+        let ramp = (repeating.as_secs_f64() / REPEAT_RAMP.as_secs_f64()).min(1.0);
+        let (slow, fast) = REPEAT_RATES;
+        Duration::from_secs_f64(1.0 / (slow + (fast - slow) * ramp))
+    }
 }
 
+/// Thickness of the divider between split panes, in pixels of text.
+pub const DIVIDER: i32 = 3;
+
+/// One of the two panes of [`Canvas::split_pane`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pane {
+    /// The left or top pane.
+    First,
+    /// The right or bottom pane.
+    Second,
+}
+
+/// How each split pane drawn with [`Canvas::split_pane`] is divided, and
+/// which divider is being dragged. Like [`TooltipState`] it has to outlive
+/// a frame, so the owner swaps it into [`Canvas::panes`] while drawing.
+#[derive(Debug, Default)]
+pub struct PaneState {
+    /// The fraction each split gives its first pane, for the splits moved
+    /// off their default.
+    ratios: HashMap<WidgetId, f32>,
+    dragging: Option<WidgetId>,
+}
+
+impl PaneState {
+    /// The fraction the split `id` gives its first pane, if it was moved.
+    pub fn ratio(&self, id: WidgetId) -> Option<f32> {
+        self.ratios.get(&id).copied()
+    }
+    /// Divides the split `id` at `ratio`, from 0 to 1, as if dragged there.
+    pub fn set_ratio(&mut self, id: WidgetId, ratio: f32) {
+        self.ratios.insert(id, ratio.clamp(0.0, 1.0));
+    }
+}
+
+/// Input for one frame.
+///
+/// Widgets see input in the order they are drawn, and one that handles a
+/// click, scroll or key consumes it so everything drawn after it in the frame
+/// sees nothing. Overlays are drawn last but sit on top, so the rects they
+/// covered in the previous frame hide the cursor from every widget drawn
+/// outside of an overlay.
+#[derive(Clone, Default)]
 pub struct Events {
     pub mouse_left: bool,
     pub mouse_middle: bool,
     pub mouse_right: bool,
     pub cursor: Option<(i32, i32)>,
+    /// Whether the left button was pressed since the last frame.
+    pub clicked: bool,
+    /// Whether the left button was released since the last frame.
+    pub released: bool,
+    /// Whether the press since the last frame was the second of a double
+    /// click.
+    pub double_clicked: bool,
+    /// Where the left button was last pressed, kept until the frame after
+    /// it is released.
+    pub press_origin: Option<(i32, i32)>,
+    /// Lines scrolled since the last frame, positive when scrolling up.
+    pub scroll: f32,
+    /// Keys pressed since the last frame.
+    pub keys_pressed: Vec<KeyCode>,
+    /// Text typed since the last frame, with the keyboard layout and
+    /// modifiers applied.
+    pub characters_typed: Vec<char>,
+    pub control: bool,
+    pub alt: bool,
+    /// Rects of the overlays drawn in the previous frame.
+    pub overlays: Vec<Rect>,
+    /// Rects of the overlays drawn so far in this frame, to be passed on as
+    /// `overlays` to the next.
+    pub next_overlays: Vec<Rect>,
+    /// Whether an overlay is being drawn, which `overlays` do not hide the
+    /// cursor from.
+    pub in_overlay: bool,
+    /// When the frame is drawn, from any fixed point such as the start of
+    /// the program, to time what is held down.
+    pub time: Duration,
 }
 
 #[derive(Clone)]
@@ -40,59 +596,222 @@ pub struct Visuals<'a> {
     pub text_size: i32,
     pub dir: CutDir,
     pub color: Color,
+    /// Extra pixels between the lines of text that take more than one.
+    pub line_spacing: i32,
+}
+
+/// The keys [`Tabs`] handles while focused.
+pub const TAB_SHORTCUTS: &[Shortcut] = &[
+    Shortcut::new(
+        Category::Navigation,
+        "Left, Right",
+        "Switch tabs after clicking the tab bar",
+    ),
+    Shortcut::new(Category::Navigation, "Home, End", "First or last tab"),
+];
+
+/// A small marker in the top right corner of a tab, such as for unsaved
+/// changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Badge {
+    Dot(Color),
+    /// A number, shown as "99+" past 99.
+    Count(u32),
+    Icon(IconId),
+}
+
+/// The icons a [`Badge`] can show.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IconId {
+    /// A yellow triangle, for something that failed.
+    Warning,
+}
+
+impl Badge {
+    fn label(&self) -> Option<String> {
+        match self {
+            Badge::Count(count) if *count > 99 => Some("99+".to_string()),
+            Badge::Count(count) => Some(count.to_string()),
+            _ => None,
+        }
+    }
+
+    /// Width of the badge in pixels, its margin included.
+    fn width(&self, visuals: &Visuals) -> i32 {
+        let pad = visuals.text_size;
+        match self.label() {
+            Some(label) => visuals.font.len(&label) * visuals.font_width() + 3 * pad,
+            None => visuals.font_height() / 2 + 2 * pad,
+        }
+    }
+
+    /// Draws the badge in the top right corner of the current rect.
+    fn draw(&self, canvas: &mut Canvas) {
+        let pad = canvas.visuals.text_size;
+        let size = canvas.visuals.font_height() / 2;
+        let Rect { x, y, width, .. } = canvas.rect;
+        let (right, top) = (x + width - pad, y + pad);
+        match *self {
+            Badge::Dot(color) => {
+                let radius = size as f32 / 3.0;
+                let center = (right - size / 2, top + size / 2);
+                canvas.pix.fill_circle(center.0, center.1, radius, color);
+            }
+            Badge::Count(_) => {
+                let label = self.label().unwrap_or_default();
+                let rect = Rect {
+                    x: x + width - self.width(&canvas.visuals) + pad,
+                    y,
+                    width: self.width(&canvas.visuals) - pad,
+                    height: canvas.visuals.font_height(),
+                };
+                canvas.with_rect(rect, |canvas| {
+                    canvas.fill(FOCUS);
+                    canvas.visuals.color = contrasting(FOCUS);
+                    canvas.padded(pad, 0, |canvas| canvas.text(&label));
+                });
+            }
+            Badge::Icon(IconId::Warning) => {
+                let triangle = [
+                    (right - size / 2, top),
+                    (right, top + size),
+                    (right - size, top + size),
+                ];
+                canvas.pix.fill_polygon(&triangle, [255, 192, 0, 255]);
+            }
+        }
+    }
 }
 
 pub struct Tabs<'a, T: Tab> {
     pub selected: &'a mut T,
+    /// Whether the tab bar has the keyboard focus, which has the left and
+    /// right arrows move to the next tab, wrapping at the ends, and Home
+    /// and End to the first and last. The selected tab is outlined then.
+    pub focused: bool,
+    /// Badges for tabs whose badge depends on more than the tab, shown
+    /// instead of [`Tab::badge`].
+    pub badges: Vec<(T, Badge)>,
 }
 
 impl<'a, T: Tab> Tabs<'a, T> {
     pub fn new(selected: &'a mut T) -> Self {
-        Self { selected }
+        Self {
+            selected,
+            focused: false,
+            badges: Vec::new(),
+        }
+    }
+
+    fn handle_keys(&mut self, canvas: &mut Canvas) {
+        let count = T::iter().len();
+        let Some(mut i) = T::iter().position(|tab| tab == *self.selected) else {
+            return;
+        };
+        for key in [
+            KeyCode::ArrowLeft,
+            KeyCode::ArrowRight,
+            KeyCode::Home,
+            KeyCode::End,
+        ] {
+            if !canvas.key_pressed(key) || canvas.events.control || canvas.events.alt {
+                continue;
+            }
+            i = match key {
+                KeyCode::ArrowLeft => (i + count - 1) % count,
+                KeyCode::ArrowRight => (i + 1) % count,
+                KeyCode::Home => 0,
+                _ => count - 1,
+            };
+            canvas.consume_key(key);
+        }
+        if let Some(tab) = T::iter().nth(i) {
+            *self.selected = tab;
+        }
     }
 }
 
 impl<T: Tab> Widget for Tabs<'_, T> {
     fn draw(&mut self, canvas: &mut Canvas) {
-        let tabs = T::iter().collect::<Vec<_>>();
-        let (width, height) = match canvas.visuals.dir {
-            CutDir::Horizontal => (canvas.rect.width / tabs.len() as i32, canvas.rect.height),
-            CutDir::Vertical => (canvas.rect.width, canvas.rect.height / tabs.len() as i32),
-        };
+        canvas.profile_scope("tabs", |canvas| {
+            if self.focused {
+                self.handle_keys(canvas);
+            }
+            let count = T::iter().len().max(1) as i32;
+            let (width, height) = match canvas.visuals.dir {
+                CutDir::Horizontal => (canvas.rect.width / count, canvas.rect.height),
+                CutDir::Vertical => (canvas.rect.width, canvas.rect.height / count),
+            };
 
-        let selecting = canvas.mouse_left();
-        for tab in tabs {
-            canvas.cut(width, height, |canvas| {
-                if canvas.mouse_left() {
-                    *self.selected = tab;
-                }
+            for tab in T::iter() {
+                canvas.cut(width, height, |canvas| {
+                    // tabs change on a full click, so dragging across them does not
+                    if canvas.released() {
+                        *self.selected = tab;
+                        canvas.consume_click();
+                    }
 
-                if tab.eq(self.selected) && (!selecting || canvas.mouse_left()) {
-                    canvas.fill(canvas.visuals.color);
-                    canvas.visuals.color = invert(canvas.visuals.color);
-                    tab.draw(canvas);
-                    canvas.visuals.color = invert(canvas.visuals.color);
-                } else {
-                    tab.draw(canvas);
-                }
-            });
-        }
+                    let selected = tab.eq(self.selected);
+                    if canvas.pressed() {
+                        canvas.fill(HOVER);
+                    } else if selected {
+                        canvas.fill(canvas.visuals.color);
+                        if self.focused {
+                            canvas.outline(FOCUS);
+                        }
+                    }
+
+                    // the label stays centered on the tab unless it would run
+                    // into the badge, and then it is centered beside it
+                    let badge = self.badges.iter().find(|(badged, _)| *badged == tab);
+                    let badge = badge.map(|(_, badge)| *badge);
+                    let mut label = canvas.rect;
+                    if let Some(badge) = badge.or_else(|| tab.badge()) {
+                        let badge_width = badge.width(&canvas.visuals);
+                        let name_width = canvas.visuals.font.len(tab.name());
+                        if name_width * canvas.visuals.font_width() + 2 * badge_width > label.width
+                        {
+                            label.width -= badge_width;
+                        }
+                        badge.draw(canvas);
+                    }
+
+                    canvas.with_rect(label, |canvas| {
+                        if selected && !canvas.pressed() {
+                            canvas.visuals.color = invert(canvas.visuals.color);
+                        }
+                        tab.draw(canvas);
+                    });
+                });
+            }
+        });
     }
 }
 
 pub trait Tab: Clone + Copy + PartialEq + Eq {
-    type Iterator: Iterator<Item = Self>;
+    type Iterator: ExactSizeIterator<Item = Self>;
 
     fn iter() -> Self::Iterator;
     fn name(&self) -> &str;
 
+    /// A marker drawn in the corner of the tab, if it has one.
+    fn badge(&self) -> Option<Badge> {
+        None
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::iter().find(|tab| tab.name() == name)
+    }
+
     fn draw(&self, canvas: &mut Canvas) {
         let len = canvas.visuals.font.len(self.name());
         canvas.center(
             len * canvas.visuals.font_width(),
-            canvas.visuals.font_height() + canvas.visuals.text_size * 4,
+            canvas.visuals.font_line_height(),
+            // names wider than the tab are cut short rather than run
+            // into the next one
             |canvas| {
-                canvas.text(self.name());
+                canvas.text_truncated(self.name());
             },
         )
     }
@@ -102,6 +821,10 @@ impl Visuals<'_> {
     pub fn font_height(&self) -> i32 {
         self.font.height * self.text_size
     }
+    /// How far apart lines of text are, with the line spacing included.
+    pub fn font_line_height(&self) -> i32 {
+        self.font_height() + self.line_spacing
+    }
     pub fn font_width(&self) -> i32 {
         self.font.width * self.text_size
     }
@@ -109,9 +832,17 @@ impl Visuals<'_> {
 
 impl Canvas<'_> {
     pub fn hover(&self) -> bool {
-        self.events
-            .cursor
-            .is_some_and(|(x, y)| self.rect.contains(x, y))
+        self.hover.ask(self.rect, false);
+        self.events.cursor.is_some_and(|(x, y)| {
+            let covered = || self.events.overlays.iter().any(|r| r.contains(x, y));
+            self.rect.contains(x, y) && (self.events.in_overlay || !covered())
+        })
+    }
+    /// The cursor while it is over the current rect, for widgets that draw
+    /// something where it is rather than only whether it is in the rect.
+    pub fn hover_cursor(&self) -> Option<(i32, i32)> {
+        self.hover.ask(self.rect, true);
+        self.events.cursor.filter(|_| self.hover())
     }
     pub fn mouse_left(&self) -> bool {
         self.hover() && self.events.mouse_left
@@ -123,17 +854,333 @@ impl Canvas<'_> {
         self.hover() && self.events.mouse_right
     }
 
+    pub fn clicked(&self) -> bool {
+        self.hover() && self.events.clicked
+    }
+    pub fn double_clicked(&self) -> bool {
+        self.hover() && self.events.double_clicked
+    }
+    fn pressed_inside(&self) -> bool {
+        self.events
+            .press_origin
+            .is_some_and(|(x, y)| self.rect.contains(x, y))
+    }
+    /// Whether the left button is held over the current rect, after being
+    /// pressed in it.
+    pub fn pressed(&self) -> bool {
+        self.mouse_left() && self.pressed_inside()
+    }
+    /// Whether the left button was pressed and then released in the current
+    /// rect, without the drag in between counting.
+    pub fn released(&self) -> bool {
+        self.hover() && self.events.released && self.pressed_inside()
+    }
+    /// Takes the click, and the press and release around it, so widgets
+    /// drawn later do not see it.
+    pub fn consume_click(&mut self) {
+        self.events.clicked = false;
+        self.events.double_clicked = false;
+        self.events.released = false;
+        self.events.mouse_left = false;
+    }
+    /// Takes the scroll, so widgets drawn later do not see it.
+    pub fn consume_scroll(&mut self) {
+        self.events.scroll = 0.0;
+    }
+    /// Takes a press of `key`, so widgets drawn later do not see it.
+    pub fn consume_key(&mut self, key: KeyCode) {
+        self.events.keys_pressed.retain(|&k| k != key);
+    }
+    /// How many frames in a row, this one included, the cursor has been in
+    /// the current rect, or 0 when it is not. Widgets are told apart by
+    /// their rect, and one that stops asking starts over from 1.
+    pub fn hover_frames(&self) -> u32 {
+        if !self.hover() {
+            return 0;
+        }
+        let id = WidgetId::new(self.rect);
+        let previous = self.hover.hover_counters.get(&id).copied().unwrap_or(0);
+        let mut current = self.hover.current.borrow_mut();
+        let frames = current.entry(id).or_insert(previous.saturating_add(1));
+        *frames
+    }
+    /// Steps for widget `id`, a control such as a stepper button that
+    /// repeats while held: 1 when it is clicked, then as many as
+    /// [`HoldRepeat`] fires this frame for as long as the button stays down
+    /// over the current rect. Letting go or moving off it stops the repeats,
+    /// and they do not start again until it is clicked again.
+    pub fn hold_repeat(&mut self, id: WidgetId) -> u32 {
+        let now = self.events.time;
+        if self.clicked() {
+            self.repeat.held.insert(id, HoldRepeat::new(now));
+            self.repeat.asked.insert(id);
+            return 1;
+        }
+        let held = self.mouse_left();
+        let Some(repeat) = self.repeat.held.get_mut(&id) else {
+            return 0;
+        };
+        if !held {
+            self.repeat.held.remove(&id);
+            return 0;
+        }
+        self.repeat.asked.insert(id);
+        repeat.fires_this_frame(now)
+    }
+    /// Registers the current rect as widget `id` for tooltips, and returns
+    /// whether it has been hovered long enough to call
+    /// [`Canvas::tooltip_show`]. The first hovered widget to register in a
+    /// frame is the one whose tooltip counts.
+    pub fn tooltip_register(&mut self, id: WidgetId) -> bool {
+        if !self.hover() || self.tooltip.registered {
+            return false;
+        }
+        let tooltip = &mut self.tooltip;
+        tooltip.registered = true;
+        if tooltip.hover_widget == Some(id) {
+            tooltip.frames_hovered = tooltip.frames_hovered.saturating_add(1);
+        } else {
+            tooltip.hover_widget = Some(id);
+            tooltip.frames_hovered = 0;
+            tooltip.dismissed = false;
+        }
+        if self.events.clicked || self.events.mouse_left {
+            tooltip.dismissed = true;
+        }
+        !tooltip.dismissed && tooltip.frames_hovered >= TOOLTIP_DELAY
+    }
+    /// Shows `text` by the cursor, unless another tooltip already shows this
+    /// frame. It is drawn later by [`Canvas::draw_tooltip`], on top of the
+    /// widgets drawn after the one it belongs to.
+    pub fn tooltip_show(&mut self, text: &str) {
+        if !self.tooltip.visible {
+            self.tooltip.visible = true;
+            self.tooltip.text.clear();
+            self.tooltip.text.push_str(text);
+        }
+    }
+    /// Shows `text` in the status bar this frame, for widgets to describe
+    /// what is under the cursor without waiting out a tooltip's delay. The
+    /// last widget to call it in a frame wins.
+    pub fn status(&mut self, text: &str) {
+        // what hovering shows on the status bar may change anywhere in the rect
+        self.hover.ask(self.rect, true);
+        self.status.text = Some(text.to_string());
+    }
+    /// Draws the tooltip shown this frame, if any, below and to the right of
+    /// the cursor and kept on screen. It takes no input, so it does not hide
+    /// the cursor from the widget it belongs to.
+    pub fn draw_tooltip(&mut self) {
+        let (Some((x, y)), true) = (self.events.cursor, self.tooltip.visible) else {
+            return;
+        };
+        let text = std::mem::take(&mut self.tooltip.text);
+        let (font_width, font_height) = (self.visuals.font_width(), self.visuals.font_height());
+        let pad = self.visuals.text_size * 2;
+        let width = self.visuals.font.len(&text) * font_width + 2 * pad;
+        let height = font_height + 2 * pad;
+        let rect = Rect {
+            x: x.min(self.pix.width - width).max(0),
+            y: (y + font_height).min(self.pix.height - height).max(0),
+            width,
+            height,
+        };
+        self.absolute(rect, |canvas| {
+            canvas.clear_rect();
+            canvas.outline(canvas.visuals.color);
+            canvas.padded(pad, pad, |canvas| canvas.text(&text));
+        });
+        self.tooltip.text = text;
+    }
+
+    /// Opens a context menu for the widget `id` on a right click over the
+    /// current rect, and while it is open has `f` add its entries and act
+    /// on the one picked. The menu is drawn later by
+    /// [`Canvas::draw_context_menu`], on top of everything else.
+    pub fn context_menu(&mut self, id: WidgetId, f: impl FnOnce(&mut Menu)) {
+        let owned = |menu: &MenuState| menu.open.as_ref().is_some_and(|open| open.owner == id);
+        if self.mouse_right() && !owned(&self.menu) {
+            self.menu.open = Some(OpenMenu {
+                owner: id,
+                at: self.events.cursor.unwrap_or((self.rect.x, self.rect.y)),
+                entries: Vec::new(),
+                rect: None,
+                focused: None,
+                activated: None,
+                drawn: false,
+            });
+        }
+        let font_height = self.visuals.font_line_height();
+        let pad = self.visuals.text_size * 2;
+        let Some(open) = self.menu.open.as_mut().filter(|open| open.owner == id) else {
+            return;
+        };
+
+        // clicks are matched against the lines of the menu drawn last frame
+        let clicked = (self.events.cursor.zip(open.rect))
+            .filter(|&((x, y), rect)| self.events.clicked && rect.contains(x, y))
+            .map(|((_, y), rect)| (y - rect.y - pad).max(0) / font_height.max(1));
+        let picked = open.activated.or(clicked.map(|line| line as usize));
+        let mut menu = Menu {
+            entries: Vec::new(),
+            picked,
+        };
+        f(&mut menu);
+
+        open.drawn = true;
+        if picked.is_some_and(|i| menu.entries.get(i).is_some_and(MenuEntry::enabled)) {
+            self.menu.open = None;
+        } else {
+            open.entries = menu.entries;
+        }
+        if clicked.is_some() {
+            self.consume_click();
+        }
+    }
+    /// Draws the open context menu, if any, by where it was opened and kept
+    /// on screen, as an overlay.
+    pub fn draw_context_menu(&mut self) {
+        let Some(open) = &mut self.menu.open else {
+            return;
+        };
+        let entries = open.entries.clone();
+        let focused = open.focused;
+        let (font_width, font_height) =
+            (self.visuals.font_width(), self.visuals.font_line_height());
+        let pad = self.visuals.text_size * 2;
+        let label_width = entries.iter().map(|entry| match entry {
+            MenuEntry::Item { label, .. } => self.visuals.font.len(label),
+            MenuEntry::Separator => 0,
+        });
+        let width = (label_width.max().unwrap_or(0) + 2) * font_width + 2 * pad;
+        let height = entries.len() as i32 * font_height + 2 * pad;
+        let (x, y) = open.at;
+        let rect = Rect {
+            x: x.min(self.pix.width - width).max(0),
+            y: y.min(self.pix.height - height).max(0),
+            width,
+            height,
+        };
+        open.rect = Some(rect);
+
+        self.overlay(rect, |canvas| {
+            canvas.clear_rect();
+            canvas.outline(canvas.visuals.color);
+            canvas.padded(pad, pad, |canvas| {
+                for (i, entry) in entries.iter().enumerate() {
+                    canvas.cut_top(font_height, |canvas| match entry {
+                        MenuEntry::Item { label, enabled } => {
+                            if *enabled && (canvas.hover() || focused == Some(i)) {
+                                canvas.fill(canvas.visuals.color);
+                                canvas.visuals.color = invert(canvas.visuals.color);
+                            } else if !enabled {
+                                canvas.visuals.color = [128, 128, 128, 255];
+                            }
+                            canvas.padded(font_width, 0, |canvas| canvas.text(label));
+                        }
+                        MenuEntry::Separator => {
+                            let y = canvas.rect.y + canvas.rect.height / 2;
+                            let (x0, x1) = (canvas.rect.x, canvas.rect.x + canvas.rect.width - 1);
+                            canvas.pix.draw_line(x0, y, x1, y, [128, 128, 128, 255]);
+                        }
+                    });
+                }
+            });
+        });
+    }
+
+    pub fn key_pressed(&self, key: KeyCode) -> bool {
+        self.events.keys_pressed.contains(&key)
+    }
+    /// Text typed since the last frame. Unlike mouse input this does not
+    /// depend on hovering, as typing goes to whichever widget has focus.
+    pub fn characters_typed(&self) -> &[char] {
+        &self.events.characters_typed
+    }
+    /// Whether `key` was pressed together with control.
+    pub fn shortcut(&self, key: KeyCode) -> bool {
+        self.events.control && self.key_pressed(key)
+    }
+    /// Whether `key` was pressed together with alt.
+    pub fn alt_shortcut(&self, key: KeyCode) -> bool {
+        self.events.alt && self.key_pressed(key)
+    }
+    /// Whether any binding of `action` was pressed.
+    pub fn action(&self, action: Action) -> bool {
+        action.pressed(&self.events)
+    }
+
+    /// Index of the first of `rects` under the cursor, if the cursor is over
+    /// the current rect.
+    pub fn hover_rect(&self, rects: &[Rect]) -> Option<usize> {
+        let (x, y) = self.hover_cursor()?;
+        rects.iter().position(|rect| rect.contains(x, y))
+    }
+    /// Cell under the cursor of a `cols` by `rows` grid over the current
+    /// rect, divided up like [`Rect::grid`].
+    pub fn hover_grid_cell(&self, cols: usize, rows: usize) -> Option<(usize, usize)> {
+        let (x, y) = self.hover_cursor()?;
+        if cols == 0 || rows == 0 {
+            return None;
+        }
+        let cell_width = (self.rect.width / cols as i32).max(1);
+        let cell_height = (self.rect.height / rows as i32).max(1);
+        let col = (((x - self.rect.x) / cell_width) as usize).min(cols - 1);
+        let row = (((y - self.rect.y) / cell_height) as usize).min(rows - 1);
+        Some((col, row))
+    }
+
+    pub fn scroll(&self) -> f32 {
+        if self.hover() {
+            self.events.scroll
+        } else {
+            0.0
+        }
+    }
+
+    /// Draws `f` in the part of `rect` inside the current rect, so the rect
+    /// never grows and never has a negative size. Rect and visuals are
+    /// restored afterwards.
     pub fn with_rect(&mut self, rect: Rect, f: impl FnOnce(&mut Self)) {
         let pushed_rect = self.rect;
         let pushed_vis = self.visuals.clone();
 
-        self.rect = rect;
+        self.rect = rect.intersect(pushed_rect);
         f(self);
 
         self.visuals = pushed_vis;
         self.rect = pushed_rect;
     }
+    /// Draws `f` in `rect` in screen coordinates, outside of the layout of
+    /// the current rect, for overlays such as toasts and popups. Rect and
+    /// visuals are restored afterwards.
+    pub fn absolute(&mut self, rect: Rect, f: impl FnOnce(&mut Self)) {
+        let pushed_rect = self.rect;
+        self.rect = Rect {
+            x: 0,
+            y: 0,
+            width: self.pix.width,
+            height: self.pix.height,
+        };
+        self.with_rect(rect, f);
+        self.rect = pushed_rect;
+    }
+    /// Draws `f` in `rect` in screen coordinates on top of everything else,
+    /// like [`Canvas::absolute`]. Widgets drawn before it in the next frame
+    /// do not see the cursor over `rect`, so overlays need to be drawn last.
+    pub fn overlay(&mut self, rect: Rect, f: impl FnOnce(&mut Self)) {
+        let in_overlay = std::mem::replace(&mut self.events.in_overlay, true);
+        self.absolute(rect, |canvas| {
+            canvas.events.next_overlays.push(canvas.rect);
+            f(canvas);
+        });
+        self.events.in_overlay = in_overlay;
+    }
+    /// Draws `f` in a `width` by `height` rect in the middle of the current
+    /// rect, made smaller if it does not fit.
     pub fn center(&mut self, width: i32, height: i32, f: impl FnOnce(&mut Self)) {
+        let width = width.clamp(0, self.rect.width);
+        let height = height.clamp(0, self.rect.height);
         self.with_rect(
             Rect {
                 x: self.rect.x + self.rect.width / 2 - width / 2,
@@ -144,69 +1191,1221 @@ impl Canvas<'_> {
             f,
         );
     }
+    /// Draws `f` in the current rect with `x` pixels taken off the left and
+    /// right and `y` off the top and bottom.
+    pub fn padded(&mut self, x: i32, y: i32, f: impl FnOnce(&mut Self)) {
+        let (x, y) = (
+            x.clamp(0, self.rect.width / 2),
+            y.clamp(0, self.rect.height / 2),
+        );
+        self.with_rect(
+            Rect {
+                x: self.rect.x + x,
+                y: self.rect.y + y,
+                width: self.rect.width - 2 * x,
+                height: self.rect.height - 2 * y,
+            },
+            f,
+        );
+    }
+    /// Lowers the text size, down to 1, until `columns` by `lines` characters
+    /// fit in the current rect.
+    pub fn shrink_to_fit(&mut self, columns: i32, lines: i32) {
+        while self.visuals.text_size > 1
+            && (columns * self.visuals.font_width() > self.rect.width
+                || lines * self.visuals.font_line_height() > self.rect.height)
+        {
+            self.visuals.text_size -= 1;
+        }
+    }
     pub fn cut_top(&mut self, height: i32, f: impl FnOnce(&mut Self)) {
+        let height = self.claim(height, self.rect.height);
         let rect = Rect {
             x: self.rect.x,
             y: self.rect.y,
             width: self.rect.width,
             height,
         };
+        // drawn before taking the space away, so the child is clipped to
+        // the rect it was cut from rather than to what is left after it
+        self.with_rect(rect, f);
+
         self.rect.y += height;
         self.rect.height -= height;
-
+    }
+    /// Like [`Canvas::cut_top`], but from the bottom of the current rect.
+    pub fn cut_bottom(&mut self, height: i32, f: impl FnOnce(&mut Self)) {
+        let height = self.claim(height, self.rect.height);
+        let rect = Rect {
+            x: self.rect.x,
+            y: self.rect.y + self.rect.height - height,
+            width: self.rect.width,
+            height,
+        };
         self.with_rect(rect, f);
+
+        self.rect.height -= height;
+    }
+    /// Clamps a size asked for by a cut to the `available` size. Asking for
+    /// more is a layout bug, which debug builds log; the result only clips.
+    fn claim(&self, size: i32, available: i32) -> i32 {
+        #[cfg(debug_assertions)]
+        if size > available {
+            log::debug!("cut of {size} pixels with only {available} left");
+        }
+        size.clamp(0, available.max(0))
     }
+    /// Draws `f` in a `width` by `height` rect at the start of the current
+    /// rect, and takes that space away along `visuals.dir` only.
     pub fn cut(&mut self, width: i32, height: i32, f: impl FnOnce(&mut Self)) {
-        let rect = match self.visuals.dir {
-            CutDir::Horizontal => {
-                let r = Rect {
-                    x: self.rect.x,
-                    y: self.rect.y,
-                    width,
-                    height,
-                };
+        let (width, height) = match self.visuals.dir {
+            CutDir::Horizontal => (self.claim(width, self.rect.width), height),
+            CutDir::Vertical => (width, self.claim(height, self.rect.height)),
+        };
+        let rect = Rect {
+            x: self.rect.x,
+            y: self.rect.y,
+            width,
+            height,
+        };
+        self.with_rect(rect, f);
+
+        match self.visuals.dir {
+            CutDir::Horizontal => {
                 self.rect.x += width;
-                r
+                self.rect.width -= width;
             }
             CutDir::Vertical => {
-                let r = Rect {
-                    x: self.rect.x,
-                    y: self.rect.y,
-                    width,
-                    height,
-                };
                 self.rect.y += height;
-                r
+                self.rect.height -= height;
             }
-        };
-        self.rect.width -= width;
-        self.rect.height -= height;
+        }
+    }
+    /// Draws `f` as a scope named `name` in the `profiling` feature's draw
+    /// times. Without the feature this is only a call to `f`.
+    #[inline(always)]
+    pub fn profile_scope(&mut self, name: &'static str, f: impl FnOnce(&mut Self)) {
+        #[cfg(feature = "profiling")]
+        crate::profiling::begin(name);
+        #[cfg(not(feature = "profiling"))]
+        let _ = name;
+        f(self);
+        #[cfg(feature = "profiling")]
+        crate::profiling::end();
+    }
+    /// Draws `f` in all of the space left along `visuals.dir`, for the last
+    /// child of a row or column.
+    pub fn cut_remaining(&mut self, f: impl FnOnce(&mut Self)) {
+        let Rect { width, height, .. } = self.rect;
+        self.cut(width, height, f);
+    }
 
-        self.with_rect(rect, f);
+    /// Calls `f` for every cell of a `cols` by `rows` grid over the current
+    /// rect, in row-major order.
+    pub fn grid(&mut self, cols: usize, rows: usize, mut f: impl FnMut(&mut Self, usize, usize)) {
+        let rect = self.rect;
+        for row in 0..rows {
+            for col in 0..cols {
+                self.with_rect(rect.grid(cols, rows, col, row), |canvas| {
+                    f(canvas, col, row)
+                });
+            }
+        }
     }
 
-    pub fn clear(&mut self) {
-        self.pix.buf.fill([0, 0, 0, 0]);
+    /// Clears the whole buffer to the background, whatever the current rect.
+    /// Only meant for the start of a frame; widgets use [`Canvas::clear_rect`].
+    pub fn clear_frame(&mut self) {
+        self.pix.clear(BACKGROUND);
+    }
+    /// Clears the current rect to the background.
+    pub fn clear_rect(&mut self) {
+        self.fill(BACKGROUND);
     }
     pub fn fill(&mut self, color: Color) {
-        for y in self.rect.y..self.rect.y + self.rect.height {
-            let start = y * self.pix.width + self.rect.x;
-            let end = start + self.rect.width;
-            self.pix.buf[start as usize..end as usize].fill(color);
+        if self.rect.is_empty() {
+            return;
+        }
+        // rects may hang off the buffer when the layout overflows
+        let (x0, x1) = (
+            self.rect.x.max(0),
+            (self.rect.x + self.rect.width).min(self.pix.width),
+        );
+        let (y0, y1) = (
+            self.rect.y.max(0),
+            (self.rect.y + self.rect.height).min(self.pix.height),
+        );
+        for y in y0..y1 {
+            let row = y * self.pix.width;
+            if x0 < x1 {
+                self.pix.buf[(row + x0) as usize..(row + x1) as usize].fill(color);
+            }
+        }
+    }
+    /// Darkens the current rect to a quarter of its brightness, as a
+    /// backdrop for what is drawn over it.
+    pub fn dim(&mut self) {
+        let (x0, x1) = (
+            self.rect.x.max(0),
+            (self.rect.x + self.rect.width).min(self.pix.width),
+        );
+        let (y0, y1) = (
+            self.rect.y.max(0),
+            (self.rect.y + self.rect.height).min(self.pix.height),
+        );
+        for y in y0..y1 {
+            let row = y * self.pix.width;
+            if x0 < x1 {
+                for c in &mut self.pix.buf[(row + x0) as usize..(row + x1) as usize] {
+                    *c = [c[0] / 4, c[1] / 4, c[2] / 4, c[3]];
+                }
+            }
+        }
+    }
+    /// Draws `pixels`, rows of `width` colors, stretched over the current
+    /// rect without smoothing.
+    pub fn blit(&mut self, pixels: &[Color], width: usize) {
+        let height = pixels.len().checked_div(width).unwrap_or(0);
+        if self.rect.is_empty() || height == 0 {
+            return;
+        }
+        let Rect { x, y, .. } = self.rect;
+        let (w, h) = (self.rect.width as usize, self.rect.height as usize);
+        let (x0, x1) = (x.max(0), (x + w as i32).min(self.pix.width));
+        let (y0, y1) = (y.max(0), (y + h as i32).min(self.pix.height));
+        for py in y0..y1 {
+            let source = &pixels[(py - y) as usize * height / h * width..][..width];
+            let row = (py * self.pix.width) as usize;
+            for px in x0..x1 {
+                self.pix.buf[row + px as usize] = source[(px - x) as usize * width / w];
+            }
+        }
+    }
+    /// Draws an arrow from left to right across the middle of the current rect.
+    pub fn arrow(&mut self) {
+        if self.rect.is_empty() {
+            return;
+        }
+        let y = self.rect.y + self.rect.height / 2;
+        let head = self.visuals.text_size * 3;
+        self.pix.draw_arrow(
+            self.rect.x,
+            y,
+            self.rect.x + self.rect.width - 1,
+            y,
+            head,
+            self.visuals.color,
+        );
+    }
+    /// Draws a border of `text_size` pixels just inside the current rect.
+    pub fn outline(&mut self, color: Color) {
+        if self.rect.is_empty() {
+            return;
+        }
+        let Rect {
+            x,
+            y,
+            width,
+            height,
+        } = self.rect;
+        let t = self.visuals.text_size;
+        for rect in [
+            Rect {
+                x,
+                y,
+                width,
+                height: t,
+            },
+            Rect {
+                x,
+                y: y + height - t,
+                width,
+                height: t,
+            },
+            Rect {
+                x,
+                y,
+                width: t,
+                height,
+            },
+            Rect {
+                x: x + width - t,
+                y,
+                width: t,
+                height,
+            },
+        ] {
+            self.with_rect(rect, |canvas| canvas.fill(color));
         }
     }
+    /// The number of chars of `s` before the gap between them closest to
+    /// the screen column `x`, when `s` is drawn from the left of the current
+    /// rect, to put a text cursor where a click lands. Ligatures and
+    /// combining marks are never split.
+    pub fn text_cursor_from_x(&self, s: &str, x: i32) -> usize {
+        let (scale, x) = (self.visuals.text_size, x - self.rect.x);
+        let edges = self.visuals.font.glyph_edges(s);
+        let closest = edges.min_by_key(|&(_, edge)| (edge * scale - x).abs());
+        closest.map_or(0, |(chars, _)| chars)
+    }
     pub fn text(&mut self, s: &str) {
         Text::new(s, self.visuals.text_size, self.visuals.color).draw(self);
     }
+    /// Like [`Canvas::text`], but cut short with "…" when it is wider than
+    /// the current rect.
+    pub fn text_truncated(&mut self, s: &str) {
+        let (font, font_width) = (self.visuals.font, self.visuals.font_width());
+        if font.len(s) * font_width <= self.rect.width {
+            return self.text(s);
+        }
+        let room = self.rect.width - font.len("…") * font_width;
+        let edges = font.glyph_edges(s);
+        let fits = edges.filter(|&(_, edge)| edge * self.visuals.text_size <= room);
+        let chars = fits.map(|(chars, _)| chars).max().unwrap_or(0);
+        let kept = s.char_indices().nth(chars).map_or(s, |(i, _)| &s[..i]);
+        self.text_fmt(format_args!("{}…", kept.trim_end()));
+    }
+    /// Like [`Canvas::text`] with `format_args!`, without allocating a
+    /// string for it every frame.
+    pub fn text_fmt(&mut self, args: std::fmt::Arguments) {
+        with_scratch(args, |text| self.text(text));
+    }
+    /// Like [`Canvas::text_truncated`] with `format_args!`, without
+    /// allocating a string for it every frame.
+    pub fn text_truncated_fmt(&mut self, args: std::fmt::Arguments) {
+        with_scratch(args, |text| self.text_truncated(text));
+    }
     pub fn tabs<T: Tab>(&mut self, selected: &mut T) {
         Tabs::new(selected).draw(self);
     }
+    pub fn button(&mut self, label: &str) -> bool {
+        let mut button = Button::new(label);
+        button.draw(self);
+        button.clicked
+    }
+    /// A button that repeats while held, returning the steps it took this
+    /// frame as by [`Canvas::hold_repeat`].
+    pub fn repeat_button(&mut self, label: &str) -> u32 {
+        let mut button = Button::new(label);
+        button.repeats = true;
+        button.draw(self);
+        button.steps
+    }
+    pub fn table(&mut self, columns: &[i32], rows: &[Vec<String>], scroll: &mut usize) {
+        ScrollableTable::new(columns, rows, scroll).draw(self);
+    }
+    pub fn wrapped_text(&mut self, s: &str) {
+        WrappedText::new(s).draw(self);
+    }
+    /// Divides the current rect in two along `visuals.dir`, side by side
+    /// when it is horizontal, and draws `f` in each pane. The divider
+    /// between them drags to resize them, with room for a few characters
+    /// left on either side, and a double click puts it back at
+    /// `default_ratio` of the way along. Where it was dragged is kept in
+    /// [`Canvas::panes`] under `id`.
+    ///
+    /// Like [`Canvas::grid`] it takes one closure for both panes, so they
+    /// can both borrow what they draw from.
+    pub fn split_pane(
+        &mut self,
+        id: WidgetId,
+        default_ratio: f32,
+        mut f: impl FnMut(&mut Self, Pane),
+    ) {
+        let horizontal = self.visuals.dir == CutDir::Horizontal;
+        let (start, extent) = match horizontal {
+            true => (self.rect.x, self.rect.width),
+            false => (self.rect.y, self.rect.height),
+        };
+        let gap = DIVIDER * self.visuals.text_size;
+        let min = match horizontal {
+            true => 4 * self.visuals.font_width(),
+            false => 2 * self.visuals.font_height(),
+        };
+        let room = (extent - gap).max(0);
+
+        let mut ratio = self.panes.ratio(id).unwrap_or(default_ratio);
+        if self.panes.dragging == Some(id) {
+            match self.events.cursor.filter(|_| self.events.mouse_left) {
+                Some((x, y)) => {
+                    let at = if horizontal { x } else { y };
+                    ratio = (at - start - gap / 2) as f32 / room.max(1) as f32;
+                }
+                None => self.panes.dragging = None,
+            }
+        }
+        let mut split = (room as f32 * ratio.clamp(0.0, 1.0)).round() as i32;
+        if room >= 2 * min {
+            split = split.clamp(min, room - min);
+        }
+        if self.panes.dragging == Some(id) {
+            self.panes.set_ratio(id, split as f32 / room.max(1) as f32);
+        }
+
+        let along = |canvas: &Self, from: i32, len: i32| match horizontal {
+            true => Rect {
+                x: from,
+                width: len,
+                ..canvas.rect
+            },
+            false => Rect {
+                y: from,
+                height: len,
+                ..canvas.rect
+            },
+        };
+        // the divider grabs a few pixels either side of where it is drawn,
+        // so it is easy to hit at any scale
+        let grab = 2.max(self.visuals.text_size);
+        let handle = along(self, start + split - grab, gap + 2 * grab);
+        let mut hovered = false;
+        self.with_rect(handle, |canvas| {
+            hovered = canvas.hover();
+            if canvas.double_clicked() {
+                canvas.panes.ratios.remove(&id);
+                canvas.panes.dragging = None;
+                canvas.consume_click();
+            } else if canvas.clicked() {
+                canvas.panes.dragging = Some(id);
+                canvas.consume_click();
+            }
+        });
+
+        let (first_rect, second_rect) = (
+            along(self, start, split),
+            along(self, start + split + gap, room - split),
+        );
+        self.with_rect(first_rect, |canvas| f(canvas, Pane::First));
+        self.with_rect(second_rect, |canvas| f(canvas, Pane::Second));
+
+        let color = match hovered || self.panes.dragging == Some(id) {
+            true => self.visuals.color,
+            false => HOVER,
+        };
+        // a line a text pixel thick down the middle of the gap
+        let line = along(
+            self,
+            start + split + self.visuals.text_size,
+            self.visuals.text_size,
+        );
+        self.with_rect(line, |canvas| canvas.fill(color));
+    }
+    /// Draws `f` in a bordered box of `columns` by `lines` characters in the
+    /// middle of the current rect, shrunk to fit when the rect is smaller.
+    pub fn modal(&mut self, columns: i32, lines: i32, f: impl FnOnce(&mut Self)) {
+        let (font_width, font_height) = (self.visuals.font_width(), self.visuals.font_height());
+        self.center(
+            (columns + 2) * font_width,
+            lines * self.visuals.font_line_height() + 2 * font_height,
+            |canvas| {
+                canvas.clear_rect();
+                canvas.outline(canvas.visuals.color);
+                canvas.padded(font_width, font_height, f);
+            },
+        );
+    }
+}
+
+pub struct ScrollableTable<'a> {
+    /// Width of each column in characters.
+    pub columns: &'a [i32],
+    pub rows: &'a [Vec<String>],
+    /// Index of the first visible row.
+    pub scroll: &'a mut usize,
+    /// Lets rows be dragged to another place when set.
+    pub reorder: Option<&'a mut DragReorder>,
+    /// The row dragged and the index it was dropped at, for the caller to
+    /// move it there.
+    pub moved: Option<(usize, usize)>,
+}
+
+impl<'a> ScrollableTable<'a> {
+    pub fn new(columns: &'a [i32], rows: &'a [Vec<String>], scroll: &'a mut usize) -> Self {
+        Self {
+            columns,
+            rows,
+            scroll,
+            reorder: None,
+            moved: None,
+        }
+    }
+
+    /// Lets rows be dragged to another place, keeping the drag in `drag`
+    /// from frame to frame.
+    pub fn reorderable(mut self, drag: &'a mut DragReorder) -> Self {
+        self.reorder = Some(drag);
+        self
+    }
+
+    fn draw_row(columns: &[i32], canvas: &mut Canvas, row: &[String]) {
+        let row_height = canvas.rect.height;
+        canvas.visuals.dir = CutDir::Horizontal;
+        for (cell, width) in row.iter().zip(columns) {
+            canvas.cut(width * canvas.visuals.font_width(), row_height, |canvas| {
+                canvas.text(cell);
+            });
+        }
+    }
+}
+
+impl Widget for ScrollableTable<'_> {
+    fn draw(&mut self, canvas: &mut Canvas) {
+        canvas.clear_rect();
+        let row_height = canvas.visuals.font_line_height();
+        let visible = (canvas.rect.height / row_height.max(1)) as usize;
+        let max_scroll = self.rows.len().saturating_sub(visible);
+
+        let lines = canvas.scroll().round() as isize;
+        *self.scroll = self.scroll.saturating_add_signed(-lines).min(max_scroll);
+
+        let (top, len) = (canvas.rect.y, self.rows.len());
+        if let Some(drag) = self.reorder.as_deref_mut() {
+            self.moved = drag.update(canvas, |(_, y)| y, row_height, len);
+            // a row held over the first or last visible row scrolls the
+            // list a row per frame
+            let y = canvas.events.cursor.map(|(_, y)| y);
+            if let (Some(_), Some(y)) = (drag.lifted(), y) {
+                let bottom = top + visible as i32 * row_height;
+                if y < top + row_height && *self.scroll > 0 {
+                    *self.scroll -= 1;
+                    drag.scroll(-row_height);
+                } else if y >= bottom - row_height && *self.scroll < max_scroll {
+                    *self.scroll += 1;
+                    drag.scroll(row_height);
+                }
+            }
+        }
+
+        for slot in *self.scroll..(*self.scroll + visible).min(len) {
+            let drag = self.reorder.as_deref_mut();
+            let index = drag
+                .as_ref()
+                .map_or(Some(slot), |drag| drag.item_at(slot, row_height, len));
+            canvas.cut_top(row_height, |canvas| {
+                let Some(index) = index else {
+                    // the gap the lifted row would drop into
+                    return;
+                };
+                if let Some(drag) = drag {
+                    drag.press_on(canvas, index, |(_, y)| y);
+                }
+                if canvas.hover() {
+                    canvas.fill(HOVER);
+                }
+                Self::draw_row(self.columns, canvas, &self.rows[index]);
+            });
+        }
+
+        let Some(drag) = self.reorder.as_deref() else {
+            return;
+        };
+        if let Some(from) = drag.lifted() {
+            let pad = canvas.visuals.text_size * 2;
+            let slot = from as i32 - *self.scroll as i32;
+            let rect = Rect {
+                x: canvas.rect.x + pad,
+                y: top + slot * row_height + drag.offset() - pad,
+                width: canvas.rect.width,
+                height: row_height + 2 * pad,
+            };
+            canvas.overlay(rect, |canvas| {
+                canvas.clear_rect();
+                canvas.outline(canvas.visuals.color);
+                canvas.padded(pad, pad, |canvas| {
+                    Self::draw_row(self.columns, canvas, &self.rows[from]);
+                });
+            });
+        }
+    }
+}
+
+/// Dragging an item of a list to move it elsewhere in the list. Pressing
+/// an item and moving the cursor [`DragReorder::THRESHOLD`] pixels lifts it,
+/// the items between close up to leave a gap where it would drop, and
+/// releasing moves it there. Escape puts it back.
+///
+/// Only the state is kept here, for list widgets to drive, and positions
+/// are along the axis the list runs, in pixels. Keep one per list, as the
+/// drag lasts over many frames.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DragReorder {
+    drag: Option<Drag>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Drag {
+    /// Index of the pressed item.
+    from: usize,
+    /// Where it was pressed, moved along as the list scrolls under it.
+    origin: i32,
+    /// Where the cursor is now.
+    at: i32,
+    /// Whether the cursor moved far enough to lift the item.
+    lifted: bool,
+}
+
+impl DragReorder {
+    /// How far the cursor has to move before a pressed item lifts, so
+    /// clicking an item still just clicks it.
+    pub const THRESHOLD: i32 = 4;
+
+    pub const SHORTCUTS: &[Shortcut] = &[Shortcut::new(
+        Category::Editing,
+        "Esc",
+        "Put back the item being dragged",
+    )];
+
+    /// Starts a drag of item `from`, pressed at `at`.
+    pub fn press(&mut self, from: usize, at: i32) {
+        self.drag = Some(Drag {
+            from,
+            origin: at,
+            at,
+            lifted: false,
+        });
+    }
+
+    /// Moves the drag to `at`, lifting the item once it is far enough from
+    /// where it was pressed.
+    pub fn drag_to(&mut self, at: i32) {
+        if let Some(drag) = &mut self.drag {
+            drag.at = at;
+            drag.lifted |= (at - drag.origin).abs() >= Self::THRESHOLD;
+        }
+    }
+
+    /// Keeps the lifted item under the cursor while the list scrolls on by
+    /// `by` pixels.
+    pub fn scroll(&mut self, by: i32) {
+        if let Some(drag) = &mut self.drag {
+            drag.origin -= by;
+        }
+    }
+
+    /// Drops the item, returning where it moved from and to, or `None` if
+    /// it was not lifted or lands where it was, in a list of `len` items
+    /// `size` pixels apart.
+    pub fn release(&mut self, size: i32, len: usize) -> Option<(usize, usize)> {
+        let to = self.target(size, len);
+        let from = self.drag.take()?.from;
+        to.filter(|&to| to != from).map(|to| (from, to))
+    }
+
+    /// Puts the item back where it was.
+    pub fn cancel(&mut self) {
+        self.drag = None;
+    }
+
+    /// Whether an item is pressed, lifted or not.
+    pub fn active(&self) -> bool {
+        self.drag.is_some()
+    }
+
+    /// The item lifted, if any.
+    pub fn lifted(&self) -> Option<usize> {
+        self.drag.filter(|drag| drag.lifted).map(|drag| drag.from)
+    }
+
+    /// How far the lifted item has moved from its place, or 0 when none is.
+    pub fn offset(&self) -> i32 {
+        self.drag
+            .filter(|drag| drag.lifted)
+            .map_or(0, |drag| drag.at - drag.origin)
+    }
+
+    /// The index the lifted item would drop at, the place its middle is
+    /// over, in a list of `len` items `size` pixels apart.
+    pub fn target(&self, size: i32, len: usize) -> Option<usize> {
+        let drag = self.drag.filter(|drag| drag.lifted && len > 0)?;
+        let moved = (drag.at - drag.origin) as f32 / size.max(1) as f32;
+        let to = drag.from as i32 + moved.round() as i32;
+        Some(to.clamp(0, len as i32 - 1) as usize)
+    }
+
+    /// The item to draw in place `slot` of a list of `len` items `size`
+    /// pixels apart, with the others closed up around the gap the lifted
+    /// item would drop into, or `None` for the gap itself.
+    pub fn item_at(&self, slot: usize, size: i32, len: usize) -> Option<usize> {
+        let (Some(from), Some(to)) = (self.lifted(), self.target(size, len)) else {
+            return Some(slot);
+        };
+        // the place among the items that are not lifted
+        let rest = match slot.cmp(&to) {
+            Ordering::Less => slot,
+            Ordering::Equal => return None,
+            Ordering::Greater => slot - 1,
+        };
+        Some(if rest < from { rest } else { rest + 1 })
+    }
+
+    /// Starts a drag of item `index` when it is pressed in the current rect,
+    /// at the position `axis` picks from the cursor.
+    pub fn press_on(&mut self, canvas: &Canvas, index: usize, axis: fn((i32, i32)) -> i32) {
+        if self.drag.is_none() && canvas.clicked() {
+            if let Some(cursor) = canvas.events.cursor {
+                self.press(index, axis(cursor));
+            }
+        }
+    }
+
+    /// Follows the cursor while the button is held, on the axis `axis`
+    /// picks, and returns the move when it is released. Escape cancels,
+    /// and is taken so it does nothing else, and so does the pressed item
+    /// going away.
+    pub fn update(
+        &mut self,
+        canvas: &mut Canvas,
+        axis: fn((i32, i32)) -> i32,
+        size: i32,
+        len: usize,
+    ) -> Option<(usize, usize)> {
+        let drag = self.drag?;
+        if drag.from >= len {
+            self.cancel();
+            return None;
+        }
+        if canvas.key_pressed(KeyCode::Escape) {
+            canvas.consume_key(KeyCode::Escape);
+            self.cancel();
+            return None;
+        }
+        if let Some(cursor) = canvas.events.cursor {
+            self.drag_to(axis(cursor));
+        }
+        if canvas.events.mouse_left {
+            return None;
+        }
+        let moved = self.release(size, len);
+        self.cancel();
+        moved
+    }
+}
+
+/// A loading indicator of 8 dots around a circle, with one bright dot that
+/// moves on by one every time it is drawn.
+pub struct Spinner {
+    pub frame: u32,
+}
+
+impl Spinner {
+    const DOTS: u32 = 8;
+
+    pub fn new() -> Self {
+        Self { frame: 0 }
+    }
+}
+
+impl Default for Spinner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Widget for Spinner {
+    fn draw(&mut self, canvas: &mut Canvas) {
+        let size = canvas.rect.width.min(canvas.rect.height);
+        let (cx, cy) = (
+            canvas.rect.x + canvas.rect.width / 2,
+            canvas.rect.y + canvas.rect.height / 2,
+        );
+        let dot = (size as f32 / 8.0).max(0.5);
+        let radius = size as f32 / 2.0 - dot;
+        let color = canvas.visuals.color;
+        for i in 0..Self::DOTS {
+            let angle = i as f32 / Self::DOTS as f32 * std::f32::consts::TAU;
+            // the frame is not blended, so dimming is done by fading to black
+            let alpha = if i == self.frame % Self::DOTS {
+                255
+            } else {
+                80
+            };
+            let faded = color.map(|c| (c as u32 * alpha / 255) as u8);
+            canvas.pix.fill_circle(
+                cx + (radius * angle.sin()).round() as i32,
+                cy - (radius * angle.cos()).round() as i32,
+                dot,
+                [faded[0], faded[1], faded[2], color[3]],
+            );
+        }
+        self.frame = self.frame.wrapping_add(1);
+    }
+}
+
+/// A level meter: a bar filled from the left to `level`, between 0 and 1,
+/// that turns from green to red as it fills.
+pub struct VuMeter {
+    pub level: f64,
+}
+
+impl Widget for VuMeter {
+    fn draw(&mut self, canvas: &mut Canvas) {
+        let level = self.level.clamp(0.0, 1.0);
+        let (low, high) = ([96, 208, 96], [255, 96, 96]);
+        let [r, g, b]: [u8; 3] = std::array::from_fn(|i| {
+            (low[i] as f64 + (high[i] as f64 - low[i] as f64) * level).round() as u8
+        });
+
+        // a thin bar through the middle of a line of text
+        let t = canvas.visuals.text_size;
+        let height = canvas.visuals.font_height() / 2;
+        canvas.center(canvas.rect.width, height, |canvas| {
+            canvas.outline(canvas.visuals.color);
+            canvas.padded(2 * t, 2 * t, |canvas| {
+                let width = (canvas.rect.width as f64 * level).round() as i32;
+                canvas.visuals.dir = CutDir::Horizontal;
+                canvas.cut(width, canvas.rect.height, |canvas| {
+                    canvas.fill([r, g, b, 255])
+                });
+            });
+        });
+    }
+}
+
+/// Keyboard focus in a grid of cells, as `(col, row)`. The arrow keys move
+/// it a cell at a time, Home and End to the ends of its row, and Page Up and
+/// Page Down by a screenful of rows. It is `None` until a key is pressed, so
+/// grids can leave the focus ring out while the mouse is used.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GridNav {
+    pub focused: Option<(usize, usize)>,
+}
+
+impl GridNav {
+    pub const SHORTCUTS: &[Shortcut] = &[
+        Shortcut::new(
+            Category::Navigation,
+            "Arrows",
+            "Move through the chord grid",
+        ),
+        Shortcut::new(Category::Navigation, "Home, End", "Start or end of the row"),
+        Shortcut::new(
+            Category::Navigation,
+            "PageUp, PageDown",
+            "Up or down a screenful",
+        ),
+        Shortcut::new(Category::Navigation, "Enter", "Select the focused chord"),
+    ];
+
+    /// Moves the focus for the keys pressed this frame in a `cols` by `rows`
+    /// grid with `page` rows on screen, and returns whether it moved. The
+    /// first key moves from `start`, such as the selected cell, or focuses
+    /// the first cell when there is none. Keys held with Ctrl or Alt are
+    /// left to shortcuts.
+    pub fn handle_keys(
+        &mut self,
+        events: &Events,
+        cols: usize,
+        rows: usize,
+        page: usize,
+        start: Option<(usize, usize)>,
+    ) -> bool {
+        if cols == 0 || rows == 0 {
+            self.focused = None;
+            return false;
+        }
+        if events.control || events.alt {
+            return false;
+        }
+        let before = self.focused;
+        let clamp = |(col, row): (usize, usize)| (col.min(cols - 1), row.min(rows - 1));
+        let Some(mut focus) = self.focused.or(start).map(clamp) else {
+            let navigating = events.keys_pressed.iter().any(|&key| Self::moves(key));
+            if navigating {
+                self.focused = Some((0, 0));
+            }
+            return navigating;
+        };
+        let mut navigating = false;
+        for &key in &events.keys_pressed {
+            let (col, row) = &mut focus;
+            match key {
+                KeyCode::ArrowLeft => *col = col.saturating_sub(1),
+                KeyCode::ArrowRight => *col += 1,
+                KeyCode::ArrowUp => *row = row.saturating_sub(1),
+                KeyCode::ArrowDown => *row += 1,
+                KeyCode::Home => *col = 0,
+                KeyCode::End => *col = cols - 1,
+                KeyCode::PageUp => *row = row.saturating_sub(page.max(1)),
+                KeyCode::PageDown => *row += page.max(1),
+                _ => continue,
+            }
+            navigating = true;
+            focus = clamp(focus);
+        }
+        if navigating {
+            self.focused = Some(focus);
+        }
+        self.focused != before
+    }
+
+    /// Whether `key` is one that moves the focus.
+    pub fn moves(key: KeyCode) -> bool {
+        matches!(
+            key,
+            KeyCode::ArrowLeft
+                | KeyCode::ArrowRight
+                | KeyCode::ArrowUp
+                | KeyCode::ArrowDown
+                | KeyCode::Home
+                | KeyCode::End
+                | KeyCode::PageUp
+                | KeyCode::PageDown
+        )
+    }
+
+    /// The focused cell, if Enter was pressed on it this frame. Alt+Enter is
+    /// left to the fullscreen shortcut.
+    pub fn activated(&self, events: &Events) -> Option<(usize, usize)> {
+        let enter = events.keys_pressed.contains(&KeyCode::Enter);
+        self.focused
+            .filter(|_| enter && !events.alt && !events.control)
+    }
+
+    /// The first of `visible` rows to show from `scroll` on, scrolled just
+    /// far enough that the focused row is among them.
+    pub fn scroll_into_view(&self, scroll: usize, visible: usize) -> usize {
+        match self.focused {
+            Some((_, row)) if row < scroll => row,
+            Some((_, row)) if row >= scroll + visible.max(1) => row + 1 - visible.max(1),
+            _ => scroll,
+        }
+    }
+}
+
+/// Colors offered as swatches by [`ColorPicker`].
+pub const SWATCHES: [Color; 8] = [
+    [255, 255, 255, 255],
+    [160, 160, 160, 255],
+    [255, 96, 96, 255],
+    [255, 160, 64, 255],
+    [255, 208, 96, 255],
+    [96, 208, 96, 255],
+    [96, 160, 255, 255],
+    [208, 128, 255, 255],
+];
+
+/// Picks a color from a row of [`SWATCHES`], or from a saturation and value
+/// square and a hue strip once expanded, with its hex code alongside to read
+/// or type in. Keep one per color edited, as it remembers the hue of greys
+/// and the text being typed.
+pub struct ColorPicker {
+    pub color: Color,
+    /// Whether the last draw picked a new color.
+    pub changed: bool,
+    expanded: bool,
+    hsv: Hsv,
+    /// The hex code being typed, while the field has focus.
+    editing: Option<String>,
+    /// The gradients, rendered when the picker expands and when the hue of
+    /// the square changes rather than every frame.
+    hue_strip: Vec<Color>,
+    square: Vec<Color>,
+    square_hue: u16,
+}
+
+impl ColorPicker {
+    /// Samples along each side of the gradients.
+    const SAMPLES: usize = 32;
+    /// Lines the gradients take below the swatches when expanded.
+    pub const EXPANDED_LINES: i32 = 8;
+
+    pub fn new(color: Color) -> Self {
+        Self {
+            color,
+            changed: false,
+            expanded: false,
+            hsv: Hsv::from_rgb(color),
+            editing: None,
+            hue_strip: Vec::new(),
+            square: Vec::new(),
+            square_hue: 0,
+        }
+    }
+
+    /// Whether the hex code is being typed.
+    pub fn editing(&self) -> bool {
+        self.editing.is_some()
+    }
+
+    pub fn set_color(&mut self, color: Color) {
+        self.color = color;
+        self.hsv = Hsv::from_rgb(color);
+        self.editing = None;
+    }
+
+    /// Lines the picker takes, with the gradients when expanded.
+    pub fn lines(&self) -> i32 {
+        1 + if self.expanded {
+            Self::EXPANDED_LINES
+        } else {
+            0
+        }
+    }
+
+    fn pick(&mut self, hsv: Hsv) {
+        self.hsv = hsv;
+        self.color = hsv.to_rgb();
+        self.changed = true;
+    }
+
+    fn render(&mut self) {
+        let n = Self::SAMPLES;
+        let step = |i: usize| (i * 255 / (n - 1)) as u8;
+        if self.hue_strip.is_empty() {
+            self.hue_strip = (0..n)
+                .map(|i| {
+                    let h = (i * HUE_STEPS as usize / n) as u16;
+                    Hsv { h, s: 255, v: 255 }.to_rgb()
+                })
+                .collect();
+        }
+        if self.square.is_empty() || self.square_hue != self.hsv.h {
+            let h = self.hsv.h;
+            self.square = (0..n * n)
+                .map(|i| Hsv {
+                    h,
+                    s: step(i % n),
+                    v: 255 - step(i / n),
+                })
+                .map(Hsv::to_rgb)
+                .collect();
+            self.square_hue = h;
+        }
+    }
+
+    fn draw_gradients(&mut self, canvas: &mut Canvas) {
+        let (font_width, font_height) = (canvas.visuals.font_width(), canvas.visuals.font_height());
+        let t = canvas.visuals.text_size;
+        let side = Self::EXPANDED_LINES * font_height - 2 * t;
+        // where the cursor is across the current rect, from 0 to 255
+        let fraction = |canvas: &Canvas, (x, y): (i32, i32)| {
+            let of =
+                |p: i32, start: i32, len: i32| ((p - start) * 255 / (len - 1).max(1)).clamp(0, 255);
+            (
+                of(x, canvas.rect.x, canvas.rect.width),
+                of(y, canvas.rect.y, canvas.rect.height),
+            )
+        };
+        canvas.visuals.dir = CutDir::Horizontal;
+        canvas.padded(0, t, |canvas| {
+            canvas.cut(side, side, |canvas| {
+                if let Some(cursor) = canvas.events.cursor.filter(|_| canvas.pressed()) {
+                    let (s, v) = fraction(canvas, cursor);
+                    let (s, v) = (s as u8, 255 - v as u8);
+                    self.pick(Hsv { s, v, ..self.hsv });
+                    canvas.consume_click();
+                }
+                canvas.blit(&self.square, Self::SAMPLES);
+                let x = canvas.rect.x + self.hsv.s as i32 * (side - 1) / 255;
+                let y = canvas.rect.y + (255 - self.hsv.v as i32) * (side - 1) / 255;
+                let marker = Rect {
+                    x: x - 2 * t,
+                    y: y - 2 * t,
+                    width: 4 * t,
+                    height: 4 * t,
+                };
+                canvas.with_rect(marker, |canvas| canvas.outline(contrasting(self.color)));
+            });
+            canvas.cut(font_width, side, |_| {});
+            canvas.cut(2 * font_width, side, |canvas| {
+                if let Some(cursor) = canvas.events.cursor.filter(|_| canvas.pressed()) {
+                    let (_, y) = fraction(canvas, cursor);
+                    let h = (y as u32 * (HUE_STEPS as u32 - 1) / 255) as u16;
+                    self.pick(Hsv { h, ..self.hsv });
+                    self.render();
+                    canvas.consume_click();
+                }
+                canvas.blit(&self.hue_strip, 1);
+                let y = canvas.rect.y
+                    + (self.hsv.h as u32 * (side as u32 - 1) / (HUE_STEPS as u32 - 1)) as i32;
+                let marker = Rect {
+                    y: y - t / 2,
+                    height: t,
+                    ..canvas.rect
+                };
+                let hue = Hsv {
+                    s: 255,
+                    v: 255,
+                    ..self.hsv
+                }
+                .to_rgb();
+                canvas.with_rect(marker, |canvas| canvas.fill(contrasting(hue)));
+            });
+        });
+    }
+}
+
+impl Widget for ColorPicker {
+    fn draw(&mut self, canvas: &mut Canvas) {
+        self.changed = false;
+        let (font_width, font_height) = (canvas.visuals.font_width(), canvas.visuals.font_height());
+        let t = canvas.visuals.text_size;
+        canvas.visuals.dir = CutDir::Vertical;
+        canvas.cut_top(font_height, |canvas| {
+            canvas.visuals.dir = CutDir::Horizontal;
+            for swatch in SWATCHES {
+                canvas.cut(2 * font_width, font_height, |canvas| {
+                    if canvas.clicked() {
+                        self.set_color(swatch);
+                        self.changed = true;
+                        canvas.consume_click();
+                    }
+                    if canvas.hover() {
+                        canvas.fill(HOVER);
+                    }
+                    if swatch == self.color {
+                        canvas.outline(canvas.visuals.color);
+                    }
+                    canvas.padded(2 * t, 2 * t, |canvas| canvas.fill(swatch));
+                });
+            }
+            canvas.cut(font_width, font_height, |_| {});
+
+            // clicking the hex code edits it, until Enter or another click
+            match &mut self.editing {
+                Some(text) => {
+                    let typed = canvas.characters_typed().iter();
+                    text.extend(typed.filter(|c| c.is_ascii_hexdigit()).take(7 - text.len()));
+                    if canvas.key_pressed(KeyCode::Backspace) && text.len() > 1 {
+                        text.pop();
+                    }
+                    if let Some(color) = parse_hex(text).filter(|_| text.len() == 7) {
+                        if color != self.color {
+                            self.color = color;
+                            self.hsv = Hsv::from_rgb(color);
+                            self.changed = true;
+                        }
+                    }
+                    let label = format!("{text}_");
+                    if canvas.button(&label) || canvas.key_pressed(KeyCode::Enter) {
+                        self.editing = None;
+                    }
+                }
+                None => {
+                    if canvas.button(&to_hex(self.color)) {
+                        self.editing = Some("#".to_string());
+                    }
+                }
+            }
+
+            if canvas.button(if self.expanded { "▲" } else { "▼" }) {
+                self.expanded = !self.expanded;
+                if self.expanded {
+                    self.render();
+                }
+            }
+        });
+        if self.expanded {
+            canvas.cut_top(Self::EXPANDED_LINES * font_height, |canvas| {
+                self.draw_gradients(canvas);
+            });
+        }
+    }
+}
+
+pub struct Button<'a> {
+    pub label: &'a str,
+    pub clicked: bool,
+    /// Whether holding the button repeats it, telling buttons apart by
+    /// their label.
+    pub repeats: bool,
+    /// Steps taken this frame by a button that repeats.
+    pub steps: u32,
+}
+
+impl<'a> Button<'a> {
+    pub fn new(label: &'a str) -> Self {
+        Self {
+            label,
+            clicked: false,
+            repeats: false,
+            steps: 0,
+        }
+    }
+}
+
+/// Text broken into lines at spaces to fit the width of the current rect,
+/// one line per cut from the top. Newlines start a new line.
+pub struct WrappedText<'a> {
+    pub text: &'a str,
+}
+
+impl<'a> WrappedText<'a> {
+    pub fn new(text: &'a str) -> Self {
+        Self { text }
+    }
+
+    /// The lines of the text when at most `columns` characters fit on each,
+    /// as measured by `len`. Words longer than a line are left to overflow.
+    pub fn lines(&self, columns: i32, len: impl Fn(&str) -> i32) -> Vec<String> {
+        let mut lines = Vec::new();
+        for paragraph in self.text.split('\n') {
+            let mut line = String::new();
+            for word in paragraph.split(' ') {
+                let candidate = if line.is_empty() {
+                    word.to_string()
+                } else {
+                    format!("{line} {word}")
+                };
+                if len(&candidate) > columns && !line.is_empty() {
+                    lines.push(std::mem::replace(&mut line, word.to_string()));
+                } else {
+                    line = candidate;
+                }
+            }
+            lines.push(line);
+        }
+        lines
+    }
+}
+
+impl Widget for WrappedText<'_> {
+    fn draw(&mut self, canvas: &mut Canvas) {
+        let columns = canvas.rect.width / canvas.visuals.font_width().max(1);
+        let lines = self.lines(columns, |s| canvas.visuals.font.len(s));
+        for line in lines {
+            canvas.cut_top(canvas.visuals.font_line_height(), |canvas| {
+                canvas.text(&line)
+            });
+        }
+    }
+}
+
+impl Widget for Button<'_> {
+    fn draw(&mut self, canvas: &mut Canvas) {
+        let width = (canvas.visuals.font.len(self.label) + 2) * canvas.visuals.font_width();
+        let height = canvas.visuals.font_height();
+        canvas.cut(width, height, |canvas| {
+            self.clicked = canvas.clicked();
+            if self.repeats {
+                self.steps = canvas.hold_repeat(WidgetId::new(self.label));
+            }
+            let len = canvas.visuals.font.len(self.label);
+            let label = self.label;
+            if canvas.hover() {
+                canvas.fill(canvas.visuals.color);
+                canvas.visuals.color = invert(canvas.visuals.color);
+            }
+            canvas.center(len * canvas.visuals.font_width(), height, |canvas| {
+                canvas.text(label);
+            });
+        });
+    }
 }
 
 pub trait Widget {
     fn draw(&mut self, canvas: &mut Canvas);
 }
 
+/// A widget of any type, for holding different widgets side by side.
+pub type BoxedWidget<'a> = Box<dyn Widget + 'a>;
+
+impl Widget for BoxedWidget<'_> {
+    fn draw(&mut self, canvas: &mut Canvas) {
+        (**self).draw(canvas);
+    }
+}
+
 pub struct Text<'a> {
     pub text: &'a str,
     pub scale: i32,
@@ -221,20 +2420,586 @@ impl<'a> Text<'a> {
 
 impl Widget for Text<'_> {
     fn draw(&mut self, canvas: &mut Canvas) {
-        let len = canvas.visuals.font.draw(
-            &mut canvas.pix,
-            self.text,
-            (
-                canvas.rect.x,
-                canvas.rect.y + canvas.visuals.font.height * self.scale,
-            ),
-            self.color,
-            self.scale,
-        );
-        canvas.cut(
-            len * canvas.visuals.font.width * self.scale,
-            canvas.visuals.font.height * self.scale,
-            |_| {},
+        canvas.profile_scope("text", |canvas| {
+            let len = canvas.visuals.font.draw(
+                &mut canvas.pix,
+                self.text,
+                (
+                    canvas.rect.x,
+                    canvas.rect.y + canvas.visuals.font.height * self.scale,
+                ),
+                self.color,
+                self.scale,
+            );
+            canvas.cut(
+                len * canvas.visuals.font.width * self.scale,
+                canvas.visuals.font.height * self.scale,
+                |_| {},
+            );
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::font::COZETTE;
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Two {
+        A,
+        B,
+    }
+
+    impl Tab for Two {
+        type Iterator = std::array::IntoIter<Self, 2>;
+        fn iter() -> Self::Iterator {
+            [Two::A, Two::B].into_iter()
+        }
+        fn name(&self) -> &str {
+            match self {
+                Two::A => "A",
+                Two::B => "B",
+            }
+        }
+    }
+
+    /// Draws on a blank 120 by 40 frame with `events`, and returns the
+    /// frame as one value per pixel.
+    fn render(events: Events, draw: impl FnOnce(&mut Canvas)) -> Vec<u8> {
+        let font = Font::from_bdf_bytes(COZETTE, 6, 13).unwrap();
+        let (width, height) = (120, 40);
+        let mut frame = vec![0; (width * height * 4) as usize];
+        let mut canvas = Canvas {
+            pix: PixBuf::from_pixels_frame(&mut frame, width, height),
+            rect: Rect {
+                x: 0,
+                y: 0,
+                width,
+                height,
+            },
+            visuals: Visuals {
+                font: &font,
+                text_size: 1,
+                dir: CutDir::Vertical,
+                color: [255, 255, 255, 255],
+                line_spacing: 0,
+            },
+            events,
+            tooltip: TooltipState::default(),
+            hover: HoverState::default(),
+            repeat: RepeatState::default(),
+            menu: MenuState::default(),
+            panes: PaneState::default(),
+            status: StatusState::default(),
+        };
+        draw(&mut canvas);
+        drop(canvas);
+        frame.chunks(4).map(|pixel| pixel[0]).collect()
+    }
+
+    /// The hover state left by drawing a frame with `draw`, with the cursor
+    /// nowhere.
+    fn hovered(draw: impl FnOnce(&mut Canvas)) -> HoverState {
+        let mut hover = HoverState::default();
+        render(Events::default(), |canvas| {
+            draw(canvas);
+            canvas.hover.end_frame();
+            hover = std::mem::take(&mut canvas.hover);
+        });
+        hover
+    }
+
+    const BUTTON: Rect = Rect {
+        x: 10,
+        y: 10,
+        width: 20,
+        height: 10,
+    };
+
+    #[test]
+    fn moving_over_nothing_hovered_draws_nothing() {
+        let hover = hovered(|canvas| {
+            canvas.with_rect(BUTTON, |canvas| {
+                canvas.clicked();
+            });
+        });
+        let moved = |from, to| hover.cursor_moved(Some(from), Some(to));
+        assert!(!moved((0, 0), (50, 30)));
+        assert!(!moved((12, 12), (28, 18)));
+        assert!(moved((0, 0), (12, 12)));
+        assert!(moved((12, 12), (0, 0)));
+        assert!(hover.cursor_moved(None, Some((12, 12))));
+        assert!(!hover.cursor_moved(None, Some((0, 0))));
+    }
+
+    #[test]
+    fn moving_inside_what_follows_the_cursor_draws() {
+        let follows = [
+            hovered(|canvas| {
+                canvas.with_rect(BUTTON, |canvas| {
+                    canvas.hover_cursor();
+                });
+            }),
+            hovered(|canvas| canvas.with_rect(BUTTON, |canvas| canvas.status("hint"))),
+        ];
+        for hover in follows {
+            assert!(hover.cursor_moved(Some((12, 12)), Some((28, 18))));
+            assert!(!hover.cursor_moved(Some((12, 12)), Some((12, 12))));
+            assert!(!hover.cursor_moved(Some((0, 0)), Some((50, 30))));
+        }
+    }
+
+    #[test]
+    fn only_the_last_frame_counts() {
+        let mut hover = hovered(|canvas| {
+            canvas.with_rect(BUTTON, |canvas| {
+                canvas.hover();
+            });
+        });
+        hover.end_frame();
+        assert!(!hover.cursor_moved(Some((0, 0)), Some((12, 12))));
+    }
+
+    /// The left button at `cursor`, pressed at `origin` and still `held`, or
+    /// released this frame if not.
+    fn mouse(cursor: Option<(i32, i32)>, origin: (i32, i32), held: bool) -> Events {
+        Events {
+            cursor,
+            press_origin: Some(origin),
+            clicked: held && cursor == Some(origin),
+            mouse_left: held,
+            released: !held,
+            ..Events::default()
+        }
+    }
+
+    /// Draws the tabs side by side, with A on the left half.
+    fn tabs(selected: &mut Two, events: Events) -> Vec<u8> {
+        render(events, |canvas| {
+            canvas.visuals.dir = CutDir::Horizontal;
+            canvas.tabs(selected);
+        })
+    }
+
+    const A: (i32, i32) = (30, 20);
+    const B: (i32, i32) = (90, 20);
+
+    #[test]
+    fn tabs_change_on_a_click_inside_one_tab() {
+        let mut selected = Two::A;
+        tabs(&mut selected, mouse(Some(B), B, true));
+        assert_eq!(selected, Two::A);
+        tabs(&mut selected, mouse(Some(B), B, false));
+        assert_eq!(selected, Two::B);
+        tabs(&mut selected, Events::default());
+        assert_eq!(selected, Two::B);
+    }
+
+    #[test]
+    fn dragging_across_tabs_changes_nothing() {
+        let mut selected = Two::A;
+        for cursor in [Some(A), Some(B), Some((119, 39))] {
+            tabs(&mut selected, mouse(cursor, A, true));
+            assert_eq!(selected, Two::A);
+        }
+        // let go over the other tab, or outside the window
+        tabs(&mut selected, mouse(Some(B), A, false));
+        assert_eq!(selected, Two::A);
+        tabs(&mut selected, mouse(None, A, false));
+        assert_eq!(selected, Two::A);
+
+        let mut selected = Two::B;
+        tabs(&mut selected, mouse(Some(A), B, false));
+        assert_eq!(selected, Two::B);
+    }
+
+    #[test]
+    fn tabs_show_the_press_under_the_held_button() {
+        // the corner pixels of A and B, out of the way of the names
+        let (a, b) = (120 + 1, 120 + 61);
+        let mut selected = Two::A;
+        let frame = tabs(&mut selected, mouse(Some(B), B, true));
+        assert_eq!((frame[a], frame[b]), (255, HOVER[0]));
+        // only while the cursor stays on the tab it was pressed on
+        let frame = tabs(&mut selected, mouse(Some(A), B, true));
+        assert_eq!((frame[a], frame[b]), (255, 0));
+    }
+
+    #[test]
+    fn only_the_first_of_stacked_widgets_takes_a_click() {
+        let (mut tab, mut below) = (Two::A, false);
+        render(mouse(Some(B), B, false), |canvas| {
+            canvas.visuals.dir = CutDir::Horizontal;
+            canvas.tabs(&mut tab);
+            canvas.with_rect(canvas.rect, |canvas| below = canvas.released());
+        });
+        assert_eq!(tab, Two::B);
+        assert!(!below);
+
+        let (mut top, mut below) = (false, false);
+        render(mouse(Some(A), A, true), |canvas| {
+            let rect = canvas.rect;
+            canvas.with_rect(rect, |canvas| {
+                top = canvas.clicked();
+                canvas.consume_click();
+            });
+            canvas.with_rect(rect, |canvas| {
+                below = canvas.clicked() || canvas.mouse_left()
+            });
+        });
+        assert!(top && !below);
+    }
+
+    #[test]
+    fn overlays_of_the_last_frame_hide_what_is_under_them() {
+        let overlay = Rect {
+            x: 0,
+            y: 0,
+            width: 60,
+            height: 40,
+        };
+        let (mut under, mut over) = (false, false);
+        let events = Events {
+            overlays: vec![overlay],
+            ..mouse(Some(A), A, true)
+        };
+        render(events, |canvas| {
+            under = canvas.button("under");
+            canvas.overlay(overlay, |canvas| over = canvas.clicked());
+        });
+        assert!(!under && over);
+    }
+
+    #[test]
+    fn clear_rect_keeps_to_the_rect() {
+        let rect = Rect {
+            x: 10,
+            y: 5,
+            width: 20,
+            height: 8,
+        };
+        let frame = render(Events::default(), |canvas| {
+            canvas.fill([255; 4]);
+            canvas.with_rect(rect, Canvas::clear_rect);
+        });
+        for (i, &pixel) in frame.iter().enumerate() {
+            let (x, y) = (i as i32 % 120, i as i32 / 120);
+            let cleared = if rect.contains(x, y) {
+                BACKGROUND[0]
+            } else {
+                255
+            };
+            assert_eq!(pixel, cleared, "({x}, {y})");
+        }
+    }
+
+    #[test]
+    fn clear_rect_clips_to_the_frame() {
+        let rect = Rect {
+            x: 100,
+            y: -10,
+            width: 50,
+            height: 20,
+        };
+        let frame = render(Events::default(), |canvas| {
+            canvas.fill([255; 4]);
+            canvas.with_rect(rect, Canvas::clear_rect);
+        });
+        let cleared = frame.iter().filter(|&&pixel| pixel == BACKGROUND[0]);
+        assert_eq!(cleared.count(), 20 * 10);
+
+        let frame = render(Events::default(), |canvas| {
+            canvas.fill([255; 4]);
+            canvas.clear_frame();
+        });
+        assert!(frame.iter().all(|&pixel| pixel == BACKGROUND[0]));
+    }
+
+    #[test]
+    fn boxed_widgets_of_different_types_draw_in_turn() {
+        let mut selected = Two::A;
+        // a click on the second tab, below the line of text
+        let click = (90, 30);
+        let events = Events {
+            cursor: Some(click),
+            press_origin: Some(click),
+            released: true,
+            ..Events::default()
+        };
+        let frame = render(events, |canvas| {
+            let mut widgets: Vec<BoxedWidget> = vec![
+                Box::new(Text::new("Boxed", 1, [255, 255, 255, 255])),
+                Box::new(Tabs::new(&mut selected)),
+            ];
+            for widget in &mut widgets {
+                widget.draw(canvas);
+            }
+        });
+        assert_eq!(selected, Two::B);
+        // the text took the top line, so the tabs were drawn under it
+        assert!(frame[..120 * 13].iter().any(|&pixel| pixel != 0));
+        assert!(frame[120 * 13..].iter().any(|&pixel| pixel != 0));
+    }
+
+    #[test]
+    fn quick_clicks_do_not_repeat() {
+        let mut repeat = HoldRepeat::new(ms(1000));
+        assert_eq!(repeat.fires_this_frame(ms(1000)), 0);
+        assert_eq!(repeat.fires_this_frame(ms(1399)), 0);
+        assert_eq!(repeat.last_fire, None);
+    }
+
+    #[test]
+    fn repeats_after_the_delay_at_the_slow_rate() {
+        let mut repeat = HoldRepeat::new(ms(0));
+        assert_eq!(repeat.fires_this_frame(ms(400)), 1);
+        assert_eq!(repeat.fires_this_frame(ms(450)), 0);
+        // about 10 a second at first
+        assert_eq!(repeat.fires_this_frame(ms(550)), 1);
+        // no repeat fires twice
+        assert_eq!(repeat.fires_this_frame(ms(550)), 0);
+    }
+
+    #[test]
+    fn repeats_speed_up_to_the_fast_rate() {
+        let mut repeat = HoldRepeat::new(ms(0));
+        let ramped = REPEAT_DELAY + REPEAT_RAMP;
+        repeat.fires_this_frame(ramped);
+        let fires = repeat.fires_this_frame(ramped + ms(1000));
+        assert!((49..=51).contains(&fires), "{fires}");
+    }
+
+    #[test]
+    fn repeats_do_not_depend_on_the_frame_rate() {
+        let mut once = HoldRepeat::new(ms(0));
+        let mut every_frame = HoldRepeat::new(ms(0));
+        let frames = (0..=3000)
+            .step_by(16)
+            .map(|t| every_frame.fires_this_frame(ms(t)));
+        assert_eq!(
+            frames.sum::<u32>(),
+            once.fires_this_frame(ms(3000 / 16 * 16))
         );
     }
+
+    #[test]
+    fn controls_not_drawn_are_let_go() {
+        let (a, b) = (WidgetId::new("a"), WidgetId::new("b"));
+        let mut state = RepeatState::default();
+        state.held.insert(a, HoldRepeat::new(ms(0)));
+        state.held.insert(b, HoldRepeat::new(ms(0)));
+        state.asked.insert(a);
+        state.end_frame();
+        assert!(state.held.contains_key(&a) && !state.held.contains_key(&b));
+        assert!(state.holding());
+        // a is not drawn in the next frame
+        state.end_frame();
+        assert!(!state.holding());
+    }
+
+    fn keys(keys: &[KeyCode]) -> Events {
+        Events {
+            keys_pressed: keys.to_vec(),
+            ..Events::default()
+        }
+    }
+
+    #[test]
+    fn arrows_move_the_focus_within_the_grid() {
+        let mut nav = GridNav::default();
+        // the first key focuses the first cell, wherever it would go
+        assert!(nav.handle_keys(&keys(&[KeyCode::ArrowDown]), 5, 4, 2, None));
+        assert_eq!(nav.focused, Some((0, 0)));
+        let moves = [KeyCode::ArrowRight, KeyCode::ArrowRight, KeyCode::ArrowDown];
+        assert!(nav.handle_keys(&keys(&moves), 5, 4, 2, None));
+        assert_eq!(nav.focused, Some((2, 1)));
+        // and stops at the edges
+        let moves = [KeyCode::ArrowUp, KeyCode::ArrowUp, KeyCode::ArrowLeft];
+        nav.handle_keys(&keys(&moves), 5, 4, 2, None);
+        assert_eq!(nav.focused, Some((1, 0)));
+        assert!(!nav.handle_keys(&keys(&[KeyCode::ArrowUp]), 5, 4, 2, None));
+        // other keys leave it be
+        assert!(!nav.handle_keys(&keys(&[KeyCode::KeyA]), 5, 4, 2, None));
+        assert_eq!(nav.focused, Some((1, 0)));
+    }
+
+    #[test]
+    fn home_end_and_pages_jump() {
+        let mut nav = GridNav {
+            focused: Some((2, 1)),
+        };
+        nav.handle_keys(&keys(&[KeyCode::End]), 5, 10, 3, None);
+        assert_eq!(nav.focused, Some((4, 1)));
+        nav.handle_keys(&keys(&[KeyCode::Home]), 5, 10, 3, None);
+        assert_eq!(nav.focused, Some((0, 1)));
+        nav.handle_keys(&keys(&[KeyCode::PageDown]), 5, 10, 3, None);
+        assert_eq!(nav.focused, Some((0, 4)));
+        nav.handle_keys(&keys(&[KeyCode::PageDown; 3]), 5, 10, 3, None);
+        assert_eq!(nav.focused, Some((0, 9)));
+        nav.handle_keys(&keys(&[KeyCode::PageUp; 4]), 5, 10, 3, None);
+        assert_eq!(nav.focused, Some((0, 0)));
+    }
+
+    #[test]
+    fn the_focus_starts_from_the_selection_and_shrinks_with_the_grid() {
+        let mut nav = GridNav::default();
+        nav.handle_keys(&keys(&[KeyCode::ArrowRight]), 5, 4, 2, Some((1, 3)));
+        assert_eq!(nav.focused, Some((2, 3)));
+        // a smaller grid pulls the focus in
+        nav.handle_keys(&keys(&[KeyCode::ArrowUp]), 2, 2, 2, None);
+        assert_eq!(nav.focused, Some((1, 0)));
+        // shortcuts are left alone
+        let ctrl = Events {
+            control: true,
+            ..keys(&[KeyCode::ArrowDown])
+        };
+        assert!(!nav.handle_keys(&ctrl, 5, 4, 2, None));
+        assert!(!nav.handle_keys(&keys(&[KeyCode::ArrowDown]), 0, 0, 2, None));
+        assert_eq!(nav.focused, None);
+    }
+
+    #[test]
+    fn enter_activates_the_focused_cell() {
+        let enter = keys(&[KeyCode::Enter]);
+        assert_eq!(GridNav::default().activated(&enter), None);
+        let nav = GridNav {
+            focused: Some((3, 2)),
+        };
+        assert_eq!(nav.activated(&enter), Some((3, 2)));
+        assert_eq!(nav.activated(&keys(&[KeyCode::Space])), None);
+        let alt = Events { alt: true, ..enter };
+        assert_eq!(nav.activated(&alt), None);
+    }
+
+    #[test]
+    fn scrolls_just_enough_to_show_the_focus() {
+        let at = |row| GridNav {
+            focused: Some((0, row)),
+        };
+        assert_eq!(at(5).scroll_into_view(3, 4), 3);
+        assert_eq!(at(7).scroll_into_view(3, 4), 4);
+        assert_eq!(at(1).scroll_into_view(3, 4), 1);
+        assert_eq!(GridNav::default().scroll_into_view(3, 4), 3);
+    }
+
+    #[test]
+    fn items_lift_past_the_threshold() {
+        let mut drag = DragReorder::default();
+        drag.press(2, 100);
+        assert!(drag.active() && drag.lifted().is_none());
+        drag.drag_to(100 + DragReorder::THRESHOLD - 1);
+        assert_eq!(drag.lifted(), None);
+        assert_eq!(drag.offset(), 0);
+        // a click without a lift moves nothing
+        assert_eq!(drag.release(10, 5), None);
+        assert!(!drag.active());
+
+        drag.press(2, 100);
+        drag.drag_to(100 - DragReorder::THRESHOLD);
+        assert_eq!(drag.lifted(), Some(2));
+        // once lifted it stays so, back where it was
+        drag.drag_to(100);
+        assert_eq!(drag.lifted(), Some(2));
+        assert_eq!(drag.release(10, 5), None);
+    }
+
+    #[test]
+    fn items_drop_where_their_middle_is() {
+        let mut drag = DragReorder::default();
+        drag.press(1, 0);
+        drag.drag_to(14);
+        assert_eq!(drag.target(10, 5), Some(2));
+        drag.drag_to(16);
+        assert_eq!(drag.target(10, 5), Some(3));
+        // and no further than the ends
+        drag.drag_to(-100);
+        assert_eq!(drag.target(10, 5), Some(0));
+        drag.drag_to(1000);
+        assert_eq!(drag.release(10, 5), Some((1, 4)));
+    }
+
+    #[test]
+    fn the_others_close_up_around_the_gap() {
+        let mut drag = DragReorder::default();
+        fn order(drag: &DragReorder) -> Vec<Option<usize>> {
+            (0..5).map(|slot| drag.item_at(slot, 10, 5)).collect()
+        }
+        assert_eq!(order(&drag), [Some(0), Some(1), Some(2), Some(3), Some(4)]);
+        drag.press(1, 0);
+        drag.drag_to(20);
+        assert_eq!(order(&drag), [Some(0), Some(2), Some(3), None, Some(4)]);
+        drag.drag_to(-10);
+        assert_eq!(order(&drag), [None, Some(0), Some(2), Some(3), Some(4)]);
+    }
+
+    #[test]
+    fn scrolling_keeps_the_item_under_the_cursor() {
+        let mut drag = DragReorder::default();
+        drag.press(0, 0);
+        drag.drag_to(10);
+        drag.scroll(20);
+        assert_eq!(drag.offset(), 30);
+        assert_eq!(drag.target(10, 5), Some(3));
+    }
+
+    #[test]
+    fn escape_puts_the_item_back() {
+        let mut drag = DragReorder::default();
+        drag.press(0, 5);
+        let events = Events {
+            keys_pressed: vec![KeyCode::Escape],
+            ..mouse(Some((10, 30)), (10, 5), true)
+        };
+        let mut moved = Some((9, 9));
+        render(events, |canvas| {
+            moved = drag.update(canvas, |(_, y)| y, 10, 3)
+        });
+        assert_eq!(moved, None);
+        assert!(!drag.active());
+    }
+
+    #[test]
+    fn table_rows_move_on_release() {
+        let rows: Vec<Vec<String>> = (0..3).map(|i| vec![i.to_string()]).collect();
+        let mut drag = DragReorder::default();
+        let mut scroll = 0;
+        let mut draw = |events| {
+            let mut moved = None;
+            render(events, |canvas| {
+                let mut table =
+                    ScrollableTable::new(&[4], &rows, &mut scroll).reorderable(&mut drag);
+                table.draw(canvas);
+                moved = table.moved;
+            });
+            moved
+        };
+        // press the first row, drag it down to the third and let go
+        assert_eq!(draw(mouse(Some((10, 5)), (10, 5), true)), None);
+        assert_eq!(draw(mouse(Some((10, 31)), (10, 5), true)), None);
+        assert_eq!(draw(mouse(Some((10, 31)), (10, 5), false)), Some((0, 2)));
+    }
+
+    #[test]
+    fn long_tables_scroll_while_dragging_at_the_edge() {
+        let rows: Vec<Vec<String>> = (0..6).map(|i| vec![i.to_string()]).collect();
+        let mut drag = DragReorder::default();
+        let mut scroll = 0;
+        for cursor in [(10, 5), (10, 35), (10, 35)] {
+            render(mouse(Some(cursor), (10, 5), true), |canvas| {
+                ScrollableTable::new(&[4], &rows, &mut scroll)
+                    .reorderable(&mut drag)
+                    .draw(canvas)
+            });
+        }
+        assert_eq!(scroll, 2);
+        assert_eq!(drag.lifted(), Some(0));
+        // three rows down from where it was lifted, and one more by the
+        // scroll of the last frame
+        assert_eq!(drag.target(13, 6), Some(4));
+    }
 }
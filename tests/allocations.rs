@@ -0,0 +1,72 @@
+//! Checks that drawing the Explore tab allocates nothing once it settles.
+//! Only runs with the `count-allocations` feature, which counts every
+//! allocation of the process:
+//!
+//! `cargo test --features count-allocations --test allocations`
+
+#![cfg(feature = "count-allocations")]
+
+use chord_explorer::alloc_count::allocations;
+use chord_explorer::explorer::Main;
+use chord_explorer::font::{Font, COZETTE};
+use chord_explorer::session::Session;
+use chord_explorer::widget::{
+    Canvas, CutDir, Events, HoverState, MenuState, PaneState, Rect, RepeatState, StatusState,
+    TooltipState, Visuals, Widget,
+};
+use chord_explorer::PixBuf;
+
+const WIDTH: i32 = 320;
+const HEIGHT: i32 = 240;
+
+#[test]
+fn steady_explore_frames_do_not_allocate() {
+    let font = Font::from_bdf_bytes(COZETTE, 6, 13)
+        .expect("the bundled font parses")
+        .with_custom_music_glyphs()
+        .with_atlas(2);
+    let mut main = Main::default();
+    main.restore(&Session {
+        tab: Some("Explore".to_string()),
+        ..Session::default()
+    });
+    let mut frame = vec![0; (WIDTH * HEIGHT * 4) as usize];
+    let mut canvas = Canvas {
+        pix: PixBuf::from_pixels_frame(&mut frame, WIDTH, HEIGHT),
+        rect: Rect {
+            x: 0,
+            y: 0,
+            width: WIDTH,
+            height: HEIGHT,
+        },
+        visuals: Visuals {
+            font: &font,
+            text_size: 2,
+            dir: CutDir::Vertical,
+            color: [255, 255, 255, 255],
+            line_spacing: 0,
+        },
+        events: Events::default(),
+        tooltip: TooltipState::default(),
+        hover: HoverState::default(),
+        repeat: RepeatState::default(),
+        menu: MenuState::default(),
+        panes: PaneState::default(),
+        status: StatusState::default(),
+    };
+
+    let mut draw = |canvas: &mut Canvas| {
+        let rect = canvas.rect;
+        canvas.clear_frame();
+        main.draw(canvas);
+        canvas.rect = rect;
+        canvas.visuals.dir = CutDir::Vertical;
+    };
+    // the first frames fill the caches and scratch buffers
+    for _ in 0..3 {
+        draw(&mut canvas);
+    }
+    let before = allocations();
+    draw(&mut canvas);
+    assert_eq!(allocations() - before, 0);
+}
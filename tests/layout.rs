@@ -0,0 +1,79 @@
+//! Draws `Main` on frames from 1 by 1 up to the default 320 by 240, on every
+//! tab and at several zooms, to check that layouts too big for the frame
+//! clip rather than panic.
+
+use chord_explorer::explorer::{Main, MainTabs};
+use chord_explorer::font::{Font, COZETTE};
+use chord_explorer::session::Session;
+use chord_explorer::widget::{
+    Canvas, CutDir, Events, HoverState, MenuState, PaneState, Rect, RepeatState, StatusState, Tab,
+    TooltipState, Visuals, Widget,
+};
+use chord_explorer::PixBuf;
+
+const WIDTH: i32 = 320;
+const HEIGHT: i32 = 240;
+
+/// Draws one frame of `main` on a `width` by `height` frame, with the cursor
+/// over the middle so the hover paths run too. It does not click, as that
+/// would press whatever button is there, such as one saving the tunings.
+fn draw(main: &mut Main, font: &Font, width: i32, height: i32) {
+    let mut frame = vec![0; (width * height * 4) as usize];
+    let middle = (width / 2, height / 2);
+    let mut canvas = Canvas {
+        pix: PixBuf::from_pixels_frame(&mut frame, width, height),
+        rect: Rect {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        },
+        visuals: Visuals {
+            font,
+            text_size: 2,
+            dir: CutDir::Vertical,
+            color: [255, 255, 255, 255],
+            line_spacing: 0,
+        },
+        events: Events {
+            cursor: Some(middle),
+            ..Events::default()
+        },
+        tooltip: TooltipState::default(),
+        hover: HoverState::default(),
+        repeat: RepeatState::default(),
+        menu: MenuState::default(),
+        panes: PaneState::default(),
+        status: StatusState::default(),
+    };
+    canvas.clear_frame();
+    main.draw(&mut canvas);
+}
+
+/// Sizes from 1 up to `max` in steps of `step`, and `max` itself.
+fn sizes(max: i32, step: usize) -> impl Iterator<Item = i32> + Clone {
+    (1..max).step_by(step).chain([max])
+}
+
+#[test]
+fn main_draws_at_every_size() {
+    let font = Font::from_bdf_bytes(COZETTE, 6, 13)
+        .expect("the bundled font parses")
+        .with_custom_music_glyphs();
+    for tab in MainTabs::iter() {
+        for zoom in [1, 2, 4] {
+            let mut main = Main::default();
+            main.restore(&Session {
+                tab: Some(tab.name().to_string()),
+                selected: Some((0, 0)),
+                zoom: Some(zoom),
+                ..Session::default()
+            });
+            for width in sizes(WIDTH, 23) {
+                for height in sizes(HEIGHT, 17) {
+                    draw(&mut main, &font, width, height);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,353 @@
+//! Renders a set of scenes without a window and compares each against its
+//! golden image in `snapshots/`, to catch visual regressions.
+//!
+//! Runs with `cargo test`. A scene that differs fails the test, after its
+//! rendering and a diff image are written to `target/snapshots/`. Run with
+//! `UPDATE_SNAPSHOTS=1 cargo test --test snapshots` to accept the current
+//! rendering as the new goldens.
+//!
+//! To add a scene, add its name and a closure drawing it to `SCENES`.
+
+use std::{
+    array, env,
+    fs::{self, File},
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use chord_explorer::explorer::{chord_card, Main, SpectrumPlot};
+use chord_explorer::export;
+use chord_explorer::font::{Font, COZETTE};
+use chord_explorer::notify::{Level, Notification, Toast};
+use chord_explorer::session::Session;
+use chord_explorer::theory;
+use chord_explorer::widget::{
+    Canvas, CutDir, Events, HoverState, MenuState, PaneState, Rect, RepeatState, Spinner,
+    StatusState, Tab, TooltipState, Visuals, Widget,
+};
+use chord_explorer::{Color, PixBuf};
+
+const WIDTH: i32 = 320;
+const HEIGHT: i32 = 240;
+
+/// Largest difference in any channel for a pixel to still match.
+const TOLERANCE: u8 = 8;
+
+/// Draws a scene on an empty frame.
+type Scene = fn(&mut Canvas);
+
+/// Every scene by the name of its golden image.
+const SCENES: &[(&str, Scene)] = &[
+    ("explore", |canvas| main_on(canvas, "Explore", None)),
+    ("explore_selected", |canvas| {
+        main_on(canvas, "Explore", Some((0, 1)))
+    }),
+    ("modes", |canvas| main_on(canvas, "Modes", None)),
+    ("tuning", |canvas| main_on(canvas, "Tuning", None)),
+    ("progression", |canvas| {
+        main_on(canvas, "Progression", Some((0, 0)))
+    }),
+    ("explore_zoom_1", |canvas| main_zoomed(canvas, "Explore", 1)),
+    ("explore_zoom_3", |canvas| main_zoomed(canvas, "Explore", 3)),
+    ("modes_zoom_1", |canvas| main_zoomed(canvas, "Modes", 1)),
+    ("modes_zoom_3", |canvas| main_zoomed(canvas, "Modes", 3)),
+    ("tuning_zoom_1", |canvas| main_zoomed(canvas, "Tuning", 1)),
+    ("tuning_zoom_3", |canvas| main_zoomed(canvas, "Tuning", 3)),
+    ("progression_zoom_1", |canvas| {
+        main_zoomed(canvas, "Progression", 1)
+    }),
+    ("progression_zoom_3", |canvas| {
+        main_zoomed(canvas, "Progression", 3)
+    }),
+    ("button", |canvas| {
+        canvas.button("Button");
+    }),
+    ("button_hover", |canvas| {
+        canvas.events.cursor = Some((4, 4));
+        canvas.button("Button");
+    }),
+    ("button_pressed", |canvas| {
+        canvas.events.cursor = Some((4, 4));
+        canvas.events.press_origin = Some((4, 4));
+        canvas.events.mouse_left = true;
+        canvas.button("Button");
+    }),
+    ("tabs_2", |canvas| tabs(canvas, &mut Two::A)),
+    ("tabs_5", |canvas| tabs(canvas, &mut Five::C)),
+    ("table", |canvas| {
+        let rows = [["C", "Major"], ["D", "Dorian"], ["E", "Phrygian"]];
+        let rows = rows.map(|row| row.map(String::from).to_vec());
+        canvas.table(&[4, 10], &rows, &mut 0);
+    }),
+    ("wrapped_text", |canvas| {
+        canvas.wrapped_text("A long line of text that does not fit on one line.");
+    }),
+    ("modal", |canvas| {
+        canvas.modal(12, 2, |canvas| canvas.text("A modal"))
+    }),
+    ("spinner", |canvas| Spinner::new().draw(canvas)),
+    ("spectrum", |canvas| {
+        // a C major triad in 12-TET over the same in just intonation, whose
+        // coinciding partials do not beat
+        let root = 261.63;
+        let names = ["C4", "E4", "G4"].map(String::from);
+        let triads = [
+            ("12-TET", [root, 329.63, 392.00]),
+            ("Just", [root, root * 5.0 / 4.0, root * 3.0 / 2.0]),
+        ];
+        let height = canvas.rect.height / 2;
+        for (title, frequencies) in triads {
+            canvas.cut_top(height, |canvas| {
+                canvas.text(title);
+                let partials = theory::spectrum(&frequencies, SpectrumPlot::HARMONICS);
+                SpectrumPlot {
+                    partials: &partials,
+                    names: &names,
+                }
+                .draw(canvas);
+            });
+        }
+    }),
+    ("toast", |canvas| {
+        // a second into its countdown, so the bar is three quarters full
+        let created = Instant::now();
+        let message = "could not save tunings: permission denied".to_string();
+        let notification = Notification::new(Level::Warn, message, created).with_action("Retry");
+        let (width, height) = Toast::size(&notification, canvas);
+        let rect = Rect {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        };
+        let now = created + Duration::from_secs(1);
+        canvas.absolute(rect, |canvas| Toast::new(&notification, now).draw(canvas));
+    }),
+];
+
+#[test]
+fn scenes_match_their_goldens() {
+    let font = font();
+    let failures = SCENES.iter().filter_map(|(name, draw)| {
+        let pixels = render(&font, *draw);
+        check(name, &pixels, WIDTH, HEIGHT).err()
+    });
+    let failures = failures.collect::<Vec<_>>();
+    assert!(
+        failures.is_empty(),
+        "{} of {} snapshots differ:\n{}",
+        failures.len(),
+        SCENES.len(),
+        failures.join("\n")
+    );
+}
+
+/// The exported chord card at 1x, which is laid out apart from the window.
+#[test]
+fn chord_card_matches_its_golden() {
+    let (pixels, width, height) = chord_card(&font(), "C♯m7", Some("C♯ E G♯ B"), 1);
+    if let Err(failure) = check("chord_card", &pixels, width, height) {
+        panic!("{failure}");
+    }
+}
+
+fn font() -> Font {
+    Font::from_bdf_bytes(COZETTE, 6, 13)
+        .expect("the bundled font parses")
+        .with_custom_music_glyphs()
+}
+
+/// Compares `pixels` against the golden image called `name`, or with
+/// `UPDATE_SNAPSHOTS=1` makes them the golden. A mismatch writes what was
+/// rendered and a diff image to `target/snapshots/`, and describes them.
+fn check(name: &str, pixels: &[Color], width: i32, height: i32) -> Result<(), String> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let (goldens, out) = (root.join("snapshots"), root.join("target/snapshots"));
+    for dir in [&goldens, &out] {
+        fs::create_dir_all(dir).expect("the snapshot directories can be created");
+    }
+    let golden = goldens.join(format!("{name}.png"));
+    if env::var_os("UPDATE_SNAPSHOTS").is_some_and(|v| v == "1") {
+        save(&golden, pixels, width, height);
+        return Ok(());
+    }
+
+    let expected = load(&golden);
+    let err = match &expected {
+        None => format!("no golden image at {}", golden.display()),
+        Some((_, golden_width)) if *golden_width != width => {
+            "the golden image has another size".to_string()
+        }
+        Some((expected, _)) => match compare(expected, pixels) {
+            Ok(()) => return Ok(()),
+            Err(err) => err,
+        },
+    };
+
+    let actual = out.join(format!("{name}.png"));
+    save(&actual, pixels, width, height);
+    let mut failure = format!("{name}: {err}, got {}", actual.display());
+    let same_size = |(expected, golden_width): &(Vec<Color>, i32)| {
+        *golden_width == width && expected.len() == pixels.len()
+    };
+    if let Some((expected, _)) = expected.filter(same_size) {
+        let diff = out.join(format!("{name}.diff.png"));
+        save(&diff, &diff_image(&expected, pixels), width, height);
+        failure += &format!(", diff in {}", diff.display());
+    }
+    Err(failure)
+}
+
+/// Draws one frame of a scene on an empty `WIDTH` by `HEIGHT` frame.
+fn render(font: &Font, draw: Scene) -> Vec<Color> {
+    let mut frame = vec![0; (WIDTH * HEIGHT * 4) as usize];
+    let mut canvas = Canvas {
+        pix: PixBuf::from_pixels_frame(&mut frame, WIDTH, HEIGHT),
+        rect: Rect {
+            x: 0,
+            y: 0,
+            width: WIDTH,
+            height: HEIGHT,
+        },
+        visuals: Visuals {
+            font,
+            text_size: 2,
+            dir: CutDir::Vertical,
+            color: [255, 255, 255, 255],
+            line_spacing: 0,
+        },
+        events: Events::default(),
+        tooltip: TooltipState::default(),
+        hover: HoverState::default(),
+        repeat: RepeatState::default(),
+        menu: MenuState::default(),
+        panes: PaneState::default(),
+        status: StatusState::default(),
+    };
+    canvas.clear_frame();
+    draw(&mut canvas);
+    drop(canvas);
+    frame.as_chunks().0.to_vec()
+}
+
+fn compare(expected: &[Color], actual: &[Color]) -> Result<(), String> {
+    if expected.len() != actual.len() {
+        return Err("the golden image has another size".to_string());
+    }
+    let differing = expected
+        .iter()
+        .zip(actual)
+        .filter(|(a, b)| !matches(a, b))
+        .count();
+    match differing {
+        0 => Ok(()),
+        n => Err(format!("{n} pixels differ")),
+    }
+}
+
+fn matches(a: &Color, b: &Color) -> bool {
+    a.iter().zip(b).all(|(a, b)| a.abs_diff(*b) <= TOLERANCE)
+}
+
+/// The actual image dimmed, with the pixels that differ in red.
+fn diff_image(expected: &[Color], actual: &[Color]) -> Vec<Color> {
+    let pixels = expected.iter().zip(actual);
+    pixels
+        .map(|(a, b)| match matches(a, b) {
+            true => [b[0] / 4, b[1] / 4, b[2] / 4, 255],
+            false => [255, 0, 0, 255],
+        })
+        .collect()
+}
+
+fn save(path: &Path, pixels: &[Color], width: i32, height: i32) {
+    if let Err(err) = export::save_png(path, pixels, width, height) {
+        panic!("could not write {}: {err}", path.display());
+    }
+}
+
+/// The pixels and width of a golden image, if it exists and is an RGBA PNG.
+fn load(path: &Path) -> Option<(Vec<Color>, i32)> {
+    let decoder = png::Decoder::new(File::open(path).ok()?);
+    let mut reader = decoder.read_info().ok()?;
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buf).ok()?;
+    if info.color_type != png::ColorType::Rgba || info.bit_depth != png::BitDepth::Eight {
+        return None;
+    }
+    let pixels = buf[..info.buffer_size()].as_chunks().0.to_vec();
+    Some((pixels, info.width as i32))
+}
+
+/// Main on `tab`, with the chord at `selected` in the Explore grid selected.
+fn main_on(canvas: &mut Canvas, tab: &str, selected: Option<(usize, usize)>) {
+    let mut main = Main::default();
+    main.restore(&Session {
+        tab: Some(tab.to_string()),
+        selected,
+        ..Session::default()
+    });
+    main.draw(canvas);
+}
+
+/// Main on `tab` at the UI scale `zoom`.
+fn main_zoomed(canvas: &mut Canvas, tab: &str, zoom: i32) {
+    let mut main = Main::default();
+    main.restore(&Session {
+        tab: Some(tab.to_string()),
+        zoom: Some(zoom),
+        ..Session::default()
+    });
+    main.draw(canvas);
+}
+
+fn tabs<T: Tab>(canvas: &mut Canvas, selected: &mut T) {
+    canvas.cut_top(canvas.visuals.font_height(), |canvas| {
+        canvas.visuals.dir = CutDir::Horizontal;
+        canvas.tabs(selected);
+    });
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Two {
+    A,
+    B,
+}
+
+impl Tab for Two {
+    type Iterator = array::IntoIter<Self, 2>;
+    fn iter() -> Self::Iterator {
+        [Two::A, Two::B].into_iter()
+    }
+    fn name(&self) -> &str {
+        match self {
+            Two::A => "One",
+            Two::B => "Two",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Five {
+    A,
+    B,
+    C,
+    D,
+    E,
+}
+
+impl Tab for Five {
+    type Iterator = array::IntoIter<Self, 5>;
+    fn iter() -> Self::Iterator {
+        [Five::A, Five::B, Five::C, Five::D, Five::E].into_iter()
+    }
+    fn name(&self) -> &str {
+        match self {
+            Five::A => "I",
+            Five::B => "II",
+            Five::C => "III",
+            Five::D => "IV",
+            Five::E => "V",
+        }
+    }
+}